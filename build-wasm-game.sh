@@ -0,0 +1,17 @@
+#!/bin/bash
+set -e
+
+echo "Building offline game WASM..."
+cargo build --target wasm32-unknown-unknown --profile wasm-release --package wasm-game
+
+echo "Generating bindings..."
+~/.cargo/bin/wasm-bindgen \
+    target/wasm32-unknown-unknown/wasm-release/wasm_game.wasm \
+    --out-dir ./wasm-game/pkg \
+    --target web
+
+echo "Optimizing wasm with wasm-opt..."
+wasm-opt -Oz ./wasm-game/pkg/wasm_game_bg.wasm \
+    -o wasm-game/pkg/wasm_game_bg.wasm
+
+echo "WASM build complete!"
@@ -0,0 +1,195 @@
+//! File containing the [`GreedyStrategy`].
+
+use crate::bot::*;
+
+/// A deterministic strategy which always takes the action that looks best right now, without
+/// looking ahead. It picks the character that would earn it the most, buys the most valuable
+/// assets it can afford and only issues liabilities when it needs the cash.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyStrategy;
+
+impl GreedyStrategy {
+    /// Creates a new greedy strategy.
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn select_character(selecting: &SelectingCharacters, id: PlayerId) -> Option<PlayerAction> {
+        if let Ok(characters) = selecting.player_get_bannable_characters(id) {
+            // Ban the character this player would earn the least with, keeping the best in play
+            let player = selecting.player(id).ok()?;
+            return characters
+                .into_iter()
+                .min_by_key(|&character| Self::character_score(character, player.assets()))
+                .map(|character| PlayerAction::BanCharacter { character });
+        }
+
+        Self::pick_character(selecting, id)
+            .map(|character| PlayerAction::SelectCharacter { character })
+    }
+
+    /// Gets the character player `id` should select, if it is their turn to select one.
+    pub(crate) fn pick_character(
+        selecting: &SelectingCharacters,
+        id: PlayerId,
+    ) -> Option<Character> {
+        let player = selecting.player(id).ok()?;
+        let characters = selecting.player_get_selectable_characters(id).ok()?;
+
+        // `max_by_key` returns the last maximum, so reverse to prefer characters called earlier
+        characters
+            .into_iter()
+            .rev()
+            .max_by_key(|&character| Self::character_score(character, player.assets()))
+    }
+
+    fn character_score(character: Character, assets: &[Asset]) -> u8 {
+        let base = match character {
+            Character::CEO => 3,
+            Character::HeadRnD => 2,
+            Character::CSO | Character::CFO => 1,
+            _ => 0,
+        };
+        let bonus = match character.color() {
+            Some(color) => assets.iter().filter(|a| a.color == color).count() as u8,
+            None => 0,
+        };
+
+        base + bonus
+    }
+
+    /// Gets the action player `id` should take in `round`, together with why the strategy takes
+    /// it. Used both to play and to give hints, see [`Round::suggest_action`].
+    pub(crate) fn round_hint(round: &Round, id: PlayerId) -> Option<Hint> {
+        let player = round.current_player();
+        if player.id() != id {
+            return None;
+        }
+
+        if should_get_bonus_cash(player, round.current_market()) {
+            let cash = player.asset_bonus() + player.market_condition_bonus(round.current_market());
+            return Some(Hint::new(
+                PlayerAction::GetBonusCash,
+                HintReason::CollectBonusCash { cash: cash as u8 },
+            ));
+        }
+
+        if player.should_give_back_cards() {
+            return player
+                .hand()
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, card)| card_value(card))
+                .map(|(card_idx, card)| {
+                    Hint::new(
+                        PlayerAction::PutBackCard { card_idx },
+                        HintReason::GiveBackLeastValuableCard {
+                            value: card_value(card),
+                        },
+                    )
+                });
+        }
+
+        if player.can_draw_cards() {
+            let has_liability = player.hand().iter().any(|c| c.is_right());
+            let (preferred, other) = match !has_liability && player.cash() < 3 {
+                true => (CardType::Liability, CardType::Asset),
+                false => (CardType::Asset, CardType::Liability),
+            };
+            if let Some(card_type) = [preferred, other]
+                .into_iter()
+                .find(|&card_type| round.can_draw(card_type))
+            {
+                return Some(Hint::new(
+                    PlayerAction::DrawCard { card_type },
+                    HintReason::DrawCards {
+                        remaining: player.cards_left_to_draw(),
+                    },
+                ));
+            }
+        }
+
+        if let Some((card_idx, asset)) =
+            buyable_assets(player).max_by_key(|(_, a)| a.gold_value + a.silver_value)
+        {
+            return Some(Hint::new(
+                PlayerAction::BuyAsset { card_idx },
+                HintReason::BuyMostValuableAsset {
+                    gold_value: asset.gold_value,
+                    silver_value: asset.silver_value,
+                },
+            ));
+        }
+
+        // Only issue a liability if it allows us to buy an asset we otherwise couldn't afford
+        if player.assets_to_play() > 0
+            && let Some((card_idx, liability)) = issuable_liabilities(round, player)
+                .filter(|(_, l)| Self::unlocks_asset(player, l.value))
+                .max_by_key(|(_, l)| l.value)
+        {
+            return Some(Hint::new(
+                PlayerAction::IssueLiability { card_idx },
+                HintReason::IssueLiabilityToAfford {
+                    value: liability.value,
+                },
+            ));
+        }
+
+        Some(Hint::new(
+            PlayerAction::EndTurn,
+            HintReason::NothingLeftToDo,
+        ))
+    }
+
+    fn unlocks_asset(player: &RoundPlayer, extra_cash: u8) -> bool {
+        player
+            .hand()
+            .iter()
+            .filter_map(|c| c.as_ref().left())
+            .any(|a| {
+                player.assets_to_play() >= player.playable_assets().color_cost(a.color)
+                    && player.cash() < a.gold_value
+                    && player.cash().saturating_add(extra_cash) >= a.gold_value
+            })
+    }
+}
+
+impl Strategy for GreedyStrategy {
+    fn next_action(&mut self, state: &GameState, id: PlayerId) -> Option<PlayerAction> {
+        match state {
+            GameState::SelectingCharacters(selecting) => Self::select_character(selecting, id),
+            GameState::Round(round) => Self::round_hint(round, id).map(|hint| hint.action),
+            GameState::BankerTarget(round) => banker_target_action(round, id),
+            GameState::Lobby(_) | GameState::Results(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::tests::started_game;
+    use claim::*;
+
+    #[test]
+    fn only_acts_on_own_turn() {
+        let state = started_game(4);
+        let current = assert_some!(acting_player(&state));
+        let other = PlayerId((current.0 + 1) % 4);
+
+        let mut bot = GreedyStrategy::new();
+        assert_none!(bot.next_action(&state, other));
+        assert_matches!(
+            bot.next_action(&state, current),
+            Some(PlayerAction::SelectCharacter { .. })
+        );
+    }
+
+    #[test]
+    fn prefers_ceo_without_assets() {
+        assert!(
+            GreedyStrategy::character_score(Character::CEO, &[])
+                > GreedyStrategy::character_score(Character::Shareholder, &[])
+        );
+    }
+}
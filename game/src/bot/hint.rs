@@ -0,0 +1,115 @@
+//! File containing [`Hint`], a suggested move for players that are still learning the game. Hints
+//! are given by the [`GreedyStrategy`], which explains why it would take the action it suggests.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use crate::bot::*;
+
+/// A suggested action, together with why it was suggested.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hint {
+    /// The action that is suggested.
+    pub action: PlayerAction,
+    /// Why the action is suggested.
+    pub reason: HintReason,
+}
+
+impl Hint {
+    /// Creates a new hint suggesting `action` because of `reason`.
+    pub fn new(action: PlayerAction, reason: HintReason) -> Self {
+        Self { action, reason }
+    }
+}
+
+/// Why a [`Hint`] suggests its action.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason")]
+pub enum HintReason {
+    /// The player used their ability, and should pick a target from its options.
+    MakeDecision {
+        /// The decision the player still has to make.
+        decision: PendingDecision,
+    },
+    /// The character of the player earns them bonus cash this turn.
+    CollectBonusCash {
+        /// The amount of cash they would get.
+        cash: u8,
+    },
+    /// The player drew too many cards, and the card that is worth the least should go back.
+    GiveBackLeastValuableCard {
+        /// What the card is worth to the player.
+        value: u8,
+    },
+    /// The player can still draw cards this turn.
+    DrawCards {
+        /// The amount of cards the player can still draw.
+        remaining: u8,
+    },
+    /// This is the most valuable asset the player can buy right now.
+    BuyMostValuableAsset {
+        /// The gold value of the asset.
+        gold_value: u8,
+        /// The silver value of the asset.
+        silver_value: u8,
+    },
+    /// Issuing this liability gives the player enough cash to buy an asset in their hand.
+    IssueLiabilityToAfford {
+        /// The cash the liability is worth.
+        value: u8,
+    },
+    /// There is nothing left this turn that would help the player.
+    NothingLeftToDo,
+}
+
+impl Round {
+    /// Suggests what player `id` could do next, with a reason a new player can learn from. Returns
+    /// `None` if it is not their turn.
+    pub fn suggest_action(&self, id: PlayerId) -> Option<Hint> {
+        match self.pending_decision() {
+            Some(decision) if self.current_player().id() == id => {
+                let action = match decision {
+                    PendingDecision::ConfirmDivest(_) => PlayerAction::ConfirmDivest,
+                    _ => PlayerAction::UseAbility,
+                };
+                Some(Hint::new(action, HintReason::MakeDecision { decision }))
+            }
+            _ => GreedyStrategy::round_hint(self, id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::tests::started_game;
+    use claim::*;
+
+    #[test]
+    fn hints_follow_the_turn() {
+        let mut state = started_game(4);
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let round = state.round().unwrap();
+        let id = round.current_player().id();
+        let other = round.players().iter().find(|p| p.id() != id).unwrap().id();
+        assert_none!(round.suggest_action(other));
+
+        // Following every hint plays the turn to its end
+        while let Ok(round) = state.round()
+            && round.current_player().id() == id
+        {
+            let hint = assert_some!(round.suggest_action(id));
+            assert_ok!(state.apply(id, hint.action));
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! This module contains computer opponents, which decide on a [`PlayerAction`] to take based on
+//! the current [`GameState`]. A bot only ever suggests an action, it is up to whoever drives the
+//! game to apply it.
+
+mod greedy;
+mod hint;
+#[cfg(feature = "shuffle")]
+mod random;
+mod simulate;
+
+pub use greedy::*;
+pub use hint::*;
+#[cfg(feature = "shuffle")]
+pub use random::*;
+pub use simulate::*;
+
+use either::Either;
+
+use crate::{game::*, player::*};
+
+/// A way of playing the game. A strategy is asked for an action whenever it is the turn of the
+/// player it controls.
+pub trait Strategy {
+    /// Gets the next action player `id` should take in `state`. Returns `None` if there is nothing
+    /// left for this player to do.
+    fn next_action(&mut self, state: &GameState, id: PlayerId) -> Option<PlayerAction>;
+}
+
+/// Gets the id of the player that is expected to act in `state`, if any.
+pub fn acting_player(state: &GameState) -> Option<PlayerId> {
+    match state {
+        GameState::SelectingCharacters(selecting) => Some(selecting.currently_selecting_id()),
+        GameState::Round(round) => Some(round.current_player().id()),
+        GameState::BankerTarget(round) => Some(round.current_player().id()),
+        GameState::Lobby(_) | GameState::Results(_) => None,
+    }
+}
+
+/// Checks whether `player` is able to buy `asset` right now.
+fn can_buy(player: &RoundPlayer, asset: &Asset) -> bool {
+    player.cash() >= asset.gold_value
+        && player.assets_to_play() >= player.playable_assets().color_cost(asset.color)
+}
+
+/// Gets the indices of all assets in the hand of `player` they can buy right now.
+fn buyable_assets(player: &RoundPlayer) -> impl Iterator<Item = (usize, &Asset)> {
+    player
+        .hand()
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, card)| card.as_ref().left().map(|asset| (idx, asset)))
+        .filter(|(_, asset)| can_buy(player, asset))
+}
+
+/// Gets the indices of all liabilities in the hand of `player` they can issue right now in `round`.
+fn issuable_liabilities<'a>(
+    round: &Round,
+    player: &'a RoundPlayer,
+) -> impl Iterator<Item = (usize, &'a Liability)> {
+    let can_issue = round.can_issue_liability(player);
+    player
+        .hand()
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, card)| card.as_ref().right().map(|liability| (idx, liability)))
+        .filter(move |_| can_issue)
+}
+
+/// Checks whether collecting the bonus cash of their character gets `player` anything.
+fn should_get_bonus_cash(player: &RoundPlayer, market: &Market) -> bool {
+    !player.has_gotten_bonus_cash()
+        && player.character().color().is_some()
+        && player.asset_bonus() + player.market_condition_bonus(market) > 0
+}
+
+/// Gets the action the target of the banker takes to pay them off. Assets with the highest market
+/// value are sold first, followed by liabilities if the target is the CFO, until the amount owed
+/// can be paid.
+fn banker_target_action(round: &BankerTargetRound, id: PlayerId) -> Option<PlayerAction> {
+    let player = round.current_player();
+    if player.id() != id {
+        return None;
+    }
+
+    let owed = round.gold_to_be_paid();
+    let pay = PlayerAction::PayBanker { cash: owed };
+    if !round.can_pay_banker() {
+        return Some(pay);
+    }
+
+    let selected = round.selected_cards();
+    let raised = selected
+        .sold_assets
+        .iter()
+        .map(|a| a.market_value as u16)
+        .chain(
+            selected
+                .issued_liabilities
+                .iter()
+                .map(|l| l.liability.value as u16),
+        )
+        .sum::<u16>();
+    if player.cash().saturating_add(Cash(raised)).covers(owed) {
+        return Some(pay);
+    }
+
+    let market = round.current_market();
+    let asset = player
+        .assets()
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !selected.sold_assets.iter().any(|a| a.asset_idx == *idx))
+        .filter(|(_, asset)| asset.market_value(market) > 0)
+        .max_by_key(|(_, asset)| asset.market_value(market))
+        .map(|(asset_id, _)| PlayerAction::SelectAssetToDivest { asset_id });
+    let liability = || {
+        player
+            .hand()
+            .iter()
+            .enumerate()
+            .filter(|_| player.character() == Character::CFO)
+            .filter(|(_, card)| card.is_right())
+            .find(|(idx, _)| {
+                !selected
+                    .issued_liabilities
+                    .iter()
+                    .any(|l| l.card_idx == *idx)
+            })
+            .map(|(liability_id, _)| PlayerAction::SelectLiabilityToIssue { liability_id })
+    };
+
+    asset.or_else(liability).or(Some(pay))
+}
+
+/// Gets the value a bot places on having `card` in their hand.
+fn card_value(card: &Either<Asset, Liability>) -> u8 {
+    match card {
+        Either::Left(asset) => asset.gold_value + asset.silver_value,
+        Either::Right(_) => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::GameData;
+    use claim::*;
+
+    pub(super) fn started_game(players: usize) -> GameState {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..players {
+            assert_ok!(lobby.join(format!("bot {i}")));
+        }
+        let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        assert_ok!(state.start_game_with_data(data));
+        state
+    }
+
+    #[test]
+    fn acting_player_follows_state() {
+        let state = GameState::new();
+        assert_eq!(acting_player(&state), None);
+
+        let state = started_game(4);
+        let selecting = state.selecting_characters().unwrap();
+        assert_eq!(
+            acting_player(&state),
+            Some(selecting.currently_selecting_id())
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! File containing the [`RandomStrategy`].
+
+use rand::{SeedableRng, rngs::SmallRng, seq::IndexedRandom};
+
+use crate::bot::*;
+
+/// A strategy which picks a random action out of every sensible action it can take. Useful as an
+/// easy opponent, or to explore the game in tests.
+#[derive(Debug, Clone)]
+pub struct RandomStrategy {
+    rng: SmallRng,
+}
+
+impl RandomStrategy {
+    /// Creates a new random strategy.
+    pub fn new() -> Self {
+        Self {
+            rng: SmallRng::from_rng(&mut rand::rng()),
+        }
+    }
+
+    /// Creates a new random strategy which always makes the same choices for the same `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    fn round_actions(round: &Round, id: PlayerId) -> Vec<PlayerAction> {
+        let player = round.current_player();
+        if player.id() != id {
+            return vec![];
+        }
+
+        // Giving back cards is mandatory before doing anything else
+        if player.should_give_back_cards() {
+            return (0..player.hand().len())
+                .map(|card_idx| PlayerAction::PutBackCard { card_idx })
+                .collect();
+        }
+
+        let mut actions = vec![PlayerAction::EndTurn];
+        if should_get_bonus_cash(player, round.current_market()) {
+            actions.push(PlayerAction::GetBonusCash);
+        }
+        if player.can_draw_cards() {
+            actions.extend(
+                [CardType::Asset, CardType::Liability]
+                    .into_iter()
+                    .filter(|&card_type| round.can_draw(card_type))
+                    .map(|card_type| PlayerAction::DrawCard { card_type }),
+            );
+        }
+        actions.extend(
+            buyable_assets(player).map(|(card_idx, _)| PlayerAction::BuyAsset { card_idx }),
+        );
+        actions.extend(
+            issuable_liabilities(round, player)
+                .map(|(card_idx, _)| PlayerAction::IssueLiability { card_idx }),
+        );
+
+        actions
+    }
+}
+
+impl Default for RandomStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn next_action(&mut self, state: &GameState, id: PlayerId) -> Option<PlayerAction> {
+        let actions = match state {
+            GameState::SelectingCharacters(selecting) => selecting
+                .player_get_bannable_characters(id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|character| PlayerAction::BanCharacter { character })
+                .chain(
+                    selecting
+                        .player_get_selectable_characters(id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|character| PlayerAction::SelectCharacter { character }),
+                )
+                .collect(),
+            GameState::Round(round) => Self::round_actions(round, id),
+            GameState::BankerTarget(round) => return banker_target_action(round, id),
+            GameState::Lobby(_) | GameState::Results(_) => vec![],
+        };
+
+        actions.choose(&mut self.rng).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::tests::started_game;
+
+    #[test]
+    fn same_seed_same_choice() {
+        let state = started_game(4);
+        let id = acting_player(&state).unwrap();
+
+        let mut first = RandomStrategy::with_seed(42);
+        let mut second = RandomStrategy::with_seed(42);
+        for _ in 0..10 {
+            assert_eq!(
+                first.next_action(&state, id),
+                second.next_action(&state, id)
+            );
+        }
+    }
+}
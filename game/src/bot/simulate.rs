@@ -0,0 +1,113 @@
+//! File containing [`simulate`] and [`simulate_parallel`], which let bots play entire games on
+//! their own, for example to quickly compare strategies or check the balance of the cards.
+
+use std::{num::NonZeroUsize, thread};
+
+use crate::{bot::*, errors::GameError};
+
+/// The most actions a simulated game may take before it is given up on.
+pub const MAX_SIMULATED_ACTIONS: usize = 100_000;
+
+/// The strategies playing a simulated game, indexed by player id.
+pub type Strategies = Vec<Box<dyn Strategy + Send>>;
+
+/// Plays `state` until it reaches its results, asking `strategies[id]` for the action of player
+/// `id` whenever it is their turn. Returns the state the game ended up in, which is not the results
+/// if a strategy had nothing left to do or the game took more than [`MAX_SIMULATED_ACTIONS`].
+///
+/// # Errors
+///
+/// Fails if a strategy suggests an action the game rejects, or if there is no strategy for the
+/// player that has to act.
+pub fn simulate(
+    mut state: GameState,
+    strategies: &mut [Box<dyn Strategy + Send>],
+) -> Result<GameState, GameError> {
+    for _ in 0..MAX_SIMULATED_ACTIONS {
+        let Some(id) = acting_player(&state) else {
+            break;
+        };
+        let strategy = strategies
+            .get_mut(usize::from(id))
+            .ok_or(GameError::InvalidPlayerIndex(id.0))?;
+        let Some(action) = strategy.next_action(&state, id) else {
+            break;
+        };
+        state.apply(id, action)?;
+    }
+
+    Ok(state)
+}
+
+/// Simulates `games` independent games spread over every available thread, see [`simulate`]. Each
+/// game is set up by calling `setup` with its index, which returns the state to start from and the
+/// strategies to play it with. The outcomes are returned in the order of their index.
+pub fn simulate_parallel<F>(games: usize, setup: F) -> Vec<Result<GameState, GameError>>
+where
+    F: Fn(usize) -> (GameState, Strategies) + Sync,
+{
+    let threads = thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+        .min(games);
+    let setup = &setup;
+
+    let mut outcomes = thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|thread| {
+                scope.spawn(move || {
+                    (thread..games)
+                        .step_by(threads)
+                        .map(|idx| {
+                            let (state, mut strategies) = setup(idx);
+                            (idx, simulate(state, &mut strategies))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            // PANIC: a thread only panics if a strategy or the game itself does, in which case
+            // passing the panic on is the right thing to do.
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    outcomes.sort_by_key(|(idx, _)| *idx);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::tests::started_game;
+    use claim::*;
+
+    fn greedy(players: usize) -> Strategies {
+        (0..players)
+            .map(|_| Box::new(GreedyStrategy::new()) as Box<dyn Strategy + Send>)
+            .collect()
+    }
+
+    #[test]
+    fn simulated_games_reach_results() {
+        let outcomes = simulate_parallel(8, |idx| {
+            let players = 4 + idx % 4;
+            (started_game(players), greedy(players))
+        });
+
+        assert_eq!(outcomes.len(), 8);
+        for (idx, outcome) in outcomes.into_iter().enumerate() {
+            let results = assert_ok!(outcome);
+            let results = assert_ok!(results.results());
+            assert_eq!(results.players().len(), 4 + idx % 4);
+        }
+    }
+
+    #[test]
+    fn missing_strategy_fails() {
+        assert_err!(simulate(started_game(4), &mut greedy(2)));
+    }
+}
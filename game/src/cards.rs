@@ -8,6 +8,9 @@
 //!     "version": "0.1",
 //!     "gamemode": "board_version"
 //!   },
+//!   "colors": [
+//!     { "color": "Red", "pattern": "diagonal-stripes", "icon": "triangle" }
+//!   ],
 //!   "deck_list": {
 //!     "asset_deck": {
 //!       "card_image_back_url": "asset_back.webp",
@@ -57,6 +60,12 @@
 //!         }
 //!       ]
 //!     }
+//!   },
+//!   "translations": {
+//!     "nl": {
+//!       "titles": { "Stable Market": "Stabiele Markt" },
+//!       "descriptions": {}
+//!     }
 //!   }
 //! }
 //! ```
@@ -66,9 +75,14 @@ use either::Either;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use std::{collections::HashSet, fs::read_to_string, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::read_to_string,
+    path::Path,
+    sync::Arc,
+};
 
-use crate::{game::*, player::*};
+use crate::{game::*, player::*, utility::stable_hash};
 
 /// Errors that can occur when parsing or loading data.
 #[derive(Debug, Error)]
@@ -79,6 +93,13 @@ pub enum DataParseError {
     /// a serde_json::Error
     #[error(transparent)]
     Serde(#[from] serde_json::Error),
+    /// A card has a color that is not in the list of colors of the data
+    #[error("Card has color {0:?}, which is not one of the colors in the data")]
+    UnknownColor(Color),
+    /// a reqwest::Error
+    #[cfg(feature = "url")]
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
 }
 
 /// Represents the json in its entirety
@@ -88,6 +109,12 @@ struct LoadedCards {
     metadata: LoadedCardsMetadata,
     /// Has the asset deck, liability deck and market and events deck
     deck_list: DeckList,
+    /// The colors cards can have, which are the standard colors if the json does not list them
+    #[serde(default = "ColorMarker::all")]
+    colors: Vec<ColorMarker>,
+    /// Translations of the cards, keyed by locale
+    #[serde(default)]
+    translations: BTreeMap<String, CardTranslations>,
 }
 
 /// Card metadata
@@ -127,6 +154,9 @@ struct AssetCard {
     card_image_url: String,
     /// Possible ability of the card
     ability: Option<AssetPowerup>,
+    /// Possible effect of the card when it is bought
+    #[serde(default)]
+    on_buy: Option<AssetEffect>,
 }
 
 /// Representation of a liability card as it appears in the json
@@ -190,7 +220,7 @@ struct EventCard {
 
 /// Overarching struct which contains the asset deck, the liability deck and the market/event deck.
 /// This can be used by the game or anything else that might want to get all cards.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameData {
     /// Deck containing all assets
     pub assets: Deck<Asset>,
@@ -198,6 +228,64 @@ pub struct GameData {
     pub liabilities: Deck<Liability>,
     /// Deck containing all markets and events
     pub market_deck: Deck<Either<Market, Event>>,
+    /// The colors cards can have, together with the markers frontends show for each of them
+    pub colors: Vec<ColorMarker>,
+    /// Translations of the cards into other languages, keyed by locale, like `"nl"`
+    pub translations: BTreeMap<String, CardTranslations>,
+}
+
+/// Translations of the titles and descriptions of cards into a single language. Both are keyed by
+/// the text as it appears in the card data, so cards without a translation keep their text.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CardTranslations {
+    /// Translated titles of assets, markets and events
+    #[serde(default)]
+    pub titles: BTreeMap<String, String>,
+    /// Translated descriptions of events
+    #[serde(default)]
+    pub descriptions: BTreeMap<String, String>,
+}
+
+impl CardTranslations {
+    /// Translates every card title and description in `value`, which is expected to be a
+    /// serialized response. Works on json rather than on the response itself, so every response
+    /// that contains cards is translated without having to know about each one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::cards::CardTranslations;
+    /// let translations = CardTranslations {
+    ///     titles: [("Stable Market".to_owned(), "Stabiele Markt".to_owned())].into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut response = serde_json::json!({ "market": { "title": "Stable Market", "rfr": 4 } });
+    /// translations.localize(&mut response);
+    /// assert_eq!(response["market"]["title"], "Stabiele Markt");
+    /// ```
+    pub fn localize(&self, value: &mut serde_json::Value) {
+        use serde_json::Value;
+
+        match value {
+            Value::Object(fields) => {
+                if let Some(Value::String(title)) = fields.get_mut("title")
+                    && let Some(translated) = self.titles.get(title)
+                {
+                    title.clone_from(translated);
+                }
+                if let Some(Value::String(description)) = fields.get_mut("description")
+                    && let Some(translated) = self.descriptions.get(description)
+                {
+                    description.clone_from(translated);
+                }
+
+                fields.values_mut().for_each(|value| self.localize(value));
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.localize(value)),
+            _ => {}
+        }
+    }
 }
 
 impl GameData {
@@ -206,9 +294,121 @@ impl GameData {
     pub fn new<P: AsRef<Path>>(cards_json_path: P) -> Result<GameData, DataParseError> {
         let json = read_to_string(cards_json_path)?;
 
-        let cards = serde_json::from_str::<LoadedCards>(&json)?;
+        Self::from_json(&json)
+    }
+
+    /// Tries parsing `json`, which should have the same layout as `boardgame.json`, into a
+    /// [`GameData`] struct. Useful for environments without a filesystem, where the card data is
+    /// bundled into the binary instead.
+    pub fn from_json(json: &str) -> Result<GameData, DataParseError> {
+        let cards = serde_json::from_str::<LoadedCards>(json)?;
+        let data = Self::from(cards);
+
+        match data
+            .assets
+            .deck
+            .iter()
+            .find(|asset| !data.colors.iter().any(|c| c.color == asset.color))
+        {
+            Some(asset) => Err(DataParseError::UnknownColor(asset.color)),
+            None => Ok(data),
+        }
+    }
+
+    /// Tries downloading a json from `url`, for example from a CMS that holds the latest version
+    /// of the cards, and parsing it like [`GameData::from_json`].
+    #[cfg(feature = "url")]
+    pub async fn from_url(url: &str) -> Result<GameData, DataParseError> {
+        let json = reqwest::get(url).await?.error_for_status()?.text().await?;
+
+        Self::from_json(&json)
+    }
+
+    /// Gets the translations of the cards for `locale`, if there are any.
+    pub fn translations(&self, locale: &str) -> Option<&CardTranslations> {
+        self.translations.get(locale)
+    }
+
+    /// Gets a checksum of every card in the data, which does not depend on the order of the
+    /// decks. Clients can compare it with the checksum of their own cards to check whether they
+    /// show the same titles and images, and replays can refuse to run on different cards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::cards::GameData;
+    /// let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+    /// let mut reversed = data.clone();
+    /// reversed.assets.deck.reverse();
+    /// assert_eq!(data.checksum(), reversed.checksum());
+    ///
+    /// reversed.assets.deck.pop();
+    /// assert_ne!(data.checksum(), reversed.checksum());
+    /// ```
+    pub fn checksum(&self) -> u64 {
+        let mut cards = self
+            .assets
+            .deck
+            .iter()
+            .map(|asset| format!("{asset:?}"))
+            .chain(self.liabilities.deck.iter().map(|l| format!("{l:?}")))
+            .chain(self.market_deck.deck.iter().map(|card| format!("{card:?}")))
+            .collect::<Vec<_>>();
+        cards.sort_unstable();
+
+        stable_hash(&(
+            cards,
+            &self.colors,
+            &self.assets.image_back_url,
+            &self.liabilities.image_back_url,
+            &self.market_deck.image_back_url,
+        ))
+    }
+
+    /// Prepends `base_url` to the image urls of every card, which are relative to the assets
+    /// folder in the json. This lets deployments serve the images from somewhere else, like a CDN,
+    /// without changing the card data itself. Without the `images` feature cards do not keep their
+    /// own urls, so only the backs of the decks are changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::cards::GameData;
+    /// let mut data = GameData::new("../assets/cards/boardgame.json").unwrap();
+    /// data.set_image_base_url("https://cdn.example.com/cards/");
+    ///
+    /// assert!(data.assets.image_back_url.starts_with("https://cdn.example.com/cards/"));
+    /// # #[cfg(feature = "images")]
+    /// # {
+    /// let asset = &data.assets.deck[0];
+    /// assert!(asset.image_front_url.starts_with("https://cdn.example.com/cards/"));
+    /// assert!(asset.image_back_url.starts_with("https://cdn.example.com/cards/"));
+    /// # }
+    /// ```
+    pub fn set_image_base_url(&mut self, base_url: &str) {
+        let base_url = base_url.trim_end_matches('/');
+        let join = |url: &str| match url.is_empty() {
+            true => String::new(),
+            false => format!("{base_url}/{}", url.trim_start_matches('/')),
+        };
+
+        let asset_back_url = Arc::new(join(&self.assets.image_back_url));
+        self.assets.image_back_url = asset_back_url.clone();
+        #[cfg(feature = "images")]
+        self.assets.for_each_card(|asset| {
+            asset.image_front_url = join(&asset.image_front_url);
+            asset.image_back_url = asset_back_url.clone();
+        });
+
+        let liability_back_url = Arc::new(join(&self.liabilities.image_back_url));
+        self.liabilities.image_back_url = liability_back_url.clone();
+        #[cfg(feature = "images")]
+        self.liabilities.for_each_card(|liability| {
+            liability.image_front_url = join(&liability.image_front_url);
+            liability.image_back_url = liability_back_url.clone();
+        });
 
-        Ok(Self::from(cards))
+        self.market_deck.image_back_url = Arc::new(join(&self.market_deck.image_back_url));
     }
 
     /// Shuffles each individual deck.
@@ -228,6 +428,7 @@ impl From<Deck<AssetCard>> for Deck<Asset> {
             .into_iter()
             .flat_map(|c| {
                 // keep borrow checker happy about moving an Arc into each Asset
+                #[cfg(feature = "images")]
                 let image_back_url = image_back_url.clone();
 
                 (0..c.copies).map(move |_| Asset {
@@ -236,7 +437,10 @@ impl From<Deck<AssetCard>> for Deck<Asset> {
                     silver_value: c.silver_value,
                     color: c.color,
                     ability: c.ability,
+                    on_buy: c.on_buy,
+                    #[cfg(feature = "images")]
                     image_front_url: c.card_image_url.clone(),
+                    #[cfg(feature = "images")]
                     image_back_url: image_back_url.clone(),
                 })
             })
@@ -254,12 +458,16 @@ impl From<Deck<LiabilityCard>> for Deck<Liability> {
             .into_iter()
             .flat_map(|c| {
                 // keep borrow checker happy about moving an Arc into each Liability
+                #[cfg(feature = "images")]
                 let image_back_url = image_back_url.clone();
 
                 (0..c.copies).map(move |_| Liability {
                     value: c.gold_value,
                     rfr_type: c.liability_type,
+                    issued_round: None,
+                    #[cfg(feature = "images")]
                     image_front_url: c.card_image_url.clone(),
+                    #[cfg(feature = "images")]
                     image_back_url: image_back_url.clone(),
                 })
             })
@@ -290,8 +498,8 @@ impl From<Deck<MarketEventCard>> for Deck<Either<Market, Event>> {
                     MarketEventDetails::Event { event } => Either::Right(Event {
                         title: c.title.clone(),
                         description: event.description.clone(),
-                        plus_gold: HashSet::new(),
-                        minus_gold: HashSet::new(),
+                        plus_gold: BTreeSet::new(),
+                        minus_gold: BTreeSet::new(),
                         skip_turn: None,
                     }),
                 })
@@ -308,6 +516,8 @@ impl From<LoadedCards> for GameData {
             assets: cards.deck_list.asset_deck.into(),
             liabilities: cards.deck_list.liability_deck.into(),
             market_deck: cards.deck_list.market_events_deck.into(),
+            colors: cards.colors,
+            translations: cards.translations,
         }
     }
 }
@@ -324,4 +534,85 @@ mod tests {
         assert_eq!(data.liabilities.len(), 50);
         assert_eq!(data.market_deck.len(), 25);
     }
+
+    #[test]
+    fn from_json_matches_file() {
+        let json = read_to_string("../assets/cards/boardgame.json").expect("could not read data");
+        let data = GameData::from_json(&json).expect("could not parse data");
+
+        assert_eq!(data.assets.len(), 60);
+        assert_eq!(data.liabilities.len(), 50);
+        assert_eq!(data.market_deck.len(), 25);
+
+        assert!(GameData::from_json("{}").is_err());
+        assert!(data.translations.is_empty());
+        assert_eq!(data.colors, ColorMarker::all());
+    }
+
+    #[test]
+    fn cards_need_a_known_color() {
+        let json = read_to_string("../assets/cards/boardgame.json").expect("could not read data");
+        let mut cards = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        cards["colors"]
+            .as_array_mut()
+            .unwrap()
+            .retain(|c| c["color"] != "Blue");
+
+        assert!(matches!(
+            GameData::from_json(&cards.to_string()),
+            Err(DataParseError::UnknownColor(Color::Blue))
+        ));
+
+        // Data without a list of colors uses the standard ones
+        cards.as_object_mut().unwrap().remove("colors");
+        let data = GameData::from_json(&cards.to_string()).expect("could not parse data");
+        assert_eq!(data.colors, ColorMarker::all());
+    }
+
+    #[test]
+    fn translations_are_loaded() {
+        let json = read_to_string("../assets/cards/boardgame.json").expect("could not read data");
+        let mut cards = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        cards["translations"] = serde_json::json!({
+            "nl": {
+                "titles": { "Stable Market": "Stabiele Markt" },
+                "descriptions": { "Something happened": "Er is iets gebeurd" }
+            }
+        });
+        let data = GameData::from_json(&cards.to_string()).expect("could not parse data");
+        let nl = data.translations("nl").expect("no dutch translations");
+        assert!(data.translations("fr").is_none());
+
+        let mut response = serde_json::json!([
+            { "title": "Stable Market", "rfr": 4 },
+            { "event": { "title": "Unknown", "description": "Something happened" } }
+        ]);
+        nl.localize(&mut response);
+        assert_eq!(response[0]["title"], "Stabiele Markt");
+        assert_eq!(response[1]["event"]["title"], "Unknown");
+        assert_eq!(response[1]["event"]["description"], "Er is iets gebeurd");
+    }
+
+    #[test]
+    fn double_market_conditions_are_loaded() {
+        let json = read_to_string("../assets/cards/boardgame.json").expect("could not read data");
+        let mut cards = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        let markets = cards["deck_list"]["market_events_deck"]["card_list"]
+            .as_array_mut()
+            .unwrap();
+        markets.retain(|card| card.get("market_status").is_some());
+        markets.truncate(1);
+        markets[0]["market_status"]["Red"] = "double up".into();
+        markets[0]["market_status"]["Blue"] = "double down".into();
+
+        let data = GameData::from_json(&cards.to_string()).expect("could not parse data");
+        let market = data
+            .market_deck
+            .deck
+            .iter()
+            .find_map(|card| card.as_ref().left())
+            .unwrap();
+        assert_eq!(market.red, MarketCondition::DoublePlus);
+        assert_eq!(market.blue, MarketCondition::DoubleMinus);
+    }
 }
@@ -8,7 +8,10 @@ use thiserror::Error;
 #[cfg(feature = "ts")]
 use ts_rs::TS;
 
-use crate::player::{AssetPowerup, Character};
+use crate::{
+    game::{DrawRule, PendingDecision, TurnPhase},
+    player::{AssetPowerup, Cash, Character},
+};
 
 /// The main error enum used by the game logic.
 #[cfg_attr(feature = "ts", derive(TS))]
@@ -72,6 +75,14 @@ pub enum GameError {
     #[error(transparent)]
     CardAbility(#[from] AssetAbilityError),
 
+    /// Errors related to the commands of the `dev` feature
+    #[error(transparent)]
+    Dev(#[from] DevError),
+
+    /// Errors related to the options a game is started with
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
     /// Error indicating when a certain index is out of bounds
     #[error("Asset index {0} is invalid")]
     InvalidAssetIndex(u8),
@@ -101,6 +112,42 @@ pub enum GameError {
     #[error("Player should still give back at least one card")]
     PlayerShouldGiveBackCard,
 
+    /// Error indicating that this player should first make the decision they started by using
+    /// their ability
+    #[error("Player should first make their pending decision: {0:?}")]
+    DecisionPending(PendingDecision),
+
+    /// Error indicating that this action makes a decision the player did not start by using their
+    /// ability
+    #[error("Player has no pending decision, use the ability first")]
+    NoDecisionPending,
+
+    /// Error indicating that a request was meant for a turn that already ended, see
+    /// [`Round::turn_nonce`](crate::game::Round::turn_nonce)
+    #[error("Turn nonce {0} does not belong to the current turn")]
+    StaleTurnNonce(u32),
+
+    /// Error indicating that a request which belongs to a turn did not say which turn it was
+    /// meant for, see [`Round::turn_nonce`](crate::game::Round::turn_nonce)
+    #[error("Request is missing the turn nonce")]
+    MissingTurnNonce,
+
+    /// Error indicating that only the host of the game is allowed to take this action
+    #[error("Only the host can do this")]
+    NotHost,
+
+    /// Error indicating that this action is not allowed in the current phase of the turn
+    #[error("Action not allowed in turn phase {0:?}")]
+    WrongTurnPhase(TurnPhase),
+
+    /// Error indicating that a player would end up with more cash than [`Cash::MAX`]
+    #[error("Player cannot hold this much cash")]
+    CashOverflow,
+
+    /// Error indicating that a player does not have enough cash to pay for something
+    #[error("Player does not have enough cash")]
+    NotEnoughCash,
+
     /// Error indicating that this action is only allowed in the lobby state
     #[error("Action only allowed in Lobby state")]
     NotLobbyState,
@@ -146,6 +193,34 @@ pub enum LobbyError {
     /// Username didn't pass validation rules.
     #[error("Username is invalid")]
     InvalidUsername,
+
+    /// Username is empty once spaces around it are removed.
+    #[error("Username is empty")]
+    EmptyUsername,
+
+    /// Username has more characters than the maximum.
+    #[error("Username is longer than {0} characters")]
+    UsernameTooLong(usize),
+
+    /// Username has a character that is not allowed in usernames.
+    #[error("Username cannot contain {0:?}")]
+    UsernameInvalidCharacter(char),
+
+    /// Username was rejected by the username filter of the lobby.
+    #[error("Username is not allowed")]
+    UsernameNotAllowed,
+
+    /// The game was started without any card data to play with.
+    #[error("No card data to start the game with")]
+    MissingGameData,
+
+    /// The game was started while not every player was ready, which the config requires.
+    #[error("Not every player is ready yet")]
+    NotEveryoneReady,
+
+    /// The host tried to kick themselves out of the lobby.
+    #[error("The host cannot kick themselves")]
+    CannotKickSelf,
 }
 
 /// Errors that can happen when someone plays a card.
@@ -165,11 +240,15 @@ pub enum PlayCardError {
     #[error("Already played the maximum allowed number of liabilities")]
     ExceedsMaximumLiabilities,
 
+    /// Player already holds as many liabilities as the game allows.
+    #[error("Already holding the maximum of {0} liabilities")]
+    LiabilityCapReached(u8),
+
     /// Player doesn't have enough cash to afford the asset.
     #[error("{cash} cash is not enough to afford asset worth {cost}")]
     CannotAffordAsset {
         /// The amount of cash a player has
-        cash: u8,
+        cash: Cash,
         /// The cost of the asset
         cost: u8,
     },
@@ -196,7 +275,7 @@ pub enum RedeemLiabilityError {
     #[error("{cash} gold is not enough to redeem liability with value {cost}")]
     NotEnoughCash {
         /// The amount of cash a player has
-        cash: u8,
+        cash: Cash,
         /// The cost of the asset
         cost: u8,
     },
@@ -265,9 +344,9 @@ pub enum PayBankerError {
     #[error("Player tried to pay the banker {got} cash when {expected} was expected")]
     NotRightCashAmount {
         /// Amount of cash expected to be paid.
-        expected: u8,
+        expected: Cash,
         /// Amount of cash that the player tried to pay
-        got: u8,
+        got: Cash,
     },
 }
 
@@ -394,6 +473,9 @@ pub enum DrawCardError {
     /// Character has already drawn the maximum allowed for the turn.
     #[error("Already drew {0} cards, which is the maximum for this character")]
     MaximumCardsDrawn(u8),
+    /// Every card of the deck is held by a player, so there is nothing left to draw.
+    #[error("There are no cards left to draw")]
+    EmptyDeck,
 }
 
 /// Errors that can happen while selecting characters.
@@ -413,6 +495,18 @@ pub enum SelectingCharactersError {
     #[error("Character is not availalble to pick")]
     UnavailableCharacter,
 
+    /// Characters are still being banned, so none can be picked yet.
+    #[error("Characters are still being banned")]
+    BanningCharacters,
+
+    /// All characters were banned already, or nobody bans characters in this game.
+    #[error("Game is not in a state where characters are being banned")]
+    NotBanningCharacters,
+
+    /// Chosen character cannot be banned, because it is the CEO or was banned already.
+    #[error("Character cannot be banned")]
+    UnbannableCharacter,
+
     /// Action is restricted to the chairman.
     #[error("Player is not chairman")]
     NotChairman,
@@ -433,4 +527,44 @@ pub enum AssetAbilityError {
     /// anymore.
     #[error("Player already confirmed choice for asset index {0}")]
     AlreadyConfirmedAssetIndex(u8),
+    /// The parameters do not fit the ability they are meant for.
+    #[error("These parameters cannot be used for card ability '{0:?}'")]
+    InvalidPowerupParams(AssetPowerup),
+}
+
+/// Errors that make a [`GameConfig`](crate::game::GameConfig) unfit to play a game with.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, PartialEq, Error, Serialize, Deserialize)]
+pub enum ConfigError {
+    /// The game cannot be played with this range of players.
+    #[error("Player count should be narrowed down from 4 to 7, {min} to {max} is invalid")]
+    InvalidPlayerCounts {
+        /// The fewest players the config allows.
+        min: u8,
+        /// The most players the config allows.
+        max: u8,
+    },
+    /// The game would end before anyone bought an asset.
+    #[error("At least one asset should have to be bought to end the game")]
+    NoAssetsForEndOfGame,
+    /// A character would give back more cards than they draw.
+    #[error("Cannot give back {} cards when drawing {}", .0.gives_back, .0.draws)]
+    GivesBackMoreThanDrawn(DrawRule),
+}
+
+/// Errors that can happen when using the debug commands of the `dev` feature.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, PartialEq, Error, Serialize, Deserialize)]
+pub enum DevError {
+    /// The game was built without the `dev` feature.
+    #[error("Dev commands are not enabled")]
+    Disabled,
+    /// No asset with this title is left in the deck.
+    #[error("No asset named {0} left in the deck")]
+    UnknownCard(String),
+    /// No market with this title is in the market deck.
+    #[error("No market named {0}")]
+    UnknownMarket(String),
 }
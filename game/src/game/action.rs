@@ -0,0 +1,176 @@
+//! File containing [`PlayerAction`], the game-level representation of every action a player can
+//! take.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use crate::player::*;
+
+/// Every action a player can take during the game. It mirrors the requests the frontend can send,
+/// which allows anything that drives the game, like bots, to describe a move without depending on
+/// the protocol.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "data")]
+pub enum PlayerAction {
+    /// Start the game from the lobby.
+    StartGame,
+    /// Make another player the host of the lobby.
+    TransferHost {
+        /// The player that becomes the host.
+        target: PlayerId,
+    },
+    /// Say whether this player is ready for the game to start.
+    SetReady {
+        /// Whether this player is ready.
+        ready: bool,
+    },
+    /// Change the username of this player in the lobby.
+    ChangeUsername {
+        /// The username to change to.
+        username: String,
+    },
+    /// Select a character.
+    SelectCharacter {
+        /// The character to select.
+        character: Character,
+    },
+    /// Ban a character from this round.
+    BanCharacter {
+        /// The character to ban.
+        character: Character,
+    },
+    /// Draw a card of a particular type.
+    DrawCard {
+        /// The type of card to draw.
+        card_type: CardType,
+    },
+    /// Give back a card that was drawn this turn.
+    PutBackCard {
+        /// The index of the card in the player's hand.
+        card_idx: usize,
+    },
+    /// Buy an asset from the player's hand.
+    BuyAsset {
+        /// The index of the card in the player's hand.
+        card_idx: usize,
+    },
+    /// Issue a liability from the player's hand.
+    IssueLiability {
+        /// The index of the card in the player's hand.
+        card_idx: usize,
+    },
+    /// Redeem an issued liability.
+    RedeemLiability {
+        /// The index of the issued liability.
+        liability_idx: usize,
+    },
+    /// Ask for the options of the player's character ability.
+    UseAbility,
+    /// Collect the bonus cash of the player's character.
+    GetBonusCash,
+    /// Fire a character.
+    FireCharacter {
+        /// The character to fire.
+        character: Character,
+    },
+    /// Terminate the credit of a character.
+    TerminateCreditCharacter {
+        /// The character whose credit is terminated.
+        character: Character,
+    },
+    /// Select an asset to sell to pay off the banker.
+    SelectAssetToDivest {
+        /// The index of the asset.
+        asset_id: usize,
+    },
+    /// Unselect an asset that was selected to pay off the banker.
+    UnselectAssetToDivest {
+        /// The index of the asset.
+        asset_id: usize,
+    },
+    /// Select a liability in the player's hand to issue to pay off the banker.
+    SelectLiabilityToIssue {
+        /// The index of the liability in the player's hand.
+        liability_id: usize,
+    },
+    /// Unselect a liability that was selected to pay off the banker.
+    UnselectLiabilityToIssue {
+        /// The index of the liability in the player's hand.
+        liability_id: usize,
+    },
+    /// Pay the banker.
+    PayBanker {
+        /// The amount of cash to pay.
+        cash: u8,
+    },
+    /// Swap cards in the player's hand with the deck.
+    SwapWithDeck {
+        /// The indices of the cards to swap.
+        card_idxs: Vec<usize>,
+    },
+    /// Swap the player's hand with the hand of another player.
+    SwapWithPlayer {
+        /// The player to swap hands with.
+        target_player_id: PlayerId,
+    },
+    /// Pick an asset of another player to divest, which is divested once confirmed with
+    /// [`PlayerAction::ConfirmDivest`].
+    DivestAsset {
+        /// The player that is forced to divest.
+        target_player_id: PlayerId,
+        /// The index of the asset to divest.
+        card_idx: usize,
+    },
+    /// Divest the asset that was picked with [`PlayerAction::DivestAsset`].
+    ConfirmDivest,
+    /// End the player's turn.
+    EndTurn,
+    /// End the turn of the current player as the host, for example when they stopped playing.
+    /// See [`GameState::force_end_turn`](crate::game::GameState::force_end_turn).
+    ForceEndTurn,
+    /// Turn minus into zero or zero into plus for a color at the end of the game.
+    MinusIntoPlus {
+        /// The color to change.
+        color: Color,
+    },
+    /// Turn the silver of an asset into gold at the end of the game.
+    SilverIntoGold {
+        /// The index of the asset.
+        asset_idx: usize,
+    },
+    /// Change the color of an asset at the end of the game.
+    ChangeAssetColor {
+        /// The index of the asset.
+        asset_idx: usize,
+        /// The new color of the asset.
+        color: Color,
+    },
+    /// Confirm the usage of an asset ability.
+    ConfirmAssetAbility {
+        /// The index of the asset.
+        asset_idx: usize,
+    },
+}
+
+impl PlayerAction {
+    /// Whether this action belongs to the turn it is taken in. Players have to send these along
+    /// with the nonce of that turn, see
+    /// [`GameState::apply_in_turn`](crate::game::GameState::apply_in_turn).
+    pub fn is_turn_scoped(&self) -> bool {
+        matches!(
+            self,
+            Self::DrawCard { .. }
+                | Self::PutBackCard { .. }
+                | Self::BuyAsset { .. }
+                | Self::IssueLiability { .. }
+                | Self::RedeemLiability { .. }
+                | Self::UseAbility
+                | Self::GetBonusCash
+                | Self::EndTurn
+                | Self::ForceEndTurn
+        )
+    }
+}
@@ -8,7 +8,7 @@ use crate::{errors::*, game::*, player::*};
 /// banker target stage, the player that was targeted can elect to issue liabilities and sell off
 /// assets at market value in order to raise cash to pay off the banker. Once they have paid off the
 /// banker, the game moves to a [`Results`] state.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BankerTargetRound {
     pub(super) current_player: PlayerId,
     pub(super) players: Players<BankerTargetPlayer>,
@@ -20,11 +20,21 @@ pub struct BankerTargetRound {
     pub(super) current_events: Vec<Event>,
     pub(super) open_characters: Vec<Character>,
     pub(super) fired_characters: Vec<Character>,
-    pub(super) gold_to_be_paid: u8,
+    pub(super) credit_termination: CreditTermination,
     pub(super) can_pay_banker: bool,
     pub(super) is_final_round: bool,
-    pub(super) selected_assets: HashMap<usize, u8>,
-    pub(super) selected_liabilities: HashMap<usize, u8>,
+    pub(super) selected_assets: BTreeMap<usize, u8>,
+    pub(super) selected_liabilities: BTreeMap<usize, u8>,
+    pub(super) config: GameConfig,
+    pub(super) data_checksum: u64,
+    pub(super) clock: Option<ChessClock>,
+    pub(super) host: Option<PlayerId>,
+    pub(super) round_number: u8,
+    pub(super) character_stats: CharacterStatsMap,
+    #[serde(default)]
+    pub(super) spectators: Spectators,
+    #[serde(default)]
+    pub(super) skip_reasons: Vec<(Character, SkipReason)>,
 }
 
 impl BankerTargetRound {
@@ -45,7 +55,12 @@ impl BankerTargetRound {
     /// The banker gets paid one + one per different color asset their target owns. This function
     /// Retrieves that amount of gold.
     pub fn gold_to_be_paid(&self) -> u8 {
-        self.gold_to_be_paid
+        self.credit_termination.total_gold
+    }
+
+    /// Gets how the amount of gold owed to the banker is made up, see [`CreditTermination`].
+    pub fn credit_termination(&self) -> &CreditTermination {
+        &self.credit_termination
     }
 
     /// This field checks whether or not a player can actually pay the banker with cash, by selling
@@ -59,6 +74,69 @@ impl BankerTargetRound {
     pub fn players(&self) -> &[BankerTargetPlayer] {
         self.players.players()
     }
+
+    /// Gets the options the game is played with.
+    pub fn config(&self) -> GameConfig {
+        self.config
+    }
+
+    /// Gets the checksum of the card data the game is played with, see
+    /// [`GameData::checksum`](crate::cards::GameData::checksum).
+    pub fn data_checksum(&self) -> u64 {
+        self.data_checksum
+    }
+
+    /// Gets the time each player has left, if the game is played with a time bank.
+    pub fn clock(&self) -> Option<&ChessClock> {
+        self.clock.as_ref()
+    }
+
+    /// Gets the id of the player that hosted the lobby, see [`Lobby::host`].
+    pub fn host(&self) -> Option<PlayerId> {
+        self.host
+    }
+
+    /// Gets which round of the game this is, counting from 1.
+    pub fn round_number(&self) -> u8 {
+        self.round_number
+    }
+
+    /// Gets the current market.
+    pub fn current_market(&self) -> &Market {
+        &self.current_market
+    }
+
+    /// Gets the events that happened so far this game, oldest first.
+    pub fn current_events(&self) -> &[Event] {
+        &self.current_events
+    }
+
+    /// Gets the [`PlayerInfo`] for each player, excluding the player that has the same id as `id`.
+    pub fn player_info(&self, id: PlayerId) -> Vec<PlayerInfo> {
+        self.spectator_info()
+            .into_iter()
+            .filter(|p| p.id != id)
+            .collect()
+    }
+
+    /// Gets the [`PlayerInfo`] for each player, as a spectator sees it. Characters that did not
+    /// have their turn yet are still closed, so they are left out.
+    pub fn spectator_info(&self) -> Vec<PlayerInfo> {
+        self.players()
+            .iter()
+            .map(|p| {
+                let mut info = PlayerInfo::from(p)
+                    .with_market(self.current_market())
+                    .with_liability_cap(&self.config)
+                    .redact(&self.config);
+                if p.character() > self.current_player().character() {
+                    info.character = None;
+                }
+                info
+            })
+            .collect()
+    }
+
     /// Get a reference to a [`BankerTargetPlayer`] based on a specific `name`.
     pub fn player_by_name(&self, name: &str) -> Result<&BankerTargetPlayer, GameError> {
         self.players()
@@ -67,12 +145,37 @@ impl BankerTargetRound {
             .ok_or_else(|| GameError::InvalidPlayerName(name.to_owned()))
     }
 
+    /// Sets a player as disconnected
+    pub fn leave(&mut self, id: PlayerId) -> Result<(), GameError> {
+        self.players.player_mut(id)?.set_is_human(false);
+        Ok(())
+    }
+
+    /// Checks whether paying the banker `cash` pays exactly what is owed, see
+    /// [`BankerTargetRound::gold_to_be_paid`]. If it does not, the error carries the amount that is
+    /// owed, so clients do not have to work it out themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PayBankerError::NotRightCashAmount`] if `cash` is not the amount that is owed.
+    pub fn check_payment(&self, cash: u8) -> Result<(), PayBankerError> {
+        match cash == self.credit_termination.total_gold {
+            true => Ok(()),
+            false => Err(PayBankerError::NotRightCashAmount {
+                expected: self.credit_termination.total_gold.into(),
+                got: cash.into(),
+            }),
+        }
+    }
+
     /// function to pay the banker and switch game back to a normal round state
     pub fn player_pay_banker(
         &mut self,
         player_id: PlayerId,
         cash: u8,
     ) -> Result<PayBankerPlayer, GameError> {
+        self.check_payment(cash)?;
+
         let banker_id = self
             .players()
             .iter()
@@ -85,35 +188,36 @@ impl BankerTargetRound {
             .get_disjoint_mut([usize::from(player_id), usize::from(banker_id)])
         {
             Ok([player, banker]) => {
-                if cash == self.gold_to_be_paid {
-                    if self.can_pay_banker {
-                        let pbp = player.pay_banker(
-                            cash,
-                            &self.selected_assets,
-                            &self.selected_liabilities,
-                            banker,
-                        )?;
-                        Ok(pbp)
-                    } else {
-                        let pbp = player.go_bankrupt_for_banker(
-                            cash,
-                            banker,
-                            self.current_market.clone(),
-                        )?;
-                        Ok(pbp)
-                    }
+                if self.can_pay_banker {
+                    let pbp = player.pay_banker(
+                        cash,
+                        &self.selected_assets,
+                        &self.selected_liabilities,
+                        banker,
+                        &mut self.assets,
+                        &mut self.liabilities,
+                    )?;
+                    Ok(pbp)
                 } else {
-                    Err(PayBankerError::NotRightCashAmount {
-                        expected: self.gold_to_be_paid,
-                        got: cash,
-                    }
-                    .into())
+                    let pbp = player.go_bankrupt_for_banker(
+                        cash,
+                        banker,
+                        self.current_market.clone(),
+                        &mut self.assets,
+                        &mut self.liabilities,
+                    )?;
+                    Ok(pbp)
                 }
             }
             Err(_) => Err(PayBankerError::NoBankerPlayer.into()),
         }
     }
 
+    /// Gets the assets and liabilities the targeted player has selected so far to pay the banker.
+    pub fn selected_cards(&self) -> SelectedAssetsAndLiabilities {
+        self.create_select_assets_liabilities()
+    }
+
     fn create_select_assets_liabilities(&self) -> SelectedAssetsAndLiabilities {
         // PANIC: unwrap is always valid because current player is always valid.
         let target = self.player(self.current_player).unwrap();
@@ -223,14 +327,7 @@ impl BankerTargetRound {
 // TODO: refactor
 impl From<&mut Round> for BankerTargetRound {
     fn from(round: &mut Round) -> Self {
-        let color_array: Vec<Color> = round
-            .current_player()
-            .assets()
-            .iter()
-            .map(|a| a.color)
-            .collect();
-
-        let gold_to_be_paid = color_array.iter().collect::<HashSet<_>>().len() as u8 + 1;
+        let credit_termination = CreditTermination::new(round.current_player().assets());
         let asset_values: Vec<u8> = round
             .current_player()
             .assets()
@@ -274,11 +371,23 @@ impl From<&mut Round> for BankerTargetRound {
             open_characters: round.open_characters.clone(),
             fired_characters: round.fired_characters.clone(),
             is_final_round: round.is_final_round,
-            gold_to_be_paid,
-            can_pay_banker: gold_to_be_paid
-                <= total_libility_value + total_asset_value + round.current_player().cash(),
-            selected_assets: HashMap::new(),
-            selected_liabilities: HashMap::new(),
+            can_pay_banker: round
+                .current_player()
+                .cash()
+                .saturating_add(total_libility_value)
+                .saturating_add(total_asset_value)
+                .covers(credit_termination.total_gold),
+            credit_termination,
+            selected_assets: BTreeMap::new(),
+            selected_liabilities: BTreeMap::new(),
+            config: round.config,
+            data_checksum: round.data_checksum,
+            clock: round.clock.clone(),
+            host: round.host,
+            round_number: round.round_number,
+            character_stats: round.character_stats.clone(),
+            spectators: round.spectators.clone(),
+            skip_reasons: round.skip_reasons.clone(),
         }
     }
 }
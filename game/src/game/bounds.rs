@@ -0,0 +1,262 @@
+//! File containing [`GameState::check_indices`], which checks every index a client sends along
+//! with an action before the game is changed. Indices are checked in one place rather than at
+//! every use, so none of the actions can be tricked into panicking or acting on the wrong card.
+
+use crate::{errors::*, game::*};
+
+impl GameState {
+    /// Checks whether every index in `action` of player `id` points at something that exists in
+    /// the current state. Returns the action with its indices normalized, which means the indices
+    /// of [`PlayerAction::SwapWithDeck`] are sorted. Actions that are not allowed in the current
+    /// state are returned as is, since applying them fails regardless.
+    pub fn check_indices(
+        &self,
+        id: PlayerId,
+        action: PlayerAction,
+    ) -> Result<PlayerAction, GameError> {
+        use PlayerAction as A;
+
+        match (self, action) {
+            (GameState::Round(round), action) => {
+                let player = round.player(id)?;
+                let hand = player.hand().len();
+
+                match action {
+                    A::PutBackCard { card_idx } => {
+                        check_index(card_idx, hand, GiveBackCardError::InvalidCardIndex)?;
+                        Ok(action)
+                    }
+                    A::BuyAsset { card_idx } | A::IssueLiability { card_idx } => {
+                        check_index(card_idx, hand, PlayCardError::InvalidCardIndex)?;
+                        Ok(action)
+                    }
+                    A::RedeemLiability { liability_idx } => {
+                        check_index(
+                            liability_idx,
+                            player.liabilities().len(),
+                            RedeemLiabilityError::InvalidLiabilityIndex,
+                        )?;
+                        Ok(action)
+                    }
+                    A::SwapWithDeck { card_idxs } => Ok(A::SwapWithDeck {
+                        card_idxs: normalize_card_idxs(card_idxs, hand)?,
+                    }),
+                    A::SwapWithPlayer { target_player_id } => {
+                        round.player(target_player_id)?;
+                        Ok(action)
+                    }
+                    A::DivestAsset {
+                        target_player_id,
+                        card_idx,
+                    } => {
+                        let target = round.player(target_player_id)?;
+                        check_index(card_idx, target.assets().len(), |_| {
+                            DivestAssetError::InvalidCardIdx
+                        })?;
+                        Ok(action)
+                    }
+                    action => Ok(action),
+                }
+            }
+            (GameState::BankerTarget(round), action) => {
+                let player = round.player(id)?;
+
+                match action {
+                    A::SelectAssetToDivest { asset_id } | A::UnselectAssetToDivest { asset_id } => {
+                        check_index(
+                            asset_id,
+                            player.assets().len(),
+                            BankerTargetSelectError::InvalidAssetId,
+                        )?;
+                        Ok(action)
+                    }
+                    A::SelectLiabilityToIssue { liability_id }
+                    | A::UnselectLiabilityToIssue { liability_id } => {
+                        check_index(
+                            liability_id,
+                            player.hand().len(),
+                            BankerTargetSelectError::InvalidLiabilityId,
+                        )?;
+                        Ok(action)
+                    }
+                    action => Ok(action),
+                }
+            }
+            (GameState::Results(results), action) => {
+                let assets = results.player(id)?.assets().len();
+
+                match action {
+                    A::SilverIntoGold { asset_idx }
+                    | A::ChangeAssetColor { asset_idx, .. }
+                    | A::ConfirmAssetAbility { asset_idx } => {
+                        check_index(asset_idx, assets, GameError::InvalidAssetIndex)?;
+                        Ok(action)
+                    }
+                    action => Ok(action),
+                }
+            }
+            (_, action) => Ok(action),
+        }
+    }
+}
+
+/// Checks whether `idx` is a valid index into something of length `len`, turning it into `error`
+/// if not. Indices that do not fit in the error are reported as [`u8::MAX`].
+fn check_index<E: Into<GameError>>(
+    idx: usize,
+    len: usize,
+    error: impl FnOnce(u8) -> E,
+) -> Result<usize, GameError> {
+    if idx < len {
+        Ok(idx)
+    } else {
+        Err(error(u8::try_from(idx).unwrap_or(u8::MAX)).into())
+    }
+}
+
+/// Sorts `card_idxs` and checks whether each of them is a unique, valid index into a hand of
+/// length `len`.
+fn normalize_card_idxs(mut card_idxs: Vec<usize>, len: usize) -> Result<Vec<usize>, SwapError> {
+    card_idxs.sort_unstable();
+
+    let in_bounds = card_idxs.last().is_none_or(|&last| last < len);
+    let unique = card_idxs.windows(2).all(|w| w[0] != w[1]);
+    if in_bounds && unique {
+        Ok(card_idxs)
+    } else {
+        Err(SwapError::InvalidCardIdxs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::GameData;
+    use claim::*;
+
+    #[test]
+    fn check_index_bounds() {
+        for len in 0..8 {
+            for idx in 0..len {
+                assert_eq!(check_index(idx, len, GameError::InvalidAssetIndex), Ok(idx));
+            }
+            assert_eq!(
+                check_index(len, len, GameError::InvalidAssetIndex),
+                Err(GameError::InvalidAssetIndex(len as u8))
+            );
+        }
+
+        assert_eq!(
+            check_index(256, 3, GameError::InvalidAssetIndex),
+            Err(GameError::InvalidAssetIndex(u8::MAX))
+        );
+        assert_eq!(
+            check_index(usize::MAX, usize::MAX, GameError::InvalidAssetIndex),
+            Err(GameError::InvalidAssetIndex(u8::MAX))
+        );
+    }
+
+    #[test]
+    fn normalize_card_idxs_bounds() {
+        assert_eq!(normalize_card_idxs(vec![], 0), Ok(vec![]));
+        assert_eq!(normalize_card_idxs(vec![2, 0, 1], 3), Ok(vec![0, 1, 2]));
+        assert_eq!(normalize_card_idxs(vec![0], 1), Ok(vec![0]));
+
+        // The last index of a hand is its length minus one
+        assert_eq!(
+            normalize_card_idxs(vec![3], 3),
+            Err(SwapError::InvalidCardIdxs)
+        );
+        assert_eq!(
+            normalize_card_idxs(vec![0], 0),
+            Err(SwapError::InvalidCardIdxs)
+        );
+        assert_eq!(
+            normalize_card_idxs(vec![1, 1], 3),
+            Err(SwapError::InvalidCardIdxs)
+        );
+        assert_eq!(
+            normalize_card_idxs(vec![0, usize::MAX], 3),
+            Err(SwapError::InvalidCardIdxs)
+        );
+    }
+
+    #[test]
+    fn actions_are_checked_against_hand() {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        lobby.set_game_data(GameData::new("../assets/cards/boardgame.json").unwrap());
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let round = state.round().unwrap();
+        let id = round.current_player().id();
+        let hand = round.current_player().hand().len();
+        assert!(hand > 0);
+
+        assert_ok!(state.check_indices(id, PlayerAction::BuyAsset { card_idx: hand - 1 }));
+        assert_eq!(
+            state.check_indices(id, PlayerAction::BuyAsset { card_idx: hand }),
+            Err(PlayCardError::InvalidCardIndex(hand as u8).into())
+        );
+        assert_eq!(
+            state.check_indices(
+                id,
+                PlayerAction::PutBackCard {
+                    card_idx: usize::MAX
+                }
+            ),
+            Err(GiveBackCardError::InvalidCardIndex(u8::MAX).into())
+        );
+        assert_eq!(
+            state.check_indices(id, PlayerAction::RedeemLiability { liability_idx: 0 }),
+            Err(RedeemLiabilityError::InvalidLiabilityIndex(0).into())
+        );
+        assert_eq!(
+            state.check_indices(
+                id,
+                PlayerAction::SwapWithDeck {
+                    card_idxs: vec![hand - 1, 0]
+                }
+            ),
+            Ok(PlayerAction::SwapWithDeck {
+                card_idxs: vec![0, hand - 1]
+            })
+        );
+        assert_eq!(
+            state.check_indices(
+                id,
+                PlayerAction::SwapWithDeck {
+                    card_idxs: vec![hand]
+                }
+            ),
+            Err(SwapError::InvalidCardIdxs.into())
+        );
+        assert_eq!(
+            state.check_indices(
+                id,
+                PlayerAction::DivestAsset {
+                    target_player_id: id,
+                    card_idx: 0
+                }
+            ),
+            Err(DivestAssetError::InvalidCardIdx.into())
+        );
+        assert_eq!(
+            state.check_indices(
+                id,
+                PlayerAction::SwapWithPlayer {
+                    target_player_id: PlayerId(7)
+                }
+            ),
+            Err(GameError::InvalidPlayerIndex(7))
+        );
+    }
+}
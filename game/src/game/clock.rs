@@ -0,0 +1,63 @@
+//! File containing the [`ChessClock`], which limits how much time each player may spend on all of
+//! their turns combined. The game itself does not keep time, whoever hosts the game measures how
+//! long a turn took and charges it with [`GameState::spend_time`].
+
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::game::*;
+
+/// The time each player has left for the rest of the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChessClock {
+    remaining: BTreeMap<PlayerId, Duration>,
+}
+
+impl ChessClock {
+    /// Creates a new clock that gives each of `players` a time bank of `bank`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use game::game::ChessClock;
+    /// # use game::player::PlayerId;
+    /// let clock = ChessClock::new([PlayerId(0)], Duration::from_secs(60));
+    /// assert_eq!(clock.remaining(PlayerId(0)), Some(Duration::from_secs(60)));
+    /// assert_eq!(clock.remaining(PlayerId(1)), None);
+    /// ```
+    pub fn new(players: impl IntoIterator<Item = PlayerId>, bank: Duration) -> Self {
+        Self {
+            remaining: players.into_iter().map(|id| (id, bank)).collect(),
+        }
+    }
+
+    /// Gets the time player `id` has left, if they are on the clock.
+    pub fn remaining(&self, id: PlayerId) -> Option<Duration> {
+        self.remaining.get(&id).copied()
+    }
+
+    /// Takes `elapsed` from the time bank of player `id`, and gets the time they have left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use game::game::ChessClock;
+    /// # use game::player::PlayerId;
+    /// let mut clock = ChessClock::new([PlayerId(0)], Duration::from_secs(60));
+    /// assert_eq!(clock.spend(PlayerId(0), Duration::from_secs(45)), Some(Duration::from_secs(15)));
+    /// assert_eq!(clock.spend(PlayerId(0), Duration::from_secs(45)), Some(Duration::ZERO));
+    /// assert!(clock.has_run_out(PlayerId(0)));
+    /// ```
+    pub fn spend(&mut self, id: PlayerId, elapsed: Duration) -> Option<Duration> {
+        let remaining = self.remaining.get_mut(&id)?;
+        *remaining = remaining.saturating_sub(elapsed);
+        Some(*remaining)
+    }
+
+    /// Checks whether player `id` has no time left, in which case all of their turns are ended
+    /// automatically.
+    pub fn has_run_out(&self, id: PlayerId) -> bool {
+        self.remaining(id).is_some_and(|r| r.is_zero())
+    }
+}
@@ -0,0 +1,274 @@
+//! File containing [`GameConfig`], the options a game can be played with. The config is chosen in
+//! the [`Lobby`] and carried along to every state after it.
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use crate::{
+    errors::ConfigError,
+    game::{ASSETS_FOR_END_OF_GAME, MAX_PLAYERS, MIN_PLAYERS, STARTING_GOLD, Scoring},
+    player::Character,
+};
+
+/// Options that change how a game is played.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// Whether the exact cash of other players is hidden. If so, the [`PlayerInfo`] of other
+    /// players only contains a [`CashBracket`](crate::player::CashBracket).
+    ///
+    /// [`PlayerInfo`]: crate::player::PlayerInfo
+    #[serde(default)]
+    pub hide_cash: bool,
+    /// The time in seconds each player has for all of their turns combined, if limited. See
+    /// [`ChessClock`](crate::game::ChessClock).
+    #[serde(default)]
+    pub time_bank: Option<u32>,
+    /// The time in seconds each turn may take, if limited. Unlike the time bank, this starts over
+    /// every turn. See [`Round::time_remaining`](crate::game::Round::time_remaining).
+    #[serde(default)]
+    pub turn_time: Option<u32>,
+    /// Whether actions during a round should follow the [`TurnPhase`] of the current player, so
+    /// all cards are drawn and given back before any of them are played.
+    #[serde(default)]
+    pub strict_phases: bool,
+    /// The most liabilities a player may hold at once, if limited. Issuing a liability past the
+    /// cap is rejected with [`PlayCardError::LiabilityCapReached`].
+    ///
+    /// [`PlayCardError::LiabilityCapReached`]: crate::errors::PlayCardError::LiabilityCapReached
+    #[serde(default)]
+    pub liability_cap: Option<u8>,
+    /// The formula players are scored with at the end of the game.
+    #[serde(default)]
+    pub scoring: Scoring,
+    /// Whether players are seated in a random order when the game starts, instead of in the order
+    /// they joined. Seats decide the ids of players and who is chairman first. Only has an effect
+    /// with the `shuffle` feature.
+    #[serde(default)]
+    pub shuffle_seats: bool,
+    /// Whether redeeming a liability costs its rfr% in cash for every round it was held, on top of
+    /// its value. See [`Liability::redemption_cost`](crate::player::Liability::redemption_cost).
+    #[serde(default)]
+    pub redemption_interest: bool,
+    /// Whether the lobby opted out of the anonymized gameplay events servers can collect for
+    /// balance analysis.
+    #[serde(default)]
+    pub telemetry_opt_out: bool,
+    /// Who bans a character from each round before characters are dealt, if anyone.
+    #[serde(default)]
+    pub character_bans: CharacterBans,
+    /// How many cards each character draws and gives back during their turn.
+    #[serde(default)]
+    pub draw_rules: DrawRules,
+    /// The round after which the game ends, if limited, even if nobody bought
+    /// [`ASSETS_FOR_END_OF_GAME`](crate::game::ASSETS_FOR_END_OF_GAME) assets. This keeps games
+    /// where nobody gets ahead from going on forever.
+    #[serde(default)]
+    pub max_rounds: Option<u8>,
+    /// The cash every player starts the game with, if different from
+    /// [`STARTING_GOLD`](crate::game::STARTING_GOLD).
+    #[serde(default)]
+    pub starting_gold: Option<u8>,
+    /// The number of assets a player has to buy for the round to be the final one, if different
+    /// from [`ASSETS_FOR_END_OF_GAME`](crate::game::ASSETS_FOR_END_OF_GAME).
+    #[serde(default)]
+    pub assets_for_end_of_game: Option<u8>,
+    /// Whether the game can only be started once every player in the lobby said they are ready,
+    /// see [`Lobby::set_ready`](crate::game::Lobby::set_ready).
+    #[serde(default)]
+    pub require_ready: bool,
+    /// The fewest players the game can be started with, if more than
+    /// [`MIN_PLAYERS`](crate::game::MIN_PLAYERS).
+    #[serde(default)]
+    pub min_players: Option<u8>,
+    /// The most players the game can be started with, if fewer than
+    /// [`MAX_PLAYERS`](crate::game::MAX_PLAYERS).
+    #[serde(default)]
+    pub max_players: Option<u8>,
+}
+
+impl GameConfig {
+    /// Gets the cash every player starts the game with.
+    pub fn starting_cash(&self) -> u8 {
+        self.starting_gold.unwrap_or(STARTING_GOLD)
+    }
+
+    /// Gets the number of assets a player has to buy for the round to be the final one.
+    pub fn assets_to_end_game(&self) -> usize {
+        self.assets_for_end_of_game
+            .map_or(ASSETS_FOR_END_OF_GAME, usize::from)
+    }
+
+    /// Gets how many players the game can be started with.
+    ///
+    /// ```
+    /// # use game::game::GameConfig;
+    /// let config = GameConfig {
+    ///     min_players: Some(5),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(config.player_counts(), 5..=7);
+    /// ```
+    pub fn player_counts(&self) -> RangeInclusive<usize> {
+        let min = self.min_players.map_or(MIN_PLAYERS, usize::from);
+        let max = self.max_players.map_or(MAX_PLAYERS, usize::from);
+        min..=max
+    }
+
+    /// Checks whether a game can be played with this config. Player counts can only be narrowed
+    /// down, since there are only enough characters for 4 to 7 players.
+    ///
+    /// ```
+    /// # use game::{errors::ConfigError, game::GameConfig};
+    /// assert_eq!(GameConfig::default().validate(), Ok(()));
+    ///
+    /// let config = GameConfig {
+    ///     min_players: Some(3),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     config.validate(),
+    ///     Err(ConfigError::InvalidPlayerCounts { min: 3, max: 7 })
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let counts = self.player_counts();
+        if counts.is_empty() || *counts.start() < MIN_PLAYERS || *counts.end() > MAX_PLAYERS {
+            return Err(ConfigError::InvalidPlayerCounts {
+                min: *counts.start() as u8,
+                max: *counts.end() as u8,
+            });
+        }
+        if self.assets_to_end_game() == 0 {
+            return Err(ConfigError::NoAssetsForEndOfGame);
+        }
+        if let Some(rule) = [self.draw_rules.default, self.draw_rules.head_rnd]
+            .into_iter()
+            .find(|rule| rule.gives_back > rule.draws)
+        {
+            return Err(ConfigError::GivesBackMoreThanDrawn(rule));
+        }
+
+        Ok(())
+    }
+}
+
+/// How many cards a character draws during their turn, and how many of those they give back.
+/// Cards are given back as they are drawn, so with the default of drawing three and giving one
+/// back, a card is owed after the third card is drawn.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawRule {
+    /// The number of cards drawn each turn.
+    pub draws: u8,
+    /// The number of drawn cards that are given back each turn.
+    pub gives_back: u8,
+}
+
+impl DrawRule {
+    /// Gets the number of cards that have to be given back after `drawn` cards were drawn, which
+    /// grows in proportion to the cards drawn.
+    ///
+    /// ```
+    /// # use game::game::DrawRule;
+    /// let rule = DrawRule { draws: 6, gives_back: 4 };
+    /// assert_eq!(rule.owed_after(2), 1);
+    /// assert_eq!(rule.owed_after(6), 4);
+    /// assert_eq!(DrawRule { draws: 2, gives_back: 0 }.owed_after(2), 0);
+    /// ```
+    pub fn owed_after(self, drawn: u8) -> u8 {
+        match self.draws {
+            0 => 0,
+            draws => (u16::from(drawn) * u16::from(self.gives_back) / u16::from(draws)) as u8,
+        }
+    }
+}
+
+/// The [`DrawRule`] of every character. The [`HeadRnD`](Character::HeadRnD) draws more cards
+/// than the others, so they have a rule of their own.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawRules {
+    /// The rule of every character except the head of R&D.
+    pub default: DrawRule,
+    /// The rule of the head of R&D.
+    pub head_rnd: DrawRule,
+}
+
+impl DrawRules {
+    /// Gets the [`DrawRule`] `character` plays by.
+    pub fn for_character(&self, character: Character) -> DrawRule {
+        match character {
+            Character::HeadRnD => self.head_rnd,
+            _ => self.default,
+        }
+    }
+}
+
+impl Default for DrawRules {
+    fn default() -> Self {
+        let rule = |character: Character| DrawRule {
+            draws: character.draws_n_cards(),
+            gives_back: character.gives_back_n_cards(),
+        };
+
+        Self {
+            default: rule(Character::CEO),
+            head_rnd: rule(Character::HeadRnD),
+        }
+    }
+}
+
+/// Who bans characters at the start of each selecting characters phase. A banned character cannot
+/// be selected that round, and takes the place of one of the open characters. Since there have to
+/// be enough characters left for everyone to select one, there are never more bans than
+/// [`CharacterBans::max_bans`] allows.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CharacterBans {
+    /// Nobody bans a character.
+    #[default]
+    Off,
+    /// The chairman bans one character.
+    Chairman,
+    /// Every player bans one character, in turn order starting with the chairman.
+    Everyone,
+}
+
+impl CharacterBans {
+    /// Gets the most characters that can be banned in a game with `player_count` players. One
+    /// character is always dealt closed, and every player has to be able to select one of the
+    /// others.
+    pub const fn max_bans(player_count: usize) -> usize {
+        Character::CHARACTERS
+            .len()
+            .saturating_sub(player_count)
+            .saturating_sub(1)
+    }
+
+    /// Gets the number of characters that are banned each round in a game with `player_count`
+    /// players.
+    ///
+    /// ```
+    /// # use game::game::CharacterBans;
+    /// assert_eq!(CharacterBans::Off.bans(4), 0);
+    /// assert_eq!(CharacterBans::Chairman.bans(4), 1);
+    /// assert_eq!(CharacterBans::Everyone.bans(4), 3);
+    /// assert_eq!(CharacterBans::Everyone.bans(7), 0);
+    /// ```
+    pub fn bans(self, player_count: usize) -> usize {
+        let bans = match self {
+            Self::Off => 0,
+            Self::Chairman => 1,
+            Self::Everyone => player_count,
+        };
+        bans.min(Self::max_bans(player_count))
+    }
+}
@@ -0,0 +1,95 @@
+//! File containing [`GameDebugger`], which lets developers step through a recorded game to find
+//! where it went wrong, for example one reconstructed from the actions of a bug report.
+
+use crate::{game::*, player::PlayerId};
+
+use super::outcome::Snapshot;
+
+/// Steps through a [`Replay`], rebuilding the game as it was after any of its actions.
+#[derive(Debug, Clone)]
+pub struct GameDebugger {
+    replay: Replay,
+}
+
+impl GameDebugger {
+    /// Creates a debugger for `replay`.
+    pub fn new(replay: Replay) -> Self {
+        Self { replay }
+    }
+
+    /// Creates a debugger for the game that starts at `start` and in which `actions` are taken in
+    /// order, with every random choice drawn from `seed`. See [`Replay::record`].
+    pub fn from_actions(
+        seed: u64,
+        start: GameState,
+        actions: impl IntoIterator<Item = (PlayerId, PlayerAction)>,
+    ) -> Result<Self, ReplayError> {
+        let mut actions = actions.into_iter();
+        Replay::record(seed, start, |_| actions.next()).map(Self::new)
+    }
+
+    /// Gets how many actions the game has.
+    pub fn len(&self) -> usize {
+        self.replay.steps().len()
+    }
+
+    /// Checks whether the game has no actions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the action at `action_index` and the player that took it.
+    pub fn action(&self, action_index: usize) -> Option<&ReplayStep> {
+        self.replay.steps().get(action_index)
+    }
+
+    /// Gets the state of the game after the first `action_index` actions were taken, so index `0`
+    /// is the state the game started in. Fails if the game does not end up in the state it was
+    /// recorded in along the way.
+    pub fn state_at(&self, action_index: usize) -> Result<GameState, ReplayError> {
+        self.replay.play_to(action_index)
+    }
+
+    /// Gets everything that changed between `a` and `b`, like the cash of the players and the
+    /// cards they hold. See [`ActionOutcome`].
+    pub fn diff(a: &GameState, b: &GameState) -> Vec<ActionOutcome> {
+        Snapshot::new(a).outcomes(&Snapshot::new(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::GameData;
+    use claim::*;
+
+    #[test]
+    fn steps_through_game() {
+        let mut start = GameState::new();
+        let lobby = start.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        lobby.set_game_data(GameData::new("../assets/cards/boardgame.json").unwrap());
+
+        let debugger = assert_ok!(GameDebugger::from_actions(
+            3,
+            start.clone(),
+            [(PlayerId(0), PlayerAction::StartGame)]
+        ));
+        assert_eq!(debugger.len(), 1);
+        assert_eq!(assert_ok!(debugger.state_at(0)), start);
+
+        let started = assert_ok!(debugger.state_at(1));
+        let id = started
+            .selecting_characters()
+            .unwrap()
+            .currently_selecting_id();
+        assert!(
+            GameDebugger::diff(&start, &started).contains(&ActionOutcome::TurnChanged {
+                player_id: Some(id)
+            })
+        );
+        assert_eq!(debugger.state_at(2), Err(ReplayError::NoSuchStep(2)));
+    }
+}
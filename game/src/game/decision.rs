@@ -0,0 +1,143 @@
+//! File containing [`PendingDecision`], the choice the current player of a [`Round`] still has to
+//! make after using their character ability. While a decision is pending, the round only accepts
+//! actions that fit it, so requests that arrive out of flow are rejected by the game itself.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use crate::{errors::*, game::*, player::*};
+
+/// A choice the current player started by using their ability, but did not make yet.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PendingDecision {
+    /// The shareholder should pick a character to fire.
+    FireCharacter,
+    /// The banker should pick a character whose credit is terminated.
+    TerminateCredit,
+    /// The regulator should swap cards with either the deck or another player.
+    RegulatorSwap,
+    /// The stakeholder should pick an asset of another player to divest.
+    DivestAsset,
+    /// The stakeholder picked an asset to divest, and should confirm it. They may also pick
+    /// another asset instead.
+    ConfirmDivest(PendingDivest),
+}
+
+impl PendingDecision {
+    /// Gets the decision a player with `character` has to make after using their ability, if any.
+    pub fn for_ability(character: Character) -> Option<Self> {
+        match character {
+            Character::Shareholder => Some(Self::FireCharacter),
+            Character::Banker => Some(Self::TerminateCredit),
+            Character::Regulator => Some(Self::RegulatorSwap),
+            Character::Stakeholder => Some(Self::DivestAsset),
+            _ => None,
+        }
+    }
+
+    /// Gets the decision `action` makes, if it makes one.
+    pub fn answered_by(action: &PlayerAction) -> Option<Self> {
+        match action {
+            PlayerAction::FireCharacter { .. } => Some(Self::FireCharacter),
+            PlayerAction::TerminateCreditCharacter { .. } => Some(Self::TerminateCredit),
+            PlayerAction::SwapWithDeck { .. } | PlayerAction::SwapWithPlayer { .. } => {
+                Some(Self::RegulatorSwap)
+            }
+            PlayerAction::DivestAsset { .. } => Some(Self::DivestAsset),
+            _ => None,
+        }
+    }
+
+    /// Checks whether `action` may be taken while this decision is pending. Besides making the
+    /// decision, the player may ask for their options again or end their turn to skip it.
+    pub fn allows(self, action: &PlayerAction) -> bool {
+        match (self, action) {
+            (_, PlayerAction::UseAbility | PlayerAction::EndTurn) => true,
+            (
+                Self::ConfirmDivest(_),
+                PlayerAction::ConfirmDivest | PlayerAction::DivestAsset { .. },
+            ) => true,
+            (decision, action) => Self::answered_by(action) == Some(decision),
+        }
+    }
+}
+
+impl Round {
+    /// Gets the decision the current player still has to make, if any.
+    pub fn pending_decision(&self) -> Option<PendingDecision> {
+        self.pending_decision
+    }
+
+    /// Checks whether player `id` may take `action` given the decision that is pending. Actions
+    /// that make a decision are only accepted after the player used their ability, and no other
+    /// actions are accepted until the decision is made. Actions of players whose turn it is not
+    /// are left for the round to reject.
+    pub fn check_pending_decision(
+        &self,
+        id: PlayerId,
+        action: &PlayerAction,
+    ) -> Result<(), GameError> {
+        if id != self.current_player {
+            return Ok(());
+        }
+
+        match self.pending_decision {
+            Some(decision) if !decision.allows(action) => Err(GameError::DecisionPending(decision)),
+            None if PendingDecision::answered_by(action).is_some() => {
+                Err(GameError::NoDecisionPending)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Updates the pending decision after `action` of the current player succeeded.
+    pub(super) fn update_pending_decision(&mut self, action: &PlayerAction) {
+        match action {
+            PlayerAction::UseAbility => {
+                self.pending_decision =
+                    PendingDecision::for_ability(self.current_player().character());
+            }
+            PlayerAction::EndTurn => self.pending_decision = None,
+            // Picking an asset to divest and confirming it keep track of the decision themselves
+            PlayerAction::DivestAsset { .. } | PlayerAction::ConfirmDivest => {}
+            action if PendingDecision::answered_by(action).is_some() => {
+                self.pending_decision = None
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decisions_allow_their_answers() {
+        let fire = PlayerAction::FireCharacter {
+            character: Character::CEO,
+        };
+        let swap = PlayerAction::SwapWithDeck { card_idxs: vec![] };
+
+        assert!(PendingDecision::FireCharacter.allows(&fire));
+        assert!(PendingDecision::FireCharacter.allows(&PlayerAction::UseAbility));
+        assert!(PendingDecision::FireCharacter.allows(&PlayerAction::EndTurn));
+        assert!(!PendingDecision::FireCharacter.allows(&swap));
+        assert!(!PendingDecision::FireCharacter.allows(&PlayerAction::GetBonusCash));
+        assert!(PendingDecision::RegulatorSwap.allows(&swap));
+        assert!(!PendingDecision::RegulatorSwap.allows(&fire));
+    }
+
+    #[test]
+    fn only_targeting_characters_decide() {
+        let deciding = Character::CHARACTERS
+            .into_iter()
+            .filter_map(PendingDecision::for_ability)
+            .count();
+        assert_eq!(deciding, 4);
+        assert_eq!(PendingDecision::for_ability(Character::CEO), None);
+    }
+}
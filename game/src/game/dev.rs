@@ -0,0 +1,104 @@
+//! Debug commands which put a [`Round`] in any situation without playing up to it, so late-game
+//! edge cases can be reproduced quickly. Only available with the `dev` feature, which should never
+//! be enabled for real games.
+
+use either::Either;
+
+use crate::{errors::*, game::*, player::*};
+
+impl Round {
+    /// Sets the cash of the player with `id` to `cash`.
+    pub fn dev_set_cash(&mut self, id: PlayerId, cash: impl Into<Cash>) -> Result<(), GameError> {
+        self.player_mut(id)?._set_cash(cash);
+        Ok(())
+    }
+
+    /// Takes the asset named `title` out of the asset deck and puts it in the hand of the player
+    /// with `id`, without counting it as drawn.
+    pub fn dev_give_card(&mut self, id: PlayerId, title: &str) -> Result<&Asset, GameError> {
+        // Check the player first so the card is not lost if they do not exist
+        self.player(id)?;
+        let idx = self
+            .assets
+            .deck
+            .iter()
+            .position(|asset| asset.title == title)
+            .ok_or_else(|| DevError::UnknownCard(title.to_owned()))?;
+        let asset = self.assets.deck.remove(idx);
+
+        let player = self.player_mut(id)?;
+        player.give_card(Either::Left(asset));
+        // PANIC: we just gave the player an asset, so the last card in their hand is that asset.
+        Ok(player.hand().last().unwrap().as_ref().left().unwrap())
+    }
+
+    /// Makes the market named `title` the current market, leaving the market deck as it is.
+    pub fn dev_force_market(&mut self, title: &str) -> Result<&Market, GameError> {
+        let market = self
+            .markets
+            .deck
+            .iter()
+            .filter_map(|card| card.as_ref().left())
+            .find(|market| market.title == title)
+            .ok_or_else(|| DevError::UnknownMarket(title.to_owned()))?;
+        self.current_market = market.clone();
+        Ok(&self.current_market)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::GameData;
+    use claim::*;
+
+    fn round() -> GameState {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        assert_ok!(state.start_game_with_data(data));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let characters = assert_ok!(selecting.player_get_selectable_characters(id));
+            assert_ok!(state.player_select_character(id, characters[0]));
+        }
+        state
+    }
+
+    #[test]
+    fn dev_commands() {
+        let mut state = round();
+        let round = state.round_mut().unwrap();
+
+        assert_ok!(round.dev_set_cash(PlayerId(2), 42));
+        assert_eq!(round.player(PlayerId(2)).unwrap().cash(), Cash(42));
+        assert_err!(round.dev_set_cash(PlayerId(9), 42));
+
+        let title = round.assets.deck[0].title.clone();
+        let cards = round.assets.deck.len();
+        assert_eq!(
+            assert_ok!(round.dev_give_card(PlayerId(1), &title)).title,
+            title
+        );
+        assert_eq!(round.assets.deck.len(), cards - 1);
+        assert_eq!(
+            round.dev_give_card(PlayerId(1), "No such asset"),
+            Err(DevError::UnknownCard("No such asset".to_owned()).into())
+        );
+
+        let title = round
+            .markets
+            .deck
+            .iter()
+            .find_map(|card| card.as_ref().left())
+            .unwrap()
+            .title
+            .clone();
+        assert_eq!(assert_ok!(round.dev_force_market(&title)).title, title);
+        assert_eq!(round.current_market().title, title);
+        assert_err!(round.dev_force_market("No such market"));
+    }
+}
@@ -0,0 +1,2039 @@
+//! File containing [`GameState::apply`] and the [`ActionEffects`] it returns, which describe what
+//! every player should be told after an action was taken. Keeping this next to the rules means
+//! anything that hosts a game only has to deliver the effects, not decide who gets to see what.
+
+use either::Either;
+
+use std::collections::HashMap;
+
+use crate::{errors::*, game::*, player::*};
+
+use super::outcome::Snapshot;
+
+/// Everything that happened because of a single action, split up by who should learn about it.
+#[derive(Debug, Clone)]
+pub struct ActionEffects {
+    /// What the player who took the action is told.
+    pub to_actor: ActorEffect,
+    /// What specific players are told, in addition to [`ActionEffects::to_all`]. This is used both
+    /// for effects that differ per player and for effects everyone but the actor should see.
+    pub to_each_player: HashMap<PlayerId, Vec<GameEffect>>,
+    /// What every player, including the actor, is told.
+    pub to_all: Vec<GameEffect>,
+    /// What people watching the game are told, in addition to [`ActionEffects::to_all`]. These
+    /// are the effects everyone but the actor is told about.
+    pub to_spectators: Vec<GameEffect>,
+    /// Everything the action changed in the game, regardless of who may know about it.
+    pub outcomes: Vec<ActionOutcome>,
+}
+
+impl ActionEffects {
+    /// Creates new effects which only tell the actor about what happened.
+    pub fn new(to_actor: ActorEffect) -> Self {
+        Self {
+            to_actor,
+            to_each_player: HashMap::new(),
+            to_all: Vec::new(),
+            to_spectators: Vec::new(),
+            outcomes: Vec::new(),
+        }
+    }
+
+    /// Tells `player` about `effect`.
+    pub fn to_player(mut self, player: PlayerId, effect: GameEffect) -> Self {
+        self.to_each_player.entry(player).or_default().push(effect);
+        self
+    }
+
+    /// Tells every player in `players` except `actor`, as well as spectators, about `effect`.
+    pub fn to_others(
+        mut self,
+        players: impl IntoIterator<Item = PlayerId>,
+        actor: PlayerId,
+        effect: GameEffect,
+    ) -> Self {
+        for player in players.into_iter().filter(|&p| p != actor) {
+            self = self.to_player(player, effect.clone());
+        }
+        self.to_spectators.push(effect);
+        self
+    }
+
+    /// Tells every player about `effect`.
+    pub fn to_all(mut self, effect: GameEffect) -> Self {
+        self.to_all.push(effect);
+        self
+    }
+
+    /// Gets every effect spectators should be told about, in the order they happened.
+    pub fn for_spectators(&self) -> Vec<GameEffect> {
+        let mut effects = self.to_all.clone();
+        effects.extend(self.to_spectators.iter().cloned());
+        effects
+    }
+
+    /// Gets every effect `player` should be told about, in the order they happened.
+    pub fn for_player(&self, player: PlayerId) -> Vec<GameEffect> {
+        let mut effects = self.to_all.clone();
+        if let Some(own) = self.to_each_player.get(&player) {
+            effects.extend(own.iter().cloned());
+        }
+        effects
+    }
+}
+
+/// What the player who took an action is told about it.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub enum ActorEffect {
+    /// The game was started.
+    StartedGame,
+    /// Another player was made the host of the lobby.
+    TransferredHost { host_id: PlayerId },
+    /// The username of this player was changed.
+    ChangedUsername { username: String },
+    /// This player said whether they are ready for the game to start.
+    SetReady { ready: bool },
+    /// A character was selected.
+    SelectedCharacter { character: Character },
+    /// A character was banned from this round.
+    BannedCharacter { character: Character },
+    /// A character was fired.
+    FiredCharacter { character: Character },
+    /// The credit line of a character was terminated.
+    TerminatedCreditCharacter { character: Character },
+    /// The banker was paid.
+    PaidBanker(PayBankerPlayer),
+    /// The selection of cards to pay the banker with changed.
+    SelectedCardsBankerTarget(SelectedAssetsAndLiabilities),
+    /// The characters the shareholder can fire.
+    FiringOptions { characters: Vec<Character> },
+    /// The characters whose credit the banker can terminate.
+    TerminationOptions { characters: Vec<Character> },
+    /// The players the regulator can swap their hand with.
+    RegulatorOptions { options: Vec<RegulatorSwapPlayer> },
+    /// The assets the stakeholder can force others to divest.
+    DivestOptions { options: Vec<DivestPlayer> },
+    /// The ability of a character that does not need any further input.
+    CharacterAbility { character: Character },
+    /// Cards were swapped with the deck for `new_cards`.
+    SwappedWithDeck {
+        new_cards: Vec<Either<Asset, Liability>>,
+    },
+    /// Hands were swapped with another player.
+    SwappedWithPlayer {
+        new_cards: Vec<Either<Asset, Liability>>,
+        target_player_id: PlayerId,
+    },
+    /// A card was drawn.
+    DrewCard {
+        card: Either<Asset, Liability>,
+        can_draw_cards: bool,
+        can_give_back_cards: bool,
+    },
+    /// A card was put back.
+    PutBackCard {
+        card_idx: usize,
+        can_draw_cards: bool,
+        can_give_back_cards: bool,
+    },
+    /// The bonus cash of a character was collected.
+    BonusCash { cash: u8 },
+    /// An asset was bought.
+    BoughtAsset {
+        asset: Asset,
+        card_idx: usize,
+        market_change: Option<MarketChange>,
+        assets_to_play: u8,
+        cash: Cash,
+        triggered_effect: Option<AssetEffect>,
+    },
+    /// A liability was issued.
+    IssuedLiability {
+        liability: Liability,
+        card_idx: usize,
+    },
+    /// An asset of another player was picked to divest, and still has to be confirmed.
+    PickedAssetToDivest {
+        target_id: PlayerId,
+        asset_idx: usize,
+        gold_cost: u8,
+    },
+    /// An asset of another player was divested.
+    DivestedAsset {
+        target_id: PlayerId,
+        asset_idx: usize,
+        gold_cost: u8,
+    },
+    /// A liability was redeemed for `cost` cash.
+    RedeemedLiability { liability_idx: usize, cost: u8 },
+    /// The turn was ended.
+    EndedTurn,
+    /// The turn of another player was ended by the host.
+    ForcedEndTurn { player_id: PlayerId },
+    /// A market color was changed.
+    MinusedIntoPlus {
+        color: Color,
+        new_market: Market,
+        new_score: f64,
+    },
+    /// The silver of an asset was turned into gold.
+    SilveredIntoGold {
+        old_asset_data: Option<SilverIntoGoldData>,
+        new_asset_data: Option<SilverIntoGoldData>,
+        new_score: f64,
+    },
+    /// The color of an asset was changed.
+    ChangedAssetColor {
+        old_asset_data: Option<ChangeAssetColorData>,
+        new_asset_data: Option<ChangeAssetColorData>,
+        new_score: f64,
+    },
+    /// The choice for an asset ability was confirmed.
+    ConfirmedAssetAbility {
+        asset_idx: usize,
+        used_on: AssetAbilityUse,
+        breakdown: ScoreBreakdown,
+    },
+}
+
+/// What players are told about an action, which can be anyone including the player that took it.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub enum GameEffect {
+    /// The game started, including everything this player needs to know about their own position.
+    GameStarted {
+        id: PlayerId,
+        cash: Cash,
+        hand: Vec<Either<Asset, Liability>>,
+        player_info: Vec<PlayerInfo>,
+        initial_market: Market,
+        data_checksum: u64,
+        color_markers: Vec<ColorMarker>,
+        seats_shuffled: bool,
+        config: GameConfig,
+    },
+    /// Someone else became the host of the lobby.
+    HostChanged { host_id: PlayerId },
+    /// Someone else in the lobby said whether they are ready for the game to start.
+    PlayerReady { player_id: PlayerId, ready: bool },
+    /// Someone in the lobby changed their username.
+    PlayersInLobby {
+        changed_player: String,
+        usernames: Vec<String>,
+        host_id: Option<PlayerId>,
+    },
+    /// A new selecting characters phase started.
+    SelectingCharacters {
+        chairman_id: PlayerId,
+        selectable_characters: Option<Vec<Character>>,
+        open_characters: Vec<Character>,
+        closed_character: Option<Character>,
+        turn_order: Vec<PlayerId>,
+        banned_characters: Vec<Character>,
+    },
+    /// A new selecting characters phase started, or someone banned a character, and a character
+    /// is to be banned next.
+    BanningCharacters {
+        chairman_id: PlayerId,
+        currently_banning_id: PlayerId,
+        bannable_characters: Option<Vec<Character>>,
+        banned_characters: Vec<Character>,
+        turn_order: Vec<PlayerId>,
+    },
+    /// Someone banned a character from this round.
+    CharacterBanned {
+        player_id: PlayerId,
+        character: Character,
+    },
+    /// Someone selected a character.
+    SelectedCharacter {
+        currently_picking_id: Option<PlayerId>,
+        selectable_characters: Option<Vec<Character>>,
+        closed_character: Option<Character>,
+    },
+    /// The turn of a new player started.
+    TurnStarts {
+        player_turn: PlayerId,
+        player_turn_cash: u8,
+        draws_n_cards: u8,
+        gives_back_n_cards: u8,
+        playable_assets: PlayableAssets,
+        playable_liabilities: u8,
+        player_character: Character,
+        can_use_ability: bool,
+        skipped_characters: Vec<Character>,
+        skip_reasons: Vec<(Character, SkipReason)>,
+        time_bank_ms: Option<u64>,
+        turn_time_ms: Option<u64>,
+        phase: TurnPhase,
+        events: Vec<Event>,
+        turn_nonce: u32,
+    },
+    /// The player whose turn it is was targeted by the banker.
+    PlayerTargetedByBanker {
+        player_turn: PlayerId,
+        cash_to_be_paid: u8,
+        credit_termination: CreditTermination,
+        is_possible_to_pay_banker: bool,
+    },
+    /// The credit line of this player was terminated by the banker, with how much they owe.
+    YourCreditTerminated(CreditTermination),
+    /// The target of the banker changed the cards they want to pay with.
+    SelectedCardsBankerTarget {
+        assets: Vec<SoldAssetToPayBanker>,
+        liability_count: usize,
+    },
+    /// Someone drew a card.
+    DrewCard {
+        player_id: PlayerId,
+        card_type: CardType,
+        deck_sizes: DeckSizes,
+    },
+    /// Someone put back a card.
+    PutBackCard {
+        player_id: PlayerId,
+        card_type: CardType,
+        deck_sizes: DeckSizes,
+    },
+    /// Someone bought an asset.
+    BoughtAsset {
+        player_id: PlayerId,
+        asset: Asset,
+        card_idx: usize,
+        market_change: Option<MarketChange>,
+        assets_to_play: u8,
+        cash: PlayerCash,
+        triggered_effect: Option<AssetEffect>,
+    },
+    /// Someone issued a liability.
+    IssuedLiability {
+        player_id: PlayerId,
+        liability: Liability,
+        card_idx: usize,
+    },
+    /// Someone redeemed a liability for `cost` cash.
+    RedeemedLiability {
+        player_id: PlayerId,
+        liability_idx: usize,
+        cost: u8,
+    },
+    /// Someone collected the bonus cash of their character.
+    PlayerGotBonusCash { player_id: PlayerId, cash: u8 },
+    /// The shareholder fired a character.
+    FiredCharacter {
+        player_id: PlayerId,
+        character: Character,
+    },
+    /// The banker terminated the credit line of a character.
+    TerminatedCreditCharacter {
+        player_id: PlayerId,
+        character: Character,
+    },
+    /// The target of the banker paid the banker.
+    PlayerPaidBanker(PayBankerPlayer),
+    /// Someone paid off (part of) what they still owed a banker when their turn started.
+    PlayerPaidBankerDebt(BankerDebtPayment),
+    /// This player will not get a turn this round.
+    YourTurnSkipped { reason: SkipReason },
+    /// The regulator swapped their hand with this player.
+    RegulatorSwappedYourCards {
+        new_cards: Vec<Either<Asset, Liability>>,
+    },
+    /// The regulator swapped their hand with another player.
+    SwappedWithPlayer {
+        regulator_id: PlayerId,
+        target_id: PlayerId,
+    },
+    /// The regulator swapped cards with the deck.
+    SwappedWithDeck {
+        asset_count: usize,
+        liability_count: usize,
+    },
+    /// The stakeholder picked an asset of this player to divest, which they still have to
+    /// confirm.
+    BeingDivested {
+        stakeholder_id: PlayerId,
+        asset_idx: usize,
+        gold_cost: u8,
+    },
+    /// The stakeholder forced someone to divest an asset.
+    AssetDivested {
+        player_id: PlayerId,
+        target_id: PlayerId,
+        asset_idx: usize,
+        paid_gold: u8,
+    },
+    /// The host ended the turn of a player, after giving back the cards they still had to give
+    /// back for them.
+    TurnForcedToEnd { player_id: PlayerId },
+    /// The game ended. The scores are ranked, see [`Results::ranking`].
+    GameEnded {
+        scores: Vec<PlayerScore>,
+        boards: Vec<FinalBoard>,
+        final_market: Market,
+    },
+    /// Someone changed one of their market colors.
+    MinusedIntoPlus {
+        player_id: PlayerId,
+        new_market: Market,
+        new_score: f64,
+    },
+    /// Someone turned the silver of an asset into gold.
+    SilveredIntoGold {
+        player_id: PlayerId,
+        old_asset_data: Option<SilverIntoGoldData>,
+        new_asset_data: Option<SilverIntoGoldData>,
+        new_score: f64,
+    },
+    /// Someone changed the color of an asset.
+    ChangedAssetColor {
+        player_id: PlayerId,
+        old_asset_data: Option<ChangeAssetColorData>,
+        new_asset_data: Option<ChangeAssetColorData>,
+        new_score: f64,
+    },
+    /// Someone confirmed the choice for an asset ability.
+    ConfirmedAssetAbility {
+        player_id: PlayerId,
+        asset_idx: usize,
+        used_on: AssetAbilityUse,
+        breakdown: ScoreBreakdown,
+    },
+}
+
+impl GameState {
+    /// Applies `action` on behalf of player `id`, and returns what each player should be told
+    /// about it. Starting the game uses the card data set with [`Lobby::set_game_data`]. Every
+    /// index in `action` is checked with [`GameState::check_indices`] before anything changes, and
+    /// during a round the action should fit the [`PendingDecision`] and [`TurnPhase`] of the
+    /// current player. Actions players send themselves should go through
+    /// [`GameState::apply_in_turn`] instead.
+    ///
+    /// Actions are applied to a staged copy of the game, which only replaces the game once every
+    /// step of the action succeeded. If an action fails, the game is left exactly as it was.
+    pub fn apply(
+        &mut self,
+        id: PlayerId,
+        action: PlayerAction,
+    ) -> Result<ActionEffects, GameError> {
+        let action = self.check_indices(id, action)?;
+        if let GameState::Round(round) = self {
+            round.check_pending_decision(id, &action)?;
+            round.check_turn_phase(id, &action)?;
+        }
+        let before = Snapshot::new(self);
+
+        let mut staged = self.clone();
+        let mut effects = staged.apply_action(id, action.clone())?;
+        if let GameState::Round(round) = &mut staged {
+            round.update_pending_decision(&action);
+        }
+        *self = staged;
+
+        effects.outcomes = before.outcomes(&Snapshot::new(self));
+        Ok(effects)
+    }
+
+    /// Applies `action` which player `id` sent during the turn with `turn_nonce`, like
+    /// [`GameState::apply`]. During a round, actions that belong to a turn (see
+    /// [`PlayerAction::is_turn_scoped`]) are rejected if they do not carry the nonce of the turn
+    /// that is being played, so a request that was sent twice cannot act on the turn after it.
+    pub fn apply_in_turn(
+        &mut self,
+        id: PlayerId,
+        action: PlayerAction,
+        turn_nonce: Option<u32>,
+    ) -> Result<ActionEffects, GameError> {
+        if let GameState::Round(round) = self
+            && action.is_turn_scoped()
+        {
+            round.check_turn_nonce(turn_nonce.ok_or(GameError::MissingTurnNonce)?)?;
+        }
+        self.apply(id, action)
+    }
+
+    fn apply_action(
+        &mut self,
+        id: PlayerId,
+        action: PlayerAction,
+    ) -> Result<ActionEffects, GameError> {
+        use PlayerAction as A;
+
+        match action {
+            A::StartGame => self.apply_start_game(id),
+            A::TransferHost { target } => {
+                let lobby = self.lobby_mut()?;
+                let host_id = lobby.transfer_host(id, target)?.id();
+
+                let effects = ActionEffects::new(ActorEffect::TransferredHost { host_id });
+                Ok(effects.to_others(
+                    lobby.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::HostChanged { host_id },
+                ))
+            }
+            A::SetReady { ready } => {
+                let lobby = self.lobby_mut()?;
+                lobby.set_ready(id, ready)?;
+
+                let effects = ActionEffects::new(ActorEffect::SetReady { ready });
+                Ok(effects.to_others(
+                    lobby.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::PlayerReady {
+                        player_id: id,
+                        ready,
+                    },
+                ))
+            }
+            A::ChangeUsername { username } => {
+                let lobby = self.lobby_mut()?;
+                let username = lobby.change_username(id, username)?.name().to_owned();
+
+                // Everyone gets the new list, the player that changed their username included
+                let changed = GameEffect::PlayersInLobby {
+                    changed_player: username.clone(),
+                    usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
+                    host_id: lobby.host().map(|p| p.id()),
+                };
+                Ok(ActionEffects::new(ActorEffect::ChangedUsername { username }).to_all(changed))
+            }
+            A::SelectCharacter { character } => self.apply_select_character(id, character),
+            A::BanCharacter { character } => {
+                self.player_ban_character(id, character)?;
+                let selecting = self.selecting_characters()?;
+
+                let effects = ActionEffects::new(ActorEffect::BannedCharacter { character });
+                Ok(selecting.players().iter().fold(effects, |effects, p| {
+                    effects
+                        .to_player(
+                            p.id(),
+                            GameEffect::CharacterBanned {
+                                player_id: id,
+                                character,
+                            },
+                        )
+                        .to_player(p.id(), selecting_characters(selecting, p.id()))
+                }))
+            }
+            A::DrawCard { card_type } => {
+                let round = self.round_mut()?;
+                let card = round.player_draw_card(id, card_type)?.cloned();
+                let player = round.player(id)?;
+
+                let effects = ActionEffects::new(ActorEffect::DrewCard {
+                    card,
+                    can_draw_cards: player.can_draw_cards(),
+                    can_give_back_cards: player.should_give_back_cards(),
+                });
+                Ok(effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::DrewCard {
+                        player_id: id,
+                        card_type,
+                        deck_sizes: round.deck_sizes(),
+                    },
+                ))
+            }
+            A::PutBackCard { card_idx } => {
+                let round = self.round_mut()?;
+                let card_type = round.player_give_back_card(id, card_idx)?;
+                let player = round.player(id)?;
+
+                let effects = ActionEffects::new(ActorEffect::PutBackCard {
+                    card_idx,
+                    can_draw_cards: player.can_draw_cards(),
+                    can_give_back_cards: player.should_give_back_cards(),
+                });
+                Ok(effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::PutBackCard {
+                        player_id: id,
+                        card_type,
+                        deck_sizes: round.deck_sizes(),
+                    },
+                ))
+            }
+            A::BuyAsset { card_idx } | A::IssueLiability { card_idx } => {
+                let round = self.round_mut()?;
+                let played_card = round.player_play_card(id, card_idx)?;
+                let players = round.players().iter().map(|p| p.id()).collect::<Vec<_>>();
+                let skipped = skipped_by_events(round, played_card.market.as_ref());
+                let player = round.player(id)?;
+                let (assets_to_play, cash) = (player.assets_to_play(), player.cash());
+
+                let effects = match played_card.used_card {
+                    Either::Left(asset) => ActionEffects::new(ActorEffect::BoughtAsset {
+                        asset: asset.clone(),
+                        card_idx,
+                        market_change: played_card.market.clone(),
+                        assets_to_play,
+                        cash,
+                        triggered_effect: asset.on_buy,
+                    })
+                    .to_others(
+                        players,
+                        id,
+                        GameEffect::BoughtAsset {
+                            player_id: id,
+                            triggered_effect: asset.on_buy,
+                            asset,
+                            card_idx,
+                            market_change: played_card.market,
+                            assets_to_play,
+                            cash: PlayerCash::seen_by_others(cash, &round.config()),
+                        },
+                    ),
+                    Either::Right(liability) => ActionEffects::new(ActorEffect::IssuedLiability {
+                        liability: liability.clone(),
+                        card_idx,
+                    })
+                    .to_others(
+                        players,
+                        id,
+                        GameEffect::IssuedLiability {
+                            player_id: id,
+                            liability,
+                            card_idx,
+                        },
+                    ),
+                };
+                Ok(skipped
+                    .into_iter()
+                    .fold(effects, |effects, (player_id, reason)| {
+                        effects.to_player(player_id, GameEffect::YourTurnSkipped { reason })
+                    }))
+            }
+            A::RedeemLiability { liability_idx } => {
+                let round = self.round_mut()?;
+                let cost = round.player_redeem_liability(id, liability_idx)?;
+
+                let effects = ActionEffects::new(ActorEffect::RedeemedLiability {
+                    liability_idx,
+                    cost,
+                });
+                Ok(effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::RedeemedLiability {
+                        player_id: id,
+                        liability_idx,
+                        cost,
+                    },
+                ))
+            }
+            A::UseAbility => self.apply_use_ability(id),
+            A::GetBonusCash => {
+                let round = self.round_mut()?;
+                let cash = round.player_get_bonus_cash_character(id)?;
+
+                let effects = ActionEffects::new(ActorEffect::BonusCash { cash });
+                Ok(effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::PlayerGotBonusCash {
+                        player_id: id,
+                        cash,
+                    },
+                ))
+            }
+            A::FireCharacter { character } => {
+                let round = self.round_mut()?;
+                round.player_fire_character(id, character)?;
+
+                let effects = ActionEffects::new(ActorEffect::FiredCharacter { character });
+                let effects = effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::FiredCharacter {
+                        player_id: id,
+                        character,
+                    },
+                );
+                Ok(match round.player_from_character(character) {
+                    Some(fired) => effects.to_player(
+                        fired.id(),
+                        GameEffect::YourTurnSkipped {
+                            reason: SkipReason::Fired { by: id },
+                        },
+                    ),
+                    None => effects,
+                })
+            }
+            A::TerminateCreditCharacter { character } => {
+                let round = self.round_mut()?;
+                round.player_terminate_credit_character(id, character)?;
+
+                let effects =
+                    ActionEffects::new(ActorEffect::TerminatedCreditCharacter { character });
+                Ok(effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::TerminatedCreditCharacter {
+                        player_id: id,
+                        character,
+                    },
+                ))
+            }
+            A::SelectAssetToDivest { asset_id } => {
+                let round = self.bankertarget_mut()?;
+                let selected = round.player_select_divest_asset(id, asset_id)?;
+                Ok(selected_cards_effects(round, id, selected))
+            }
+            A::UnselectAssetToDivest { asset_id } => {
+                let round = self.bankertarget_mut()?;
+                let selected = round.player_unselect_divest_asset(id, asset_id)?;
+                Ok(selected_cards_effects(round, id, selected))
+            }
+            A::SelectLiabilityToIssue { liability_id } => {
+                let round = self.bankertarget_mut()?;
+                let selected = round.player_select_issue_liability(id, liability_id)?;
+                Ok(selected_cards_effects(round, id, selected))
+            }
+            A::UnselectLiabilityToIssue { liability_id } => {
+                let round = self.bankertarget_mut()?;
+                let selected = round.player_unselect_issue_liability(id, liability_id)?;
+                Ok(selected_cards_effects(round, id, selected))
+            }
+            A::PayBanker { cash } => {
+                let round = self.bankertarget_mut()?;
+                let paid = round.player_pay_banker(id, cash)?;
+                let players = round.players().iter().map(|p| p.id()).collect::<Vec<_>>();
+                *self = GameState::Round(round.into());
+
+                let effects = ActionEffects::new(ActorEffect::PaidBanker(paid.clone()));
+                Ok(effects.to_others(players, id, GameEffect::PlayerPaidBanker(paid)))
+            }
+            A::SwapWithDeck { card_idxs } => {
+                let round = self.round_mut()?;
+                let DeckSwap {
+                    count:
+                        AssetLiabilityCount {
+                            asset_count,
+                            liability_count,
+                        },
+                    new_cards,
+                } = round.player_swap_with_deck(id, card_idxs)?;
+
+                let effects = ActionEffects::new(ActorEffect::SwappedWithDeck { new_cards });
+                Ok(effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::SwappedWithDeck {
+                        asset_count,
+                        liability_count,
+                    },
+                ))
+            }
+            A::SwapWithPlayer { target_player_id } => {
+                let round = self.round_mut()?;
+                let hands = round.player_swap_with_player(id, target_player_id)?;
+                let others = round
+                    .players()
+                    .iter()
+                    .map(|p| p.id())
+                    .filter(|&p| p != target_player_id);
+
+                let effects = ActionEffects::new(ActorEffect::SwappedWithPlayer {
+                    new_cards: hands.regulator_new_hand,
+                    target_player_id,
+                });
+                Ok(effects
+                    .to_others(
+                        others,
+                        id,
+                        GameEffect::SwappedWithPlayer {
+                            regulator_id: id,
+                            target_id: target_player_id,
+                        },
+                    )
+                    .to_player(
+                        target_player_id,
+                        GameEffect::RegulatorSwappedYourCards {
+                            new_cards: hands.target_new_hand,
+                        },
+                    ))
+            }
+            A::DivestAsset {
+                target_player_id,
+                card_idx,
+            } => {
+                let round = self.round_mut()?;
+                let divest = round.player_pick_divest_asset(id, target_player_id, card_idx)?;
+
+                let effects = ActionEffects::new(ActorEffect::PickedAssetToDivest {
+                    target_id: divest.target_id,
+                    asset_idx: divest.asset_idx,
+                    gold_cost: divest.gold_cost,
+                });
+                Ok(effects.to_player(
+                    divest.target_id,
+                    GameEffect::BeingDivested {
+                        stakeholder_id: id,
+                        asset_idx: divest.asset_idx,
+                        gold_cost: divest.gold_cost,
+                    },
+                ))
+            }
+            A::ConfirmDivest => {
+                let round = self.round_mut()?;
+                let divest = round.player_confirm_divest(id)?;
+
+                let effects = ActionEffects::new(ActorEffect::DivestedAsset {
+                    target_id: divest.target_id,
+                    asset_idx: divest.asset_idx,
+                    gold_cost: divest.gold_cost,
+                });
+                Ok(effects.to_others(
+                    round.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::AssetDivested {
+                        player_id: id,
+                        target_id: divest.target_id,
+                        asset_idx: divest.asset_idx,
+                        paid_gold: divest.gold_cost,
+                    },
+                ))
+            }
+            A::EndTurn => self.apply_end_turn(id),
+            A::ForceEndTurn => {
+                let round = self.round()?;
+                round.check_host(id)?;
+                let player_id = round.current_player().id();
+                let effects = self.force_end_turn_effects(
+                    player_id,
+                    ActionEffects::new(ActorEffect::ForcedEndTurn { player_id }),
+                )?;
+                self.end_turn_effects(effects.to_all(GameEffect::TurnForcedToEnd { player_id }))
+            }
+            A::MinusIntoPlus { color } => {
+                let results = self.results_mut()?;
+                let new_market = results.toggle_minus_into_plus(id, color)?;
+                let new_score = results.player(id)?.score();
+
+                let effects = ActionEffects::new(ActorEffect::MinusedIntoPlus {
+                    color,
+                    new_market: new_market.clone(),
+                    new_score,
+                });
+                Ok(effects.to_others(
+                    results.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::MinusedIntoPlus {
+                        player_id: id,
+                        new_market,
+                        new_score,
+                    },
+                ))
+            }
+            A::SilverIntoGold { asset_idx } => {
+                let results = self.results_mut()?;
+                let ToggleSilverIntoGold {
+                    old_asset_data,
+                    new_asset_data,
+                } = results.toggle_silver_into_gold(id, asset_idx)?;
+                let new_score = results.player(id)?.score();
+
+                let effects = ActionEffects::new(ActorEffect::SilveredIntoGold {
+                    old_asset_data,
+                    new_asset_data,
+                    new_score,
+                });
+                Ok(effects.to_others(
+                    results.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::SilveredIntoGold {
+                        player_id: id,
+                        old_asset_data,
+                        new_asset_data,
+                        new_score,
+                    },
+                ))
+            }
+            A::ChangeAssetColor { asset_idx, color } => {
+                let results = self.results_mut()?;
+                let ToggleChangeAssetColor {
+                    old_asset_data,
+                    new_asset_data,
+                } = results.toggle_change_asset_color(id, asset_idx, color)?;
+                let new_score = results.player(id)?.score();
+
+                let effects = ActionEffects::new(ActorEffect::ChangedAssetColor {
+                    old_asset_data,
+                    new_asset_data,
+                    new_score,
+                });
+                Ok(effects.to_others(
+                    results.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::ChangedAssetColor {
+                        player_id: id,
+                        old_asset_data,
+                        new_asset_data,
+                        new_score,
+                    },
+                ))
+            }
+            A::ConfirmAssetAbility { asset_idx } => {
+                let results = self.results_mut()?;
+                let used = results.confirm_asset_ability(id, asset_idx)?;
+                let breakdown = ScoreBreakdown::from(results.player(id)?);
+
+                let effects = ActionEffects::new(ActorEffect::ConfirmedAssetAbility {
+                    asset_idx,
+                    used_on: used.used_on,
+                    breakdown: breakdown.clone(),
+                });
+                Ok(effects.to_others(
+                    results.players().iter().map(|p| p.id()),
+                    id,
+                    GameEffect::ConfirmedAssetAbility {
+                        player_id: id,
+                        asset_idx,
+                        used_on: used.used_on,
+                        breakdown,
+                    },
+                ))
+            }
+        }
+    }
+
+    fn apply_start_game(&mut self, id: PlayerId) -> Result<ActionEffects, GameError> {
+        let data = self
+            .lobby()?
+            .game_data()
+            .cloned()
+            .ok_or(LobbyError::MissingGameData)?;
+        self.lobby()?.check_host(id)?;
+        let color_markers = data.colors.clone();
+        let seats_shuffled = cfg!(feature = "shuffle") && self.lobby()?.config().shuffle_seats;
+        self.start_game_with_data(data)?;
+
+        let selecting = self.selecting_characters()?;
+        let effects = selecting.players().iter().fold(
+            ActionEffects::new(ActorEffect::StartedGame),
+            |effects, p| {
+                effects
+                    .to_player(
+                        p.id(),
+                        GameEffect::GameStarted {
+                            id: p.id(),
+                            cash: p.cash(),
+                            hand: p.hand().to_vec(),
+                            player_info: selecting.player_info(p.id()),
+                            initial_market: selecting.current_market().clone(),
+                            data_checksum: selecting.data_checksum(),
+                            color_markers: color_markers.clone(),
+                            seats_shuffled,
+                            config: selecting.config(),
+                        },
+                    )
+                    .to_player(p.id(), selecting_characters(selecting, p.id()))
+            },
+        );
+
+        Ok(effects)
+    }
+
+    fn apply_select_character(
+        &mut self,
+        id: PlayerId,
+        character: Character,
+    ) -> Result<ActionEffects, GameError> {
+        self.player_select_character(id, character)?;
+
+        let effects = ActionEffects::new(ActorEffect::SelectedCharacter { character });
+        match self {
+            GameState::SelectingCharacters(selecting) => {
+                Ok(selecting.players().iter().fold(effects, |effects, p| {
+                    effects.to_player(
+                        p.id(),
+                        GameEffect::SelectedCharacter {
+                            currently_picking_id: Some(selecting.currently_selecting_id()),
+                            selectable_characters: selecting
+                                .player_get_selectable_characters(p.id())
+                                .ok(),
+                            closed_character: selecting.player_get_closed_character(p.id()).ok(),
+                        },
+                    )
+                }))
+            }
+            GameState::Round(round) => Ok(turn_start_effects(round, effects)),
+            GameState::Lobby(_) => Err(GameError::NotAvailableInLobbyState),
+            GameState::BankerTarget(_) => Err(GameError::NotAvailableInBankerTargetState),
+            GameState::Results(_) => Err(GameError::NotAvailableInResultsState),
+        }
+    }
+
+    fn apply_use_ability(&mut self, id: PlayerId) -> Result<ActionEffects, GameError> {
+        let round = self.round_mut()?;
+        let player = round.player(id)?;
+        if round.current_player().id() != player.id() {
+            return Err(GameError::InvalidPlayerIndex(0));
+        }
+
+        let effect = match player.character() {
+            Character::Shareholder => ActorEffect::FiringOptions {
+                characters: round.player_get_fireble_characters(),
+            },
+            Character::Banker => ActorEffect::TerminationOptions {
+                characters: round.player_get_fireble_characters(),
+            },
+            Character::Regulator => ActorEffect::RegulatorOptions {
+                options: round.player_get_regulator_swap_players(),
+            },
+            Character::Stakeholder => ActorEffect::DivestOptions {
+                options: round.get_divest_assets(id)?,
+            },
+            character => ActorEffect::CharacterAbility { character },
+        };
+
+        Ok(ActionEffects::new(effect))
+    }
+
+    fn apply_end_turn(&mut self, id: PlayerId) -> Result<ActionEffects, GameError> {
+        self.end_player_turn(id)?;
+        self.end_turn_effects(ActionEffects::new(ActorEffect::EndedTurn))
+    }
+
+    /// Ends the turn of player `id` because they ran out of time, regardless of the phase their
+    /// turn is in, and gets what everyone should be told about it. See
+    /// [`Round::force_end_turn`](crate::game::Round::force_end_turn).
+    pub fn end_turn_out_of_time(&mut self, id: PlayerId) -> Result<ActionEffects, GameError> {
+        let effects =
+            self.force_end_turn_effects(id, ActionEffects::new(ActorEffect::EndedTurn))?;
+        self.end_turn_effects(effects)
+    }
+
+    /// Forces the turn of player `id` to end and adds the cards they gave back because of it to
+    /// `effects`.
+    fn force_end_turn_effects(
+        &mut self,
+        id: PlayerId,
+        effects: ActionEffects,
+    ) -> Result<ActionEffects, GameError> {
+        // The turn ends along with giving back the cards, so the decks are counted first
+        let mut deck_sizes = self.round()?.deck_sizes();
+        let put_back = self.force_end_turn(id)?;
+
+        Ok(put_back.into_iter().fold(effects, |effects, card_type| {
+            match card_type {
+                CardType::Asset => deck_sizes.assets += 1,
+                CardType::Liability => deck_sizes.liabilities += 1,
+            }
+            effects.to_all(GameEffect::PutBackCard {
+                player_id: id,
+                card_type,
+                deck_sizes,
+            })
+        }))
+    }
+
+    /// Adds what everyone should be told about the state the game is in after a turn ended to
+    /// `effects`.
+    fn end_turn_effects(&mut self, effects: ActionEffects) -> Result<ActionEffects, GameError> {
+        match self {
+            GameState::SelectingCharacters(selecting) => {
+                Ok(selecting.players().iter().fold(effects, |effects, p| {
+                    effects.to_player(p.id(), selecting_characters(selecting, p.id()))
+                }))
+            }
+            GameState::Round(round) => {
+                let effects = turn_start_effects(round, effects);
+
+                if round.banker_target() != Some(round.current_player().character()) {
+                    return Ok(effects);
+                }
+
+                let target = BankerTargetRound::from(&mut *round);
+                let target_id = target.current_player().id();
+                let effects = effects
+                    .to_player(
+                        target_id,
+                        GameEffect::YourCreditTerminated(target.credit_termination().clone()),
+                    )
+                    .to_all(GameEffect::PlayerTargetedByBanker {
+                        player_turn: target_id,
+                        cash_to_be_paid: target.gold_to_be_paid(),
+                        credit_termination: target.credit_termination().clone(),
+                        is_possible_to_pay_banker: target.can_pay_banker(),
+                    });
+                *self = GameState::BankerTarget(target);
+
+                Ok(effects)
+            }
+            GameState::Results(results) => Ok(effects.to_all(GameEffect::GameEnded {
+                scores: results.ranking(),
+                boards: results.final_boards(),
+                final_market: results.final_market().clone(),
+            })),
+            GameState::Lobby(_) => Err(GameError::NotAvailableInLobbyState),
+            GameState::BankerTarget(_) => Err(GameError::NotAvailableInBankerTargetState),
+        }
+    }
+}
+
+/// Gets the effect telling player `id` a new selecting characters phase started, or who bans a
+/// character next if characters are still being banned. Characters are only dealt once the last
+/// character is banned, so until then nobody is told which characters are open.
+fn selecting_characters(selecting: &SelectingCharacters, id: PlayerId) -> GameEffect {
+    match selecting.currently_banning_id() {
+        Some(currently_banning_id) => GameEffect::BanningCharacters {
+            chairman_id: selecting.chairman_id(),
+            currently_banning_id,
+            bannable_characters: selecting.player_get_bannable_characters(id).ok(),
+            banned_characters: selecting.banned_characters().to_vec(),
+            turn_order: selecting.turn_order(),
+        },
+        None => GameEffect::SelectingCharacters {
+            chairman_id: selecting.chairman_id(),
+            selectable_characters: selecting.player_get_selectable_characters(id).ok(),
+            open_characters: selecting.open_characters().to_vec(),
+            closed_character: selecting.player_get_closed_character(id).ok(),
+            turn_order: selecting.turn_order(),
+            banned_characters: selecting.banned_characters().to_vec(),
+        },
+    }
+}
+
+/// Gets the players whose turn is skipped by the events drawn in `market_change`, if they still
+/// had a turn to come this round.
+fn skipped_by_events(
+    round: &Round,
+    market_change: Option<&MarketChange>,
+) -> Vec<(PlayerId, SkipReason)> {
+    let current_character = round.current_player().character();
+    market_change
+        .into_iter()
+        .flat_map(|change| &change.events)
+        .filter_map(|event| {
+            let character = event.skip_turn.filter(|&c| c > current_character)?;
+            let player = round.player_from_character(character)?;
+            let reason = SkipReason::Event {
+                title: event.title.clone(),
+            };
+            Some((player.id(), reason))
+        })
+        .collect()
+}
+
+/// Adds the effects telling everyone the turn of the current player started, and what they paid
+/// off of their banker debts, to `effects`.
+fn turn_start_effects(round: &Round, effects: ActionEffects) -> ActionEffects {
+    let effects = effects.to_all(turn_starts(round));
+    round
+        .debt_payments()
+        .iter()
+        .fold(effects, |effects, payment| {
+            effects.to_all(GameEffect::PlayerPaidBankerDebt(*payment))
+        })
+}
+
+/// Gets the effect telling everyone the turn of the current player started.
+fn turn_starts(round: &Round) -> GameEffect {
+    let current_player = round.current_player();
+
+    GameEffect::TurnStarts {
+        player_turn: current_player.id(),
+        player_turn_cash: current_player.turn_cash(),
+        player_character: current_player.character(),
+        can_use_ability: current_player.can_use_ability(),
+        draws_n_cards: current_player.draws_n_cards(),
+        gives_back_n_cards: current_player.gives_back_n_cards(),
+        playable_assets: current_player.playable_assets(),
+        playable_liabilities: current_player.playable_liabilities(),
+        skipped_characters: round.skipped_characters(),
+        skip_reasons: round
+            .skipped_characters()
+            .into_iter()
+            .filter_map(|c| Some((c, round.skip_reason(c)?.clone())))
+            .collect(),
+        time_bank_ms: round
+            .clock()
+            .and_then(|clock| clock.remaining(current_player.id()))
+            .map(|remaining| remaining.as_millis() as u64),
+        turn_time_ms: round
+            .time_remaining()
+            .map(|remaining| remaining.as_millis() as u64),
+        phase: round.turn_phase(),
+        events: round.current_events().to_vec(),
+        turn_nonce: round.turn_nonce(),
+    }
+}
+
+/// Gets the effects of the target of the banker changing the cards they want to pay with.
+fn selected_cards_effects(
+    round: &BankerTargetRound,
+    id: PlayerId,
+    selected: SelectedAssetsAndLiabilities,
+) -> ActionEffects {
+    let effect = GameEffect::SelectedCardsBankerTarget {
+        assets: selected.sold_assets.clone(),
+        liability_count: selected.issued_liabilities.len(),
+    };
+
+    ActionEffects::new(ActorEffect::SelectedCardsBankerTarget(selected)).to_others(
+        round.players().iter().map(|p| p.id()),
+        id,
+        effect,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cards::GameData, utility::with_seed};
+    use claim::*;
+
+    use std::time::Duration;
+
+    fn lobby(players: usize) -> GameState {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..players {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        lobby.set_game_data(data);
+        state
+    }
+
+    #[test]
+    fn start_game_tells_everyone() {
+        let mut state = lobby(4);
+        let config = GameConfig {
+            max_rounds: Some(5),
+            ..Default::default()
+        };
+        state.lobby_mut().unwrap().set_config(config);
+        let checksum = assert_some!(state.data_checksum());
+        let effects = assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+
+        assert_matches!(effects.to_actor, ActorEffect::StartedGame);
+        assert!(effects.to_all.is_empty());
+        assert_eq!(state.data_checksum(), Some(checksum));
+        for i in 0..4 {
+            let player_effects = effects.for_player(PlayerId(i));
+            assert_matches!(
+                player_effects.as_slice(),
+                [
+                    GameEffect::GameStarted { id, data_checksum, color_markers, config: c, .. },
+                    GameEffect::SelectingCharacters { .. }
+                ] if *id == PlayerId(i)
+                    && *c == config
+                    && *data_checksum == checksum
+                    && color_markers.len() == Color::COLORS.len()
+            );
+        }
+    }
+
+    #[test]
+    fn failed_actions_change_nothing() {
+        let mut state = lobby(4);
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        let before = state.clone();
+
+        let id = state
+            .selecting_characters()
+            .unwrap()
+            .currently_selecting_id();
+        let other = PlayerId((id.0 + 1) % 4);
+        let character = state
+            .selecting_characters()
+            .unwrap()
+            .player_get_selectable_characters(id)
+            .unwrap()[0];
+
+        assert_err!(state.apply(other, PlayerAction::SelectCharacter { character }));
+        assert_err!(state.apply(id, PlayerAction::EndTurn));
+        assert_err!(state.apply(id, PlayerAction::StartGame));
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn only_host_starts_game() {
+        let mut state = lobby(4);
+        assert_eq!(
+            state.apply(PlayerId(1), PlayerAction::StartGame).map(drop),
+            Err(GameError::NotHost)
+        );
+
+        let effects = assert_ok!(state.apply(
+            PlayerId(0),
+            PlayerAction::TransferHost {
+                target: PlayerId(1)
+            }
+        ));
+        assert_matches!(
+            effects.to_actor,
+            ActorEffect::TransferredHost {
+                host_id: PlayerId(1)
+            }
+        );
+        assert_matches!(
+            effects.for_player(PlayerId(2)).as_slice(),
+            [GameEffect::HostChanged {
+                host_id: PlayerId(1)
+            }]
+        );
+
+        assert_err!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        assert_ok!(state.apply(PlayerId(1), PlayerAction::StartGame));
+    }
+
+    #[test]
+    fn start_waits_for_everyone_ready() {
+        let mut state = lobby(4);
+        state.lobby_mut().unwrap().set_config(GameConfig {
+            require_ready: true,
+            ..Default::default()
+        });
+        for i in 0..3 {
+            let effects =
+                assert_ok!(state.apply(PlayerId(i), PlayerAction::SetReady { ready: true }));
+            assert_matches!(effects.to_actor, ActorEffect::SetReady { ready: true });
+            assert_matches!(
+                effects.for_player(PlayerId(3)).as_slice(),
+                [GameEffect::PlayerReady { player_id, ready: true }] if *player_id == PlayerId(i)
+            );
+        }
+
+        assert_eq!(
+            state.apply(PlayerId(0), PlayerAction::StartGame).map(drop),
+            Err(GameError::Lobby(LobbyError::NotEveryoneReady))
+        );
+        assert_ok!(state.apply(PlayerId(3), PlayerAction::SetReady { ready: true }));
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+    }
+
+    #[test]
+    fn changed_username_is_shared() {
+        let mut state = lobby(4);
+        let username = "Alice".to_owned();
+
+        let effects =
+            assert_ok!(state.apply(PlayerId(1), PlayerAction::ChangeUsername { username }));
+        assert_matches!(
+            &effects.to_actor,
+            ActorEffect::ChangedUsername { username } if username == "Alice"
+        );
+        for id in state.player_ids() {
+            assert_matches!(
+                effects.for_player(id).as_slice(),
+                [GameEffect::PlayersInLobby { changed_player, usernames, host_id: Some(PlayerId(0)) }]
+                    if changed_player == "Alice" && usernames[1] == "Alice"
+            );
+        }
+
+        let taken = "player 0".to_owned();
+        assert_err!(state.apply(
+            PlayerId(1),
+            PlayerAction::ChangeUsername { username: taken }
+        ));
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        let late = "Bob".to_owned();
+        assert_err!(state.apply(PlayerId(1), PlayerAction::ChangeUsername { username: late }));
+    }
+
+    #[test]
+    fn start_game_needs_data() {
+        let mut state = GameState::new();
+        assert_eq!(
+            state.apply(PlayerId(0), PlayerAction::StartGame).map(drop),
+            Err(GameError::Lobby(LobbyError::MissingGameData))
+        );
+    }
+
+    #[test]
+    fn hidden_cash_is_bracketed() {
+        let mut state = lobby(4);
+        state.lobby_mut().unwrap().set_config(GameConfig {
+            hide_cash: true,
+            ..Default::default()
+        });
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+
+        let selecting = state.selecting_characters().unwrap();
+        assert!(selecting.config().hide_cash);
+        for info in selecting.player_info(PlayerId(0)) {
+            assert_eq!(info.cash, PlayerCash::Bracket(CashBracket::Low));
+        }
+    }
+
+    #[test]
+    fn bought_asset_tells_buying_power_left() {
+        let mut state = lobby(4);
+        state.lobby_mut().unwrap().set_config(GameConfig {
+            hide_cash: true,
+            ..Default::default()
+        });
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let round = state.round_mut().unwrap();
+        let id = round.current_player().id();
+        round.player_mut(id).unwrap()._set_cash(20);
+        let card_idx = round
+            .current_player()
+            .hand()
+            .iter()
+            .position(Either::is_left)
+            .unwrap();
+
+        let effects = assert_ok!(state.apply(id, PlayerAction::BuyAsset { card_idx }));
+        let player = state.round().unwrap().player(id).unwrap();
+        let (left, cash) = (player.assets_to_play(), player.cash());
+        assert_matches!(
+            effects.to_actor,
+            ActorEffect::BoughtAsset { assets_to_play, cash: c, .. }
+                if assets_to_play == left && c == cash
+        );
+        let other = PlayerId((id.0 + 1) % 4);
+        assert!(effects.for_player(other).iter().any(|e| matches!(
+            e,
+            GameEffect::BoughtAsset { assets_to_play, cash: PlayerCash::Bracket(bracket), .. }
+                if *assets_to_play == left && *bracket == CashBracket::new(cash)
+        )));
+    }
+
+    #[test]
+    fn time_bank_is_surfaced() {
+        let mut state = lobby(4);
+        state.lobby_mut().unwrap().set_config(GameConfig {
+            time_bank: Some(60),
+            ..Default::default()
+        });
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+
+        let effects = loop {
+            let selecting = state.selecting_characters().unwrap();
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            let effects = assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+            if state.round().is_ok() {
+                break effects;
+            }
+        };
+        assert_matches!(
+            effects.to_all.as_slice(),
+            [GameEffect::TurnStarts {
+                time_bank_ms: Some(60_000),
+                ..
+            }]
+        );
+
+        let id = state.round().unwrap().current_player().id();
+        assert_none!(state.out_of_time());
+        assert_eq!(
+            state.spend_time(id, Duration::from_secs(61)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(state.out_of_time(), Some(id));
+    }
+
+    #[test]
+    fn abilities_wait_for_their_decision() {
+        let mut state = lobby(4);
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            // Prefer characters with a decision, so at least one player has one this round
+            let selectable = selecting.player_get_selectable_characters(id).unwrap();
+            let character = selectable
+                .iter()
+                .copied()
+                .find(|c| PendingDecision::for_ability(*c).is_some())
+                .unwrap_or(selectable[0]);
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let fire = PlayerAction::FireCharacter {
+            character: Character::CEO,
+        };
+        let id = state.round().unwrap().current_player().id();
+        assert_eq!(
+            state.apply(id, fire.clone()).map(drop),
+            Err(GameError::NoDecisionPending)
+        );
+
+        while let Ok(round) = state.round()
+            && PendingDecision::for_ability(round.current_player().character()).is_none()
+        {
+            let id = round.current_player().id();
+            assert_ok!(state.apply(id, PlayerAction::EndTurn));
+        }
+
+        let round = state.round().unwrap();
+        let id = round.current_player().id();
+        let decision = PendingDecision::for_ability(round.current_player().character());
+        assert_ok!(state.apply(id, PlayerAction::UseAbility));
+        assert_eq!(state.round().unwrap().pending_decision(), decision);
+
+        assert_eq!(
+            state.apply(id, PlayerAction::GetBonusCash).map(drop),
+            Err(GameError::DecisionPending(decision.unwrap()))
+        );
+        assert_ok!(state.apply(id, PlayerAction::EndTurn));
+        assert_none!(state.round().unwrap().pending_decision());
+    }
+
+    /// Starts a round where the shareholder is the current player. The shareholder is not always
+    /// selectable, so the round is played with the first seed where it is.
+    fn shareholder_round() -> GameState {
+        (0..)
+            .map(|seed| {
+                let mut state = lobby(4);
+                with_seed(seed, || {
+                    assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+                    while let Ok(selecting) = state.selecting_characters() {
+                        let id = selecting.currently_selecting_id();
+                        let characters = selecting.player_get_selectable_characters(id).unwrap();
+                        let character = characters
+                            .iter()
+                            .copied()
+                            .find(|&c| c == Character::Shareholder)
+                            .or_else(|| characters.iter().copied().find(Character::can_be_fired))
+                            .unwrap_or(characters[0]);
+                        assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+                    }
+                });
+                state
+            })
+            .find(|state| {
+                state.round().unwrap().current_player().character() == Character::Shareholder
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn firing_options_are_characters_still_to_play() {
+        let mut state = shareholder_round();
+        let round = state.round().unwrap();
+        let id = round.current_player().id();
+        let mut in_play = round
+            .players()
+            .iter()
+            .map(|p| p.character())
+            .filter(|c| c.can_be_fired())
+            .collect::<Vec<_>>();
+        in_play.sort();
+
+        let effects = assert_ok!(state.apply(id, PlayerAction::UseAbility));
+        assert_matches!(
+            effects.to_actor,
+            ActorEffect::FiringOptions { characters } if characters == in_play
+        );
+    }
+
+    #[test]
+    fn fired_player_is_told_their_turn_is_skipped() {
+        let mut state = shareholder_round();
+        let round = state.round().unwrap();
+        let shareholder = round.current_player().id();
+        assert_eq!(round.current_player().character(), Character::Shareholder);
+        let fired = round
+            .players()
+            .iter()
+            .find(|p| p.character().can_be_fired())
+            .unwrap();
+        let (fired_id, character) = (fired.id(), fired.character());
+
+        assert_ok!(state.apply(shareholder, PlayerAction::UseAbility));
+        let effects =
+            assert_ok!(state.apply(shareholder, PlayerAction::FireCharacter { character }));
+        assert!(effects.for_player(fired_id).iter().any(|e| matches!(
+            e,
+            GameEffect::YourTurnSkipped {
+                reason: SkipReason::Fired { by }
+            } if *by == shareholder
+        )));
+        assert!(
+            effects
+                .for_spectators()
+                .iter()
+                .all(|e| !matches!(e, GameEffect::YourTurnSkipped { .. }))
+        );
+    }
+
+    #[test]
+    fn ability_use_is_shared() {
+        let mut state = shareholder_round();
+        let round = state.round().unwrap();
+        let shareholder = round.current_player().id();
+        let other = round.next_player().unwrap().id();
+        let character = round.player_get_fireble_characters()[0];
+        assert_matches!(
+            turn_starts(round),
+            GameEffect::TurnStarts {
+                can_use_ability: true,
+                ..
+            }
+        );
+
+        assert_ok!(state.apply(shareholder, PlayerAction::UseAbility));
+        assert_ok!(state.apply(shareholder, PlayerAction::FireCharacter { character }));
+        let round = state.round().unwrap();
+        assert!(!round.current_player().can_use_ability());
+        let info = round.player_info(other);
+        assert!(
+            info.iter()
+                .all(|p| p.has_used_ability == (p.id == shareholder))
+        );
+    }
+
+    #[test]
+    fn target_is_told_before_divest_is_confirmed() {
+        let mut state = (0..)
+            .map(|seed| {
+                let mut state = lobby(4);
+                with_seed(seed, || {
+                    assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+                    while let Ok(selecting) = state.selecting_characters() {
+                        let id = selecting.currently_selecting_id();
+                        let characters = selecting.player_get_selectable_characters(id).unwrap();
+                        let character = characters
+                            .iter()
+                            .copied()
+                            .find(|&c| c == Character::Stakeholder)
+                            .or_else(|| characters.iter().copied().find(|&c| c != Character::CSO))
+                            .unwrap_or(characters[0]);
+                        assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+                    }
+                });
+                state
+            })
+            .find(|state| {
+                let players = state.round().unwrap().players();
+                players
+                    .iter()
+                    .any(|p| p.character() == Character::Stakeholder)
+            })
+            .unwrap();
+
+        // Everyone before the stakeholder buys what they can, so there is something to divest
+        while let Ok(round) = state.round()
+            && round.current_player().character() != Character::Stakeholder
+        {
+            let id = round.current_player().id();
+            for card_idx in (0..round.current_player().hand().len()).rev() {
+                let _ = state.apply(id, PlayerAction::BuyAsset { card_idx });
+            }
+            assert_ok!(state.apply(id, PlayerAction::EndTurn));
+        }
+
+        let round = state.round_mut().unwrap();
+        let stakeholder = round.current_player().id();
+        round.player_mut(stakeholder).unwrap()._set_cash(20);
+        let (target_id, asset_idx) = round
+            .players()
+            .iter()
+            .filter(|p| p.character().can_be_forced_to_divest())
+            .find_map(|p| {
+                let idx = p.assets().iter().position(|a| a.color.is_divestable())?;
+                Some((p.id(), idx))
+            })
+            .unwrap();
+        let assets = round.player(target_id).unwrap().assets().len();
+
+        let divest = PlayerAction::DivestAsset {
+            target_player_id: target_id,
+            card_idx: asset_idx,
+        };
+        assert_eq!(
+            state
+                .apply(stakeholder, PlayerAction::ConfirmDivest)
+                .map(drop),
+            Err(GameError::NoDecisionPending)
+        );
+        assert_ok!(state.apply(stakeholder, PlayerAction::UseAbility));
+        let effects = assert_ok!(state.apply(stakeholder, divest));
+        assert_matches!(effects.to_actor, ActorEffect::PickedAssetToDivest { .. });
+        assert!(effects.for_player(target_id).iter().any(|e| matches!(
+            e,
+            GameEffect::BeingDivested { stakeholder_id, .. } if *stakeholder_id == stakeholder
+        )));
+        assert_eq!(
+            state
+                .round()
+                .unwrap()
+                .player(target_id)
+                .unwrap()
+                .assets()
+                .len(),
+            assets
+        );
+
+        let effects = assert_ok!(state.apply(stakeholder, PlayerAction::ConfirmDivest));
+        assert_matches!(effects.to_actor, ActorEffect::DivestedAsset { .. });
+        assert_eq!(
+            state
+                .round()
+                .unwrap()
+                .player(target_id)
+                .unwrap()
+                .assets()
+                .len(),
+            assets - 1
+        );
+        assert_none!(state.round().unwrap().pending_decision());
+    }
+
+    #[test]
+    fn credit_termination_is_explained_to_the_target() {
+        let mut state = lobby(4);
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let round = state.round_mut().unwrap();
+        let id = round.current_player().id();
+        let target = round.next_player().unwrap();
+        let (target_id, character) = (target.id(), target.character());
+        let expected = CreditTermination::new(target.assets());
+        round.banker_target = Some(character);
+
+        let effects = assert_ok!(state.apply(id, PlayerAction::EndTurn));
+        assert_eq!(
+            state.bankertarget().unwrap().credit_termination(),
+            &expected
+        );
+        assert!(effects.for_player(target_id).iter().any(|e| matches!(
+            e,
+            GameEffect::YourCreditTerminated(termination) if *termination == expected
+        )));
+        assert!(
+            effects
+                .for_player(id)
+                .iter()
+                .all(|e| !matches!(e, GameEffect::YourCreditTerminated(_)))
+        );
+
+        let cash = expected.total_gold + 1;
+        assert_eq!(
+            state
+                .apply(target_id, PlayerAction::PayBanker { cash })
+                .map(drop),
+            Err(PayBankerError::NotRightCashAmount {
+                expected: expected.total_gold.into(),
+                got: cash.into(),
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn game_end_shows_final_boards() {
+        let mut state = lobby(4);
+        state.lobby_mut().unwrap().set_config(GameConfig {
+            max_rounds: Some(1),
+            ..Default::default()
+        });
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+        let market = state.round().unwrap().current_market().clone();
+
+        let effects = loop {
+            let id = state.round().unwrap().current_player().id();
+            let effects = assert_ok!(state.apply(id, PlayerAction::EndTurn));
+            if state.results().is_ok() {
+                break effects;
+            }
+        };
+
+        let results = state.results().unwrap();
+        assert_matches!(
+            effects.to_all.as_slice(),
+            [GameEffect::GameEnded { boards, final_market, .. }]
+                if *boards == results.final_boards() && *final_market == market
+        );
+        for (board, player) in results.final_boards().iter().zip(results.players()) {
+            assert_eq!(board.id, player.id());
+            assert_eq!(board.cash, player.cash());
+            assert_eq!(board.breakdown.score, player.score());
+        }
+    }
+
+    #[test]
+    fn turn_starts_include_events() {
+        let mut state = lobby(4);
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let round = state.round_mut().unwrap();
+        let event = round
+            .markets
+            .deck
+            .iter()
+            .find_map(|card| card.as_ref().right())
+            .unwrap()
+            .clone();
+        round.current_events.push(event.clone());
+        assert_eq!(round.current_events(), std::slice::from_ref(&event));
+
+        let id = round.current_player().id();
+        let effects = assert_ok!(state.apply(id, PlayerAction::EndTurn));
+        assert!(effects.to_all.iter().any(|e| matches!(
+            e,
+            GameEffect::TurnStarts { events, .. } if *events == [event.clone()]
+        )));
+    }
+
+    #[test]
+    fn turn_starts_say_why_characters_were_skipped() {
+        let mut state = shareholder_round();
+        let round = state.round().unwrap();
+        let shareholder = round.current_player().id();
+        let character = round.player_get_fireble_characters()[0];
+        assert_ok!(state.apply(shareholder, PlayerAction::UseAbility));
+        assert_ok!(state.apply(shareholder, PlayerAction::FireCharacter { character }));
+        let reason = SkipReason::Fired { by: shareholder };
+        assert_eq!(state.round().unwrap().skip_reason(character), Some(&reason));
+
+        let effects = assert_ok!(state.apply(shareholder, PlayerAction::EndTurn));
+        let skip_reasons = effects.to_all.iter().find_map(|e| match e {
+            GameEffect::TurnStarts {
+                skipped_characters,
+                skip_reasons,
+                ..
+            } if skipped_characters.contains(&character) => Some(skip_reasons),
+            _ => None,
+        });
+        assert_eq!(skip_reasons.unwrap(), &[(character, reason)]);
+    }
+
+    #[test]
+    fn characters_are_banned_before_selecting() {
+        let mut state = lobby(4);
+        state.lobby_mut().unwrap().set_config(GameConfig {
+            character_bans: CharacterBans::Everyone,
+            ..Default::default()
+        });
+        let effects = assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+
+        let selecting = state.selecting_characters().unwrap();
+        let chairman = selecting.chairman_id();
+        assert_eq!(selecting.currently_banning_id(), Some(chairman));
+        assert!(effects.for_player(chairman).iter().any(|e| matches!(
+            e,
+            GameEffect::BanningCharacters {
+                bannable_characters: Some(_),
+                ..
+            }
+        )));
+        assert_eq!(
+            state
+                .apply(
+                    chairman,
+                    PlayerAction::SelectCharacter {
+                        character: Character::CEO
+                    }
+                )
+                .map(drop),
+            Err(SelectingCharactersError::BanningCharacters.into())
+        );
+        assert_eq!(
+            state
+                .apply(
+                    chairman,
+                    PlayerAction::BanCharacter {
+                        character: Character::CEO
+                    }
+                )
+                .map(drop),
+            Err(SelectingCharactersError::UnbannableCharacter.into())
+        );
+
+        let mut banned = vec![];
+        let effects = loop {
+            let selecting = state.selecting_characters().unwrap();
+            let Some(id) = selecting.currently_banning_id() else {
+                panic!("banning ended without effects");
+            };
+            assert_eq!(id, selecting.turn_order()[banned.len()]);
+            let character = selecting.player_get_bannable_characters(id).unwrap()[0];
+            banned.push(character);
+
+            let effects = assert_ok!(state.apply(id, PlayerAction::BanCharacter { character }));
+            if state
+                .selecting_characters()
+                .unwrap()
+                .currently_banning_id()
+                .is_none()
+            {
+                break effects;
+            }
+        };
+
+        // Four players can ban three characters, which leaves no open characters
+        let selecting = state.selecting_characters().unwrap();
+        assert_eq!(selecting.banned_characters(), banned);
+        assert!(selecting.open_characters().is_empty());
+        let selectable = assert_ok!(selecting.player_get_selectable_characters(chairman));
+        assert!(selectable.iter().all(|c| !banned.contains(c)));
+        assert!(effects.for_player(chairman).iter().any(|e| matches!(
+            e,
+            GameEffect::SelectingCharacters {
+                selectable_characters: Some(_),
+                banned_characters,
+                ..
+            } if *banned_characters == banned
+        )));
+    }
+
+    #[test]
+    fn host_forces_end_turn() {
+        let mut state = lobby(4);
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let id = state.round().unwrap().current_player().id();
+        let draw = PlayerAction::DrawCard {
+            card_type: CardType::Asset,
+        };
+        while state.round().unwrap().current_player().can_draw_cards() {
+            let effects = assert_ok!(state.apply(id, draw.clone()));
+            let deck_sizes = state.round().unwrap().deck_sizes();
+            assert!(effects.for_spectators().iter().any(
+                |e| matches!(e, GameEffect::DrewCard { deck_sizes: d, .. } if *d == deck_sizes)
+            ));
+        }
+        let gives_back = state.round().unwrap().current_player().gives_back_n_cards();
+        let assets_left = state.round().unwrap().deck_sizes().assets;
+
+        assert_eq!(
+            state
+                .apply(PlayerId(1), PlayerAction::ForceEndTurn)
+                .map(drop),
+            Err(GameError::NotHost)
+        );
+        let effects = assert_ok!(state.apply(PlayerId(0), PlayerAction::ForceEndTurn));
+
+        assert_matches!(effects.to_actor, ActorEffect::ForcedEndTurn { player_id } if player_id == id);
+        let put_back = effects
+            .to_all
+            .iter()
+            .filter(|e| matches!(e, GameEffect::PutBackCard { player_id, .. } if *player_id == id))
+            .count();
+        assert_eq!(put_back, usize::from(gives_back));
+        assert!(effects.to_all.iter().any(|e| matches!(
+            e,
+            GameEffect::PutBackCard { deck_sizes, .. }
+                if deck_sizes.assets == assets_left + usize::from(gives_back)
+        )));
+        assert!(
+            effects.to_all.iter().any(
+                |e| matches!(e, GameEffect::TurnForcedToEnd { player_id } if *player_id == id)
+            )
+        );
+        assert_ne!(
+            state.round().map(|round| round.current_player().id()),
+            Ok(id)
+        );
+    }
+
+    #[test]
+    fn strict_phases_are_enforced() {
+        let mut state = lobby(4);
+        state.lobby_mut().unwrap().set_config(GameConfig {
+            strict_phases: true,
+            ..Default::default()
+        });
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+
+        let effects = loop {
+            let selecting = state.selecting_characters().unwrap();
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            let effects = assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+            if state.round().is_ok() {
+                break effects;
+            }
+        };
+        assert_matches!(
+            effects.to_all.as_slice(),
+            [GameEffect::TurnStarts {
+                phase: TurnPhase::Draw,
+                ..
+            }]
+        );
+
+        let id = state.round().unwrap().current_player().id();
+        assert_eq!(
+            state.apply(id, PlayerAction::EndTurn).map(drop),
+            Err(GameError::WrongTurnPhase(TurnPhase::Draw))
+        );
+
+        let draw = PlayerAction::DrawCard {
+            card_type: CardType::Asset,
+        };
+        while state.round().unwrap().turn_phase() == TurnPhase::Draw {
+            assert_ok!(state.apply(id, draw.clone()));
+        }
+        assert_eq!(state.round().unwrap().turn_phase(), TurnPhase::Cleanup);
+        assert_eq!(
+            state
+                .apply(id, PlayerAction::BuyAsset { card_idx: 0 })
+                .map(drop),
+            Err(GameError::WrongTurnPhase(TurnPhase::Cleanup))
+        );
+
+        assert_ok!(state.apply(id, PlayerAction::PutBackCard { card_idx: 0 }));
+        assert_eq!(state.round().unwrap().turn_phase(), TurnPhase::Main);
+        assert_ok!(state.apply(id, PlayerAction::EndTurn));
+    }
+
+    #[test]
+    fn others_are_told_about_actions() {
+        let mut state = lobby(4);
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+
+        let id = state
+            .selecting_characters()
+            .unwrap()
+            .currently_selecting_id();
+        let character = state
+            .selecting_characters()
+            .unwrap()
+            .player_get_selectable_characters(id)
+            .unwrap()[0];
+        let effects = assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        assert_matches!(effects.to_actor, ActorEffect::SelectedCharacter { character: c } if c == character);
+        assert_eq!(effects.to_each_player.len(), 4);
+
+        let others = ActionEffects::new(ActorEffect::EndedTurn).to_others(
+            [PlayerId(0), PlayerId(1), PlayerId(2)],
+            PlayerId(1),
+            GameEffect::RedeemedLiability {
+                player_id: PlayerId(1),
+                liability_idx: 0,
+                cost: 1,
+            },
+        );
+        assert!(others.for_player(PlayerId(1)).is_empty());
+        assert_eq!(others.for_spectators().len(), 1);
+        assert_eq!(others.for_player(PlayerId(0)).len(), 1);
+        assert_eq!(others.for_player(PlayerId(2)).len(), 1);
+    }
+}
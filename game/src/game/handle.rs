@@ -0,0 +1,110 @@
+//! File containing [`GameHandle`], which shares a [`GameState`] between async tasks. Every action
+//! goes through the handle, so anyone interested in what happens in the game can subscribe to the
+//! effects of each action instead of polling the state.
+
+use tokio::sync::{Mutex, broadcast};
+
+use std::sync::Arc;
+
+use crate::{errors::GameError, game::*, player::PlayerId};
+
+/// How many applied actions a subscriber can fall behind before it misses some.
+const EFFECTS_CAPACITY: usize = 64;
+
+/// An action that was applied to the game behind a [`GameHandle`], together with its effects.
+#[derive(Debug, Clone)]
+pub struct AppliedAction {
+    /// The player that took the action.
+    pub player_id: PlayerId,
+    /// The action itself.
+    pub action: PlayerAction,
+    /// What the action changed, see [`GameState::apply`].
+    pub effects: ActionEffects,
+}
+
+/// A [`GameState`] that can be shared between async tasks. Actions are applied one at a time, and
+/// the effects of every action are sent to everyone that [subscribed](GameHandle::subscribe).
+/// Clones share the same game.
+#[derive(Debug, Clone)]
+pub struct GameHandle {
+    state: Arc<Mutex<GameState>>,
+    effects: broadcast::Sender<AppliedAction>,
+}
+
+impl GameHandle {
+    /// Creates a handle to `state`.
+    pub fn new(state: GameState) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            effects: broadcast::channel(EFFECTS_CAPACITY).0,
+        }
+    }
+
+    /// Applies `action` on behalf of player `id`, see [`GameState::apply`]. Waits for any other
+    /// action to be applied first. Once applied, the effects are also sent to every subscriber.
+    pub async fn apply(
+        &self,
+        id: PlayerId,
+        action: PlayerAction,
+    ) -> Result<ActionEffects, GameError> {
+        let mut state = self.state.lock().await;
+        let effects = state.apply(id, action.clone())?;
+
+        // Nobody might be listening, which is fine
+        let _ = self.effects.send(AppliedAction {
+            player_id: id,
+            action,
+            effects: effects.clone(),
+        });
+
+        Ok(effects)
+    }
+
+    /// Gets a receiver for the effects of every action applied from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AppliedAction> {
+        self.effects.subscribe()
+    }
+
+    /// Calls `f` with the current state of the game, while no action can be applied.
+    pub async fn read<R>(&self, f: impl FnOnce(&GameState) -> R) -> R {
+        f(&*self.state.lock().await)
+    }
+
+    /// Gets a copy of the current state of the game.
+    pub async fn state(&self) -> GameState {
+        self.state.lock().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::GameData;
+    use claim::*;
+
+    #[tokio::test]
+    async fn subscribers_see_applied_actions() {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        lobby.set_game_data(GameData::new("../assets/cards/boardgame.json").unwrap());
+
+        let handle = GameHandle::new(state);
+        let mut effects = handle.subscribe();
+
+        assert_err!(handle.apply(PlayerId(0), PlayerAction::EndTurn).await);
+        assert_ok!(handle.apply(PlayerId(0), PlayerAction::StartGame).await);
+
+        let applied = assert_ok!(effects.try_recv());
+        assert_eq!(applied.player_id, PlayerId(0));
+        assert_eq!(applied.action, PlayerAction::StartGame);
+        assert_err!(effects.try_recv());
+        assert!(
+            handle
+                .read(|state| state.selecting_characters().is_ok())
+                .await
+        );
+    }
+}
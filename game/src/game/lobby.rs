@@ -1,6 +1,6 @@
 //! File containing the lobby state of the game.
 
-use std::path::Path;
+use std::{path::Path, time::Duration};
 
 use either::Either;
 
@@ -9,13 +9,32 @@ use crate::{cards::GameData, errors::*, game::*, player::*};
 /// Cash each player starts with
 pub const STARTING_GOLD: u8 = 1;
 
+/// The fewest players a game can be played with
+pub const MIN_PLAYERS: usize = 4;
+
+/// The most players a game can be played with
+pub const MAX_PLAYERS: usize = 7;
+
 /// State containing all information related to the lobby stage of the game. In the lobby state,
 /// players are allowed to join and leave freely. When between 4 to 7 players are in the lobby,
 /// players are allowed to start a game.
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Lobby {
     /// The players in the lobby
     players: Players<LobbyPlayer>,
+    /// The cards the game is played with once it starts
+    game_data: Option<GameData>,
+    /// The options the game is played with once it starts
+    config: GameConfig,
+    /// Rejects usernames that are not allowed, on top of the rules in [`validate_username`]. A
+    /// filter cannot be stored, so it has to be set again after the lobby is restored.
+    #[serde(skip)]
+    username_filter: Option<UsernameFilter>,
+    /// The username of the player that manages the lobby, see [`Lobby::host`]
+    host: Option<String>,
+    /// Everyone watching the lobby without playing, see [`Lobby::spectate`]
+    #[serde(default)]
+    spectators: Spectators,
 }
 
 impl Lobby {
@@ -32,6 +51,33 @@ impl Lobby {
         Self::default()
     }
 
+    /// Gets the cards the game will be played with, if they were set.
+    pub fn game_data(&self) -> Option<&GameData> {
+        self.game_data.as_ref()
+    }
+
+    /// Sets the cards the game will be played with when it is started through
+    /// [`GameState::apply`].
+    pub fn set_game_data(&mut self, data: GameData) {
+        self.game_data = Some(data);
+    }
+
+    /// Gets the options the game will be played with.
+    pub fn config(&self) -> GameConfig {
+        self.config
+    }
+
+    /// Sets the options the game will be played with once it starts.
+    pub fn set_config(&mut self, config: GameConfig) {
+        self.config = config;
+    }
+
+    /// Sets the filter that usernames of players joining the lobby have to pass, for example to
+    /// keep out profanity.
+    pub fn set_username_filter(&mut self, filter: UsernameFilter) {
+        self.username_filter = Some(filter);
+    }
+
     /// Returns the number of players in the lobby, also referred to as its 'length'.
     ///
     /// Examples
@@ -153,8 +199,149 @@ impl Lobby {
         self.players().iter().map(|p| p.name()).collect()
     }
 
-    /// Allows a player to join the lobby based on a username. If the username is not yet taken, the
-    /// player is added to the list of players and a reference to it will be returned.
+    /// Gets the host of the lobby, who is the only player allowed to start the game. The first
+    /// player to join becomes the host. When the host leaves, the player that joined after them
+    /// the earliest takes over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::{errors::GameError, game::Lobby, player::PlayerId};
+    /// # fn main() -> Result<(), GameError> {
+    /// let mut lobby = Lobby::default();
+    /// assert_eq!(lobby.host(), None);
+    ///
+    /// lobby.join("player 1".to_owned())?;
+    /// lobby.join("player 2".to_owned())?;
+    /// assert_eq!(lobby.host().map(|p| p.name()), Some("player 1"));
+    ///
+    /// lobby.leave("player 1");
+    /// assert_eq!(lobby.host().map(|p| p.id()), Some(PlayerId(0)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn host(&self) -> Option<&LobbyPlayer> {
+        let host = self.host.as_deref()?;
+        self.players().iter().find(|p| p.name() == host)
+    }
+
+    /// Makes player `target` the host of the lobby on behalf of player `id`, who has to be the
+    /// current host. Returns the new host.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::{errors::GameError, game::Lobby, player::PlayerId};
+    /// # fn main() -> Result<(), GameError> {
+    /// let mut lobby = Lobby::default();
+    /// lobby.join("player 1".to_owned())?;
+    /// lobby.join("player 2".to_owned())?;
+    ///
+    /// assert!(lobby.transfer_host(PlayerId(1), PlayerId(0)).is_err());
+    /// lobby.transfer_host(PlayerId(0), PlayerId(1))?;
+    /// assert_eq!(lobby.host().map(|p| p.name()), Some("player 2"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn transfer_host(
+        &mut self,
+        id: PlayerId,
+        target: PlayerId,
+    ) -> Result<&LobbyPlayer, GameError> {
+        self.check_host(id)?;
+        self.host = Some(self.players.player(target)?.name().to_owned());
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("host transferred");
+
+        self.players.player(target)
+    }
+
+    /// Sets whether player `id` is ready for the game to start. Players that join are not ready
+    /// until they say so. Returns the player.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::{errors::GameError, game::Lobby, player::PlayerId};
+    /// # fn main() -> Result<(), GameError> {
+    /// let mut lobby = Lobby::default();
+    /// lobby.join("player 1".to_owned())?;
+    /// lobby.join("player 2".to_owned())?;
+    ///
+    /// lobby.set_ready(PlayerId(0), true)?;
+    /// assert!(!lobby.all_ready());
+    /// lobby.set_ready(PlayerId(1), true)?;
+    /// assert!(lobby.all_ready());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_ready(&mut self, id: PlayerId, ready: bool) -> Result<&LobbyPlayer, GameError> {
+        self.players.player_mut(id)?.set_ready(ready);
+        self.players.player(id)
+    }
+
+    /// Checks whether every player in the lobby is ready for the game to start. Only stops the
+    /// game from starting if [`GameConfig::require_ready`] is set.
+    pub fn all_ready(&self) -> bool {
+        self.players().iter().all(LobbyPlayer::is_ready)
+    }
+
+    /// Checks whether player `id` is allowed to kick player `target` out of the lobby, which only
+    /// the host can do to anyone but themselves. Returns the player that would be kicked.
+    pub fn check_kick(&self, id: PlayerId, target: PlayerId) -> Result<&LobbyPlayer, GameError> {
+        self.check_host(id)?;
+        if id == target {
+            return Err(LobbyError::CannotKickSelf.into());
+        }
+        self.players.player(target)
+    }
+
+    /// Kicks player `target` out of the lobby on behalf of player `id`, who has to be the host.
+    /// The players after them move up a seat, like they do when someone leaves. Returns the
+    /// player that was kicked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::{errors::GameError, game::Lobby, player::PlayerId};
+    /// # fn main() -> Result<(), GameError> {
+    /// let mut lobby = Lobby::default();
+    /// lobby.join("player 1".to_owned())?;
+    /// lobby.join("player 2".to_owned())?;
+    /// lobby.join("player 3".to_owned())?;
+    ///
+    /// assert!(lobby.kick(PlayerId(1), PlayerId(2)).is_err());
+    /// assert!(lobby.kick(PlayerId(0), PlayerId(0)).is_err());
+    /// assert_eq!(lobby.kick(PlayerId(0), PlayerId(1))?.name(), "player 2");
+    /// assert_eq!(lobby.usernames(), vec!["player 1", "player 3"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn kick(&mut self, id: PlayerId, target: PlayerId) -> Result<LobbyPlayer, GameError> {
+        let kicked = self.check_kick(id, target)?.clone();
+        self.leave(kicked.name());
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("player kicked");
+
+        Ok(kicked)
+    }
+
+    /// Checks whether player `id` is the host of the lobby.
+    pub(super) fn check_host(&self, id: PlayerId) -> Result<(), GameError> {
+        match self.host() {
+            Some(host) if host.id() == id => Ok(()),
+            _ => Err(GameError::NotHost),
+        }
+    }
+
+    /// Allows a player to join the lobby based on a username. The username is normalized and
+    /// checked with [`validate_username`], and cannot only differ in case from the username of
+    /// another player. If it passes, the player is added to the list of players under the
+    /// normalized username and a reference to it will be returned.
     ///
     /// # Examples
     ///
@@ -166,26 +353,90 @@ impl Lobby {
     /// lobby.join("player 2".to_owned())?;
     ///
     /// assert_eq!(lobby.usernames(), vec!["player 1", "player 2"]);
+    /// assert!(lobby.join(" Player 1".to_owned()).is_err());
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
     pub fn join(&mut self, username: String) -> Result<&LobbyPlayer, LobbyError> {
-        match self.players().iter().find(|p| p.name() == username) {
+        let username = validate_username(&username, self.username_filter.as_ref())?;
+
+        match self
+            .players()
+            .iter()
+            .find(|p| usernames_collide(p.name(), &username))
+        {
             Some(_) => Err(LobbyError::UsernameAlreadyTaken(username)),
+            None if self.spectators.contains(&username) => {
+                Err(LobbyError::UsernameAlreadyTaken(username))
+            }
             None => {
                 let id = PlayerId(self.players.len() as u8);
-                let name = username.clone();
-                let player = LobbyPlayer::new(id, name, true);
+                let player = LobbyPlayer::new(id, username, true);
 
+                self.host.get_or_insert_with(|| player.name().to_owned());
                 self.players.0.push(player);
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!(?id, "player joined");
+
                 Ok(&self.players.0[self.players.len() - 1])
             }
         }
     }
 
+    /// Changes the username of player `id`, with the same rules as [`Lobby::join`]. Players can
+    /// change the case of their own username, but cannot take one that collides with the username
+    /// of another player. The host stays the host under their new username. Returns the player
+    /// with their new, normalized username.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::{errors::GameError, game::Lobby, player::PlayerId};
+    /// # fn main() -> Result<(), GameError> {
+    /// let mut lobby = Lobby::default();
+    /// lobby.join("player 1".to_owned())?;
+    /// lobby.join("player 2".to_owned())?;
+    /// lobby.change_username(PlayerId(0), " alice ".to_owned())?;
+    ///
+    /// assert_eq!(lobby.usernames(), vec!["alice", "player 2"]);
+    /// assert_eq!(lobby.host().map(|p| p.name()), Some("alice"));
+    /// assert!(lobby.change_username(PlayerId(0), "Player 2".to_owned()).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn change_username(
+        &mut self,
+        id: PlayerId,
+        username: String,
+    ) -> Result<&LobbyPlayer, GameError> {
+        let username = validate_username(&username, self.username_filter.as_ref())?;
+        if self
+            .players()
+            .iter()
+            .any(|p| p.id() != id && usernames_collide(p.name(), &username))
+        {
+            return Err(LobbyError::UsernameAlreadyTaken(username).into());
+        }
+
+        let player = self.players.player_mut(id)?;
+        if self.host.as_deref() == Some(player.name()) {
+            self.host = Some(username.clone());
+        }
+        player.set_name(username);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("username changed");
+
+        self.players.player(id)
+    }
+
     /// Allows a player to leave the lobby based on their username. If that username is in the list,
     /// the player will be removed and `true` will be returned. If the player cannot be removed,
-    /// the function will return `false` instead.
+    /// the function will return `false` instead. If the host leaves, the player that joined the
+    /// earliest becomes the new host.
     ///
     /// NOTE: this function will reorder player ids if a player that is not at the end of the list
     /// leaves the lobby
@@ -204,11 +455,18 @@ impl Lobby {
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret)
+    )]
     pub fn leave(&mut self, username: &str) -> bool {
         match self.players().iter().position(|p| p.name() == username) {
             Some(pos) => {
                 // PANIC: we just verified this is a valid position so removing here cannot crash.
                 self.players.0.remove(pos);
+                if self.host.as_deref() == Some(username) {
+                    self.host = self.players().first().map(|p| p.name().to_owned());
+                }
                 self.players_mut()
                     .iter_mut()
                     .zip(0u8..)
@@ -219,6 +477,24 @@ impl Lobby {
         }
     }
 
+    /// Gets everyone watching the lobby, who are listed apart from the players in it.
+    pub fn spectators(&self) -> &Spectators {
+        &self.spectators
+    }
+
+    /// Lets `username` watch the lobby, with the same rules as [`Lobby::join`]. Spectators do not
+    /// count towards the players needed to start, and keep watching once the game started.
+    pub fn spectate(&mut self, username: String) -> Result<&str, LobbyError> {
+        let names = self.players.iter().map(|p| p.name());
+        self.spectators
+            .join(&username, names, self.username_filter.as_ref())
+    }
+
+    /// Stops `username` from watching the lobby. Returns whether they were watching.
+    pub fn stop_spectating(&mut self, username: &str) -> bool {
+        self.spectators.leave(username)
+    }
+
     /// Gets the [`PlayerInfo`] for each player, excluding the player
     /// that has the same id as `id`.
     ///
@@ -244,8 +520,13 @@ impl Lobby {
             .collect()
     }
 
-    /// Checks whether or not the game can start. The game can start if the room has between 4 and
-    /// 7 players.
+    /// Gets the [`PlayerInfo`] for each player, as a spectator sees it.
+    pub fn spectator_info(&self) -> Vec<PlayerInfo> {
+        self.players().iter().map(Into::into).collect()
+    }
+
+    /// Checks whether or not the game can start. The game can start if the room has as many players
+    /// as [`GameConfig::player_counts`] allows, which is between 4 and 7 by default.
     ///
     /// # Examples
     /// ```
@@ -262,7 +543,7 @@ impl Lobby {
     /// # }
     /// ```
     pub fn can_start(&self) -> bool {
-        (4..=7).contains(&self.players.len())
+        self.config.player_counts().contains(&self.players.len())
     }
 
     /// Starts the game when between 4 to 7 players are in the lobby and potentially returns the new [`GameState`] if the game is started. Takes in `data_path`, which is meant to be a path
@@ -284,6 +565,31 @@ impl Lobby {
                 Err(e) => panic!("{e}"),
             };
 
+            self.start_game_with_data(data)
+        } else {
+            Err(GameError::InvalidPlayerCount(self.players().len() as u8))
+        }
+    }
+
+    /// Starts the game when between 4 to 7 players are in the lobby, using already loaded `data`
+    /// to fill each deck. Potentially returns the new [`GameState`] if the game is started.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err)
+    )]
+    pub(super) fn start_game_with_data(&mut self, data: GameData) -> Result<GameState, GameError> {
+        self.config.validate()?;
+        if self.config.require_ready && !self.all_ready() {
+            return Err(LobbyError::NotEveryoneReady.into());
+        }
+        if self.can_start() {
+            let data_checksum = data.checksum();
+
+            #[cfg(feature = "shuffle")]
+            if self.config.shuffle_seats {
+                self.shuffle_seats();
+            }
+
             #[cfg(feature = "shuffle")]
             let data = {
                 let mut data = data;
@@ -295,7 +601,7 @@ impl Lobby {
             let mut liabilities = data.liabilities;
             let mut markets = data.market_deck;
 
-            let players = self.init_players(&mut assets, &mut liabilities);
+            let players = self.init_players(&mut assets, &mut liabilities)?;
             let current_market = Lobby::initial_market(&mut markets).unwrap_or_default();
 
             let chairman = players
@@ -306,16 +612,30 @@ impl Lobby {
             debug_assert_eq!(chairman, PlayerId(0));
 
             let characters = ObtainingCharacters::new(players.len(), chairman)?;
+            let clock = self.config.time_bank.map(|secs| {
+                ChessClock::new(
+                    players.iter().map(|p| p.id()),
+                    Duration::from_secs(secs.into()),
+                )
+            });
 
             let selecting = GameState::SelectingCharacters(SelectingCharacters {
                 players,
                 characters,
+                banned_characters: Vec::new(),
                 assets,
                 liabilities,
                 markets,
                 chairman,
                 current_market,
                 current_events: Vec::new(),
+                config: self.config,
+                data_checksum,
+                clock,
+                host: self.host().map(|p| p.id()),
+                round_number: 1,
+                character_stats: CharacterStatsMap::new(),
+                spectators: self.spectators.clone(),
             });
 
             Ok(selecting)
@@ -324,33 +644,48 @@ impl Lobby {
         }
     }
 
+    /// Seats the players in a random order, giving each of them the id of their new seat.
+    #[cfg(feature = "shuffle")]
+    fn shuffle_seats(&mut self) {
+        use rand::seq::SliceRandom;
+
+        crate::utility::with_rng(|rng| self.players.0.shuffle(rng));
+        self.players_mut()
+            .iter_mut()
+            .zip(0u8..)
+            .for_each(|(p, id)| p.set_id(PlayerId(id)));
+    }
+
     /// Initializes [`SelectingCharactersPlayer`](crate::player::SelectingCharactersPlayer) with
-    /// their appropriate starting gold and their initial hand.
+    /// their appropriate starting gold and their initial hand. Fails if the decks are too small to
+    /// deal everyone a hand.
     fn init_players(
         &mut self,
         assets: &mut Deck<Asset>,
         liabilities: &mut Deck<Liability>,
-    ) -> Players<SelectingCharactersPlayer> {
+    ) -> Result<Players<SelectingCharactersPlayer>, DrawCardError> {
         self.players.0.sort_by_key(|p| p.id());
 
         let players = self
             .players()
             .iter()
             .map(|p| {
-                let assets = [assets.draw(), assets.draw()];
-                let liabilities = [liabilities.draw(), liabilities.draw()];
-                SelectingCharactersPlayer::new(
+                let mut draw_asset = || assets.draw().ok_or(DrawCardError::EmptyDeck);
+                let assets = [draw_asset()?, draw_asset()?];
+                let mut draw_liability = || liabilities.draw().ok_or(DrawCardError::EmptyDeck);
+                let liabilities = [draw_liability()?, draw_liability()?];
+                Ok(SelectingCharactersPlayer::new(
                     p.name().to_owned(),
                     p.id(),
                     assets,
                     liabilities,
-                    STARTING_GOLD,
+                    self.config.starting_cash().into(),
                     p.is_human(),
-                )
+                ))
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        Players(players)
+        Ok(Players(players))
     }
 
     /// Grab market card if available. If no market cards are in the deck, `None` is returned.
@@ -1,16 +1,56 @@
 //! This is where the game logic, excluding the player-specific logic, is located.
 
+mod action;
 mod banker_target;
+mod bounds;
+mod clock;
+mod config;
+mod debugger;
+mod decision;
+#[cfg(feature = "dev")]
+mod dev;
+mod effects;
+#[cfg(feature = "handle")]
+mod handle;
 mod lobby;
+mod notation;
+mod outcome;
+mod phase;
+mod puzzle;
+mod render;
+mod replay;
 mod results;
 mod round;
+mod save;
+mod scoring;
 mod selecting_characters;
+mod spectators;
+mod stats;
+mod username;
 
+pub use action::*;
 pub use banker_target::*;
+pub use clock::*;
+pub use config::*;
+pub use debugger::*;
+pub use decision::*;
+pub use effects::*;
+#[cfg(feature = "handle")]
+pub use handle::*;
 pub use lobby::*;
+pub use notation::*;
+pub use outcome::*;
+pub use phase::*;
+pub use puzzle::*;
+pub use replay::*;
 pub use results::*;
 pub use round::*;
+pub use save::*;
+pub use scoring::*;
 pub use selecting_characters::*;
+pub use spectators::*;
+pub use stats::*;
+pub use username::*;
 
 use either::Either;
 use serde::{Deserialize, Serialize};
@@ -18,13 +58,19 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet},
     path::Path,
     sync::Arc,
+    time::Duration,
     vec,
 };
 
-use crate::{errors::*, player::*, utility::serde_asset_liability};
+use crate::{
+    cards::GameData,
+    errors::*,
+    player::*,
+    utility::{serde_asset_liability, stable_hash},
+};
 
 /// Amount of assets required to end the game
 pub const ASSETS_FOR_END_OF_GAME: usize = 6;
@@ -40,29 +86,56 @@ pub struct Event {
     /// A narration of the event which describes what happens
     pub description: String,
     /// A set of colors that gain gold because of this event
-    pub plus_gold: HashSet<Color>,
+    pub plus_gold: BTreeSet<Color>,
     /// A set of colors that lose gold because of this event
-    pub minus_gold: HashSet<Color>,
+    pub minus_gold: BTreeSet<Color>,
     /// A character that skips their turn because of this event
     pub skip_turn: Option<Character>,
 }
 
+/// Why a player does not get a turn this round.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SkipReason {
+    /// The shareholder fired their character.
+    Fired {
+        /// The id of the player who is the shareholder.
+        by: PlayerId,
+    },
+    /// An event skips the turn of their character, see [`Event::skip_turn`].
+    Event {
+        /// The title of the event.
+        title: String,
+    },
+}
+
 /// A representation of the market condition for a specific color. It can either be
-/// 1. Up: (+)
-/// 2. Zero: ( )
-/// 3. Minus: (-)
+/// 1. Double up: (++)
+/// 2. Up: (+)
+/// 3. Zero: ( )
+/// 4. Minus: (-)
+/// 5. Double minus: (--)
+///
+/// The double conditions are only reached through market cards that define them.
 ///
 /// NOTE: The default state is `Zero`, which is also the case when parsing with serde.
 #[cfg_attr(feature = "ts", derive(TS))]
 #[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
 #[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum MarketCondition {
+    /// The market for this color is strongly up
+    #[serde(rename = "double up")]
+    DoublePlus,
     /// The market for this color is up
     #[serde(rename = "up")]
     Plus,
     /// The market for this color is down
     #[serde(rename = "down")]
     Minus,
+    /// The market for this color is strongly down
+    #[serde(rename = "double down")]
+    DoubleMinus,
     /// The market for this color is neutral
     #[default]
     #[serde(rename = "zero")]
@@ -70,22 +143,40 @@ pub enum MarketCondition {
 }
 
 impl MarketCondition {
+    /// Gets how many times the silver value of an asset counts in this condition, from `-2` for
+    /// `DoubleMinus` up to `2` for `DoublePlus`.
+    pub fn multiplier(self) -> i8 {
+        match self {
+            Self::DoublePlus => 2,
+            Self::Plus => 1,
+            Self::Zero => 0,
+            Self::Minus => -1,
+            Self::DoubleMinus => -2,
+        }
+    }
+
     /// Makes into a higher market condition:
-    /// `Plus` and `Zero` become `Plus`, `Minus` becomes `Zero.
+    /// `Plus` and `Zero` become `Plus`, `Minus` becomes `Zero`, `DoubleMinus` becomes `Minus`.
+    /// `DoublePlus` stays as it is.
     pub fn make_higher(&mut self) -> Self {
         *self = match self {
+            Self::DoublePlus => Self::DoublePlus,
             Self::Plus | Self::Zero => Self::Plus,
             Self::Minus => Self::Zero,
+            Self::DoubleMinus => Self::Minus,
         };
         *self
     }
 
     /// Makes into a lower market condition:
-    /// `Zero` and `Minus` become `Minus`, `Plus` becomes `Zero.
+    /// `Zero` and `Minus` become `Minus`, `Plus` becomes `Zero`, `DoublePlus` becomes `Plus`.
+    /// `DoubleMinus` stays as it is.
     pub fn make_lower(&mut self) -> Self {
         *self = match self {
+            Self::DoubleMinus => Self::DoubleMinus,
             Self::Minus | Self::Zero => Self::Minus,
             Self::Plus => Self::Zero,
+            Self::DoublePlus => Self::Plus,
         };
         *self
     }
@@ -131,6 +222,19 @@ impl Market {
             Color::Blue => self.blue,
         }
     }
+
+    /// Gets a mutable reference to the market condition for a specific color. Together with
+    /// [`Market::color_condition`], this is the only place that knows which field belongs to
+    /// which color.
+    pub fn color_condition_mut(&mut self, color: Color) -> &mut MarketCondition {
+        match color {
+            Color::Red => &mut self.red,
+            Color::Green => &mut self.green,
+            Color::Purple => &mut self.purple,
+            Color::Yellow => &mut self.yellow,
+            Color::Blue => &mut self.blue,
+        }
+    }
 }
 
 impl Default for Market {
@@ -148,10 +252,6 @@ impl Default for Market {
     }
 }
 
-fn default_backup_deck<T>() -> Box<[T]> {
-    Box::new([])
-}
-
 /// A wrapper struct around `Vec<T>` which allows for easy interaction with it as a deck of cards.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Deck<T> {
@@ -161,9 +261,9 @@ pub struct Deck<T> {
     /// The list of actual cards
     #[serde(rename = "card_list")]
     pub deck: Vec<T>,
-    /// A backup of the deck, which is set when the deck is created.
-    #[serde(skip, default = "default_backup_deck")]
-    backup_deck: Box<[T]>,
+    /// The cards that were discarded, which are shuffled back into the deck once it runs out.
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    discarded: Vec<T>,
 }
 
 impl<T: Clone> Deck<T> {
@@ -177,10 +277,9 @@ impl<T: Clone> Deck<T> {
     /// assert_eq!(deck.deck, [1, 2, 3]);
     /// ```
     pub fn new(deck: Vec<T>) -> Self {
-        let backup_deck = deck.clone().into_boxed_slice();
         Self {
             deck,
-            backup_deck,
+            discarded: Vec::new(),
             image_back_url: String::new().into(),
         }
     }
@@ -204,23 +303,30 @@ impl<T: Clone> Deck<T> {
         deck
     }
 
-    /// Draws a new card from the deck. If the deck ran out it is restored from the backup deck,
-    /// reshuffled and then a card is drawn from that new deck instead.
-    pub fn draw(&mut self) -> T {
-        match self.deck.pop() {
-            Some(card) => card,
-            None => {
-                self.deck = self.backup_deck.to_vec();
-
-                #[cfg(feature = "shuffle")]
-                self.shuffle();
+    /// Draws a new card from the deck. If the deck ran out the discarded cards are shuffled back
+    /// into it first, so every card is only ever in one place. Returns `None` if there are no
+    /// cards left to draw at all, because every card is held by a player.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::game::Deck;
+    /// let mut deck = Deck::new(vec![1]);
+    /// assert_eq!(deck.draw(), Some(1));
+    /// assert_eq!(deck.draw(), None);
+    ///
+    /// deck.discard(1);
+    /// assert_eq!(deck.draw(), Some(1));
+    /// ```
+    pub fn draw(&mut self) -> Option<T> {
+        if self.deck.is_empty() {
+            self.deck = std::mem::take(&mut self.discarded);
 
-                // TODO: maybe fix for if the deck was empty when initialized, because in that case
-                // it still crashes. This isn't a concern for our game though and I prefer to not
-                // return `Option` here.
-                self.deck.pop().unwrap()
-            }
+            #[cfg(feature = "shuffle")]
+            self.shuffle();
         }
+
+        self.deck.pop()
     }
 }
 
@@ -235,6 +341,42 @@ impl<T> Deck<T> {
         self.deck.is_empty()
     }
 
+    /// Changes every card in the deck with `f`, including the discarded cards.
+    pub fn for_each_card(&mut self, mut f: impl FnMut(&mut T)) {
+        self.deck.iter_mut().for_each(&mut f);
+        self.discarded.iter_mut().for_each(f);
+    }
+
+    /// Gets the cards that were discarded since the deck was last reshuffled.
+    pub fn discarded(&self) -> &[T] {
+        &self.discarded
+    }
+
+    /// Discards a card, which is shuffled back into the deck once it runs out.
+    pub fn discard(&mut self, card: T) {
+        self.discarded.push(card);
+    }
+
+    /// Gets the cards the next draws come from, which are the discarded cards if the deck ran
+    /// out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::game::Deck;
+    /// let mut deck = Deck::new(vec![1]);
+    /// deck.discard(2);
+    /// assert_eq!(deck.upcoming(), [1]);
+    /// deck.deck.clear();
+    /// assert_eq!(deck.upcoming(), [2]);
+    /// ```
+    pub fn upcoming(&self) -> &[T] {
+        match self.deck.is_empty() {
+            true => &self.discarded,
+            false => &self.deck,
+        }
+    }
+
     /// Sets the card url of the back image of the cards in the deck.
     pub fn set_image_back_url(&mut self, url: &str) {
         self.image_back_url = Arc::new(url.to_owned());
@@ -245,13 +387,21 @@ impl<T> Deck<T> {
         self.deck.insert(0, card);
     }
 
-    /// Randomly reshuffles the deck
+    /// Randomly reshuffles the deck, with the seeded rng of [`with_seed`] if set.
+    ///
+    /// [`with_seed`]: crate::utility::with_seed
     #[cfg(feature = "shuffle")]
     pub fn shuffle(&mut self) {
+        crate::utility::with_rng(|rng| self.shuffle_with(rng));
+    }
+
+    /// Randomly reshuffles the deck with `rng`, so the caller decides where the randomness comes
+    /// from.
+    #[cfg(feature = "shuffle")]
+    pub fn shuffle_with<R: rand::Rng + ?Sized>(&mut self, rng: &mut R) {
         use rand::seq::SliceRandom;
 
-        let mut rng = rand::rng();
-        self.deck.shuffle(&mut rng);
+        self.deck.shuffle(rng);
     }
 }
 
@@ -259,7 +409,7 @@ impl<T> Default for Deck<T> {
     fn default() -> Self {
         Self {
             deck: Default::default(),
-            backup_deck: Default::default(),
+            discarded: Default::default(),
             image_back_url: Default::default(),
         }
     }
@@ -297,43 +447,101 @@ pub struct ObtainingCharacters {
 impl ObtainingCharacters {
     /// Creates a new instance based on the player count and the chairman id.
     pub fn new(player_count: usize, chairman_id: PlayerId) -> Result<Self, GameError> {
-        let open_character_count = match player_count {
+        Self::with_bans(player_count, chairman_id, &[])
+    }
+
+    /// Creates a new instance based on the player count and the chairman id, where the `banned`
+    /// characters are left out of the deck before any character is dealt. Every banned character
+    /// takes the place of an open character. Characters are shuffled with the seeded rng of
+    /// [`with_seed`](crate::utility::with_seed) if set.
+    pub fn with_bans(
+        player_count: usize,
+        chairman_id: PlayerId,
+        banned: &[Character],
+    ) -> Result<Self, GameError> {
+        #[cfg(feature = "shuffle")]
+        {
+            crate::utility::with_rng(|rng| Self::shuffled(player_count, chairman_id, banned, rng))
+        }
+        #[cfg(not(feature = "shuffle"))]
+        {
+            Self::deal(player_count, chairman_id, banned, |_, _| {})
+        }
+    }
+
+    /// Creates a new instance like [`ObtainingCharacters::with_bans`], where the characters are
+    /// shuffled with `rng` before they are dealt. The CEO is never dealt as an open character.
+    #[cfg(feature = "shuffle")]
+    pub fn shuffled<R: rand::Rng + ?Sized>(
+        player_count: usize,
+        chairman_id: PlayerId,
+        banned: &[Character],
+        rng: &mut R,
+    ) -> Result<Self, GameError> {
+        Self::deal(
+            player_count,
+            chairman_id,
+            banned,
+            |available_characters, open_character_count| {
+                available_characters.shuffle_with(rng);
+
+                let ceo_pos = available_characters
+                    .deck
+                    .iter()
+                    .position(|c| *c == Character::CEO)
+                    .unwrap();
+                // PANIC: this is completely safe because `Character::CHARACTERS always contains all
+                // characters, which of course includes the CEO.
+
+                // Get CEO out of the first `open_character_count` positions
+                if (0..open_character_count).contains(&ceo_pos) {
+                    let ceo_insert =
+                        rng.random_range(open_character_count..(available_characters.len() - 1));
+                    // PANIC: We know `ceo_pos` to be a valid position, so removing it cannot crash.
+                    assert_eq!(available_characters.deck.remove(ceo_pos), Character::CEO);
+                    available_characters.deck.insert(ceo_insert, Character::CEO);
+                }
+                // CEO is now out of bottom positions of the deck (start of list) but we want it out
+                // of the top of the deck (end of list)
+                available_characters.deck.reverse();
+            },
+        )
+    }
+
+    /// Deals the characters that are not `banned`, after putting them in order with `shuffle`.
+    /// `shuffle` is given the deck of characters and the number of open characters.
+    fn deal(
+        player_count: usize,
+        chairman_id: PlayerId,
+        banned: &[Character],
+        shuffle: impl FnOnce(&mut Deck<Character>, usize),
+    ) -> Result<Self, GameError> {
+        let open_character_count: usize = match player_count {
             4 => 2,
             5 => 1,
             6 | 7 => 0,
             c => return Err(GameError::InvalidPlayerCount(c as u8)),
         };
-
-        let mut available_characters = Deck::new(Character::CHARACTERS.to_vec());
-        #[cfg(feature = "shuffle")]
+        if banned.len() > CharacterBans::max_bans(player_count) || banned.contains(&Character::CEO)
         {
-            available_characters.shuffle();
-
-            let ceo_pos = available_characters
-                .deck
-                .iter()
-                .position(|c| *c == Character::CEO)
-                .unwrap();
-            // PANIC: this is completely safe because `Character::CHARACTERS always contains all
-            // characters, which of course includes the CEO.
-
-            // Get CEO out of the first `open_character_count` positions
-            if (0..open_character_count).contains(&ceo_pos) {
-                let ceo_insert =
-                    rand::random_range(open_character_count..(available_characters.len() - 1));
-                // PANIC: We know `ceo_pos` to be a valid position, so removing it cannot crash.
-                assert_eq!(available_characters.deck.remove(ceo_pos), Character::CEO);
-                available_characters.deck.insert(ceo_insert, Character::CEO);
-            }
-            // CEO is now out of bottom positions of the deck (start of list) but we want it out
-            // of the top of the deck (end of list)
-            available_characters.deck.reverse();
+            return Err(SelectingCharactersError::UnbannableCharacter.into());
         }
+        let open_character_count = open_character_count.saturating_sub(banned.len());
+
+        let mut available_characters = Deck::new(
+            Character::CHARACTERS
+                .into_iter()
+                .filter(|c| !banned.contains(c))
+                .collect(),
+        );
+        shuffle(&mut available_characters, open_character_count);
 
+        // PANIC: there are always more characters than are open, closed or selected, so the deck
+        // cannot run out.
         let open_characters = (0..open_character_count)
-            .map(|_| available_characters.draw())
+            .map(|_| available_characters.draw().unwrap())
             .collect();
-        let closed_character = available_characters.draw();
+        let closed_character = available_characters.draw().unwrap();
 
         Ok(ObtainingCharacters {
             player_count,
@@ -408,8 +616,17 @@ impl ObtainingCharacters {
 pub struct MarketChange {
     /// A list of evenOts encountered in search for a market card
     pub events: Vec<Event>,
+    /// How much cash every player gained, or lost if negative, because of the events. Players
+    /// whose cash did not change are left out.
+    #[serde(default)]
+    pub cash_changes: Vec<(PlayerId, i16)>,
     /// The new market card
     pub new_market: Market,
+    /// How many cards were drawn from the market deck for this change, events included.
+    pub cards_drawn: usize,
+    /// How many cards are left in the market deck, before the discarded cards are shuffled back
+    /// into it.
+    pub cards_remaining: usize,
 }
 
 /// Data used when someone plays a card
@@ -461,6 +678,70 @@ pub struct SelectedAssetsAndLiabilities {
     pub issued_liabilities: Vec<IssuedLiabilityToPayBanker>,
 }
 
+/// What the target of the banker owes after their credit line was terminated: one gold, plus one
+/// gold for every color of asset they own.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreditTermination {
+    /// The gold that is owed no matter which assets the target owns.
+    pub base_gold: u8,
+    /// Every color of asset the target owns, which each add one gold.
+    pub colors: Vec<Color>,
+    /// The total amount of gold owed to the banker.
+    pub total_gold: u8,
+}
+
+impl CreditTermination {
+    /// The gold owed no matter which assets the target owns.
+    pub const BASE_GOLD: u8 = 1;
+
+    /// Works out what a player owning `assets` owes the banker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::game::CreditTermination;
+    /// let termination = CreditTermination::new(&[]);
+    /// assert_eq!(termination.total_gold, 1);
+    /// assert!(termination.colors.is_empty());
+    /// ```
+    pub fn new(assets: &[Asset]) -> Self {
+        let colors = Color::COLORS
+            .into_iter()
+            .filter(|&color| assets.iter().any(|a| a.color == color))
+            .collect::<Vec<_>>();
+
+        Self {
+            base_gold: Self::BASE_GOLD,
+            total_gold: Self::BASE_GOLD + colors.len() as u8,
+            colors,
+        }
+    }
+}
+
+/// How many cards are left to draw in the asset and liability decks, not counting the discarded
+/// cards that are shuffled back into them once they run out.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckSizes {
+    /// The number of cards left in the asset deck.
+    pub assets: usize,
+    /// The number of cards left in the liability deck.
+    pub liabilities: usize,
+}
+
+impl DeckSizes {
+    /// Counts the cards left in `assets` and `liabilities`.
+    pub fn new(assets: &Deck<Asset>, liabilities: &Deck<Liability>) -> Self {
+        Self {
+            assets: assets.len(),
+            liabilities: liabilities.len(),
+        }
+    }
+}
+
 /// Data used when a turn ends
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TurnEnded {
@@ -471,7 +752,7 @@ pub struct TurnEnded {
 }
 
 /// Wrapper struct around `Vec<P>` to make interacting with them as players internally much easier.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Players<P>(Vec<P>);
 
 impl<P> Players<P> {
@@ -644,7 +925,7 @@ impl<P> IntoIterator for Players<P> {
 /// 2. Selecting Characters ([`SelectingCharacters`])
 /// 3. Round ([`Round`])
 /// 4. Results ([`Results`])
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameState {
     /// Lobby state of the game. In this state players can freely join and leave the game
     Lobby(Lobby),
@@ -661,6 +942,14 @@ pub enum GameState {
     Results(Results),
 }
 
+// Games are simulated across threads and shared between the tasks of servers, so the state and
+// everything in it has to stay `Send + Sync`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<GameState>();
+    assert_send_sync::<crate::cards::GameData>();
+};
+
 impl GameState {
     /// Creates a new instance of the game. The game starts in lobby state.
     ///
@@ -675,6 +964,76 @@ impl GameState {
         Self::default()
     }
 
+    /// Gets the checksum of the card data the game is played with, see [`GameData::checksum`].
+    /// Returns `None` in a lobby where no card data was set yet.
+    pub fn data_checksum(&self) -> Option<u64> {
+        match self {
+            Self::Lobby(lobby) => lobby.game_data().map(GameData::checksum),
+            Self::SelectingCharacters(s) => Some(s.data_checksum()),
+            Self::Round(round) => Some(round.data_checksum()),
+            Self::BankerTarget(round) => Some(round.data_checksum()),
+            Self::Results(results) => Some(results.data_checksum()),
+        }
+    }
+
+    /// Gets a hash of the complete state of the game. Two games in the exact same state always
+    /// have the same hash, so it can be used to check whether a replayed game ended up in the same
+    /// place as the original. The hash is only stable between builds of the same version of the
+    /// game, since it is based on the [`Debug`] representation of the state.
+    pub fn state_hash(&self) -> u64 {
+        stable_hash(self)
+    }
+
+    /// Takes `elapsed` from the time bank of player `id`, if the game is played with a
+    /// [`ChessClock`]. Returns the time they have left. If it is the turn of `id`, `elapsed` is also
+    /// charged to the turn, see [`Round::time_remaining`].
+    pub fn spend_time(&mut self, id: PlayerId, elapsed: Duration) -> Option<Duration> {
+        let clock = match self {
+            Self::SelectingCharacters(s) => s.clock.as_mut(),
+            Self::Round(round) => {
+                if round.current_player == id {
+                    round.turn_time_spent += elapsed;
+                }
+                round.clock.as_mut()
+            }
+            Self::BankerTarget(round) => round.clock.as_mut(),
+            Self::Lobby(_) | Self::Results(_) => None,
+        };
+        clock?.spend(id, elapsed)
+    }
+
+    /// Gets the player whose turn it is in the [`Round`] if they have run out of time, either in
+    /// their time bank or for this turn, which means their turn should be ended for them.
+    pub fn out_of_time(&self) -> Option<PlayerId> {
+        let round = self.round().ok()?;
+        let id = round.current_player().id();
+        let bank_ran_out = round.clock().is_some_and(|clock| clock.has_run_out(id));
+        let turn_ran_out = round.time_remaining().is_some_and(|left| left.is_zero());
+        (bank_ran_out || turn_ran_out).then_some(id)
+    }
+
+    /// Gets how many cards are left in the asset and liability decks. Returns `None` in a lobby,
+    /// where nothing was dealt yet, and once the game has ended.
+    pub fn deck_sizes(&self) -> Option<DeckSizes> {
+        match self {
+            Self::SelectingCharacters(s) => Some(DeckSizes::new(&s.assets, &s.liabilities)),
+            Self::Round(round) => Some(DeckSizes::new(&round.assets, &round.liabilities)),
+            Self::BankerTarget(round) => Some(DeckSizes::new(&round.assets, &round.liabilities)),
+            Self::Lobby(_) | Self::Results(_) => None,
+        }
+    }
+
+    /// Gets the ids of every player in the game, regardless of its state.
+    pub fn player_ids(&self) -> Vec<PlayerId> {
+        match self {
+            Self::Lobby(lobby) => lobby.players().iter().map(|p| p.id()).collect(),
+            Self::SelectingCharacters(s) => s.players().iter().map(|p| p.id()).collect(),
+            Self::Round(round) => round.players().iter().map(|p| p.id()).collect(),
+            Self::BankerTarget(round) => round.players().iter().map(|p| p.id()).collect(),
+            Self::Results(results) => results.players().iter().map(|p| p.id()).collect(),
+        }
+    }
+
     /// Tries to get a `&`[`Lobby`] state. Returns an error if the game is not in a lobby state.
     ///
     /// # Examples
@@ -850,6 +1209,19 @@ impl GameState {
         }
     }
 
+    /// Starts the game if enough players are in the lobby, like [`GameState::start_game`]. Instead of
+    /// loading [`boardgame.json`](crate::cards) from a path, it uses `data` that was loaded
+    /// beforehand, for example with [`GameData::from_json`].
+    pub fn start_game_with_data(&mut self, data: GameData) -> Result<(), GameError> {
+        match self {
+            Self::Lobby(lobby) => {
+                *self = lobby.start_game_with_data(data)?;
+                Ok(())
+            }
+            _ => Err(GameError::NotLobbyState),
+        }
+    }
+
     /// Allows a player with `id` to select `character` if that character is available. If this was
     /// the last player to select a character, the state will be transformed from
     /// [`SelectingCharacters`] to [`Round`]
@@ -867,6 +1239,16 @@ impl GameState {
         Ok(())
     }
 
+    /// Allows a player with `id` to ban `character` from this round, if it is their turn to ban one.
+    pub fn player_ban_character(
+        &mut self,
+        id: PlayerId,
+        character: Character,
+    ) -> Result<(), GameError> {
+        self.selecting_characters_mut()?
+            .player_ban_character(id, character)
+    }
+
     /// Allows player with `id` to end their turn.
     /// If it was the last player in a round, transforms the internal state from [`Round`] back to
     /// [`SelectingCharacters`].
@@ -885,6 +1267,16 @@ impl GameState {
             }
         }
     }
+
+    /// Ends the turn of player `id` even if they still had to give back cards. See
+    /// [`Round::force_end_turn`].
+    pub fn force_end_turn(&mut self, id: PlayerId) -> Result<Vec<CardType>, GameError> {
+        let (put_back, ended) = self.round_mut()?.force_end_turn(id)?;
+        if let Either::Right(state) = ended {
+            *self = state;
+        }
+        Ok(put_back)
+    }
 }
 
 impl Default for GameState {
@@ -899,11 +1291,70 @@ mod tests {
     use claim::*;
     use itertools::Itertools;
 
+    #[test]
+    fn state_hash_follows_state() {
+        let mut game = GameState::new();
+        let empty = game.state_hash();
+        assert_eq!(empty, GameState::new().state_hash());
+
+        assert_ok!(game.lobby_mut().unwrap().join("player".to_owned()));
+        assert_ne!(game.state_hash(), empty);
+        assert_eq!(game.state_hash(), game.clone().state_hash());
+    }
+
+    #[test]
+    fn restored_state_accepts_same_actions() {
+        use crate::{
+            bot::{GreedyStrategy, MAX_SIMULATED_ACTIONS, Strategy, acting_player},
+            cards::GameData,
+            utility::with_seed,
+        };
+
+        let mut game = GameState::new();
+        let lobby = game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        lobby.set_config(GameConfig {
+            time_bank: Some(60),
+            ..Default::default()
+        });
+        lobby.set_game_data(GameData::new("../assets/cards/boardgame.json").unwrap());
+        assert_ok!(game.apply(PlayerId(0), PlayerAction::StartGame));
+
+        let mut strategy = GreedyStrategy::new();
+        for seed in 0..MAX_SIMULATED_ACTIONS as u64 {
+            let json = serde_json::to_string(&game).unwrap();
+            let mut restored: GameState = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored, game);
+
+            let Some(id) = acting_player(&game) else {
+                break;
+            };
+            let action = strategy.next_action(&game, id).unwrap();
+            let applied = with_seed(seed, || game.apply(id, action.clone()).is_ok());
+            let restored_applied = with_seed(seed, || restored.apply(id, action).is_ok());
+
+            assert_eq!(applied, restored_applied);
+            assert_eq!(restored, game);
+        }
+
+        assert_ok!(game.results());
+    }
+
     #[test]
     fn market_condition_make_higher() {
         assert_eq!(MarketCondition::Minus.make_higher(), MarketCondition::Zero);
         assert_eq!(MarketCondition::Zero.make_higher(), MarketCondition::Plus);
         assert_eq!(MarketCondition::Plus.make_higher(), MarketCondition::Plus);
+        assert_eq!(
+            MarketCondition::DoubleMinus.make_higher(),
+            MarketCondition::Minus
+        );
+        assert_eq!(
+            MarketCondition::DoublePlus.make_higher(),
+            MarketCondition::DoublePlus
+        );
     }
 
     #[test]
@@ -911,6 +1362,14 @@ mod tests {
         assert_eq!(MarketCondition::Minus.make_lower(), MarketCondition::Minus);
         assert_eq!(MarketCondition::Zero.make_lower(), MarketCondition::Minus);
         assert_eq!(MarketCondition::Plus.make_lower(), MarketCondition::Zero);
+        assert_eq!(
+            MarketCondition::DoublePlus.make_lower(),
+            MarketCondition::Plus
+        );
+        assert_eq!(
+            MarketCondition::DoubleMinus.make_lower(),
+            MarketCondition::DoubleMinus
+        );
     }
 
     #[test]
@@ -933,6 +1392,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn absent_players_get_a_character_picked() {
+        let mut game = GameState::new();
+        let lobby = game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("Player {i}")));
+        }
+        assert_ok!(game.start_game("../assets/cards/boardgame.json"));
+
+        let selecting = game.selecting_characters_mut().unwrap();
+        let id = selecting.currently_selecting_id();
+        assert_none!(selecting.absent_pick());
+
+        assert_ok!(selecting.leave(id));
+        let (picked_for, character) = assert_some!(selecting.absent_pick());
+        assert_eq!(picked_for, id);
+        assert!(
+            selecting
+                .player_get_selectable_characters(id)
+                .unwrap()
+                .contains(&character)
+        );
+
+        assert_ok!(game.apply(id, PlayerAction::SelectCharacter { character }));
+        let selecting = game.selecting_characters().unwrap();
+        assert_ne!(selecting.currently_selecting_id(), id);
+        assert_none!(selecting.absent_pick());
+    }
+
     #[test]
     fn player_from_character() {
         for i in 4..=7 {
@@ -1039,6 +1527,51 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "shuffle")]
+    fn shuffles_with_rng() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let deck = || {
+            let mut deck = Deck::new((0..32).collect::<Vec<_>>());
+            deck.shuffle_with(&mut SmallRng::seed_from_u64(7));
+            deck.deck
+        };
+        assert_eq!(deck(), deck());
+        assert_ne!(deck(), (0..32).collect::<Vec<_>>());
+
+        let characters = || {
+            let mut rng = SmallRng::seed_from_u64(7);
+            ObtainingCharacters::shuffled(4, PlayerId(0), &[], &mut rng).unwrap()
+        };
+        assert_eq!(characters(), characters());
+    }
+
+    #[test]
+    fn banned_characters_are_not_dealt() {
+        let banned = [Character::Shareholder, Character::CFO];
+        let characters = assert_ok!(ObtainingCharacters::with_bans(4, PlayerId(0), &banned));
+        let pickable = assert_ok!(characters.peek());
+
+        assert_eq!(characters.open_characters().len(), 0);
+        assert!(
+            pickable
+                .characters
+                .iter()
+                .chain(pickable.closed_character.as_ref())
+                .all(|c| !banned.contains(c))
+        );
+        assert_eq!(
+            ObtainingCharacters::with_bans(4, PlayerId(0), &[Character::CEO]),
+            Err(SelectingCharactersError::UnbannableCharacter.into())
+        );
+        assert_err!(ObtainingCharacters::with_bans(
+            7,
+            PlayerId(0),
+            &[Character::Shareholder]
+        ));
+    }
+
     #[test]
     fn player_play_card() {
         for i in 4..=7 {
@@ -1068,7 +1601,7 @@ mod tests {
 
             assert_ok!(round.player_play_card(current_player, hand_len - 1));
             assert_eq!(
-                cash_before + liability_value,
+                assert_ok!(cash_before.checked_add(liability_value)),
                 round.player(current_player).unwrap().cash()
             );
 
@@ -1089,7 +1622,7 @@ mod tests {
 
             assert_ok!(round.player_play_card(current_player, hand_len - 1));
             assert_eq!(
-                cash_before - liability_value,
+                assert_ok!(cash_before.checked_sub(liability_value)),
                 round.player(current_player).unwrap().cash()
             );
 
@@ -1128,6 +1661,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn market_change_counts_drawn_cards() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        let round = game.round_mut().expect("Game not in round state");
+        let current_player = round.current_player().id();
+        draw_cards(round, current_player, [CardType::Asset]);
+        round.player_mut(current_player).unwrap()._set_cash(50);
+        let markets_before = round.markets.len();
+
+        let hand_len = round.player(current_player).unwrap().hand().len();
+        let played = assert_ok!(round.player_play_card(current_player, hand_len - 1));
+        let change = played
+            .market
+            .expect("the first asset should change the market");
+
+        assert_eq!(change.cards_drawn, change.events.len() + 1);
+        assert_eq!(change.cards_remaining, round.markets.len());
+        assert_eq!(markets_before - change.cards_drawn, change.cards_remaining);
+    }
+
+    #[test]
+    fn turn_nonce_goes_stale_when_turn_ends() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        let round = game.round().expect("not in round state");
+        let current_player = round.current_player().id();
+        let nonce = round.turn_nonce();
+        assert_ok!(round.check_turn_nonce(nonce));
+
+        play_turn(&mut game, current_player);
+
+        let round = game.round().expect("not in round state");
+        assert_ne!(round.turn_nonce(), nonce);
+        assert_eq!(
+            round.check_turn_nonce(nonce),
+            Err(GameError::StaleTurnNonce(nonce))
+        );
+
+        // Ending a turn with the nonce of the turn before it, or without one, does nothing
+        let current_player = round.current_player().id();
+        let draw = PlayerAction::DrawCard {
+            card_type: CardType::Asset,
+        };
+        assert_eq!(
+            game.apply_in_turn(current_player, PlayerAction::EndTurn, Some(nonce))
+                .map(drop),
+            Err(GameError::StaleTurnNonce(nonce))
+        );
+        assert_eq!(
+            game.apply_in_turn(current_player, draw.clone(), None)
+                .map(drop),
+            Err(GameError::MissingTurnNonce)
+        );
+        assert_eq!(game.round().unwrap().current_player().id(), current_player);
+
+        let nonce = game.round().unwrap().turn_nonce();
+        assert_ok!(game.apply_in_turn(current_player, draw, Some(nonce)));
+    }
+
     #[test]
     fn player_play_card_invalid_id() {
         let mut game = pick_with_players(4).expect("couldn't pick characters");
@@ -1153,6 +1744,120 @@ mod tests {
         )
     }
 
+    #[test]
+    fn player_play_card_liability_cap() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        let round = game.round_mut().expect("Game not in round state");
+        round.config.liability_cap = Some(0);
+
+        let current_player = round.current_player().id();
+        draw_cards(round, current_player, [CardType::Liability]);
+        let info = round.player_info(round.next_player().unwrap().id());
+        assert!(info.iter().all(|p| p.liability_cap == Some(0)));
+
+        let player = round.player(current_player).unwrap();
+        assert!(!round.can_issue_liability(player));
+        let card_idx = player.hand().len() - 1;
+        assert_matches!(
+            round.player_play_card(current_player, card_idx),
+            Err(GameError::PlayCard(PlayCardError::LiabilityCapReached(0)))
+        );
+
+        round.config.liability_cap = Some(1);
+        assert_ok!(round.player_play_card(current_player, card_idx));
+    }
+
+    #[test]
+    fn turn_summary() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        let round = game.round_mut().expect("Game not in round state");
+        let id = round.current_player().id();
+        let character = round.current_player().character();
+
+        let summary = assert_ok!(round.turn_summary(id));
+        assert_eq!(summary.cards_left_to_draw, character.draws_n_cards());
+        assert_eq!(
+            summary.cards_left_to_give_back,
+            character.gives_back_n_cards()
+        );
+        assert_eq!(summary.liabilities_left, character.playable_liabilities());
+        assert_eq!(summary.can_use_ability, character.has_active_ability());
+        assert_eq!(summary.assets_left.len(), Color::COLORS.len());
+        assert!(summary.assets_left.values().all(|&left| left > 0));
+
+        draw_cards(
+            round,
+            id,
+            [CardType::Asset, CardType::Asset, CardType::Asset],
+        );
+        assert_ok!(round.player_give_back_card(id, 0));
+        let summary = assert_ok!(round.turn_summary(id));
+        assert_eq!(summary.cards_left_to_draw, character.draws_n_cards() - 3);
+        assert_eq!(
+            summary.cards_left_to_give_back,
+            character.gives_back_n_cards() - 1
+        );
+
+        round.config.liability_cap = Some(0);
+        assert_eq!(assert_ok!(round.turn_summary(id)).liabilities_left, 0);
+
+        let next_player = round.next_player().expect("couldn't get next player").id();
+        assert_matches!(
+            round.turn_summary(next_player),
+            Err(GameError::NotPlayersTurn)
+        );
+    }
+
+    #[test]
+    fn draw_odds() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        let round = game.round_mut().expect("Game not in round state");
+        fn sums_to_one<K>(odds: &BTreeMap<K, f64>) -> bool {
+            (odds.values().sum::<f64>() - 1.0).abs() < 1e-9
+        }
+
+        let odds = round.draw_odds(CardType::Asset);
+        assert_eq!(odds.cards_left, round.assets.len());
+        assert!(sums_to_one(&odds.colors));
+        assert!(sums_to_one(&odds.values));
+
+        let odds = round.draw_odds(CardType::Liability);
+        assert!(odds.colors.is_empty());
+        assert!(sums_to_one(&odds.values));
+
+        let color = round.assets.deck[0].color;
+        round.assets.deck.retain(|a| a.color == color);
+        let odds = round.draw_odds(CardType::Asset);
+        assert_eq!(odds.colors, BTreeMap::from([(color, 1.0)]));
+
+        let asset = round.assets.deck.pop().unwrap();
+        round.assets.deck.clear();
+        round.assets.discard(asset);
+        let odds = round.draw_odds(CardType::Asset);
+        assert_eq!(odds.cards_left, 0);
+        assert_eq!(odds.colors, BTreeMap::from([(color, 1.0)]));
+    }
+
+    #[test]
+    fn draws_reshuffle_discarded_cards() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        let round = game.round_mut().expect("Game not in round state");
+        let id = round.current_player().id();
+
+        let mut deck = std::mem::take(&mut round.assets.deck);
+        assert_matches!(
+            round.player_draw_card(id, CardType::Asset),
+            Err(GameError::DrawCard(DrawCardError::EmptyDeck))
+        );
+
+        let asset = deck.pop().unwrap();
+        round.assets.discard(asset.clone());
+        let drawn = assert_ok!(round.player_draw_card(id, CardType::Asset));
+        assert_eq!(drawn.left(), Some(&asset));
+        assert!(round.assets.is_empty());
+        assert!(round.assets.discarded().is_empty());
+    }
+
     #[test]
     fn end_player_turn_no_actions() {
         let mut game = pick_with_players(4).expect("couldn't pick characters");
@@ -1215,6 +1920,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn game_ends_after_max_rounds() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        game.round_mut().unwrap().config.max_rounds = Some(2);
+
+        for round_number in 1..=2 {
+            for _ in 0..4 {
+                let current_player = game.round().unwrap().current_player().id();
+                play_turn(&mut game, current_player);
+            }
+
+            if round_number == 1 {
+                assert_matches!(game, GameState::SelectingCharacters(_));
+                finish_selecting_characters(&mut game);
+            }
+        }
+
+        assert_matches!(game, GameState::Results(_));
+    }
+
+    #[test]
+    fn character_stats_add_up() {
+        let mut game = pick_with_players(4).expect("couldn't pick characters");
+        let round = game.round_mut().unwrap();
+        round.config.max_rounds = Some(1);
+        let first = round.current_player();
+        let first_stats = round.character_stats()[&first.character()];
+        assert_eq!(first_stats.turns, 1);
+        assert_eq!(first_stats.income, u32::from(first.turn_cash()));
+
+        for _ in 0..4 {
+            let current_player = game.round().unwrap().current_player().id();
+            play_turn(&mut game, current_player);
+        }
+
+        let results = game.results().unwrap();
+        let stats = results.character_stats();
+        assert_eq!(stats.values().map(|s| s.turns).sum::<u32>(), 4);
+        for player in results.players() {
+            assert_eq!(stats[&player.character()].turns, 1);
+        }
+    }
+
+    #[test]
+    fn config_changes_starting_rules() {
+        let mut game = GameState::new();
+        let lobby = game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+
+        lobby.set_config(GameConfig {
+            max_players: Some(8),
+            ..Default::default()
+        });
+        assert_eq!(
+            game.start_game("../assets/cards/boardgame.json"),
+            Err(GameError::Config(ConfigError::InvalidPlayerCounts {
+                min: 4,
+                max: 8
+            }))
+        );
+
+        let lobby = game.lobby_mut().unwrap();
+        lobby.set_config(GameConfig {
+            min_players: Some(5),
+            ..Default::default()
+        });
+        assert!(!lobby.can_start());
+
+        lobby.set_config(GameConfig {
+            starting_gold: Some(10),
+            ..Default::default()
+        });
+        assert_ok!(game.start_game("../assets/cards/boardgame.json"));
+        let selecting = game.selecting_characters().unwrap();
+        assert!(
+            selecting
+                .players()
+                .iter()
+                .all(|p| p.cash() == Cash::from(10))
+        );
+    }
+
     #[test]
     fn pick_characters() {
         for i in 0..=3 {
@@ -1326,6 +2115,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn turn_timer_starts_over_every_turn() {
+        let mut game = GameState::new();
+        let lobby = game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        lobby.set_config(GameConfig {
+            turn_time: Some(30),
+            ..Default::default()
+        });
+        assert_ok!(game.start_game("../assets/cards/boardgame.json"));
+        finish_selecting_characters(&mut game);
+
+        let id = game.round().unwrap().current_player().id();
+        let other = PlayerId((id.0 + 1) % 4);
+        game.spend_time(other, Duration::from_secs(20));
+        game.spend_time(id, Duration::from_secs(20));
+        assert_eq!(
+            game.round().unwrap().time_remaining(),
+            Some(Duration::from_secs(10))
+        );
+        assert_none!(game.out_of_time());
+
+        game.spend_time(id, Duration::from_secs(20));
+        assert_eq!(game.out_of_time(), Some(id));
+        assert_ok!(game.force_end_turn(id));
+
+        let round = game.round().unwrap();
+        assert_ne!(round.current_player().id(), id);
+        assert_eq!(round.time_remaining(), Some(Duration::from_secs(30)));
+        assert_none!(game.out_of_time());
+    }
+
+    #[test]
+    fn spectators_follow_the_game() {
+        let mut game = GameState::new();
+        assert_ok!(game.spectate("viewer".to_owned()));
+        for i in 0..4 {
+            assert_ok!(game.lobby_mut().unwrap().join(format!("player {i}")));
+        }
+        assert_err!(game.lobby_mut().unwrap().join("Viewer".to_owned()));
+        assert_eq!(game.lobby().unwrap().len(), 4);
+        assert_ok!(game.start_game("../assets/cards/boardgame.json"));
+
+        assert_eq!(game.spectators().usernames(), ["viewer"]);
+        assert_err!(game.spectate("player 1".to_owned()));
+        assert!(game.stop_spectating("viewer"));
+        assert!(!game.stop_spectating("viewer"));
+        assert!(game.spectators().is_empty());
+    }
+
+    #[test]
+    fn spectators_see_no_hands_or_closed_characters() {
+        let game = pick_with_players(4).unwrap();
+        let round = game.round().unwrap();
+        let info = game.spectator_info();
+
+        assert_eq!(info.len(), 4);
+        for player in &info {
+            let character = round.player(player.id).unwrap().character();
+            match character > round.current_player().character() {
+                true => assert!(player.character.is_none()),
+                false => assert_eq!(player.character, Some(character)),
+            }
+        }
+    }
+
     fn pick_with_players(player_count: usize) -> Result<GameState, GameError> {
         let mut game = GameState::new();
         let lobby = game.lobby_mut().expect("game not in lobby state");
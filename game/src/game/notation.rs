@@ -0,0 +1,641 @@
+//! File containing [`Notation`], a short text version of a full game that can be shared in chat
+//! or on forums. Each move is written down as it is applied, with cards named by their title
+//! instead of their index in a hand, which only makes sense while the game is going on.
+//!
+//! A game looks like this:
+//!
+//! ```text
+//! [Player "alice"]
+//! [Player "bob"]
+//!
+//! 1. P0 start
+//! 2. P1 pick CEO
+//! 3. P0 draw liability
+//! 4. P0 buy "Cool Asset"
+//! 5. P0 issue BankLoan:2
+//! 6. P0 end
+//! ```
+//!
+//! A notation can be read back with [`str::parse`] and replayed with [`Notation::replay`]. Cards
+//! are looked up by their title again, so replaying only reproduces the game if the decks are
+//! dealt in the same order as when it was written down. Games started with the `shuffle` feature
+//! are dealt randomly, so those can only be replayed as far as the cards allow.
+
+use either::Either;
+use thiserror::Error;
+
+use std::{fmt, str::FromStr};
+
+use crate::{cards::GameData, errors::*, game::*, player::*};
+
+/// A game written down move by move.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Notation {
+    players: Vec<String>,
+    moves: Vec<(PlayerId, String)>,
+}
+
+impl Notation {
+    /// Creates a new, empty notation for a game played by `players`, in order of their id.
+    pub fn new(players: Vec<String>) -> Self {
+        Self {
+            players,
+            moves: vec![],
+        }
+    }
+
+    /// Gets the names of the players of the game, in order of their id.
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    /// Gets every move written down so far, together with the player that made it.
+    pub fn moves(&self) -> &[(PlayerId, String)] {
+        &self.moves
+    }
+
+    /// Applies `action` of player `id` to `state`, and writes it down if it succeeded. See
+    /// [`GameState::apply`].
+    pub fn apply(
+        &mut self,
+        state: &mut GameState,
+        id: PlayerId,
+        action: PlayerAction,
+    ) -> Result<ActionEffects, GameError> {
+        let action = state.check_indices(id, action)?;
+        let notation = write_move(state, id, &action)?;
+        let effects = state.apply(id, action)?;
+
+        self.moves.push((id, notation));
+        Ok(effects)
+    }
+
+    /// Reads a single move like `draw asset` or `buy "Cool Asset"` into the action player `id`
+    /// would take with it in `state`. Returns `None` if the move does not fit the game.
+    pub fn read_move(state: &GameState, id: PlayerId, notation: &str) -> Option<PlayerAction> {
+        read_move(state, id, notation)
+    }
+
+    /// Writes down `action` of player `id` as a single move, the way [`Notation::apply`] would.
+    pub fn write_move(
+        state: &GameState,
+        id: PlayerId,
+        action: PlayerAction,
+    ) -> Result<String, GameError> {
+        let action = state.check_indices(id, action)?;
+        write_move(state, id, &action)
+    }
+
+    /// Plays the game written down in this notation again, starting from a lobby with the players
+    /// of this notation and the decks in `data`. Returns the game after the last move.
+    pub fn replay(&self, data: GameData) -> Result<GameState, NotationError> {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().map_err(NotationError::Lobby)?;
+        for name in &self.players {
+            lobby
+                .join(name.clone())
+                .map_err(|e| NotationError::Lobby(e.into()))?;
+        }
+        lobby.set_game_data(data);
+
+        for (i, (id, notation)) in self.moves.iter().enumerate() {
+            let number = i + 1;
+            let action =
+                read_move(&state, *id, notation).ok_or(NotationError::InvalidMove(number))?;
+            state
+                .apply(*id, action)
+                .map_err(|source| NotationError::Rejected { number, source })?;
+        }
+
+        Ok(state)
+    }
+}
+
+/// Errors that can occur when reading a game from its notation.
+#[derive(Debug, PartialEq, Error)]
+pub enum NotationError {
+    /// A line is neither a player nor a move.
+    #[error("Line {0} is not a player or a move")]
+    InvalidLine(usize),
+    /// The players could not join the lobby the game is replayed in.
+    #[error("Players could not join the game: {0}")]
+    Lobby(GameError),
+    /// A move does not describe an action that can be taken at that point in the game.
+    #[error("Move {0} does not fit the game at that point")]
+    InvalidMove(usize),
+    /// A move was rejected by the game.
+    #[error("Move {number} was rejected: {source}")]
+    Rejected {
+        /// The number of the move.
+        number: usize,
+        /// Why the game rejected the move.
+        source: GameError,
+    },
+}
+
+impl FromStr for Notation {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut notation = Notation::default();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            let invalid = || NotationError::InvalidLine(i + 1);
+
+            if line.is_empty() {
+                continue;
+            } else if let Some(name) = line
+                .strip_prefix("[Player ")
+                .and_then(|l| l.strip_suffix(']'))
+            {
+                let name = unquote(name).ok_or_else(invalid)?;
+                notation.players.push(name);
+            } else {
+                let (_, rest) = line.split_once(". P").ok_or_else(invalid)?;
+                let (id, notation_move) = rest.split_once(' ').ok_or_else(invalid)?;
+                let id = id.parse().map_err(|_| invalid())?;
+                notation
+                    .moves
+                    .push((PlayerId(id), notation_move.trim().to_owned()));
+            }
+        }
+
+        Ok(notation)
+    }
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in &self.players {
+            writeln!(f, "[Player {name:?}]")?;
+        }
+        writeln!(f)?;
+
+        for (i, (id, notation)) in self.moves.iter().enumerate() {
+            writeln!(f, "{}. P{} {notation}", i + 1, id.0)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes down `action` of player `id` as it would be applied to `state`. Every index in `action`
+/// should already be checked with [`GameState::check_indices`].
+fn write_move(state: &GameState, id: PlayerId, action: &PlayerAction) -> Result<String, GameError> {
+    use PlayerAction as A;
+
+    let notation = match action {
+        A::StartGame => "start".to_owned(),
+        A::TransferHost { target } => format!("host P{}", target.0),
+        A::SetReady { ready: true } => "ready".to_owned(),
+        A::SetReady { ready: false } => "unready".to_owned(),
+        A::ChangeUsername { username } => format!("rename {username:?}"),
+        A::SelectCharacter { character } => format!("pick {character:?}"),
+        A::BanCharacter { character } => format!("ban {character:?}"),
+        A::DrawCard {
+            card_type: CardType::Asset,
+        } => "draw asset".to_owned(),
+        A::DrawCard {
+            card_type: CardType::Liability,
+        } => "draw liability".to_owned(),
+        A::PutBackCard { card_idx } => format!("return {}", hand_card(state, id, *card_idx)?),
+        A::BuyAsset { card_idx } => format!("buy {}", hand_card(state, id, *card_idx)?),
+        A::IssueLiability { card_idx } => format!("issue {}", hand_card(state, id, *card_idx)?),
+        A::RedeemLiability { liability_idx } => {
+            let liability = &state.round()?.player(id)?.liabilities()[*liability_idx];
+            format!("redeem {}", liability_token(liability))
+        }
+        A::UseAbility => "ability".to_owned(),
+        A::GetBonusCash => "bonus".to_owned(),
+        A::FireCharacter { character } => format!("fire {character:?}"),
+        A::TerminateCreditCharacter { character } => format!("terminate {character:?}"),
+        A::SelectAssetToDivest { asset_id } => {
+            format!("sell {}", player_asset(state, id, *asset_id)?)
+        }
+        A::UnselectAssetToDivest { asset_id } => {
+            format!("unsell {}", player_asset(state, id, *asset_id)?)
+        }
+        A::SelectLiabilityToIssue { liability_id } => {
+            format!("raise {}", hand_card(state, id, *liability_id)?)
+        }
+        A::UnselectLiabilityToIssue { liability_id } => {
+            format!("unraise {}", hand_card(state, id, *liability_id)?)
+        }
+        A::PayBanker { cash } => format!("pay {cash}"),
+        A::SwapWithDeck { card_idxs } => {
+            let cards = card_idxs
+                .iter()
+                .map(|&idx| hand_card(state, id, idx))
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("swap deck {}", cards.join(" "))
+                .trim_end()
+                .to_owned()
+        }
+        A::SwapWithPlayer { target_player_id } => format!("swap P{}", target_player_id.0),
+        A::DivestAsset {
+            target_player_id,
+            card_idx,
+        } => format!(
+            "divest P{} {}",
+            target_player_id.0,
+            player_asset(state, *target_player_id, *card_idx)?
+        ),
+        A::ConfirmDivest => "confirm-divest".to_owned(),
+        A::EndTurn => "end".to_owned(),
+        A::ForceEndTurn => "force-end".to_owned(),
+        A::MinusIntoPlus { color } => format!("minus-into-plus {color:?}"),
+        A::SilverIntoGold { asset_idx } => {
+            format!("silver-into-gold {}", player_asset(state, id, *asset_idx)?)
+        }
+        A::ChangeAssetColor { asset_idx, color } => {
+            format!("recolor {} {color:?}", player_asset(state, id, *asset_idx)?)
+        }
+        A::ConfirmAssetAbility { asset_idx } => {
+            format!("confirm {}", player_asset(state, id, *asset_idx)?)
+        }
+    };
+
+    Ok(notation)
+}
+
+/// Gets the hand of player `id` in `state`.
+fn hand(state: &GameState, id: PlayerId) -> Result<&[Either<Asset, Liability>], GameError> {
+    match state {
+        GameState::Lobby(_) => Err(GameError::NotAvailableInLobbyState),
+        GameState::SelectingCharacters(s) => Ok(s.player(id)?.hand()),
+        GameState::Round(r) => Ok(r.player(id)?.hand()),
+        GameState::BankerTarget(r) => Ok(r.player(id)?.hand()),
+        GameState::Results(r) => Ok(r.player(id)?.hand()),
+    }
+}
+
+/// Gets the assets player `id` bought in `state`.
+fn assets(state: &GameState, id: PlayerId) -> Result<&[Asset], GameError> {
+    match state {
+        GameState::Lobby(_) => Err(GameError::NotAvailableInLobbyState),
+        GameState::SelectingCharacters(s) => Ok(s.player(id)?.assets()),
+        GameState::Round(r) => Ok(r.player(id)?.assets()),
+        GameState::BankerTarget(r) => Ok(r.player(id)?.assets()),
+        GameState::Results(r) => Ok(r.player(id)?.assets()),
+    }
+}
+
+/// Writes down the card at `idx` in the hand of player `id`.
+fn hand_card(state: &GameState, id: PlayerId, idx: usize) -> Result<String, GameError> {
+    let card = hand(state, id)?
+        .get(idx)
+        .ok_or(GameError::InvalidAssetIndex(idx as u8))?;
+
+    Ok(match card {
+        Either::Left(asset) => asset_token(asset),
+        Either::Right(liability) => liability_token(liability),
+    })
+}
+
+/// Writes down the asset at `idx` that player `id` bought.
+fn player_asset(state: &GameState, id: PlayerId, idx: usize) -> Result<String, GameError> {
+    assets(state, id)?
+        .get(idx)
+        .map(asset_token)
+        .ok_or(GameError::InvalidAssetIndex(idx as u8))
+}
+
+/// Writes down `asset` by its title.
+fn asset_token(asset: &Asset) -> String {
+    format!("{:?}", asset.title)
+}
+
+/// Writes down `liability` by its type and value, since liabilities do not have a title.
+fn liability_token(liability: &Liability) -> String {
+    format!("{:?}:{}", liability.rfr_type, liability.value)
+}
+
+/// Reads `notation` back into the action player `id` took in `state`, looking up cards by their
+/// title. Returns `None` if the notation does not fit the game.
+fn read_move(state: &GameState, id: PlayerId, notation: &str) -> Option<PlayerAction> {
+    use PlayerAction as A;
+
+    let tokens = tokenize(notation)?;
+    let tokens = tokens.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let action = match tokens.as_slice() {
+        ["start"] => A::StartGame,
+        ["host", player] => A::TransferHost {
+            target: read_player(player)?,
+        },
+        ["ready"] => A::SetReady { ready: true },
+        ["unready"] => A::SetReady { ready: false },
+        ["rename", username] => A::ChangeUsername {
+            username: unquote(username)?,
+        },
+        ["pick", character] => A::SelectCharacter {
+            character: read_character(character)?,
+        },
+        ["ban", character] => A::BanCharacter {
+            character: read_character(character)?,
+        },
+        ["draw", "asset"] => A::DrawCard {
+            card_type: CardType::Asset,
+        },
+        ["draw", "liability"] => A::DrawCard {
+            card_type: CardType::Liability,
+        },
+        ["return", card] => A::PutBackCard {
+            card_idx: find_hand_card(state, id, card, &[])?,
+        },
+        ["buy", card] => A::BuyAsset {
+            card_idx: find_hand_card(state, id, card, &[])?,
+        },
+        ["issue", card] => A::IssueLiability {
+            card_idx: find_hand_card(state, id, card, &[])?,
+        },
+        ["redeem", card] => A::RedeemLiability {
+            liability_idx: state
+                .round()
+                .ok()?
+                .player(id)
+                .ok()?
+                .liabilities()
+                .iter()
+                .position(|l| liability_token(l) == *card)?,
+        },
+        ["ability"] => A::UseAbility,
+        ["bonus"] => A::GetBonusCash,
+        ["fire", character] => A::FireCharacter {
+            character: read_character(character)?,
+        },
+        ["terminate", character] => A::TerminateCreditCharacter {
+            character: read_character(character)?,
+        },
+        ["sell", card] => A::SelectAssetToDivest {
+            asset_id: find_asset(state, id, card)?,
+        },
+        ["unsell", card] => A::UnselectAssetToDivest {
+            asset_id: find_asset(state, id, card)?,
+        },
+        ["raise", card] => A::SelectLiabilityToIssue {
+            liability_id: find_hand_card(state, id, card, &[])?,
+        },
+        ["unraise", card] => A::UnselectLiabilityToIssue {
+            liability_id: find_hand_card(state, id, card, &[])?,
+        },
+        ["pay", cash] => A::PayBanker {
+            cash: cash.parse().ok()?,
+        },
+        ["swap", "deck", cards @ ..] => {
+            let mut card_idxs = vec![];
+            for card in cards {
+                let idx = find_hand_card(state, id, card, &card_idxs)?;
+                card_idxs.push(idx);
+            }
+            A::SwapWithDeck { card_idxs }
+        }
+        ["swap", player] => A::SwapWithPlayer {
+            target_player_id: read_player(player)?,
+        },
+        ["divest", player, card] => {
+            let target_player_id = read_player(player)?;
+            A::DivestAsset {
+                target_player_id,
+                card_idx: find_asset(state, target_player_id, card)?,
+            }
+        }
+        ["confirm-divest"] => A::ConfirmDivest,
+        ["end"] => A::EndTurn,
+        ["force-end"] => A::ForceEndTurn,
+        ["minus-into-plus", color] => A::MinusIntoPlus {
+            color: read_color(color)?,
+        },
+        ["silver-into-gold", card] => A::SilverIntoGold {
+            asset_idx: find_asset(state, id, card)?,
+        },
+        ["recolor", card, color] => A::ChangeAssetColor {
+            asset_idx: find_asset(state, id, card)?,
+            color: read_color(color)?,
+        },
+        ["confirm", card] => A::ConfirmAssetAbility {
+            asset_idx: find_asset(state, id, card)?,
+        },
+        _ => return None,
+    };
+
+    Some(action)
+}
+
+/// Splits `notation` into words, keeping quoted titles together with their quotes.
+fn tokenize(notation: &str) -> Option<Vec<String>> {
+    let mut tokens = vec![];
+    let mut rest = notation.trim();
+
+    while !rest.is_empty() {
+        let end = if let Some(quoted) = rest.strip_prefix('"') {
+            // Skip escaped characters, so a title can contain quotes
+            let mut escaped = false;
+            let close = quoted.find(|c| {
+                let close = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                close
+            })?;
+            close + 2
+        } else {
+            rest.find(' ').unwrap_or(rest.len())
+        };
+
+        tokens.push(rest[..end].to_owned());
+        rest = rest[end..].trim_start();
+    }
+
+    Some(tokens)
+}
+
+/// Reads back a title written with [`asset_token`] or a player name in a header.
+fn unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.push(chars.next()?),
+            c => unquoted.push(c),
+        }
+    }
+
+    Some(unquoted)
+}
+
+/// Finds the index of the first card written down as `card` in the hand of player `id`, skipping
+/// the indices in `taken`.
+fn find_hand_card(state: &GameState, id: PlayerId, card: &str, taken: &[usize]) -> Option<usize> {
+    hand(state, id)
+        .ok()?
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !taken.contains(idx))
+        .find(|(_, c)| match c {
+            Either::Left(asset) => asset_token(asset) == card,
+            Either::Right(liability) => liability_token(liability) == card,
+        })
+        .map(|(idx, _)| idx)
+}
+
+/// Finds the index of the first asset written down as `card` that player `id` bought.
+fn find_asset(state: &GameState, id: PlayerId, card: &str) -> Option<usize> {
+    assets(state, id)
+        .ok()?
+        .iter()
+        .position(|asset| asset_token(asset) == card)
+}
+
+fn read_character(character: &str) -> Option<Character> {
+    Character::CHARACTERS
+        .into_iter()
+        .find(|c| format!("{c:?}") == character)
+}
+
+fn read_color(color: &str) -> Option<Color> {
+    Color::COLORS
+        .into_iter()
+        .find(|c| format!("{c:?}") == color)
+}
+
+fn read_player(player: &str) -> Option<PlayerId> {
+    player.strip_prefix('P')?.parse().ok().map(PlayerId)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    fn data() -> GameData {
+        GameData::new("../assets/cards/boardgame.json").unwrap()
+    }
+
+    #[test]
+    fn moves_are_written_down() {
+        let names = (0..4).map(|i| format!("player {i}")).collect::<Vec<_>>();
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for name in &names {
+            assert_ok!(lobby.join(name.clone()));
+        }
+        lobby.set_game_data(data());
+
+        let mut notation = Notation::new(names);
+        assert_ok!(notation.apply(&mut state, PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(notation.apply(&mut state, id, PlayerAction::SelectCharacter { character }));
+        }
+
+        let id = state.round().unwrap().current_player().id();
+        let draw = PlayerAction::DrawCard {
+            card_type: CardType::Liability,
+        };
+        while !state
+            .round()
+            .unwrap()
+            .current_player()
+            .should_give_back_cards()
+        {
+            assert_ok!(notation.apply(&mut state, id, draw.clone()));
+        }
+        let card = assert_ok!(hand_card(&state, id, 0));
+        assert_ok!(notation.apply(&mut state, id, PlayerAction::PutBackCard { card_idx: 0 }));
+        assert_eq!(
+            notation.moves().last(),
+            Some(&(id, format!("return {card}")))
+        );
+
+        // Failed actions are not written down
+        let moves = notation.moves().len();
+        assert_err!(notation.apply(&mut state, id, PlayerAction::PayBanker { cash: 1 }));
+        assert_eq!(notation.moves().len(), moves);
+
+        let text = notation.to_string();
+        assert!(text.starts_with("[Player \"player 0\"]\n"));
+        assert!(text.contains("\n1. P0 start\n"));
+        assert!(text.contains(&format!("\n2. P{} pick ", notation.moves()[1].0.0)));
+        assert_eq!(text.lines().count(), 4 + 1 + notation.moves().len());
+    }
+
+    #[test]
+    fn notation_is_read_back() {
+        let text = "[Player \"alice \\\"the\\\" great\"]\n[Player \"bob\"]\n\n1. P0 start\n2. P1 buy \"Cool Asset\"\n";
+        let notation = assert_ok!(text.parse::<Notation>());
+        assert_eq!(notation.players(), ["alice \"the\" great", "bob"]);
+        assert_eq!(
+            notation.moves(),
+            [
+                (PlayerId(0), "start".to_owned()),
+                (PlayerId(1), "buy \"Cool Asset\"".to_owned())
+            ]
+        );
+        assert_eq!(notation.to_string(), text);
+
+        assert_eq!(
+            "[Player bob]".parse::<Notation>(),
+            Err(NotationError::InvalidLine(1))
+        );
+        assert_eq!(
+            "\n1. start".parse::<Notation>(),
+            Err(NotationError::InvalidLine(2))
+        );
+    }
+
+    #[test]
+    fn moves_are_read_against_the_game() {
+        let names = (0..4).map(|i| format!("player {i}")).collect::<Vec<_>>();
+        let notation =
+            assert_ok!(format!("{}\n1. P0 start\n", Notation::new(names)).parse::<Notation>());
+        let state = assert_ok!(notation.replay(data()));
+        let selecting = assert_ok!(state.selecting_characters());
+
+        let id = selecting.currently_selecting_id();
+        let card = assert_ok!(hand_card(&state, id, 1));
+        // Cards with the same title are looked up by the first one
+        let idx = assert_some!(find_hand_card(&state, id, &card, &[]));
+        assert_eq!(
+            read_move(&state, id, &format!("return {card}")),
+            Some(PlayerAction::PutBackCard { card_idx: idx })
+        );
+        assert_none!(read_move(&state, id, "buy \"Not An Asset\""));
+        assert_none!(read_move(&state, id, "pick Nobody"));
+
+        let wrong = format!("{notation}2. P{} end\n3. P{} pick Nobody\n", id.0, id.0);
+        assert_matches!(
+            assert_ok!(wrong.parse::<Notation>()).replay(data()),
+            Err(NotationError::Rejected { number: 2, .. })
+        );
+        let wrong = format!("{notation}2. P{} pick Nobody\n", id.0);
+        assert_eq!(
+            assert_ok!(wrong.parse::<Notation>()).replay(data()),
+            Err(NotationError::InvalidMove(2))
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "shuffle"))]
+    fn games_are_replayed() {
+        let names = (0..4).map(|i| format!("player {i}")).collect::<Vec<_>>();
+        let mut notation = Notation::new(names);
+        let mut state = assert_ok!(notation.replay(data()));
+        assert_ok!(notation.apply(&mut state, PlayerId(0), PlayerAction::StartGame));
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(notation.apply(&mut state, id, PlayerAction::SelectCharacter { character }));
+        }
+        let id = state.round().unwrap().current_player().id();
+        let draw = PlayerAction::DrawCard {
+            card_type: CardType::Liability,
+        };
+        assert_ok!(notation.apply(&mut state, id, draw));
+
+        let replayed = assert_ok!(notation.to_string().parse::<Notation>());
+        assert_eq!(replayed, notation);
+        assert_eq!(assert_ok!(replayed.replay(data())), state);
+    }
+}
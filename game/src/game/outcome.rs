@@ -0,0 +1,250 @@
+//! File containing [`ActionOutcome`], a single description of what an action changed in the game.
+//! The methods on [`Round`] and the other states each return their own struct, which is fine for
+//! building responses, but bots and replays only care about what moved where. Outcomes are found
+//! by comparing the game before and after an action, so every action is covered the same way.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use crate::{game::*, player::*};
+
+/// One thing that changed because of an action.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome")]
+pub enum ActionOutcome {
+    /// The amount of cards in one of the zones of a player changed.
+    CardsMoved {
+        /// The player owning the zone.
+        player_id: PlayerId,
+        /// The zone the cards moved in or out of.
+        zone: CardZone,
+        /// The amount of cards in the zone before the action.
+        before: usize,
+        /// The amount of cards in the zone after the action.
+        after: usize,
+    },
+    /// The cash of a player changed.
+    CashChanged {
+        /// The player whose cash changed.
+        player_id: PlayerId,
+        /// How much cash the player gained, or lost if negative.
+        delta: i32,
+    },
+    /// A new market card became active.
+    MarketChanged {
+        /// The market that is now active.
+        new_market: Market,
+    },
+    /// Someone else is now expected to act, or nobody is if the phase has no turns.
+    TurnChanged {
+        /// The player that is expected to act next.
+        player_id: Option<PlayerId>,
+    },
+}
+
+/// A place where a player keeps cards.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardZone {
+    /// The cards in the hand of a player.
+    Hand,
+    /// The assets a player bought.
+    Assets,
+    /// The liabilities a player issued.
+    Liabilities,
+}
+
+/// The parts of a player [`ActionOutcome`]s are computed from.
+#[derive(Debug, Clone, PartialEq)]
+struct PlayerSnapshot {
+    id: PlayerId,
+    cash: Cash,
+    hand: usize,
+    assets: usize,
+    liabilities: usize,
+}
+
+/// The parts of a [`GameState`] [`ActionOutcome`]s are computed from.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Snapshot {
+    players: Vec<PlayerSnapshot>,
+    market: Option<Market>,
+    acting_player: Option<PlayerId>,
+}
+
+macro_rules! player_snapshots {
+    ($players:expr) => {
+        $players
+            .iter()
+            .map(|p| PlayerSnapshot {
+                id: p.id(),
+                cash: p.cash(),
+                hand: p.hand().len(),
+                assets: p.assets().len(),
+                liabilities: p.liabilities().len(),
+            })
+            .collect()
+    };
+}
+
+impl Snapshot {
+    /// Takes a snapshot of `state`.
+    pub(crate) fn new(state: &GameState) -> Self {
+        let (players, market) = match state {
+            GameState::Lobby(_) => (vec![], None),
+            GameState::SelectingCharacters(s) => (
+                player_snapshots!(s.players()),
+                Some(s.current_market().clone()),
+            ),
+            GameState::Round(r) => (
+                player_snapshots!(r.players()),
+                Some(r.current_market().clone()),
+            ),
+            GameState::BankerTarget(r) => (
+                player_snapshots!(r.players()),
+                Some(r.current_market().clone()),
+            ),
+            GameState::Results(r) => (player_snapshots!(r.players()), None),
+        };
+
+        Self {
+            players,
+            market,
+            acting_player: crate::bot::acting_player(state),
+        }
+    }
+
+    /// Gets everything that changed between `self` and `after`. Players that only appear in one
+    /// of the two snapshots, like when the game starts, are not compared.
+    pub(crate) fn outcomes(&self, after: &Snapshot) -> Vec<ActionOutcome> {
+        let mut outcomes = vec![];
+
+        for new in &after.players {
+            let Some(old) = self.players.iter().find(|p| p.id == new.id) else {
+                continue;
+            };
+
+            for (zone, before, after) in [
+                (CardZone::Hand, old.hand, new.hand),
+                (CardZone::Assets, old.assets, new.assets),
+                (CardZone::Liabilities, old.liabilities, new.liabilities),
+            ] {
+                if before != after {
+                    outcomes.push(ActionOutcome::CardsMoved {
+                        player_id: new.id,
+                        zone,
+                        before,
+                        after,
+                    });
+                }
+            }
+
+            if old.cash != new.cash {
+                outcomes.push(ActionOutcome::CashChanged {
+                    player_id: new.id,
+                    delta: new.cash.get() as i32 - old.cash.get() as i32,
+                });
+            }
+        }
+
+        if let Some(new_market) = &after.market
+            && self.market.as_ref() != Some(new_market)
+        {
+            outcomes.push(ActionOutcome::MarketChanged {
+                new_market: new_market.clone(),
+            });
+        }
+
+        if self.acting_player != after.acting_player {
+            outcomes.push(ActionOutcome::TurnChanged {
+                player_id: after.acting_player,
+            });
+        }
+
+        outcomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::GameData;
+    use claim::*;
+
+    fn started_game() -> GameState {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        lobby.set_game_data(GameData::new("../assets/cards/boardgame.json").unwrap());
+        assert_ok!(state.apply(PlayerId(0), PlayerAction::StartGame));
+        state
+    }
+
+    #[test]
+    fn nothing_changed() {
+        let state = started_game();
+        let snapshot = Snapshot::new(&state);
+        assert_eq!(snapshot.outcomes(&Snapshot::new(&state)), []);
+    }
+
+    #[test]
+    fn selecting_character_changes_turn() {
+        let mut state = started_game();
+        let selecting = state.selecting_characters().unwrap();
+        let id = selecting.currently_selecting_id();
+        let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+
+        let effects = assert_ok!(state.apply(id, PlayerAction::SelectCharacter { character }));
+        assert_matches!(
+            effects.outcomes.as_slice(),
+            [ActionOutcome::TurnChanged { player_id: Some(next) }] if *next != id
+        );
+    }
+
+    #[test]
+    fn cards_and_cash_are_compared() {
+        let before = Snapshot {
+            players: vec![PlayerSnapshot {
+                id: PlayerId(0),
+                cash: Cash(5),
+                hand: 4,
+                assets: 0,
+                liabilities: 0,
+            }],
+            market: None,
+            acting_player: Some(PlayerId(0)),
+        };
+        let mut after = before.clone();
+        after.players[0].cash = Cash(2);
+        after.players[0].hand = 3;
+        after.players[0].assets = 1;
+
+        assert_eq!(
+            before.outcomes(&after),
+            [
+                ActionOutcome::CardsMoved {
+                    player_id: PlayerId(0),
+                    zone: CardZone::Hand,
+                    before: 4,
+                    after: 3,
+                },
+                ActionOutcome::CardsMoved {
+                    player_id: PlayerId(0),
+                    zone: CardZone::Assets,
+                    before: 0,
+                    after: 1,
+                },
+                ActionOutcome::CashChanged {
+                    player_id: PlayerId(0),
+                    delta: -3,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! File containing [`TurnPhase`], the part of a turn the current player of a [`Round`] is in. The
+//! phase is derived from what the player did so far this turn, so it never disagrees with the
+//! counters that decide whether a player can still draw or should give back cards.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use crate::{errors::*, game::*, player::*};
+
+/// The part of a turn a player is in.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnPhase {
+    /// The player can still draw cards.
+    Draw,
+    /// The player drew every card they could and can play cards or end their turn.
+    Main,
+    /// The player used their ability and should make the [`PendingDecision`] it started.
+    Ability,
+    /// The player drew too many cards and should give some back before doing anything else.
+    Cleanup,
+}
+
+impl TurnPhase {
+    /// Checks whether `action` may be taken in this phase when phases are enforced, see
+    /// [`GameConfig::strict_phases`]. Actions during the [`TurnPhase::Ability`] phase are checked
+    /// by [`Round::check_pending_decision`] instead.
+    pub fn allows(self, action: &PlayerAction) -> bool {
+        use PlayerAction as A;
+
+        match self {
+            TurnPhase::Draw => {
+                matches!(action, A::DrawCard { .. } | A::UseAbility | A::GetBonusCash)
+            }
+            TurnPhase::Main => !matches!(action, A::DrawCard { .. } | A::PutBackCard { .. }),
+            TurnPhase::Ability => true,
+            TurnPhase::Cleanup => matches!(action, A::PutBackCard { .. }),
+        }
+    }
+}
+
+impl RoundPlayer {
+    /// Gets the phase of the turn of this player, ignoring any [`PendingDecision`]. Use
+    /// [`Round::turn_phase`] to include it.
+    pub fn phase(&self) -> TurnPhase {
+        if self.should_give_back_cards() {
+            TurnPhase::Cleanup
+        } else if self.can_draw_cards() {
+            TurnPhase::Draw
+        } else {
+            TurnPhase::Main
+        }
+    }
+}
+
+impl Round {
+    /// Gets the phase of the turn of the current player.
+    pub fn turn_phase(&self) -> TurnPhase {
+        match self.pending_decision {
+            Some(_) => TurnPhase::Ability,
+            // Nobody can be made to wait for cards that are all held by players
+            None => match self.current_player().phase() {
+                TurnPhase::Draw
+                    if !self.can_draw(CardType::Asset) && !self.can_draw(CardType::Liability) =>
+                {
+                    TurnPhase::Main
+                }
+                phase => phase,
+            },
+        }
+    }
+
+    /// Checks whether player `id` may take `action` in the current [`TurnPhase`]. Phases are only
+    /// enforced if the game is played with [`GameConfig::strict_phases`], otherwise the round only
+    /// rejects actions that are impossible, like drawing more cards than allowed.
+    pub fn check_turn_phase(&self, id: PlayerId, action: &PlayerAction) -> Result<(), GameError> {
+        let phase = self.turn_phase();
+        if self.config.strict_phases && id == self.current_player && !phase.allows(action) {
+            Err(GameError::WrongTurnPhase(phase))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_allow_actions() {
+        let draw = PlayerAction::DrawCard {
+            card_type: CardType::Asset,
+        };
+        let put_back = PlayerAction::PutBackCard { card_idx: 0 };
+        let buy = PlayerAction::BuyAsset { card_idx: 0 };
+
+        assert!(TurnPhase::Draw.allows(&draw));
+        assert!(!TurnPhase::Draw.allows(&buy));
+        assert!(!TurnPhase::Draw.allows(&PlayerAction::EndTurn));
+        assert!(TurnPhase::Main.allows(&buy));
+        assert!(TurnPhase::Main.allows(&PlayerAction::EndTurn));
+        assert!(!TurnPhase::Main.allows(&draw));
+        assert!(TurnPhase::Cleanup.allows(&put_back));
+        assert!(!TurnPhase::Cleanup.allows(&buy));
+    }
+}
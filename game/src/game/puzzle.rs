@@ -0,0 +1,257 @@
+//! File containing [`Puzzle`], a single turn played from a preset position with a goal to reach
+//! before it ends. Puzzles teach the scoring formula by letting a player try to get to a certain
+//! score with the cards they are given.
+//!
+//! There is no way to build an arbitrary [`GameState`] yet, so a puzzle only describes the player
+//! solving it and the market. The decks to draw from come from the [`GameData`] the puzzle is
+//! started with.
+
+use std::time::Duration;
+
+use either::Either;
+use serde::{Deserialize, Serialize};
+
+use crate::{cards::GameData, errors::*, game::*, player::*, utility::serde_asset_liability};
+
+/// A preset position together with the goal to reach from it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    /// The position the turn starts in.
+    pub position: PuzzlePosition,
+    /// What the player should reach before their turn ends.
+    pub goal: PuzzleGoal,
+}
+
+/// The player solving a [`Puzzle`] at the start of their turn, and the market they play in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzlePosition {
+    /// The character the player plays as.
+    pub character: Character,
+    /// The cash the player starts their turn with, which already includes their turn cash.
+    pub cash: u8,
+    /// The cards in the hand of the player.
+    #[serde(with = "serde_asset_liability::vec")]
+    pub hand: Vec<Either<Asset, Liability>>,
+    /// The assets the player bought earlier in the game.
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+    /// The liabilities the player issued earlier in the game.
+    #[serde(default)]
+    pub liabilities: Vec<Liability>,
+    /// The market the turn is played in.
+    pub market: Market,
+}
+
+/// What the player should reach to solve a [`Puzzle`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "goal")]
+pub enum PuzzleGoal {
+    /// The final score of the player should be at least `score` when their turn ends.
+    ReachScore {
+        /// The score to reach.
+        score: f64,
+    },
+}
+
+/// How a [`Puzzle`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleResult {
+    /// Whether the goal was reached.
+    pub solved: bool,
+    /// The final score of the player when their turn ended.
+    pub score: f64,
+}
+
+/// A [`Puzzle`] that is being played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzleGame {
+    state: GameState,
+    goal: PuzzleGoal,
+    result: Option<PuzzleResult>,
+}
+
+impl Puzzle {
+    /// The id of the player solving the puzzle.
+    pub const PLAYER_ID: PlayerId = PlayerId(0);
+
+    /// Starts the puzzle, drawing cards from the decks in `data`.
+    pub fn start(self, data: GameData) -> PuzzleGame {
+        let PuzzlePosition {
+            character,
+            cash,
+            hand,
+            assets,
+            liabilities,
+            market,
+        } = self.position;
+
+        let player = RoundPlayer::at_turn_start(
+            Self::PLAYER_ID,
+            "puzzle".to_owned(),
+            character,
+            cash,
+            hand,
+            assets,
+            liabilities,
+        );
+
+        let data_checksum = data.checksum();
+        let round = Round {
+            current_player: Self::PLAYER_ID,
+            players: Players(vec![player]),
+            assets: data.assets,
+            liabilities: data.liabilities,
+            markets: data.market_deck,
+            chairman: Self::PLAYER_ID,
+            current_market: market,
+            current_events: vec![],
+            open_characters: vec![],
+            fired_characters: vec![],
+            banker_target: None,
+            is_final_round: false,
+            config: GameConfig::default(),
+            data_checksum,
+            clock: None,
+            host: None,
+            pending_decision: None,
+            debt_payments: vec![],
+            round_number: 1,
+            character_stats: CharacterStatsMap::new(),
+            spectators: Spectators::default(),
+            turn_time_spent: Duration::ZERO,
+            skip_reasons: vec![],
+        };
+
+        PuzzleGame {
+            state: GameState::Round(round),
+            goal: self.goal,
+            result: None,
+        }
+    }
+}
+
+impl PuzzleGoal {
+    /// Checks whether `player` reached this goal, scoring them in `market`.
+    pub fn check(&self, player: &RoundPlayer, market: &Market) -> PuzzleResult {
+        let score = ResultsPlayer::new(player.clone(), market).score();
+
+        match *self {
+            PuzzleGoal::ReachScore { score: goal } => PuzzleResult {
+                solved: score >= goal,
+                score,
+            },
+        }
+    }
+}
+
+impl PuzzleGame {
+    /// Gets the game the puzzle is played in.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Gets the goal of the puzzle.
+    pub fn goal(&self) -> PuzzleGoal {
+        self.goal
+    }
+
+    /// Gets how the puzzle ended, if the turn has ended.
+    pub fn result(&self) -> Option<PuzzleResult> {
+        self.result
+    }
+
+    /// Applies `action` of the player solving the puzzle. Ending the turn checks the goal, after
+    /// which the result is returned and no more actions are accepted.
+    pub fn apply(&mut self, action: PlayerAction) -> Result<Option<PuzzleResult>, GameError> {
+        if self.result.is_some() {
+            return Err(GameError::NotRoundState);
+        }
+
+        if !matches!(action, PlayerAction::EndTurn) {
+            self.state.apply(Puzzle::PLAYER_ID, action)?;
+            return Ok(None);
+        }
+
+        let round = self.state.round()?;
+        let player = round.current_player();
+        if player.phase() == TurnPhase::Cleanup {
+            return Err(GameError::PlayerShouldGiveBackCard);
+        }
+
+        let result = self.goal.check(player, round.current_market());
+        self.result = Some(result);
+        Ok(Some(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    fn market() -> Market {
+        Market {
+            rfr: 1,
+            ..Default::default()
+        }
+    }
+
+    fn puzzle(data: &GameData) -> Puzzle {
+        let asset = data.assets.deck[0].clone();
+        let score = 2.0 * asset.gold_value as f64;
+
+        Puzzle {
+            position: PuzzlePosition {
+                character: Character::CEO,
+                cash: asset.gold_value,
+                hand: vec![Either::Left(asset)],
+                assets: vec![],
+                liabilities: vec![],
+                market: market(),
+            },
+            goal: PuzzleGoal::ReachScore { score },
+        }
+    }
+
+    #[test]
+    fn goal_is_checked_at_end_of_turn() {
+        let mut data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        // Buying the first asset draws a new market, which should score the same
+        data.market_deck = Deck::new(vec![Either::Left(market()); 4]);
+        let mut game = puzzle(&data).start(data.clone());
+
+        // Without buying the asset, the score is only the cash
+        let result = assert_some!(assert_ok!(game.apply(PlayerAction::EndTurn)));
+        assert!(!result.solved);
+        assert_err!(game.apply(PlayerAction::EndTurn));
+
+        let mut game = puzzle(&data).start(data.clone());
+        while game
+            .state()
+            .round()
+            .unwrap()
+            .current_player()
+            .can_draw_cards()
+        {
+            assert_ok!(game.apply(PlayerAction::DrawCard {
+                card_type: CardType::Liability
+            }));
+        }
+        while game
+            .state()
+            .round()
+            .unwrap()
+            .current_player()
+            .should_give_back_cards()
+        {
+            assert_ok!(game.apply(PlayerAction::PutBackCard { card_idx: 1 }));
+        }
+        assert_none!(assert_ok!(
+            game.apply(PlayerAction::BuyAsset { card_idx: 0 })
+        ));
+
+        let result = assert_some!(assert_ok!(game.apply(PlayerAction::EndTurn)));
+        assert!(result.solved);
+        assert_eq!(game.result(), Some(result));
+    }
+}
@@ -0,0 +1,274 @@
+//! File containing the human-readable dump of a [`GameState`], which shows everything on the table
+//! at once for logs, debugging and bug reports.
+
+use std::fmt::{self, Write};
+
+use either::Either;
+
+use crate::{game::*, player::*};
+
+impl GameState {
+    /// Renders the game as text: the market, how many cards are left in each deck, what every
+    /// player has on the table and in hand, and whose turn it is. Everything is shown, including
+    /// the hands and cash of every player, so this should never be sent to players.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::game::GameState;
+    /// let mut state = GameState::new();
+    /// state.lobby_mut().unwrap().join("alice".to_owned()).unwrap();
+    ///
+    /// let text = state.render_text();
+    /// assert!(text.starts_with("Lobby"));
+    /// assert!(text.contains("P0 alice"));
+    /// ```
+    pub fn render_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for GameState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameState::Lobby(lobby) => {
+                writeln!(f, "Lobby")?;
+                for player in lobby.players() {
+                    let host = match lobby.host().map(|h| h.id()) == Some(player.id()) {
+                        true => " (host)",
+                        false => "",
+                    };
+                    writeln!(f, "P{} {}{host}", player.id().0, player.name())?;
+                }
+                Ok(())
+            }
+            GameState::SelectingCharacters(selecting) => {
+                writeln!(
+                    f,
+                    "Round {} - selecting characters",
+                    selecting.round_number()
+                )?;
+                write_market(f, selecting.current_market())?;
+                write_decks(
+                    f,
+                    &selecting.assets,
+                    &selecting.liabilities,
+                    &selecting.markets,
+                )?;
+                let action = match selecting.currently_banning_id() {
+                    Some(_) => "banning",
+                    None => "selecting",
+                };
+                writeln!(
+                    f,
+                    "Turn: P{} {action}, open {:?}, banned {:?}",
+                    selecting.currently_selecting_id().0,
+                    selecting.open_characters(),
+                    selecting.banned_characters(),
+                )?;
+                write_players(
+                    f,
+                    selecting
+                        .players()
+                        .iter()
+                        .map(|p| (PlayerInfo::from(p), p.hand())),
+                    selecting.currently_selecting_id(),
+                )
+            }
+            GameState::Round(round) => {
+                writeln!(f, "Round {}", round.round_number())?;
+                write_market(f, round.current_market())?;
+                write_decks(f, &round.assets, &round.liabilities, &round.markets)?;
+                let current = round.current_player();
+                writeln!(
+                    f,
+                    "Turn: P{} {:?}, {:?} phase",
+                    current.id().0,
+                    current.character(),
+                    round.turn_phase(),
+                )?;
+                write_players(
+                    f,
+                    round
+                        .players()
+                        .iter()
+                        .map(|p| (PlayerInfo::from(p), p.hand())),
+                    current.id(),
+                )
+            }
+            GameState::BankerTarget(target) => {
+                writeln!(f, "Round {} - paying the banker", target.round_number())?;
+                write_market(f, target.current_market())?;
+                write_decks(f, &target.assets, &target.liabilities, &target.markets)?;
+                let current = target.current_player();
+                writeln!(
+                    f,
+                    "Turn: P{} {:?}, owes {} to the banker",
+                    current.id().0,
+                    current.character(),
+                    target.gold_to_be_paid(),
+                )?;
+                write_players(
+                    f,
+                    target
+                        .players()
+                        .iter()
+                        .map(|p| (PlayerInfo::from(p), p.hand())),
+                    current.id(),
+                )
+            }
+            GameState::Results(results) => {
+                writeln!(f, "Results")?;
+                for (rank, score) in results.ranking().iter().enumerate() {
+                    writeln!(
+                        f,
+                        "{}. P{} {} {}",
+                        rank + 1,
+                        score.id().0,
+                        score.name(),
+                        score.fixed_score(),
+                    )?;
+                }
+                for player in results.players() {
+                    writeln!(f)?;
+                    write_player(f, &PlayerInfo::from(player), player.hand(), false)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn write_market(f: &mut fmt::Formatter<'_>, market: &Market) -> fmt::Result {
+    write!(f, "Market: {}", market.title)?;
+    for color in Color::COLORS {
+        let condition = match market.color_condition(color) {
+            MarketCondition::DoublePlus => "++",
+            MarketCondition::Plus => "+",
+            MarketCondition::Zero => "0",
+            MarketCondition::Minus => "-",
+            MarketCondition::DoubleMinus => "--",
+        };
+        write!(f, " {color:?} {condition}")?;
+    }
+    writeln!(f)
+}
+
+fn write_decks(
+    f: &mut fmt::Formatter<'_>,
+    assets: &Deck<Asset>,
+    liabilities: &Deck<Liability>,
+    markets: &Deck<Either<Market, Event>>,
+) -> fmt::Result {
+    writeln!(
+        f,
+        "Decks: {} assets ({} discarded), {} liabilities ({} discarded), {} markets ({} discarded)",
+        assets.len(),
+        assets.discarded().len(),
+        liabilities.len(),
+        liabilities.discarded().len(),
+        markets.len(),
+        markets.discarded().len(),
+    )
+}
+
+fn write_players<'a>(
+    f: &mut fmt::Formatter<'_>,
+    players: impl Iterator<Item = (PlayerInfo, &'a [Either<Asset, Liability>])>,
+    current: PlayerId,
+) -> fmt::Result {
+    for (player, hand) in players {
+        writeln!(f)?;
+        write_player(f, &player, hand, player.id == current)?;
+    }
+    Ok(())
+}
+
+/// Writes down `player` with the cards in their `hand`. Cards are written down the way a
+/// [`Notation`] names them, so they can be copied into a move.
+fn write_player(
+    f: &mut fmt::Formatter<'_>,
+    player: &PlayerInfo,
+    hand: &[Either<Asset, Liability>],
+    current: bool,
+) -> fmt::Result {
+    let marker = if current { "> " } else { "" };
+    write!(f, "{marker}P{} {}", player.id.0, player.name)?;
+    if let Some(character) = player.character {
+        write!(f, " [{character:?}]")?;
+    }
+    match player.cash {
+        PlayerCash::Exact(cash) => write!(f, ", cash {cash}")?,
+        PlayerCash::Bracket(bracket) => write!(f, ", cash {bracket:?}")?,
+    }
+    if player.banker_debt > 0 {
+        write!(f, ", owes {} to bankers", player.banker_debt)?;
+    }
+    writeln!(f)?;
+
+    writeln!(
+        f,
+        "  assets:{}",
+        cards(player.assets.iter().map(Either::Left))
+    )?;
+    writeln!(
+        f,
+        "  liabilities:{}",
+        cards(player.liabilities.iter().map(Either::Right))
+    )?;
+    writeln!(f, "  hand:{}", cards(hand.iter().map(Either::as_ref)))
+}
+
+fn cards<'a>(cards: impl Iterator<Item = Either<&'a Asset, &'a Liability>>) -> String {
+    cards.fold(String::new(), |mut text, card| {
+        let _ = match card {
+            Either::Left(asset) => write!(
+                text,
+                " {:?} ({:?} {}/{})",
+                asset.title, asset.color, asset.gold_value, asset.silver_value
+            ),
+            Either::Right(liability) => {
+                write!(text, " {:?}:{}", liability.rfr_type, liability.value)
+            }
+        };
+        text
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::GameData;
+    use claim::*;
+
+    #[test]
+    fn renders_every_player() {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("player {i}")));
+        }
+        let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        assert_ok!(state.start_game_with_data(data));
+        assert!(
+            state
+                .render_text()
+                .starts_with("Round 1 - selecting characters")
+        );
+
+        while let Ok(selecting) = state.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let characters = assert_ok!(selecting.player_get_selectable_characters(id));
+            assert_ok!(state.player_select_character(id, characters[0]));
+        }
+        let text = state.render_text();
+        let round = state.round().unwrap();
+
+        assert!(text.contains(&format!("Market: {}", round.current_market().title)));
+        assert!(text.contains(&format!("Decks: {} assets", round.assets.len())));
+        assert!(text.contains(&format!("> P{} ", round.current_player().id().0)));
+        for player in round.players() {
+            assert!(text.contains(&format!("P{} {} [", player.id().0, player.name())));
+        }
+    }
+}
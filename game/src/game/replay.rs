@@ -0,0 +1,169 @@
+//! File containing [`Replay`], which checks that a game is deterministic. A game is played under a
+//! seed while the hash of the state after every action is recorded, after which it can be played
+//! again from the start to check it ends up in exactly the same states. Anything that makes a game
+//! play out differently, like iterating over a `HashSet`, float math or randomness that does not
+//! go through [`with_seed`], shows up as the first action where the hashes differ.
+
+use thiserror::Error;
+
+use crate::{
+    bot::MAX_SIMULATED_ACTIONS, errors::GameError, game::*, player::PlayerId, utility::with_seed,
+};
+
+/// A single recorded action of a [`Replay`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayStep {
+    /// The player that took the action.
+    pub player_id: PlayerId,
+    /// The action itself.
+    pub action: PlayerAction,
+    /// The [`GameState::state_hash`] of the game after the action was applied.
+    pub state_hash: u64,
+}
+
+/// Errors that can occur when recording or verifying a [`Replay`].
+#[derive(Debug, Error, PartialEq)]
+pub enum ReplayError {
+    /// Error indicating that an action was rejected by the game.
+    #[error(transparent)]
+    Game(#[from] GameError),
+    /// Error indicating that there is no step with this index.
+    #[error("There is no step {0}")]
+    NoSuchStep(usize),
+    /// Error indicating that replaying the game led to a different state than it did before.
+    #[error("Step {step} led to state {actual:#x} instead of {expected:#x}")]
+    Diverged {
+        /// The index of the first step that led to a different state.
+        step: usize,
+        /// The hash of the state the step led to when it was recorded.
+        expected: u64,
+        /// The hash of the state the step led to when it was replayed.
+        actual: u64,
+    },
+}
+
+/// A game that was played under a seed, recorded action by action.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    seed: u64,
+    start: GameState,
+    steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    /// Plays a game from `start` with every random choice drawn from `seed`, see [`with_seed`].
+    /// `play` is asked for the next action to take until it returns `None`, or until
+    /// [`MAX_SIMULATED_ACTIONS`] were taken.
+    pub fn record(
+        seed: u64,
+        start: GameState,
+        mut play: impl FnMut(&GameState) -> Option<(PlayerId, PlayerAction)>,
+    ) -> Result<Self, ReplayError> {
+        let steps = with_seed(seed, || {
+            let mut state = start.clone();
+            let mut steps = Vec::new();
+
+            while steps.len() < MAX_SIMULATED_ACTIONS
+                && let Some((player_id, action)) = play(&state)
+            {
+                state.apply(player_id, action.clone())?;
+                steps.push(ReplayStep {
+                    player_id,
+                    action,
+                    state_hash: state.state_hash(),
+                });
+            }
+
+            Ok::<_, GameError>(steps)
+        })?;
+
+        Ok(Self { seed, start, steps })
+    }
+
+    /// Gets every recorded action, in the order it was taken.
+    pub fn steps(&self) -> &[ReplayStep] {
+        &self.steps
+    }
+
+    /// Plays the recorded actions again under the same seed and checks the game ends up in the
+    /// same state after every single one of them. Returns the state the replay ended in.
+    pub fn verify(&self) -> Result<GameState, ReplayError> {
+        self.play_to(self.steps.len())
+    }
+
+    /// Plays the first `steps` recorded actions again under the same seed, checking the state
+    /// after each of them like [`Replay::verify`].
+    pub(super) fn play_to(&self, steps: usize) -> Result<GameState, ReplayError> {
+        if steps > self.steps.len() {
+            return Err(ReplayError::NoSuchStep(steps));
+        }
+
+        with_seed(self.seed, || {
+            let mut state = self.start.clone();
+
+            for (step, recorded) in self.steps[..steps].iter().enumerate() {
+                state.apply(recorded.player_id, recorded.action.clone())?;
+
+                let actual = state.state_hash();
+                if actual != recorded.state_hash {
+                    return Err(ReplayError::Diverged {
+                        step,
+                        expected: recorded.state_hash,
+                        actual,
+                    });
+                }
+            }
+
+            Ok(state)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bot::*, cards::GameData};
+    use claim::*;
+
+    fn lobby(players: usize) -> GameState {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut().unwrap();
+        for i in 0..players {
+            assert_ok!(lobby.join(format!("bot {i}")));
+        }
+        lobby.set_game_data(GameData::new("../assets/cards/boardgame.json").unwrap());
+        state
+    }
+
+    fn record(seed: u64) -> Replay {
+        let mut strategy = GreedyStrategy::new();
+        let mut started = false;
+
+        assert_ok!(Replay::record(seed, lobby(5), |state| {
+            if !started {
+                started = true;
+                return Some((PlayerId(0), PlayerAction::StartGame));
+            }
+            let id = acting_player(state)?;
+            strategy.next_action(state, id).map(|action| (id, action))
+        }))
+    }
+
+    #[test]
+    fn replays_are_deterministic() {
+        let replay = record(7);
+        let end = assert_ok!(replay.verify());
+
+        assert_ok!(end.results());
+        assert_eq!(end.state_hash(), replay.steps().last().unwrap().state_hash);
+        assert_eq!(record(7).steps(), replay.steps());
+    }
+
+    #[test]
+    fn tampered_replay_diverges() {
+        let mut replay = record(7);
+        replay.steps[3].state_hash ^= 1;
+
+        assert_matches!(replay.verify(), Err(ReplayError::Diverged { step: 3, .. }));
+    }
+}
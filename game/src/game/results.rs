@@ -4,11 +4,16 @@ use crate::{errors::*, game::*, player::*};
 
 /// State containing all information related to the results state of the game. In the resuts stage,
 /// players can see their scores.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Results {
     pub(super) players: Players<ResultsPlayer>,
     // TODO: implement events
     pub(super) final_events: Vec<Event>,
+    pub(super) data_checksum: u64,
+    pub(super) character_stats: CharacterStatsMap,
+    pub(super) final_market: Market,
+    #[serde(default)]
+    pub(super) spectators: Spectators,
 }
 
 impl Results {
@@ -27,6 +32,12 @@ impl Results {
             .ok_or_else(|| GameError::InvalidPlayerName(name.to_owned()))
     }
 
+    /// Sets a player as disconnected
+    pub fn leave(&mut self, id: PlayerId) -> Result<(), GameError> {
+        self.players.player_mut(id)?.set_is_human(false);
+        Ok(())
+    }
+
     /// Gets a slice of all players in the lobby.
     /// See [`Players::players`] for further information
     pub fn players(&self) -> &[ResultsPlayer] {
@@ -36,10 +47,15 @@ impl Results {
     /// Returns a list of [`PlayerScore`], which contains the player id as well as their final
     /// score.
     pub fn player_scores(&self) -> Vec<PlayerScore> {
-        self.players()
-            .iter()
-            .map(|p| PlayerScore::new(p.id(), p.name(), p.score()))
-            .collect()
+        self.players().iter().map(Into::into).collect()
+    }
+
+    /// Returns the [`PlayerScore`] of every player from the highest to the lowest
+    /// [`FixedScore`]. Players with the same score stay in the order of their ids.
+    pub fn ranking(&self) -> Vec<PlayerScore> {
+        let mut scores = self.player_scores();
+        scores.sort_by_key(|s| std::cmp::Reverse(s.fixed_score()));
+        scores
     }
 
     /// Gets the [`PlayerInfo`] for each player, excluding the player that has the same id as `id`.
@@ -51,11 +67,38 @@ impl Results {
             .collect()
     }
 
+    /// Gets the [`PlayerInfo`] for each player, as a spectator sees it.
+    pub fn spectator_info(&self) -> Vec<PlayerInfo> {
+        self.players().iter().map(Into::into).collect()
+    }
+
+    /// Gets the checksum of the card data the game was played with, see
+    /// [`GameData::checksum`](crate::cards::GameData::checksum).
+    pub fn data_checksum(&self) -> u64 {
+        self.data_checksum
+    }
+
+    /// Gets how each character was played over the course of the game, see [`CharacterStats`].
+    pub fn character_stats(&self) -> &CharacterStatsMap {
+        &self.character_stats
+    }
+
     /// Gets the list of events that happened over the course of the game
     pub fn final_events(&self) -> &[Event] {
         &self.final_events
     }
 
+    /// Gets the market the game ended in, before anyone changed their own market with an asset
+    /// ability.
+    pub fn final_market(&self) -> &Market {
+        &self.final_market
+    }
+
+    /// Gets the [`FinalBoard`] of every player, in the order of their ids.
+    pub fn final_boards(&self) -> Vec<FinalBoard> {
+        self.players().iter().map(Into::into).collect()
+    }
+
     /// Increases one of the market conditions of a certain color for player with `id`. This means
     /// that minus is turned into zero and zero is turned into plus. Returns the resulting market.
     pub fn toggle_minus_into_plus(
@@ -94,19 +137,32 @@ impl Results {
         Ok(data)
     }
 
+    /// Gets the score player `id` would have if they used `powerup` on what `params` points to,
+    /// without changing their choices. See [`ResultsPlayer::evaluate_powerup`].
+    pub fn evaluate_powerup(
+        &self,
+        id: PlayerId,
+        powerup: AssetPowerup,
+        params: PowerupParams,
+    ) -> Result<f64, GameError> {
+        self.player(id)?.evaluate_powerup(powerup, params)
+    }
+
     /// Asset abilities are toggleable by default. This function confirms the current configuration
     /// for this particular player, after which they cannot toggle this particular index anymore.
+    /// Returns what the ability was used on.
     pub fn confirm_asset_ability(
         &mut self,
         id: PlayerId,
         asset_idx: usize,
-    ) -> Result<(), GameError> {
+    ) -> Result<UsedAssetAbility, GameError> {
         let player = self.players.player_mut(id)?;
         player.confirm_asset_ability(asset_idx)
     }
 }
 
-/// Representation of a player's final score, which contains their id as well as their score.
+/// Representation of a player's final score, which contains their id as well as their score. The
+/// score is a float to show in breakdowns, while the [`FixedScore`] is used to rank players.
 ///
 /// # Examples
 ///
@@ -124,6 +180,7 @@ pub struct PlayerScore {
     id: PlayerId,
     name: String,
     score: f64,
+    fixed_score: FixedScore,
 }
 
 impl PlayerScore {
@@ -137,7 +194,13 @@ impl PlayerScore {
     /// ```
     pub fn new(id: PlayerId, name: &str, score: f64) -> Self {
         let name = name.to_owned();
-        Self { id, name, score }
+        let fixed_score = FixedScore::from_f64(score);
+        Self {
+            id,
+            name,
+            score,
+            fixed_score,
+        }
     }
 
     /// Gets a [`PlayerScore`]'s `id` field.
@@ -178,4 +241,109 @@ impl PlayerScore {
     pub fn score(&self) -> f64 {
         self.score
     }
+
+    /// Gets a [`PlayerScore`]'s score in hundredths, which players are ranked by.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::{game::{FixedScore, PlayerScore}, player::PlayerId};
+    /// let score = PlayerScore::new(PlayerId(0), "oxey", 10.0);
+    /// assert_eq!(score.fixed_score(), FixedScore(1000));
+    /// ```
+    pub fn fixed_score(&self) -> FixedScore {
+        self.fixed_score
+    }
+}
+
+impl From<&ResultsPlayer> for PlayerScore {
+    fn from(player: &ResultsPlayer) -> Self {
+        Self {
+            id: player.id(),
+            name: player.name().to_owned(),
+            score: player.score(),
+            fixed_score: player.fixed_score(),
+        }
+    }
+}
+
+/// Everything a player ended the game with, so results can be shown in full without keeping track
+/// of the game along the way.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FinalBoard {
+    /// The id of the player.
+    pub id: PlayerId,
+    /// The character the player held in the final round.
+    pub character: Character,
+    /// The cash the player ended with.
+    pub cash: Cash,
+    /// The assets the player ended with.
+    pub assets: Vec<Asset>,
+    /// The liabilities the player ended with.
+    pub liabilities: Vec<Liability>,
+    /// The market the player is scored with, which asset abilities can change.
+    pub market: Market,
+    /// How the score of the player is made up.
+    pub breakdown: ScoreBreakdown,
+}
+
+impl From<&ResultsPlayer> for FinalBoard {
+    fn from(player: &ResultsPlayer) -> Self {
+        Self {
+            id: player.id(),
+            character: player.character(),
+            cash: player.cash(),
+            assets: player.assets().to_vec(),
+            liabilities: player.liabilities().to_vec(),
+            market: player.market().clone(),
+            breakdown: player.into(),
+        }
+    }
+}
+
+/// The parts the score of a player is made up of. The [`Scoring`] of the game decides how they are
+/// combined into the score.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// The total market value of the assets of the player.
+    pub fcf: f64,
+    /// The total gold value of the assets of the player.
+    pub total_gold: u8,
+    /// The total silver value of the assets of the player.
+    pub total_silver: u8,
+    /// The trade credit the player owes.
+    pub trade_credit: u8,
+    /// The bank loans the player owes.
+    pub bank_loan: u8,
+    /// The bonds the player owes.
+    pub bonds: u8,
+    /// The bonus for owning assets of all five colors.
+    pub all_five_colors_bonus: u8,
+    /// The bonus for owning six assets.
+    pub six_assets_bonus: u8,
+    /// The asset abilities the player confirmed, which the other parts already include.
+    pub asset_abilities: Vec<UsedAssetAbility>,
+    /// The final score of the player.
+    pub score: f64,
+}
+
+impl From<&ResultsPlayer> for ScoreBreakdown {
+    fn from(player: &ResultsPlayer) -> Self {
+        Self {
+            fcf: player.fcf(),
+            total_gold: player.total_gold(),
+            total_silver: player.total_silver(),
+            trade_credit: player.trade_credit(),
+            bank_loan: player.bank_loan(),
+            bonds: player.bonds(),
+            all_five_colors_bonus: player.all_five_colors_bonus(),
+            six_assets_bonus: player.six_assets_bonus(),
+            asset_abilities: player.used_asset_abilities().to_vec(),
+            score: player.score(),
+        }
+    }
 }
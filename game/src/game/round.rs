@@ -1,14 +1,16 @@
 //! File containing the round state of the game.
 
 use either::Either;
+use itertools::Itertools;
+use std::{collections::BTreeMap, time::Duration};
 
-use crate::{errors::*, game::*, player::*};
+use crate::{errors::*, game::*, player::*, utility::serde_asset_liability};
 
 /// State containing all information related to the round state of the game. In the round stage,
 /// players each play a turn where they can draw cards, play cards and use their character ability.
 /// After every player has played a turn, players will be able to select characters again. If one
 /// player reached six or more assets during a round, the game will move to [`Results`] instead.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Round {
     pub(super) current_player: PlayerId,
     pub(super) players: Players<RoundPlayer>,
@@ -22,9 +24,28 @@ pub struct Round {
     pub(super) fired_characters: Vec<Character>,
     pub(super) banker_target: Option<Character>,
     pub(super) is_final_round: bool,
+    pub(super) config: GameConfig,
+    pub(super) data_checksum: u64,
+    pub(super) clock: Option<ChessClock>,
+    pub(super) host: Option<PlayerId>,
+    pub(super) pending_decision: Option<PendingDecision>,
+    pub(super) debt_payments: Vec<BankerDebtPayment>,
+    pub(super) round_number: u8,
+    pub(super) character_stats: CharacterStatsMap,
+    #[serde(default)]
+    pub(super) spectators: Spectators,
+    #[serde(default)]
+    pub(super) turn_time_spent: Duration,
+    #[serde(default)]
+    pub(super) skip_reasons: Vec<(Character, SkipReason)>,
 }
 
 impl Round {
+    /// Gets how many cards are left in the asset and liability decks.
+    pub fn deck_sizes(&self) -> DeckSizes {
+        DeckSizes::new(&self.assets, &self.liabilities)
+    }
+
     /// Get a reference to a [`RoundPlayer`] based on a specific `PlayerId`. Note that the players
     /// are in order, so id 0 refers to the player at index 0 and so on.
     /// See [`Players::player`] for further information
@@ -53,6 +74,45 @@ impl Round {
             .ok_or_else(|| GameError::InvalidPlayerName(name.to_owned()))
     }
 
+    /// Gets the payments towards banker debts the current player made when their turn started, see
+    /// [`BankerDebt`].
+    pub fn debt_payments(&self) -> &[BankerDebtPayment] {
+        &self.debt_payments
+    }
+
+    /// Starts the turn of the player with `id` by giving them their turn cash, after which they pay
+    /// off what they can of their banker debts.
+    pub(super) fn start_turn(&mut self, id: PlayerId) -> Result<(), GameError> {
+        let player = self.players.player_mut(id)?;
+        let turn_cash = player.turn_cash();
+        player.start_turn()?;
+        record_turn(&mut self.character_stats, player.character(), turn_cash);
+        self.debt_payments = self.pay_banker_debts(id)?;
+        Ok(())
+    }
+
+    /// Lets the player with `id` pay off as much of their banker debts as their cash allows, oldest
+    /// debt first.
+    fn pay_banker_debts(&mut self, id: PlayerId) -> Result<Vec<BankerDebtPayment>, GameError> {
+        let bankers = self
+            .player(id)?
+            .banker_debts()
+            .iter()
+            .map(|debt| debt.banker_id)
+            .collect::<Vec<_>>();
+
+        let mut payments = vec![];
+        for banker_id in bankers {
+            if let Ok([player, banker]) = self
+                .players
+                .get_disjoint_mut([usize::from(id), usize::from(banker_id)])
+            {
+                payments.extend(player.pay_banker_debt(banker)?);
+            }
+        }
+        Ok(payments)
+    }
+
     /// Get a reference to the [`RoundPlayer`] whose turn it is.
     pub fn current_player(&self) -> &RoundPlayer {
         // PANIC: This is an invariant that holds because `self.current_player` is only assigned by
@@ -101,6 +161,68 @@ impl Round {
     pub fn open_characters(&self) -> &[Character] {
         &self.open_characters
     }
+    /// Checks whether `player` may issue another liability this turn, which also checks
+    /// [`GameConfig::liability_cap`].
+    pub fn can_issue_liability(&self, player: &RoundPlayer) -> bool {
+        player.can_play_liability() && player.is_under_liability_cap(self.config.liability_cap)
+    }
+
+    /// Gets what the player with `id` can still do this turn, if it is their turn.
+    pub fn turn_summary(&self, id: PlayerId) -> Result<TurnSummary, GameError> {
+        let player = self.player_as_current(id)?;
+        let playable = player.playable_assets();
+        let assets_left = Color::COLORS
+            .into_iter()
+            .map(|color| (color, player.assets_to_play() / playable.color_cost(color)))
+            .collect();
+        let liabilities_left = match self.config.liability_cap {
+            Some(cap) => (cap as usize)
+                .saturating_sub(player.liabilities().len())
+                .min(player.liabilities_to_play() as usize) as u8,
+            None => player.liabilities_to_play(),
+        };
+
+        Ok(TurnSummary {
+            cards_left_to_draw: player.cards_left_to_draw(),
+            cards_left_to_give_back: player
+                .gives_back_n_cards()
+                .saturating_sub(player.total_cards_given_back()),
+            assets_left,
+            liabilities_left,
+            can_use_ability: player.can_use_ability(),
+        })
+    }
+
+    /// Checks whether there is a card of `card_type` left to draw, which there is not when every
+    /// card of its deck is held by a player.
+    pub fn can_draw(&self, card_type: CardType) -> bool {
+        match card_type {
+            CardType::Asset => !self.assets.upcoming().is_empty(),
+            CardType::Liability => !self.liabilities.upcoming().is_empty(),
+        }
+    }
+
+    /// Gets the chance of drawing each color and value when drawing a card of `card_type`, based on
+    /// the cards left in its deck. Since nobody knows the order of the deck, this is what a player
+    /// counting cards could work out on their own.
+    pub fn draw_odds(&self, card_type: CardType) -> DrawOdds {
+        match card_type {
+            CardType::Asset => {
+                let assets = self.assets.upcoming();
+                DrawOdds {
+                    cards_left: self.assets.len(),
+                    colors: DrawOdds::odds(assets, |a| a.color),
+                    values: DrawOdds::odds(assets, |a| a.gold_value),
+                }
+            }
+            CardType::Liability => DrawOdds {
+                cards_left: self.liabilities.len(),
+                colors: BTreeMap::new(),
+                values: DrawOdds::odds(self.liabilities.upcoming(), |l| l.value),
+            },
+        }
+    }
+
     ///Gets the character who is currently targeted by the banker if one is available
     pub fn banker_target(&self) -> Option<Character> {
         self.banker_target
@@ -108,11 +230,22 @@ impl Round {
 
     /// Gets the [`PlayerInfo`] for each player, excluding the player that has the same id as `id`.
     pub fn player_info(&self, id: PlayerId) -> Vec<PlayerInfo> {
+        self.spectator_info()
+            .into_iter()
+            .filter(|p| p.id != id)
+            .collect()
+    }
+
+    /// Gets the [`PlayerInfo`] for each player, as a spectator sees it. Characters that did not
+    /// have their turn yet are still closed, so they are left out.
+    pub fn spectator_info(&self) -> Vec<PlayerInfo> {
         self.players()
             .iter()
-            .filter(|p| p.id() != id)
             .map(|p| {
-                let mut info: PlayerInfo = p.into();
+                let mut info = PlayerInfo::from(p)
+                    .with_market(self.current_market())
+                    .with_liability_cap(&self.config)
+                    .redact(&self.config);
                 if p.character() > self.current_player().character() {
                     info.character = None;
                 }
@@ -121,11 +254,80 @@ impl Round {
             .collect()
     }
 
+    /// Gets the options the game is played with.
+    pub fn config(&self) -> GameConfig {
+        self.config
+    }
+
+    /// Gets the checksum of the card data the game is played with, see
+    /// [`GameData::checksum`](crate::cards::GameData::checksum).
+    pub fn data_checksum(&self) -> u64 {
+        self.data_checksum
+    }
+
+    /// Gets the time each player has left, if the game is played with a time bank.
+    pub fn clock(&self) -> Option<&ChessClock> {
+        self.clock.as_ref()
+    }
+
+    /// Gets the time the current player has left for this turn, if turns are limited by
+    /// [`GameConfig::turn_time`]. Time is charged with [`GameState::spend_time`], and once it runs
+    /// out [`GameState::out_of_time`] tells the host to end the turn.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        let turn_time = Duration::from_secs(self.config.turn_time?.into());
+        Some(turn_time.saturating_sub(self.turn_time_spent))
+    }
+
+    /// Gets the id of the player that hosted the lobby, see [`Lobby::host`].
+    pub fn host(&self) -> Option<PlayerId> {
+        self.host
+    }
+
+    /// Gets which round of the game this is, counting from 1.
+    pub fn round_number(&self) -> u8 {
+        self.round_number
+    }
+
+    /// Gets the nonce of the turn that is being played, which is sent along when the turn starts.
+    /// Requests that belong to a turn have to echo it, so a request that was sent twice cannot act
+    /// on the turn after it as well. Every turn of a game has a different nonce, since each
+    /// character only plays once per round.
+    pub fn turn_nonce(&self) -> u32 {
+        u32::from(self.round_number) << 8 | self.current_player().character() as u32
+    }
+
+    /// Checks whether `turn_nonce` is the nonce of the turn that is being played, see
+    /// [`Round::turn_nonce`].
+    pub fn check_turn_nonce(&self, turn_nonce: u32) -> Result<(), GameError> {
+        match self.turn_nonce() == turn_nonce {
+            true => Ok(()),
+            false => Err(GameError::StaleTurnNonce(turn_nonce)),
+        }
+    }
+
+    /// Checks whether player `id` is the host of the game.
+    pub fn check_host(&self, id: PlayerId) -> Result<(), GameError> {
+        match self.host {
+            Some(host) if host == id => Ok(()),
+            _ => Err(GameError::NotHost),
+        }
+    }
+
     /// Gets the current market
     pub fn current_market(&self) -> &Market {
         &self.current_market
     }
 
+    /// Gets the events that happened so far this game, oldest first
+    pub fn current_events(&self) -> &[Event] {
+        &self.current_events
+    }
+
+    /// Gets how each character was played so far, see [`CharacterStats`].
+    pub fn character_stats(&self) -> &CharacterStatsMap {
+        &self.character_stats
+    }
+
     /// Gets whether or not this is the final round
     pub fn is_final_round(&self) -> bool {
         self.is_final_round
@@ -134,6 +336,14 @@ impl Round {
     /// Internally used function that checks whether a player with such an `id` exists, and whether
     /// that player is actually the current player. If this is the case, a mutable reference to the
     /// player is returned.
+    fn player_as_current(&self, id: PlayerId) -> Result<&RoundPlayer, GameError> {
+        match self.players.player(id) {
+            Ok(player) if player.id() == self.current_player => Ok(player),
+            Ok(_) => Err(GameError::NotPlayersTurn),
+            Err(e) => Err(e),
+        }
+    }
+
     fn player_as_current_mut(&mut self, id: PlayerId) -> Result<&mut RoundPlayer, GameError> {
         match self.players.player_mut(id) {
             Ok(player) if player.id() == self.current_player => Ok(player),
@@ -142,18 +352,19 @@ impl Round {
         }
     }
 
-    /// Gets a list of characters that are available to be fired this round. This will exclude the
-    /// list of [`Round::open_characters`] as well as characters that have already been skipped or
-    /// fired this round.
-    pub fn player_get_fireble_characters(&mut self) -> Vec<Character> {
-        Character::CHARACTERS
-            .into_iter()
+    /// Gets a list of characters that are available to be fired this round. These are the
+    /// characters of players whose turn is still to come, excluding characters that have already
+    /// been skipped or fired this round. Characters that nobody selected, like the
+    /// [`Round::open_characters`] and the closed character, are never included.
+    pub fn player_get_fireble_characters(&self) -> Vec<Character> {
+        let current_character = self.current_player().character();
+        self.players()
+            .iter()
+            .map(|p| p.character())
             .filter(|c| {
-                c.can_be_fired()
-                    && !self.fired_characters.contains(c)
-                    && !self.open_characters.contains(c)
+                c.can_be_fired() && *c > current_character && !self.fired_characters.contains(c)
             })
-            .clone()
+            .sorted()
             .collect()
     }
 
@@ -175,15 +386,28 @@ impl Round {
     /// player was the first to buy their first, second, third, fourth, fifth, seventh, eight or
     /// ninth asset, a new market and corresponding triggered events will be returned. The card that
     /// was played will also be returned.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_play_card(
         &mut self,
         id: PlayerId,
         card_idx: usize,
     ) -> Result<PlayerPlayedCard, GameError> {
         let old_max_bought_assets = self.max_bought_assets();
+        let liability_cap = self.config.liability_cap;
+        let round_number = self.round_number;
         let player = self.player_as_current_mut(id)?;
 
-        match player.play_card(card_idx)? {
+        if let Some(cap) = liability_cap
+            && player.hand().get(card_idx).is_some_and(Either::is_right)
+            && !player.is_under_liability_cap(liability_cap)
+        {
+            return Err(PlayCardError::LiabilityCapReached(cap).into());
+        }
+
+        match player.play_card(card_idx, round_number)? {
             Either::Left(asset) => {
                 if !self.is_final_round() && self.check_is_final_round() {
                     // Keep the borrow checker happy
@@ -221,22 +445,32 @@ impl Round {
 
     /// This allows player with id `id` to redeem a liability at index `liability_idx` if they are
     /// the [`CFO`](Character::CFO) and if they can afford to pay off the debt. If they can redeem
-    /// the liability, it will be added back into the deck.
+    /// the liability, it will be added back into the deck and the cash it cost is returned, see
+    /// [`Liability::redemption_cost`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_redeem_liability(
         &mut self,
         id: PlayerId,
         liability_idx: usize,
-    ) -> Result<(), GameError> {
+    ) -> Result<u8, GameError> {
+        let round_number = self.round_number;
+        let config = self.config;
         let player = self.player_as_current_mut(id)?;
 
-        let liability = player.redeem_liability(liability_idx)?;
+        let mut liability = player.redeem_liability(liability_idx, round_number, &config)?;
+        let cost = liability.redemption_cost(round_number, &config);
+        liability.issued_round = None;
         self.liabilities.put_back(liability);
 
-        Ok(())
+        Ok(cost)
     }
 
     /// This allows player with id `id` to draw a card of card type `card_type`. If they were
     /// allowed to draw that card, a reference to the card will be returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
     pub fn player_draw_card(
         &mut self,
         id: PlayerId,
@@ -248,10 +482,18 @@ impl Round {
             Ok(player) if player.id() == self.current_player => match card_type {
                 CardType::Asset => {
                     let asset = player.draw_asset(&mut self.assets)?;
+
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(?asset, "drew asset");
+
                     Ok(Either::Left(asset))
                 }
                 CardType::Liability => {
                     let liability = player.draw_liability(&mut self.liabilities)?;
+
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(?liability, "drew liability");
+
                     Ok(Either::Right(liability))
                 }
             },
@@ -262,6 +504,10 @@ impl Round {
 
     /// This allows player with id `id` to give back a card from their hand at index `card_idx`. If
     /// they were able to give back the card, the card type of this card will be returned.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_give_back_card(
         &mut self,
         id: PlayerId,
@@ -281,6 +527,10 @@ impl Round {
         }
     }
     /// Gets players bonus cash based on their characters color. If successfull returns gold.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_get_bonus_cash_character(
         &mut self,
         player_id: PlayerId,
@@ -288,12 +538,18 @@ impl Round {
         let market = &self.current_market.clone();
         let player = self.player_as_current_mut(player_id)?;
         let cash = player.get_bonus_cash_character(market)?;
+        let character = player.character();
+        record_income(&mut self.character_stats, character, cash);
         Ok(cash)
     }
 
     /// This allows player with id `id` to fire a player who has character `character` if they are
     /// the shareholder. If this is successful, the player who got fired will not play their turn
     /// this round.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_fire_character(
         &mut self,
         id: PlayerId,
@@ -302,12 +558,18 @@ impl Round {
         let player = self.player_as_current_mut(id)?;
         let character = player.fire_character(character)?;
         self.fired_characters.push(character);
+        self.skip_reasons
+            .push((character, SkipReason::Fired { by: id }));
         Ok(character)
     }
 
     /// This allows player with id `id` to fire a player who has character `character` if they are
     /// the shareholder. If this is successful, the player who got fired will not play their turn
     /// this round.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_terminate_credit_character(
         &mut self,
         id: PlayerId,
@@ -321,12 +583,16 @@ impl Round {
 
     /// This allows player with id `id` to swap a list of cards from their hand at indexes
     /// `card_idxs` with the deck. If succesful, this function returns the number of cards that were
-    /// swapped with the deck in total.
+    /// swapped with the deck and the cards the player drew to replace them.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_swap_with_deck(
         &mut self,
         id: PlayerId,
         card_idxs: Vec<usize>,
-    ) -> Result<AssetLiabilityCount, GameError> {
+    ) -> Result<DeckSwap, GameError> {
         // cant use player_as_current_mut here because of multiple mutable borrows of self. hmm.
         let player = match self.players.player_mut(id) {
             Ok(player) if player.id() == self.current_player => player,
@@ -334,13 +600,16 @@ impl Round {
             Err(e) => return Err(e),
         };
 
-        let drawcount =
-            player.swap_with_deck(card_idxs, &mut self.assets, &mut self.liabilities)?;
-        Ok(drawcount)
+        let swap = player.swap_with_deck(card_idxs, &mut self.assets, &mut self.liabilities)?;
+        Ok(swap)
     }
 
     /// This allows a player with id `id` to swap their hand of cards with a player with id
     /// `target_id`. If succesful, a copy of each player's new hand is returned.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_swap_with_player(
         &mut self,
         id: PlayerId,
@@ -382,6 +651,10 @@ impl Round {
     /// This allows a player with id `id` to force player with id `target_id` to divest an asset at
     /// index `asset_idx` for market value minus 1. If succesful, returns the amount of gold it cost
     /// to divest the asset for.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn player_divest_asset(
         &mut self,
         id: PlayerId,
@@ -409,7 +682,7 @@ impl Round {
             {
                 Ok([stakeholder, target]) => {
                     let cost = stakeholder.divest_asset(target, asset_idx, &self.current_market)?;
-                    target.remove_asset(asset_idx)?;
+                    self.assets.discard(target.remove_asset(asset_idx)?);
                     Ok(cost)
                 }
                 Err(_) => Err(DivestAssetError::InvalidCharacter.into()),
@@ -419,6 +692,58 @@ impl Round {
         }
     }
 
+    /// Lets the stakeholder with id `id` pick the asset at index `asset_idx` of player `target_id`
+    /// to divest. Nothing changes hands yet: the divest waits for the stakeholder to confirm it with
+    /// [`Round::player_confirm_divest`], so the target can be told about it first. Picking another
+    /// asset before confirming replaces the pick. Returns the picked divest.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
+    pub fn player_pick_divest_asset(
+        &mut self,
+        id: PlayerId,
+        target_id: PlayerId,
+        asset_idx: usize,
+    ) -> Result<PendingDivest, GameError> {
+        if id == target_id {
+            return Err(DivestAssetError::InvalidCharacter.into());
+        }
+        let stakeholder = self.player_as_current(id)?;
+        let target = self
+            .player(target_id)
+            .map_err(|_| DivestAssetError::InvalidCharacter)?;
+        let gold_cost = stakeholder.divest_cost(target, asset_idx, &self.current_market)?;
+
+        let divest = PendingDivest {
+            target_id,
+            asset_idx,
+            gold_cost,
+        };
+        self.pending_decision = Some(PendingDecision::ConfirmDivest(divest));
+        Ok(divest)
+    }
+
+    /// Divests the asset the stakeholder with id `id` picked with
+    /// [`Round::player_pick_divest_asset`]. Returns the divest, with what it cost in the end.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
+    pub fn player_confirm_divest(&mut self, id: PlayerId) -> Result<PendingDivest, GameError> {
+        self.player_as_current(id)?;
+        let Some(PendingDecision::ConfirmDivest(divest)) = self.pending_decision else {
+            return Err(GameError::NoDecisionPending);
+        };
+
+        let gold_cost = self.player_divest_asset(id, divest.target_id, divest.asset_idx)?;
+        self.pending_decision = None;
+        Ok(PendingDivest {
+            gold_cost,
+            ..divest
+        })
+    }
+
     /// Gets a list of [`DivestPlayer`], which contains their player id as well as each asset that
     /// can be divested as well as the current cost to do so. This list excludes their own cards.
     pub fn get_divest_assets(&mut self, id: PlayerId) -> Result<Vec<DivestPlayer>, GameError> {
@@ -467,22 +792,32 @@ impl Round {
         skipped
     }
 
+    /// Gets why `character` does not get a turn this round, if they were fired or an event skips
+    /// their turn. Characters that nobody selected have no reason.
+    pub fn skip_reason(&self, character: Character) -> Option<&SkipReason> {
+        self.skip_reasons
+            .iter()
+            .find_map(|(c, reason)| (*c == character).then_some(reason))
+    }
+
     /// Ends the turn of the player with id `id`. If succesful and this player is not the last
     /// player to play this round, this function, returns [`TurnEnded`], which contains the next
     /// player as well as whether or not the game has ended. If succesful and the player is the last
     /// turn of the round, returs a new [`GameState`] of [`SelectingCharacters`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err)
+    )]
     pub(super) fn end_player_turn(
         &mut self,
         id: PlayerId,
     ) -> Result<Either<TurnEnded, GameState>, GameError> {
         let player = self.player_as_current_mut(id)?;
-        if !player.should_give_back_cards() {
+        if player.phase() != TurnPhase::Cleanup {
             if let Some(id) = self.next_player().map(|p| p.id()) {
-                let player = self.players.player_mut(id)?;
-
-                player.start_turn();
-
-                self.current_player = player.id();
+                self.start_turn(id)?;
+                self.current_player = id;
+                self.turn_time_spent = Duration::ZERO;
 
                 let turn_ended = TurnEnded {
                     next_player: Some(self.current_player),
@@ -490,7 +825,7 @@ impl Round {
                 };
 
                 Ok(Either::Left(turn_ended))
-            } else if !self.is_final_round() {
+            } else if !self.is_final_round() && !self.reached_max_rounds() {
                 let maybe_ceo = self.player_from_character(Character::CEO);
                 let chairman_id = match maybe_ceo.map(|p| p.id()) {
                     Some(id) => id,
@@ -510,29 +845,49 @@ impl Round {
                 let state = GameState::SelectingCharacters(SelectingCharacters {
                     players,
                     characters,
+                    banned_characters: Vec::new(),
                     assets,
                     liabilities,
                     markets,
                     chairman: chairman_id,
                     current_market,
                     current_events,
+                    config: self.config,
+                    data_checksum: self.data_checksum,
+                    clock: self.clock.take(),
+                    host: self.host,
+                    round_number: self.round_number.saturating_add(1),
+                    character_stats: std::mem::take(&mut self.character_stats),
+                    spectators: std::mem::take(&mut self.spectators),
                 });
 
                 Ok(Either::Right(state))
             } else {
+                // There are no turns left to pay off banker debts in, so the cash left is used
+                for id in self.players.iter().map(|p| p.id()).collect::<Vec<_>>() {
+                    self.pay_banker_debts(id)?;
+                }
+
                 let final_events = std::mem::take(&mut self.current_events);
                 let players = std::mem::take(&mut self.players);
 
                 let players = Players(
                     players
                         .into_iter()
-                        .map(|round_player| ResultsPlayer::new(round_player, self.current_market()))
+                        .map(|round_player| {
+                            ResultsPlayer::new(round_player, self.current_market())
+                                .with_scoring(self.config.scoring)
+                        })
                         .collect(),
                 );
 
                 let state = GameState::Results(Results {
                     players,
                     final_events,
+                    data_checksum: self.data_checksum,
+                    character_stats: std::mem::take(&mut self.character_stats),
+                    spectators: std::mem::take(&mut self.spectators),
+                    final_market: self.current_market.clone(),
                 });
 
                 Ok(Either::Right(state))
@@ -542,10 +897,43 @@ impl Round {
         }
     }
 
-    /// Checks whether someone has bought equal to or more assets than [`ASSETS_FOR_END_OF_GAME`].
-    /// If so, this should be the final round.
+    /// Ends the turn of player `id` even if they still had to give back cards, so a player that
+    /// stalls the game or runs out of time can be moved along. Cards are given back starting from
+    /// the card they drew last, and any decision they did not make yet is dropped. Returns the
+    /// types of the cards that were given back, in the order they were given back, along with how
+    /// the turn ended.
+    pub fn force_end_turn(
+        &mut self,
+        id: PlayerId,
+    ) -> Result<(Vec<CardType>, Either<TurnEnded, GameState>), GameError> {
+        let mut put_back = vec![];
+
+        while self.player(id)?.should_give_back_cards() {
+            let card_idx = *self
+                .player(id)?
+                .cards_drawn()
+                .last()
+                .ok_or(GameError::PlayerShouldGiveBackCard)?;
+            put_back.push(self.player_give_back_card(id, card_idx)?);
+        }
+        self.pending_decision = None;
+
+        let ended = self.end_player_turn(id)?;
+        Ok((put_back, ended))
+    }
+
+    /// Checks whether someone has bought equal to or more assets than
+    /// [`GameConfig::assets_to_end_game`]. If so, this should be the final round.
     fn check_is_final_round(&self) -> bool {
-        self.max_bought_assets() >= ASSETS_FOR_END_OF_GAME
+        self.max_bought_assets() >= self.config.assets_to_end_game()
+    }
+
+    /// Checks whether this round is the last one the [`GameConfig`] allows, in which case the game
+    /// ends with it even if this is not the final round.
+    fn reached_max_rounds(&self) -> bool {
+        self.config
+            .max_rounds
+            .is_some_and(|max| self.round_number >= max)
     }
 
     /// Returns the highest amount of assets of any player.
@@ -562,29 +950,76 @@ impl Round {
     fn should_refresh_market(&self, old_max_bought_assets: usize) -> bool {
         let max_bought_assets = self.max_bought_assets();
 
-        max_bought_assets > old_max_bought_assets && max_bought_assets != ASSETS_FOR_END_OF_GAME
+        max_bought_assets > old_max_bought_assets
+            && max_bought_assets != self.config.assets_to_end_game()
     }
 
     /// Generates a new market change. Cards will be taken from the market/event deck one by one
-    /// until a new market is encountered, returning a [`MarketChange`].
+    /// until a new market is encountered, returning a [`MarketChange`]. Characters that an event
+    /// skips are skipped for the rest of this round, and every player gains or loses the gold of
+    /// every event right away. The old market and the events are discarded.
     fn refresh_market(&mut self) -> MarketChange {
         let mut events = vec![];
+        let mut cards_drawn = 0;
+        let mut cash_changes: Vec<(PlayerId, i16)> = vec![];
+        let current_character = self.current_player().character();
 
-        loop {
+        let new_market = loop {
             match self.markets.draw() {
-                Either::Left(new_market) => {
-                    self.current_market = new_market.clone();
-                    break MarketChange { events, new_market };
+                Some(Either::Left(new_market)) => {
+                    cards_drawn += 1;
+                    let old_market =
+                        std::mem::replace(&mut self.current_market, new_market.clone());
+                    self.markets.discard(Either::Left(old_market));
+                    break new_market;
                 }
-                Either::Right(event) => {
+                // There is no other market left, so the current market stays
+                None => break self.current_market.clone(),
+                Some(Either::Right(event)) => {
+                    cards_drawn += 1;
+                    // Skipping works the same as being fired, for the rest of this round
+                    if let Some(character) = event.skip_turn
+                        && character > current_character
+                        && !self.fired_characters.contains(&character)
+                    {
+                        self.fired_characters.push(character);
+                        let reason = SkipReason::Event {
+                            title: event.title.clone(),
+                        };
+                        self.skip_reasons.push((character, reason));
+                    }
+                    for player in self.players.players_mut() {
+                        let delta = player.apply_event(&event);
+                        match cash_changes.iter_mut().find(|(id, _)| *id == player.id()) {
+                            Some((_, total)) => *total += delta,
+                            None => cash_changes.push((player.id(), delta)),
+                        }
+                    }
                     self.current_events.push(event.clone());
                     events.push(event);
                 }
             }
+        };
+
+        // Events are only discarded now, so the same event cannot be drawn twice in one change
+        for event in &events {
+            self.markets.discard(Either::Right(event.clone()));
+        }
+        cash_changes.retain(|&(_, delta)| delta != 0);
+        MarketChange {
+            events,
+            cash_changes,
+            new_market,
+            cards_drawn,
+            cards_remaining: self.markets.len(),
         }
     }
 
     /// Sets a player as disconnected
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn leave(&mut self, id: PlayerId) -> Result<(), GameError> {
         match self.players.player_mut(id) {
             Ok(player) => {
@@ -623,7 +1058,7 @@ impl Round {
 
 /// Used to return the amount of assets and liabilities that were returned to the deck when the
 /// [`Character::Regulator`] swaps with the deck.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AssetLiabilityCount {
     /// The amount of assets returned to the deck.
     pub asset_count: usize,
@@ -650,12 +1085,70 @@ impl AssetLiabilityCount {
     }
 }
 
+/// Used to return the cards the [`Character::Regulator`] swapped with the deck.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeckSwap {
+    /// The amount of assets and liabilities returned to the deck.
+    pub count: AssetLiabilityCount,
+    /// The cards drawn from the deck to replace the returned cards.
+    #[serde(with = "serde_asset_liability::vec")]
+    pub new_cards: Vec<Either<Asset, Liability>>,
+}
+
+/// What the current player of a [`Round`] can still do this turn, see [`Round::turn_summary`].
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TurnSummary {
+    /// The number of cards the player can still draw.
+    pub cards_left_to_draw: u8,
+    /// The number of cards the player still has to give back, including those for cards they have
+    /// yet to draw.
+    pub cards_left_to_give_back: u8,
+    /// The number of assets of each color the player can still buy, if they can afford them.
+    pub assets_left: BTreeMap<Color, u8>,
+    /// The number of liabilities the player can still issue, which takes
+    /// [`GameConfig::liability_cap`] into account.
+    pub liabilities_left: u8,
+    /// Whether the player can still use the ability of their character.
+    pub can_use_ability: bool,
+}
+
+/// The chances of drawing each kind of card from a deck, see [`Round::draw_odds`]. Every chance is
+/// between 0 and 1, and the chances of each map add up to 1.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrawOdds {
+    /// The number of cards left in the deck before it is reshuffled.
+    pub cards_left: usize,
+    /// The chance of drawing an asset of each color. Empty for liabilities.
+    pub colors: BTreeMap<Color, f64>,
+    /// The chance of drawing a card of each value, which is the gold value for assets.
+    pub values: BTreeMap<u8, f64>,
+}
+
+impl DrawOdds {
+    fn odds<T, K: Ord>(cards: &[T], key: impl Fn(&T) -> K) -> BTreeMap<K, f64> {
+        let counts = cards.iter().fold(BTreeMap::new(), |mut counts, card| {
+            *counts.entry(key(card)).or_insert(0usize) += 1;
+            counts
+        });
+        counts
+            .into_iter()
+            .map(|(k, count)| (k, count as f64 / cards.len() as f64))
+            .collect()
+    }
+}
+
 /// Used to return the new hands for the regulator and its player target.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HandsAfterSwap {
     /// The new hand of the regulator
+    #[serde(with = "serde_asset_liability::vec")]
     pub regulator_new_hand: Vec<Either<Asset, Liability>>,
     /// The new hand for the regulator's target
+    #[serde(with = "serde_asset_liability::vec")]
     pub target_new_hand: Vec<Either<Asset, Liability>>,
 }
 
@@ -674,6 +1167,17 @@ impl From<&mut BankerTargetRound> for Round {
             fired_characters: btround.fired_characters.clone(),
             is_final_round: btround.is_final_round,
             banker_target: None,
+            config: btround.config,
+            data_checksum: btround.data_checksum,
+            clock: btround.clock.clone(),
+            host: btround.host,
+            pending_decision: None,
+            debt_payments: vec![],
+            round_number: btround.round_number,
+            character_stats: btround.character_stats.clone(),
+            spectators: btround.spectators.clone(),
+            turn_time_spent: Duration::ZERO,
+            skip_reasons: btround.skip_reasons.clone(),
         }
     }
 }
@@ -0,0 +1,221 @@
+//! File containing [`SavedGame`], a game stored in a versioned format so it can still be loaded
+//! after the server is upgraded. Every save carries the [`SAVE_VERSION`] it was written with, and
+//! older saves are migrated one version at a time before they are read.
+//!
+//! A save stores the [`Notation`] of the game, which can always be replayed, and the serialized
+//! [`GameState`] it was in. Fields added to the state later on get a default when an older state is
+//! read, so only changes that cannot be defaulted need a migration.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{cards::GameData, game::*};
+
+/// The version of the format [`SavedGame`] writes.
+pub const SAVE_VERSION: u32 = 3;
+
+/// Migrates the json of a save to the next version. The migration at index `i` turns a save of
+/// version `i + 1` into a save of version `i + 2`.
+type Migration = fn(&mut Value) -> Result<(), SaveError>;
+
+/// Every migration since the first version, oldest first.
+const MIGRATIONS: &[Migration] = &[add_data_checksum, add_state];
+
+/// A game stored in the format of [`SAVE_VERSION`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    /// The version of the format the game was saved with.
+    pub version: u32,
+    /// The game written down as text, see [`Notation`].
+    pub notation: String,
+    /// The checksum of the cards the game was played with, see
+    /// [`GameData::checksum`]. Saves from before checksums were stored do not have one.
+    pub data_checksum: Option<u64>,
+    /// The state the game was in when it was saved. Saves from before states were stored do not
+    /// have one, and are loaded by replaying their notation.
+    pub state: Option<GameState>,
+}
+
+/// Errors that can occur when loading a [`SavedGame`].
+#[derive(Debug, Error)]
+pub enum SaveError {
+    /// The save is not valid json, or does not match its version.
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+    /// The save does not say which version it was written with.
+    #[error("Save has no version")]
+    MissingVersion,
+    /// The save was written by a newer version than this one.
+    #[error("Save version {0} is newer than the supported version {SAVE_VERSION}")]
+    UnsupportedVersion(u32),
+    /// The game was saved with different cards than it is loaded with.
+    #[error("Save was played with different cards")]
+    DataMismatch,
+    /// The notation of the game could not be read or replayed.
+    #[error(transparent)]
+    Notation(#[from] NotationError),
+}
+
+impl SavedGame {
+    /// Saves the game written down in `notation`, which is in `state` and played with cards that
+    /// have `data_checksum`.
+    pub fn new(notation: &Notation, state: &GameState, data_checksum: u64) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            notation: notation.to_string(),
+            data_checksum: Some(data_checksum),
+            state: Some(state.clone()),
+        }
+    }
+
+    /// Writes this save as json.
+    pub fn to_json(&self) -> Result<String, SaveError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Reads a save from `json`, migrating it from the version it was written with.
+    pub fn from_json(json: &str) -> Result<Self, SaveError> {
+        let save = migrate(serde_json::from_str(json)?, MIGRATIONS)?;
+        Ok(serde_json::from_value(save)?)
+    }
+
+    /// Reads the notation of the saved game.
+    pub fn notation(&self) -> Result<Notation, SaveError> {
+        Ok(self.notation.parse()?)
+    }
+
+    /// Loads the saved game by replaying it with the decks in `data`. See [`Notation::replay`].
+    /// Saves that know which cards they were played with refuse to load with different ones.
+    pub fn load(&self, data: GameData) -> Result<GameState, SaveError> {
+        self.check_data(&data)?;
+        Ok(self.notation()?.replay(data)?)
+    }
+
+    /// Restores the saved game from its stored state, or loads it like [`SavedGame::load`] if
+    /// the save has no state. Like loading, this refuses cards the game was not played with.
+    pub fn restore(&self, data: GameData) -> Result<GameState, SaveError> {
+        match &self.state {
+            Some(state) => {
+                self.check_data(&data)?;
+                Ok(state.clone())
+            }
+            None => self.load(data),
+        }
+    }
+
+    /// Checks whether the game was played with `data`, if the save knows which cards it was
+    /// played with.
+    fn check_data(&self, data: &GameData) -> Result<(), SaveError> {
+        if self
+            .data_checksum
+            .is_some_and(|checksum| checksum != data.checksum())
+        {
+            return Err(SaveError::DataMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Version 2 stores the checksum of the cards, which older saves do not know.
+fn add_data_checksum(save: &mut Value) -> Result<(), SaveError> {
+    save["data_checksum"] = Value::Null;
+    Ok(())
+}
+
+/// Version 3 stores the state of the game, which older saves do not have.
+fn add_state(save: &mut Value) -> Result<(), SaveError> {
+    save["state"] = Value::Null;
+    Ok(())
+}
+
+/// Runs every migration in `migrations` that `save` did not have yet, and sets its version to the
+/// latest one.
+fn migrate(mut save: Value, migrations: &[Migration]) -> Result<Value, SaveError> {
+    let latest = migrations.len() as u32 + 1;
+    let version = save
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or(SaveError::MissingVersion)?;
+    let version = u32::try_from(version).map_err(|_| SaveError::UnsupportedVersion(u32::MAX))?;
+
+    match version {
+        0 => return Err(SaveError::MissingVersion),
+        v if v > latest => return Err(SaveError::UnsupportedVersion(v)),
+        _ => {}
+    }
+
+    for migration in &migrations[version as usize - 1..] {
+        migration(&mut save)?;
+    }
+    save["version"] = latest.into();
+
+    Ok(save)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+    use serde_json::json;
+
+    #[test]
+    fn saves_round_trip() {
+        let names = (0..4).map(|i| format!("player {i}")).collect::<Vec<_>>();
+        let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        let mut notation = Notation::new(names);
+        let mut state = assert_ok!(notation.replay(data.clone()));
+        assert_ok!(notation.apply(&mut state, PlayerId(0), PlayerAction::StartGame));
+
+        let save = SavedGame::new(&notation, &state, data.checksum());
+        let loaded = assert_ok!(SavedGame::from_json(&assert_ok!(save.to_json())));
+        assert_eq!(loaded, save);
+        assert_eq!(assert_ok!(loaded.notation()), notation);
+        assert_ok!(assert_ok!(loaded.load(data.clone())).selecting_characters());
+        assert_eq!(assert_ok!(loaded.restore(data.clone())), state);
+
+        let mut other = data.clone();
+        other.assets.deck.pop();
+        assert_matches!(loaded.load(other.clone()), Err(SaveError::DataMismatch));
+        assert_matches!(loaded.restore(other), Err(SaveError::DataMismatch));
+
+        // The first version did not store which cards were used, so it loads with any cards
+        let old = json!({ "version": 1, "notation": save.notation }).to_string();
+        let old = assert_ok!(SavedGame::from_json(&old));
+        assert_eq!(old.data_checksum, None);
+        assert_eq!(old.state, None);
+        assert_ok!(assert_ok!(old.restore(data)).selecting_characters());
+    }
+
+    #[test]
+    fn old_saves_are_migrated() {
+        fn rename(save: &mut Value) -> Result<(), SaveError> {
+            save["notation"] = save["moves"].take();
+            Ok(())
+        }
+        let migrations: &[Migration] = &[rename];
+
+        let old = json!({ "version": 1, "moves": "1. P0 start" });
+        let new = assert_ok!(migrate(old, migrations));
+        assert_eq!(
+            new,
+            json!({ "version": 2, "moves": null, "notation": "1. P0 start" })
+        );
+
+        // Saves of the latest version are left alone
+        assert_eq!(assert_ok!(migrate(new.clone(), migrations)), new);
+
+        assert_matches!(
+            migrate(json!({ "version": 3 }), migrations),
+            Err(SaveError::UnsupportedVersion(3))
+        );
+        assert_matches!(
+            migrate(json!({ "notation": "" }), migrations),
+            Err(SaveError::MissingVersion)
+        );
+        assert_matches!(
+            SavedGame::from_json(r#"{ "version": 1 }"#),
+            Err(SaveError::Serde(_))
+        );
+    }
+}
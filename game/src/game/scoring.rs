@@ -0,0 +1,234 @@
+//! File containing [`ScoringStrategy`], the formula that turns the balance sheet of a
+//! [`ResultsPlayer`] into their final score. The formula a game uses is chosen with
+//! [`GameConfig::scoring`].
+//!
+//! Besides the `f64` score used to show a breakdown, every strategy gives a [`FixedScore`]. Fixed
+//! scores are computed with integers and rounded in a defined way, so players are ranked the same
+//! on every platform.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use std::fmt;
+
+use crate::player::*;
+
+/// A formula that scores a player at the end of the game.
+pub trait ScoringStrategy: fmt::Debug + Send + Sync {
+    /// Gets the final score of `player`.
+    fn score(&self, player: &ResultsPlayer) -> f64;
+
+    /// Gets the final score of `player` as a [`FixedScore`], which is what players are ranked by.
+    /// By default this rounds [`ScoringStrategy::score`], strategies that can should compute it
+    /// without floats instead.
+    fn fixed_score(&self, player: &ResultsPlayer) -> FixedScore {
+        FixedScore::from_f64(self.score(player))
+    }
+}
+
+/// A score in hundredths of a point. Scores that are not a whole number of hundredths are rounded
+/// to the nearest one, and halves are rounded away from zero.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(
+    Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct FixedScore(pub i32);
+
+impl FixedScore {
+    /// The amount of hundredths in a point.
+    pub const SCALE: i32 = 100;
+
+    /// Rounds `score` to hundredths. Scores that do not fit are clamped, and `NaN` is zero.
+    pub fn from_f64(score: f64) -> Self {
+        // `as` saturates, and rounds NaN to zero
+        Self((score * Self::SCALE as f64).round() as i32)
+    }
+
+    /// Gets the score `numerator / denominator` in points, rounded to hundredths. Panics if
+    /// `denominator` is zero.
+    pub fn from_ratio(numerator: i128, denominator: i128) -> Self {
+        let (numerator, denominator) = match denominator < 0 {
+            true => (-numerator, -denominator),
+            false => (numerator, denominator),
+        };
+        let scaled = numerator * Self::SCALE as i128;
+        // Round the magnitude half up, which rounds halves away from zero
+        let rounded = scaled.signum() * ((2 * scaled.abs() + denominator) / (2 * denominator));
+
+        Self(rounded.clamp(i32::MIN as i128, i32::MAX as i128) as i32)
+    }
+
+    /// Gets this score in points.
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64 / Self::SCALE as f64
+    }
+}
+
+impl fmt::Display for FixedScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let hundredths = self.0.unsigned_abs();
+        write!(f, "{sign}{}.{:02}", hundredths / 100, hundredths % 100)
+    }
+}
+
+/// The scoring strategies a game can be configured with.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scoring {
+    /// The formula of the board game, see [`WaccScoring`].
+    #[default]
+    Wacc,
+    /// A formula without a cost of capital for younger players, see [`SimpleScoring`].
+    Simple,
+}
+
+impl Scoring {
+    /// Gets the strategy that scores players with this formula.
+    pub fn strategy(self) -> &'static dyn ScoringStrategy {
+        match self {
+            Self::Wacc => &WaccScoring,
+            Self::Simple => &SimpleScoring,
+        }
+    }
+}
+
+/// The formula of the board game. The free cash flow of a player is divided by their weighted
+/// average cost of capital, which grows with the risk free rate, the market risk premium and the
+/// debt of the player. Half of the debt and all cash and bonuses are added on top.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct WaccScoring;
+
+impl ScoringStrategy for WaccScoring {
+    fn score(&self, player: &ResultsPlayer) -> f64 {
+        let cash = player.cash().get() as f64;
+        let gold = player.total_gold() as f64;
+        let silver = player.total_silver() as f64;
+
+        let trade_credit = player.trade_credit() as f64;
+        let bank_loan = player.bank_loan() as f64;
+        let bonds = player.bonds() as f64;
+        let debt = trade_credit + bank_loan + bonds;
+
+        let bonuses = bonuses(player);
+
+        let rfr = player.market().rfr as f64;
+        let mrp = player.market().mrp as f64;
+
+        let beta = silver / gold;
+
+        let drp = (trade_credit + bank_loan * 2.0 + bonds * 3.0) / (gold + cash);
+
+        let wacc = rfr + drp + beta * mrp;
+
+        // beta == inf || fcf / wacc == inf
+        if gold == 0.0 || wacc == 0.0 {
+            // lim_wacc->inf fcf / wacc = 0 || fcf / 0 = inf
+            (debt / 3.0) + cash + bonuses
+        } else {
+            let fcf = player.fcf();
+
+            (fcf / (0.1 * wacc)) + (debt / 3.0) + cash + bonuses
+        }
+    }
+
+    fn fixed_score(&self, player: &ResultsPlayer) -> FixedScore {
+        let cash = player.cash().get() as i128;
+        let gold = player.total_gold() as i128;
+        let silver = player.total_silver() as i128;
+
+        let trade_credit = player.trade_credit() as i128;
+        let bank_loan = player.bank_loan() as i128;
+        let bonds = player.bonds() as i128;
+        let debt = trade_credit + bank_loan + bonds;
+        let weighted_debt = trade_credit + bank_loan * 2 + bonds * 3;
+
+        let bonuses = (player.six_assets_bonus() + player.all_five_colors_bonus()) as i128;
+
+        let rfr = player.market().rfr as i128;
+        let mrp = player.market().mrp as i128;
+
+        // wacc = rfr + weighted_debt / (gold + cash) + (silver / gold) * mrp, which is
+        // wacc_numerator / (gold * (gold + cash))
+        let wacc_numerator =
+            rfr * gold * (gold + cash) + weighted_debt * gold + silver * mrp * (gold + cash);
+
+        if gold == 0 || wacc_numerator == 0 {
+            FixedScore::from_ratio(debt + 3 * (cash + bonuses), 3)
+        } else {
+            // fcf / (0.1 * wacc) = 10 * fcf * gold * (gold + cash) / wacc_numerator
+            let fcf = fcf(player);
+            let numerator =
+                30 * fcf * gold * (gold + cash) + (debt + 3 * (cash + bonuses)) * wacc_numerator;
+
+            FixedScore::from_ratio(numerator, 3 * wacc_numerator)
+        }
+    }
+}
+
+/// A formula for younger players: the cash of a player plus the market value of their assets, minus
+/// their debt, with the same bonuses as the board game.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SimpleScoring;
+
+impl ScoringStrategy for SimpleScoring {
+    fn score(&self, player: &ResultsPlayer) -> f64 {
+        let debt = player.trade_credit() + player.bank_loan() + player.bonds();
+
+        player.cash().get() as f64 + player.fcf() - debt as f64 + bonuses(player)
+    }
+
+    fn fixed_score(&self, player: &ResultsPlayer) -> FixedScore {
+        let debt = player.trade_credit() + player.bank_loan() + player.bonds();
+        let bonuses = player.six_assets_bonus() + player.all_five_colors_bonus();
+
+        FixedScore::from_ratio(
+            player.cash().get() as i128 + fcf(player) - debt as i128 + bonuses as i128,
+            1,
+        )
+    }
+}
+
+fn bonuses(player: &ResultsPlayer) -> f64 {
+    (player.six_assets_bonus() + player.all_five_colors_bonus()) as f64
+}
+
+/// Gets the same value as [`ResultsPlayer::fcf`] without floats.
+fn fcf(player: &ResultsPlayer) -> i128 {
+    player
+        .assets()
+        .iter()
+        .map(|asset| {
+            let gold = asset.gold_value as i128;
+            let silver = asset.silver_value as i128;
+            let mul = player.market().color_condition(asset.color).multiplier() as i128;
+            gold + silver * mul
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_scores_round_half_away_from_zero() {
+        assert_eq!(FixedScore::from_ratio(1, 3), FixedScore(33));
+        assert_eq!(FixedScore::from_ratio(2, 3), FixedScore(67));
+        assert_eq!(FixedScore::from_ratio(1, 200), FixedScore(1));
+        assert_eq!(FixedScore::from_ratio(-1, 200), FixedScore(-1));
+        assert_eq!(FixedScore::from_ratio(1, -200), FixedScore(-1));
+        assert_eq!(FixedScore::from_ratio(-2, 3), FixedScore(-67));
+        assert_eq!(FixedScore::from_ratio(7, 1), FixedScore(700));
+
+        assert_eq!(FixedScore::from_f64(0.125), FixedScore(13));
+        assert_eq!(FixedScore::from_f64(f64::NAN), FixedScore(0));
+        assert_eq!(FixedScore::from_f64(f64::INFINITY), FixedScore(i32::MAX));
+
+        assert_eq!(FixedScore(1234).to_string(), "12.34");
+        assert_eq!(FixedScore(-5).to_string(), "-0.05");
+    }
+}
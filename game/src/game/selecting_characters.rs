@@ -1,22 +1,34 @@
 //! File containing the selecting characters state of the game.
 
+use std::time::Duration;
+
 use either::Either;
 
 use crate::{errors::*, game::*, player::*};
 
 /// State containing all information related to the selecting characters state of the game. In the
 /// selecting characters stage, players select a character one by one until everyone has selected
-/// a character, after which a round starts.
-#[derive(Debug, Clone, PartialEq)]
+/// a character, after which a round starts. If the game is played with [`CharacterBans`], players
+/// first ban characters one by one before anyone can select one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SelectingCharacters {
     pub(super) players: Players<SelectingCharactersPlayer>,
     pub(super) characters: ObtainingCharacters,
+    pub(super) banned_characters: Vec<Character>,
     pub(super) assets: Deck<Asset>,
     pub(super) liabilities: Deck<Liability>,
     pub(super) markets: Deck<Either<Market, Event>>,
     pub(super) chairman: PlayerId,
     pub(super) current_market: Market,
     pub(super) current_events: Vec<Event>,
+    pub(super) config: GameConfig,
+    pub(super) data_checksum: u64,
+    pub(super) clock: Option<ChessClock>,
+    pub(super) host: Option<PlayerId>,
+    pub(super) round_number: u8,
+    pub(super) character_stats: CharacterStatsMap,
+    #[serde(default)]
+    pub(super) spectators: Spectators,
 }
 
 impl SelectingCharacters {
@@ -47,9 +59,35 @@ impl SelectingCharacters {
         self.chairman
     }
 
-    /// Gets the id of the player that's currently selecting a character
+    /// Gets the id of the player that's currently selecting a character, or banning one while
+    /// characters are being banned.
     pub fn currently_selecting_id(&self) -> PlayerId {
-        (self.characters.applies_to_player() as u8).into()
+        self.currently_banning_id()
+            .unwrap_or_else(|| (self.characters.applies_to_player() as u8).into())
+    }
+
+    /// Gets the id of the player that's currently banning a character, if characters are still
+    /// being banned this round. Players ban in turn order, starting with the chairman.
+    pub fn currently_banning_id(&self) -> Option<PlayerId> {
+        let bans = self.config.character_bans.bans(self.players.len());
+        (self.banned_characters.len() < bans).then(|| {
+            let idx =
+                (usize::from(self.chairman) + self.banned_characters.len()) % self.players.len();
+            (idx as u8).into()
+        })
+    }
+
+    /// Gets the characters that were banned this round so far.
+    pub fn banned_characters(&self) -> &[Character] {
+        &self.banned_characters
+    }
+
+    /// Checks that no more characters are being banned, so characters can be selected.
+    fn check_not_banning(&self) -> Result<(), SelectingCharactersError> {
+        match self.currently_banning_id() {
+            Some(_) => Err(SelectingCharactersError::BanningCharacters),
+            None => Ok(()),
+        }
     }
 
     /// Internally used function that checks whether a player with such an `id` exists, and whether
@@ -71,6 +109,7 @@ impl SelectingCharacters {
         id: PlayerId,
     ) -> Result<Vec<Character>, GameError> {
         let _ = self.player_as_current(id)?;
+        self.check_not_banning()?;
 
         self.characters
             .peek()
@@ -81,6 +120,7 @@ impl SelectingCharacters {
     /// Gets the closed character for the player with `id` if they're chairman.
     pub fn player_get_closed_character(&self, id: PlayerId) -> Result<Character, GameError> {
         let _ = self.player_as_current(id)?;
+        self.check_not_banning()?;
 
         match self.characters.peek()?.closed_character {
             Some(closed_character) => Ok(closed_character),
@@ -88,15 +128,66 @@ impl SelectingCharacters {
         }
     }
 
+    /// Gets a list of characters the player with `id` can ban, if it's their turn to ban a
+    /// character next. The CEO cannot be banned, since they become the next chairman.
+    pub fn player_get_bannable_characters(
+        &self,
+        id: PlayerId,
+    ) -> Result<Vec<Character>, GameError> {
+        let _ = self.player_as_current(id)?;
+        if self.currently_banning_id().is_none() {
+            return Err(SelectingCharactersError::NotBanningCharacters.into());
+        }
+
+        Ok(Character::CHARACTERS
+            .into_iter()
+            .filter(|c| *c != Character::CEO && !self.banned_characters.contains(c))
+            .collect())
+    }
+
+    /// Allows player with `id` to ban `character` from this round, if it is their turn to ban one.
+    /// Once the last character is banned, the characters are dealt again without the banned ones.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err)
+    )]
+    pub fn player_ban_character(
+        &mut self,
+        id: PlayerId,
+        character: Character,
+    ) -> Result<(), GameError> {
+        if !self
+            .player_get_bannable_characters(id)?
+            .contains(&character)
+        {
+            return Err(SelectingCharactersError::UnbannableCharacter.into());
+        }
+        self.banned_characters.push(character);
+
+        if self.currently_banning_id().is_none() {
+            self.characters = ObtainingCharacters::with_bans(
+                self.players.len(),
+                self.chairman,
+                &self.banned_characters,
+            )?;
+        }
+        Ok(())
+    }
+
     /// Allows player with `id` to select `character`, if it is their turn and if that character is
     /// available to select. If they are the last player to select a character, a new [`GameState`]
     /// is returned of type [`Round`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err)
+    )]
     pub(super) fn player_select_character(
         &mut self,
         id: PlayerId,
         character: Character,
     ) -> Result<Option<GameState>, GameError> {
         let currently_selecting_id = self.currently_selecting_id();
+        self.check_not_banning()?;
 
         match self.players.player_mut(id) {
             Ok(p) if p.id() == currently_selecting_id => {
@@ -127,7 +218,10 @@ impl SelectingCharacters {
                     let players = players
                         .0
                         .into_iter()
-                        .map(TryInto::try_into)
+                        .map(|p| {
+                            RoundPlayer::try_from(p)
+                                .map(|p| p.with_draw_rules(&self.config.draw_rules))
+                        })
                         .collect::<Result<_, _>>()?;
 
                     let players = Players(players);
@@ -145,9 +239,20 @@ impl SelectingCharacters {
                         fired_characters,
                         banker_target,
                         is_final_round: false,
+                        config: self.config,
+                        data_checksum: self.data_checksum,
+                        clock: self.clock.take(),
+                        host: self.host,
+                        pending_decision: None,
+                        debt_payments: vec![],
+                        round_number: self.round_number,
+                        character_stats: std::mem::take(&mut self.character_stats),
+                        spectators: std::mem::take(&mut self.spectators),
+                        turn_time_spent: Duration::ZERO,
+                        skip_reasons: vec![],
                     };
 
-                    round.players.player_mut(current_player)?.start_turn();
+                    round.start_turn(current_player)?;
 
                     Ok(Some(GameState::Round(round)))
                 } else {
@@ -160,8 +265,12 @@ impl SelectingCharacters {
     }
 
     /// Gets the list of open characters, which are the characters nobody can select this round.
+    /// Characters are only dealt once the last character is banned, so until then this is empty.
     pub fn open_characters(&self) -> &[Character] {
-        self.characters.open_characters()
+        match self.currently_banning_id() {
+            Some(_) => &[],
+            None => self.characters.open_characters(),
+        }
     }
 
     /// Gets a list of player ids that represent the order each player's turn is in. The chairman
@@ -173,18 +282,59 @@ impl SelectingCharacters {
         (start..limit).chain(0..start).map(Into::into).collect()
     }
 
+    /// Gets the options the game is played with.
+    pub fn config(&self) -> GameConfig {
+        self.config
+    }
+
+    /// Gets the checksum of the card data the game is played with, see
+    /// [`GameData::checksum`](crate::cards::GameData::checksum).
+    pub fn data_checksum(&self) -> u64 {
+        self.data_checksum
+    }
+
+    /// Gets the time each player has left, if the game is played with a time bank.
+    pub fn clock(&self) -> Option<&ChessClock> {
+        self.clock.as_ref()
+    }
+
+    /// Gets the id of the player that hosted the lobby, see [`Lobby::host`].
+    pub fn host(&self) -> Option<PlayerId> {
+        self.host
+    }
+
+    /// Gets which round of the game this is, counting from 1.
+    pub fn round_number(&self) -> u8 {
+        self.round_number
+    }
+
     /// Get the current market
     pub fn current_market(&self) -> &Market {
         &self.current_market
     }
 
+    /// Get the events that happened so far this game, oldest first
+    pub fn current_events(&self) -> &[Event] {
+        &self.current_events
+    }
+
     /// Gets the [`PlayerInfo`] for each player, excluding the player that has the same id as `id`.
     pub fn player_info(&self, id: PlayerId) -> Vec<PlayerInfo> {
+        self.spectator_info()
+            .into_iter()
+            .filter(|p| p.id != id)
+            .collect()
+    }
+
+    /// Gets the [`PlayerInfo`] for each player, as a spectator sees it.
+    pub fn spectator_info(&self) -> Vec<PlayerInfo> {
         self.players()
             .iter()
-            .filter(|p| p.id() != id)
             .map(|p| {
-                let mut info: PlayerInfo = p.into();
+                let mut info = PlayerInfo::from(p)
+                    .with_market(self.current_market())
+                    .with_liability_cap(&self.config)
+                    .redact(&self.config);
                 // Filter out the characters of players that have not had their turn yet
                 info.character = None;
                 info
@@ -193,6 +343,10 @@ impl SelectingCharacters {
     }
 
     /// Sets a player as disconnected
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), ret, err)
+    )]
     pub fn leave(&mut self, id: PlayerId) -> Result<(), GameError> {
         match self.players.player_mut(id) {
             Ok(player) => {
@@ -202,6 +356,21 @@ impl SelectingCharacters {
             Err(e) => Err(e),
         }
     }
+
+    /// Gets the character picked on behalf of the player that is currently selecting, if they
+    /// left the game. Nobody is waiting on an absent player, so whoever drives the game should
+    /// select this character for them to keep the selection going. The pick is the one
+    /// [`GreedyStrategy`](crate::bot::GreedyStrategy) would make.
+    pub fn absent_pick(&self) -> Option<(PlayerId, Character)> {
+        let id = self.currently_selecting_id();
+        if self.player(id).ok()?.is_human() {
+            return None;
+        }
+
+        let character = crate::bot::GreedyStrategy::pick_character(self, id)?;
+        Some((id, character))
+    }
+
     /// Allows a player to rejoin
     pub fn rejoin(&mut self, id: PlayerId) -> Result<&SelectingCharactersPlayer, GameError> {
         let player = self.players.player_mut(id)?;
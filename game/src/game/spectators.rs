@@ -0,0 +1,135 @@
+//! File containing the spectators of a game, who watch it without playing in it.
+
+use crate::{errors::*, game::*, player::*};
+
+/// The usernames of everyone watching a game without playing in it. Spectators can be attached in
+/// every state of the game, and stay attached when the game moves on to the next state. They are
+/// only ever shown what is public, see [`GameState::spectator_info`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spectators(Vec<String>);
+
+impl Spectators {
+    /// Gets the usernames of every spectator, in the order they started watching.
+    pub fn usernames(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Gets the amount of spectators.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks whether nobody is watching.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks whether `username` collides with the username of a spectator, see
+    /// [`usernames_collide`].
+    pub fn contains(&self, username: &str) -> bool {
+        self.0.iter().any(|s| usernames_collide(s, username))
+    }
+
+    /// Adds `username` as a spectator, unless it collides with one of `taken` or another
+    /// spectator. Returns the normalized username.
+    pub(super) fn join<'a>(
+        &mut self,
+        username: &str,
+        mut taken: impl Iterator<Item = &'a str>,
+        filter: Option<&UsernameFilter>,
+    ) -> Result<&str, LobbyError> {
+        let username = validate_username(username, filter)?;
+        if self.contains(&username) || taken.any(|name| usernames_collide(name, &username)) {
+            return Err(LobbyError::UsernameAlreadyTaken(username));
+        }
+
+        self.0.push(username);
+        // PANIC: a username was just pushed, so there is a last one
+        Ok(self.0.last().unwrap())
+    }
+
+    /// Removes the spectator with exactly `username`. Returns whether they were watching.
+    pub(super) fn leave(&mut self, username: &str) -> bool {
+        let len = self.0.len();
+        self.0.retain(|s| s != username);
+        self.0.len() != len
+    }
+}
+
+impl GameState {
+    /// Gets everyone watching the game without playing in it.
+    pub fn spectators(&self) -> &Spectators {
+        match self {
+            Self::Lobby(lobby) => lobby.spectators(),
+            Self::SelectingCharacters(s) => &s.spectators,
+            Self::Round(round) => &round.spectators,
+            Self::BankerTarget(round) => &round.spectators,
+            Self::Results(results) => &results.spectators,
+        }
+    }
+
+    /// Lets `username` watch the game, in whatever state it is in. Spectators follow the same
+    /// username rules as players in the lobby, and cannot take the username of a player or of
+    /// another spectator. Returns the normalized username.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::{errors::GameError, game::GameState};
+    /// # fn main() -> Result<(), GameError> {
+    /// let mut game = GameState::new();
+    /// game.lobby_mut()?.join("player 1".to_owned())?;
+    ///
+    /// assert_eq!(game.spectate(" viewer ".to_owned())?, "viewer");
+    /// assert!(game.spectate("Player 1".to_owned()).is_err());
+    /// assert_eq!(game.lobby()?.usernames(), vec!["player 1"]);
+    /// assert_eq!(game.spectators().usernames(), ["viewer"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spectate(&mut self, username: String) -> Result<&str, LobbyError> {
+        match self {
+            Self::Lobby(lobby) => lobby.spectate(username),
+            Self::SelectingCharacters(s) => {
+                let names = s.players.iter().map(|p| p.name());
+                s.spectators.join(&username, names, None)
+            }
+            Self::Round(round) => {
+                let names = round.players.iter().map(|p| p.name());
+                round.spectators.join(&username, names, None)
+            }
+            Self::BankerTarget(round) => {
+                let names = round.players.iter().map(|p| p.name());
+                round.spectators.join(&username, names, None)
+            }
+            Self::Results(results) => {
+                let names = results.players.iter().map(|p| p.name());
+                results.spectators.join(&username, names, None)
+            }
+        }
+    }
+
+    /// Stops `username` from watching the game. Returns whether they were watching.
+    pub fn stop_spectating(&mut self, username: &str) -> bool {
+        match self {
+            Self::Lobby(lobby) => lobby.stop_spectating(username),
+            Self::SelectingCharacters(s) => s.spectators.leave(username),
+            Self::Round(round) => round.spectators.leave(username),
+            Self::BankerTarget(round) => round.spectators.leave(username),
+            Self::Results(results) => results.spectators.leave(username),
+        }
+    }
+
+    /// Gets the [`PlayerInfo`] of every player as a spectator sees it. Spectators are shown
+    /// exactly what players are shown of each other: no hands, no exact cash when cash is hidden,
+    /// and no characters that are still closed.
+    pub fn spectator_info(&self) -> Vec<PlayerInfo> {
+        match self {
+            Self::Lobby(lobby) => lobby.spectator_info(),
+            Self::SelectingCharacters(s) => s.spectator_info(),
+            Self::Round(round) => round.spectator_info(),
+            Self::BankerTarget(round) => round.spectator_info(),
+            Self::Results(results) => results.spectator_info(),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! File containing [`CharacterStats`], which add up how each character was played over the course
+//! of a game. The stats are kept up to date while rounds are played, and end up in the
+//! [`Results`](crate::game::Results) for balance analysis.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use std::collections::BTreeMap;
+
+use crate::player::*;
+
+/// How a single character was played, added up over every turn anyone played it.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharacterStats {
+    /// The number of turns played as the character.
+    pub turns: u32,
+    /// The cash earned as the character from turn cash and bonus cash.
+    pub income: u32,
+}
+
+/// The [`CharacterStats`] of every character that was played so far. Characters that nobody
+/// played are left out.
+pub type CharacterStatsMap = BTreeMap<Character, CharacterStats>;
+
+/// Records that a turn as `character` started, in which `income` was earned right away.
+pub(super) fn record_turn(stats: &mut CharacterStatsMap, character: Character, income: u8) {
+    let stats = stats.entry(character).or_default();
+    stats.turns += 1;
+    stats.income += u32::from(income);
+}
+
+/// Records that `income` was earned as `character` during their turn.
+pub(super) fn record_income(stats: &mut CharacterStatsMap, character: Character, income: u8) {
+    stats.entry(character).or_default().income += u32::from(income);
+}
@@ -0,0 +1,125 @@
+//! File containing the rules a username has to follow to join a [`Lobby`]. Usernames are
+//! normalized before they are checked, so names that look the same are treated the same.
+
+use unicode_normalization::UnicodeNormalization;
+
+use std::{fmt, sync::Arc};
+
+use crate::errors::LobbyError;
+
+/// The most characters a username can have after it is normalized.
+pub const MAX_USERNAME_LENGTH: usize = 20;
+
+/// A check that rejects usernames which are not allowed, for example because they contain
+/// profanity. The check is given the normalized username and returns `true` if it is allowed.
+#[derive(Clone)]
+pub struct UsernameFilter(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl UsernameFilter {
+    /// Creates a filter that allows the usernames for which `allowed` returns `true`.
+    pub fn new(allowed: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(allowed))
+    }
+
+    /// Checks whether `username` is allowed by this filter.
+    pub fn allows(&self, username: &str) -> bool {
+        (self.0)(username)
+    }
+}
+
+impl fmt::Debug for UsernameFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UsernameFilter").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for UsernameFilter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Trims `username` and puts it in Unicode normalization form C, which is how usernames are
+/// stored once a player joined.
+///
+/// # Examples
+///
+/// ```
+/// # use game::game::normalize_username;
+/// assert_eq!(normalize_username("  player 1 "), "player 1");
+/// assert_eq!(normalize_username("Zoe\u{0301}"), "Zo\u{00e9}");
+/// ```
+pub fn normalize_username(username: &str) -> String {
+    username.trim().nfc().collect()
+}
+
+/// Checks whether two normalized usernames would be mistaken for each other, which is the case if
+/// they only differ in case.
+pub fn usernames_collide(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// Normalizes `username` and checks that it is not empty, not too long, only has letters, numbers,
+/// spaces and `_-.` in it, and is allowed by `filter`. Returns the normalized username.
+pub fn validate_username(
+    username: &str,
+    filter: Option<&UsernameFilter>,
+) -> Result<String, LobbyError> {
+    let username = normalize_username(username);
+
+    if username.is_empty() {
+        return Err(LobbyError::EmptyUsername);
+    }
+    if username.chars().count() > MAX_USERNAME_LENGTH {
+        return Err(LobbyError::UsernameTooLong(MAX_USERNAME_LENGTH));
+    }
+    if let Some(c) = username
+        .chars()
+        .find(|&c| !(c.is_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.')))
+    {
+        return Err(LobbyError::UsernameInvalidCharacter(c));
+    }
+    if filter.is_some_and(|filter| !filter.allows(&username)) {
+        return Err(LobbyError::UsernameNotAllowed);
+    }
+
+    Ok(username)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    #[test]
+    fn usernames_are_validated() {
+        assert_eq!(assert_ok!(validate_username(" Ann ", None)), "Ann");
+        assert_eq!(
+            validate_username("   ", None),
+            Err(LobbyError::EmptyUsername)
+        );
+        assert_ok!(validate_username(&"a".repeat(MAX_USERNAME_LENGTH), None));
+        assert_eq!(
+            validate_username(&"a".repeat(MAX_USERNAME_LENGTH + 1), None),
+            Err(LobbyError::UsernameTooLong(MAX_USERNAME_LENGTH))
+        );
+        assert_eq!(
+            validate_username("a\u{200b}b", None),
+            Err(LobbyError::UsernameInvalidCharacter('\u{200b}'))
+        );
+        assert_eq!(
+            validate_username("<b>", None),
+            Err(LobbyError::UsernameInvalidCharacter('<'))
+        );
+
+        let filter = UsernameFilter::new(|name| !name.to_lowercase().contains("darn"));
+        assert_ok!(validate_username("Dan", Some(&filter)));
+        assert_eq!(
+            validate_username("DarnIt", Some(&filter)),
+            Err(LobbyError::UsernameNotAllowed)
+        );
+
+        assert!(usernames_collide("Ann", "aNN"));
+        assert!(!usernames_collide("Ann", "Anna"));
+    }
+}
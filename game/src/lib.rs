@@ -3,6 +3,7 @@
 
 #![warn(missing_docs)]
 
+pub mod bot;
 pub mod cards;
 pub mod errors;
 pub mod game;
@@ -3,21 +3,23 @@
 use crate::{errors::*, game::*, player::*};
 
 use either::Either;
-use std::collections::{HashMap, hash_map::Entry};
+use std::collections::{BTreeMap, btree_map::Entry};
 
 /// The player type that corresponds to the [`BankerTargetRound`](crate::game::BankerTargetRound)
 /// stage of the game.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BankerTargetPlayer {
     pub(super) id: PlayerId,
     pub(super) name: String,
-    pub(super) cash: u8,
+    pub(super) cash: Cash,
     pub(super) assets: Vec<Asset>,
     pub(super) liabilities: Vec<Liability>,
     pub(super) character: Character,
     pub(super) hand: Vec<Either<Asset, Liability>>,
+    pub(super) draw_rule: DrawRule,
     pub(super) liabilities_to_play: u8,
     pub(super) was_first_to_six_assets: bool,
+    pub(super) banker_debts: Vec<BankerDebt>,
     pub(super) is_human: bool,
 }
 
@@ -32,11 +34,36 @@ impl BankerTargetPlayer {
         &self.name
     }
 
+    /// Gets the amount of cash of the player
+    pub fn cash(&self) -> Cash {
+        self.cash
+    }
+
+    /// Gets a list of bought assets of the player
+    pub fn assets(&self) -> &[Asset] {
+        &self.assets
+    }
+
+    /// Gets a list of issued liabilities of the player
+    pub fn liabilities(&self) -> &[Liability] {
+        &self.liabilities
+    }
+
     /// Gets the character for this player
     pub fn character(&self) -> Character {
         self.character
     }
 
+    /// Gets the human state of this player
+    pub fn is_human(&self) -> bool {
+        self.is_human
+    }
+
+    /// Sets the human state of this player
+    pub fn set_is_human(&mut self, human: bool) {
+        self.is_human = human;
+    }
+
     /// Gets an asset at a particular index from this player.
     pub fn asset(&self, asset_idx: usize) -> Result<&Asset, GameError> {
         self.assets
@@ -49,15 +76,35 @@ impl BankerTargetPlayer {
         &self.hand
     }
 
+    /// Gets what this player still owes to bankers.
+    pub fn banker_debts(&self) -> &[BankerDebt] {
+        &self.banker_debts
+    }
+
+    /// Adds `amount` to what this player owes the banker with `banker_id`.
+    fn add_banker_debt(&mut self, banker_id: PlayerId, amount: u8) {
+        match self
+            .banker_debts
+            .iter_mut()
+            .find(|d| d.banker_id == banker_id)
+        {
+            Some(debt) => debt.amount = debt.amount.saturating_add(amount),
+            None => self.banker_debts.push(BankerDebt { banker_id, amount }),
+        }
+    }
+
     /// Pays the banker in the round with everything the player owns that are worth anything. This
     /// means that this function ignores assets that are worth zero or negative cash in the current
-    /// market.
+    /// market. Whatever is still owed after that becomes a [`BankerDebt`]. The cards used to pay
+    /// are discarded to `asset_deck` and `liability_deck`.
     pub fn go_bankrupt_for_banker(
         &mut self,
         cash: u8,
         banker: &mut BankerTargetPlayer,
         market: Market,
-    ) -> Result<PayBankerPlayer, PayBankerError> {
+        asset_deck: &mut Deck<Asset>,
+        liability_deck: &mut Deck<Liability>,
+    ) -> Result<PayBankerPlayer, GameError> {
         let mut new_selected_cards: SelectedAssetsAndLiabilities = SelectedAssetsAndLiabilities {
             sold_assets: vec![],
             issued_liabilities: vec![],
@@ -110,16 +157,24 @@ impl BankerTargetPlayer {
         }
 
         // Sell assets and libilities for targeted player
-        let extra_asset_cash: u8 = new_selected_cards
-            .sold_assets
-            .iter()
-            .map(|s| s.market_value)
-            .sum();
-        let extra_liability_cash: u8 = new_selected_cards
-            .issued_liabilities
-            .iter()
-            .map(|l| l.liability.value)
-            .sum();
+        let extra_asset_cash = Cash::checked_sum(
+            new_selected_cards
+                .sold_assets
+                .iter()
+                .map(|s| s.market_value),
+        )?;
+        let extra_liability_cash = Cash::checked_sum(
+            new_selected_cards
+                .issued_liabilities
+                .iter()
+                .map(|l| l.liability.value),
+        )?;
+        let total_available_cash = self
+            .cash
+            .checked_add(extra_asset_cash)?
+            .checked_add(extra_liability_cash)?;
+        let new_banker_cash = banker.cash.checked_add(total_available_cash)?;
+
         let mut asset_ids: Vec<usize> = new_selected_cards
             .sold_assets
             .iter()
@@ -127,7 +182,7 @@ impl BankerTargetPlayer {
             .collect();
         asset_ids.sort();
         for id in asset_ids.iter().rev() {
-            self.assets.remove(*id);
+            asset_deck.discard(self.assets.remove(*id));
         }
 
         let mut liability_ids: Vec<usize> = new_selected_cards
@@ -137,46 +192,58 @@ impl BankerTargetPlayer {
             .collect();
         liability_ids.sort();
         for id in liability_ids.iter().rev() {
-            self.hand.remove(*id);
+            self.hand
+                .remove(*id)
+                .either(|a| asset_deck.discard(a), |l| liability_deck.discard(l));
             self.liabilities_to_play -= 1;
         }
-        let total_available_cash = extra_asset_cash + extra_liability_cash + self.cash;
         if total_available_cash < cash {
-            //TODO Pay banker the maximum amount target can affort after selling
-            banker.cash += total_available_cash;
-            self.cash = 0;
+            // The total is less than what is owed, which is at most `u8::MAX`
+            let paid_amount = total_available_cash.get() as u8;
+            banker.cash = new_banker_cash;
+            self.cash = Cash::ZERO;
+            let remaining_debt = cash - paid_amount;
+            self.add_banker_debt(banker.id, remaining_debt);
 
             Ok(PayBankerPlayer {
-                paid_amount: total_available_cash,
+                paid_amount,
                 new_banker_cash: banker.cash,
                 new_target_cash: self.cash,
                 target_id: self.id,
                 banker_id: banker.id,
                 selected_cards: new_selected_cards.clone(),
+                remaining_debt,
             })
         } else {
             Err(PayBankerError::NotRightCashAmount {
                 expected: total_available_cash,
-                got: cash,
-            })
+                got: cash.into(),
+            }
+            .into())
         }
     }
 
-    /// Pays the banker in the round the requested amount of gold
+    /// Pays the banker in the round the requested amount of gold. The cards used to pay are
+    /// discarded to `asset_deck` and `liability_deck`.
     pub fn pay_banker(
         &mut self,
         cash: u8,
-        selected_assets: &HashMap<usize, u8>,
-        selected_liabilities: &HashMap<usize, u8>,
+        selected_assets: &BTreeMap<usize, u8>,
+        selected_liabilities: &BTreeMap<usize, u8>,
         banker: &mut BankerTargetPlayer,
-    ) -> Result<PayBankerPlayer, PayBankerError> {
-        let extra_asset_cash = selected_assets.values().sum::<u8>();
-        let extra_liability_cash = selected_liabilities.values().sum::<u8>();
+        asset_deck: &mut Deck<Asset>,
+        liability_deck: &mut Deck<Liability>,
+    ) -> Result<PayBankerPlayer, GameError> {
+        let extra_asset_cash = Cash::checked_sum(selected_assets.values().copied())?;
+        let extra_liability_cash = Cash::checked_sum(selected_liabilities.values().copied())?;
+        let available_cash = self
+            .cash
+            .checked_add(extra_asset_cash)?
+            .checked_add(extra_liability_cash)?;
 
-        if self.cash + extra_asset_cash + extra_liability_cash >= cash {
-            banker.cash += cash;
-            self.cash += extra_asset_cash + extra_liability_cash;
-            self.cash -= cash;
+        if available_cash.covers(cash) {
+            banker.cash = banker.cash.checked_add(cash)?;
+            self.cash = available_cash.checked_sub(cash)?;
 
             // TODO: reuse in `create_select_assets_liabilities` somehow
             let sold_assets = selected_assets
@@ -210,7 +277,7 @@ impl BankerTargetPlayer {
 
             for asset_idx in asset_idxs.iter().rev() {
                 // TODO: figure out if this can have invalid indices
-                self.assets.remove(*asset_idx);
+                asset_deck.discard(self.assets.remove(*asset_idx));
             }
 
             let mut liability_idxs = selected_liabilities
@@ -221,7 +288,9 @@ impl BankerTargetPlayer {
             liability_idxs.sort();
 
             for card_idx in liability_idxs.iter().rev() {
-                self.hand.remove(*card_idx);
+                self.hand
+                    .remove(*card_idx)
+                    .either(|a| asset_deck.discard(a), |l| liability_deck.discard(l));
                 self.liabilities_to_play -= 1;
             }
 
@@ -232,9 +301,10 @@ impl BankerTargetPlayer {
                 target_id: self.id,
                 banker_id: banker.id,
                 selected_cards,
+                remaining_debt: 0,
             })
         } else {
-            Err(PayBankerError::NotEnoughCash)
+            Err(PayBankerError::NotEnoughCash.into())
         }
     }
 
@@ -243,7 +313,7 @@ impl BankerTargetPlayer {
         &mut self,
         asset_id: usize,
         market: &Market,
-        selected_assets: &mut HashMap<usize, u8>,
+        selected_assets: &mut BTreeMap<usize, u8>,
     ) -> Result<&Asset, BankerTargetSelectError> {
         if let Some(asset) = self.assets.get(asset_id) {
             if let Entry::Vacant(entry) = selected_assets.entry(asset_id) {
@@ -267,7 +337,7 @@ impl BankerTargetPlayer {
     pub fn unselect_divest_asset(
         &mut self,
         asset_id: usize,
-        selected_assets: &mut HashMap<usize, u8>,
+        selected_assets: &mut BTreeMap<usize, u8>,
     ) -> Result<&Asset, BankerTargetSelectError> {
         if let Some(asset) = self.assets.get(asset_id) {
             if let Some(_market_value) = selected_assets.remove(&asset_id) {
@@ -286,7 +356,7 @@ impl BankerTargetPlayer {
     pub fn select_issue_liability(
         &mut self,
         card_idx: usize,
-        selected_liabilities: &mut HashMap<usize, u8>,
+        selected_liabilities: &mut BTreeMap<usize, u8>,
     ) -> Result<&Liability, BankerTargetSelectError> {
         if self.character == Character::CFO {
             if let Some(Either::Right(liability)) = self.hand.get(card_idx) {
@@ -313,7 +383,7 @@ impl BankerTargetPlayer {
     pub fn unselect_issue_liability(
         &mut self,
         card_idx: usize,
-        selected_liabilities: &mut HashMap<usize, u8>,
+        selected_liabilities: &mut BTreeMap<usize, u8>,
     ) -> Result<&Liability, BankerTargetSelectError> {
         if let Some(Either::Right(liability)) = self.hand.get(card_idx) {
             if let Some(_market_value) = selected_liabilities.remove(&card_idx) {
@@ -340,15 +410,38 @@ impl From<BankerTargetPlayer> for RoundPlayer {
             hand: player.hand,
             liabilities_to_play: player.liabilities_to_play,
             cards_drawn: vec![],
-            bonus_draw_cards: 0,
+            draw_rule: player.draw_rule,
             assets_to_play: playable_assets.total(),
             playable_assets,
             total_cards_drawn: 0,
             total_cards_given_back: 0,
+            extra_draws: 0,
             has_used_ability: false,
             has_gotten_bonus_cash: false,
             was_first_to_six_assets: false,
+            banker_debts: player.banker_debts,
             is_human: player.is_human,
         }
     }
 }
+
+impl From<&BankerTargetPlayer> for PlayerInfo {
+    fn from(player: &BankerTargetPlayer) -> Self {
+        Self {
+            name: player.name.clone(),
+            id: player.id,
+            hand: Self::hand(&player.hand),
+            assets: player.assets.clone(),
+            liabilities: player.liabilities.clone(),
+            cash: PlayerCash::Exact(player.cash),
+            character: Some(player.character),
+            is_human: player.is_human,
+            total_asset_gold: Self::total_asset_gold(&player.assets),
+            total_debt: Self::total_debt(&player.liabilities),
+            market_value: None,
+            liability_cap: None,
+            banker_debt: Self::banker_debt(&player.banker_debts),
+            has_used_ability: false,
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! File containing [`Cash`], the amount of gold a player holds. Cash only changes through
+//! [`Cash::checked_add`] and [`Cash::checked_sub`], so a long game can never overflow or go below
+//! zero without the action that caused it being rejected.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use std::fmt;
+
+use crate::errors::GameError;
+
+/// An amount of cash held by a player.
+///
+/// # Examples
+///
+/// ```
+/// # use game::{errors::GameError, player::Cash};
+/// let cash = Cash::from(3);
+/// assert_eq!(cash.checked_add(2), Ok(Cash(5)));
+/// assert_eq!(cash.checked_sub(4), Err(GameError::NotEnoughCash));
+/// assert_eq!(Cash::MAX.checked_add(1), Err(GameError::CashOverflow));
+/// ```
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(
+    Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Cash(pub u16);
+
+impl Cash {
+    /// No cash at all.
+    pub const ZERO: Self = Self(0);
+    /// The most cash a player can hold.
+    pub const MAX: Self = Self(u16::MAX);
+
+    /// Gets the amount of cash.
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    /// Adds `amount` to this cash. Returns [`GameError::CashOverflow`] if the result does not fit.
+    pub fn checked_add(self, amount: impl Into<Cash>) -> Result<Self, GameError> {
+        self.0
+            .checked_add(amount.into().0)
+            .map(Self)
+            .ok_or(GameError::CashOverflow)
+    }
+
+    /// Takes `amount` from this cash. Returns [`GameError::NotEnoughCash`] if there is less cash
+    /// than `amount`.
+    pub fn checked_sub(self, amount: impl Into<Cash>) -> Result<Self, GameError> {
+        self.0
+            .checked_sub(amount.into().0)
+            .map(Self)
+            .ok_or(GameError::NotEnoughCash)
+    }
+
+    /// Adds `amount` to this cash, staying at [`Cash::MAX`] if the result does not fit. Only use
+    /// this to reason about cash, never to change the cash of a player.
+    pub fn saturating_add(self, amount: impl Into<Cash>) -> Self {
+        Self(self.0.saturating_add(amount.into().0))
+    }
+
+    /// Adds up every amount in `amounts`. Returns [`GameError::CashOverflow`] if the total does not
+    /// fit.
+    pub fn checked_sum<C: Into<Cash>>(
+        amounts: impl IntoIterator<Item = C>,
+    ) -> Result<Self, GameError> {
+        amounts
+            .into_iter()
+            .try_fold(Self::ZERO, |total, amount| total.checked_add(amount))
+    }
+
+    /// Checks whether this cash is enough to pay `amount`.
+    pub fn covers(self, amount: impl Into<Cash>) -> bool {
+        self >= amount.into()
+    }
+}
+
+impl From<u8> for Cash {
+    fn from(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl PartialEq<u8> for Cash {
+    fn eq(&self, other: &u8) -> bool {
+        *self == Cash::from(*other)
+    }
+}
+
+impl PartialOrd<u8> for Cash {
+    fn partial_cmp(&self, other: &u8) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(&Cash::from(*other)))
+    }
+}
+
+impl fmt::Display for Cash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -3,17 +3,24 @@
 use crate::player::*;
 
 /// The player type corresponding to the [`Lobby`](crate::game::Lobby) state of the game.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LobbyPlayer {
     id: PlayerId,
     name: String,
     is_human: bool,
+    #[serde(default)]
+    is_ready: bool,
 }
 
 impl LobbyPlayer {
     /// Instantiates a new lobby player based on an id and a name.
     pub fn new(id: PlayerId, name: String, is_human: bool) -> Self {
-        Self { id, name, is_human }
+        Self {
+            id,
+            name,
+            is_human,
+            is_ready: false,
+        }
     }
 
     /// Gets the id of the player
@@ -31,10 +38,30 @@ impl LobbyPlayer {
         &self.name
     }
 
+    /// Sets the name of the player
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     /// Gets the humanity state of the player
     pub fn is_human(&self) -> bool {
         self.is_human
     }
+
+    /// Sets the humanity state of the player
+    pub fn set_is_human(&mut self, human: bool) {
+        self.is_human = human;
+    }
+
+    /// Gets whether the player is ready for the game to start
+    pub fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    /// Sets whether the player is ready for the game to start
+    pub fn set_ready(&mut self, ready: bool) {
+        self.is_ready = ready;
+    }
 }
 
 impl From<&LobbyPlayer> for PlayerInfo {
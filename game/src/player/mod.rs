@@ -2,13 +2,17 @@
 //! to interact with them.
 
 mod banker_target;
+mod cash;
 mod lobby;
+mod perk;
 mod results;
 mod round;
 mod selecting_characters;
 
 pub use banker_target::*;
+pub use cash::*;
 pub use lobby::*;
+pub use perk::*;
 pub use results::*;
 pub use round::*;
 pub use selecting_characters::*;
@@ -19,12 +23,13 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "ts")]
 use ts_rs::TS;
 
+#[cfg(feature = "images")]
 use std::sync::Arc;
 
 use crate::{errors::*, game::*};
 
 /// Representation of an asset card. Each asset has a gold and a silver value, as well as an
-/// associated color. Some cards alse have an [`AssetPowerup`].
+/// associated color. Some cards alse have an [`AssetPowerup`] or an [`AssetEffect`].
 #[cfg_attr(feature = "ts", derive(TS))]
 #[cfg_attr(feature = "ts", ts(rename = "AssetCard"))]
 #[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
@@ -40,9 +45,15 @@ pub struct Asset {
     pub color: Color,
     /// Whether or not this asset has an [`AssetPowerup`].
     pub ability: Option<AssetPowerup>,
-    /// Url containing the relative location of the card in the assets folder
+    /// The [`AssetEffect`] this asset triggers when it is bought, if any.
+    #[serde(default)]
+    pub on_buy: Option<AssetEffect>,
+    /// Url containing the relative location of the card in the assets folder, unless a base url
+    /// was set with [`GameData::set_image_base_url`](crate::cards::GameData::set_image_base_url)
+    #[cfg(feature = "images")]
     pub image_front_url: String,
     /// Url containing the relative location of the back of the card in the assets folder
+    #[cfg(feature = "images")]
     pub image_back_url: Arc<String>,
 }
 
@@ -50,11 +61,7 @@ impl Asset {
     /// Gets the current value of the asset based on the given market condition. Note that this
     /// value can be negative.
     pub fn market_value(&self, market: &Market) -> i8 {
-        let mul: i8 = match market.color_condition(self.color) {
-            MarketCondition::Plus => 1,
-            MarketCondition::Minus => -1,
-            MarketCondition::Zero => 0,
-        };
+        let mul = market.color_condition(self.color).multiplier();
         self.gold_value as i8 + self.silver_value as i8 * mul
     }
 
@@ -89,6 +96,20 @@ pub enum AssetPowerup {
     CountAsAnyColor,
 }
 
+/// An effect some assets trigger during the round, as soon as they are bought. Unlike an
+/// [`AssetPowerup`], which is only used at the end of the game.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssetEffect {
+    /// The player who bought the asset can draw one more card this turn.
+    #[serde(rename = "When bought, draw an extra card this turn")]
+    DrawExtraCard,
+    /// The player who bought the asset can issue one more liability this turn.
+    #[serde(rename = "When bought, issue an extra liability this turn")]
+    IssueExtraLiability,
+}
+
 /// Representation of a liability card. Each liability has an associated gold value as well as a
 /// [`LiabilityType`], which determines how expensive it is to issue this liability.
 #[cfg_attr(feature = "ts", derive(TS))]
@@ -100,9 +121,15 @@ pub struct Liability {
     pub value: u8,
     /// The card's [`LiabilityType`], which determines how expensive it is to issue this liability.
     pub rfr_type: LiabilityType,
-    /// Url containing the relative location of the card in the assets folder.
+    /// The round this liability was issued in, counting from 1, if it was issued.
+    #[serde(default)]
+    pub issued_round: Option<u8>,
+    /// Url containing the relative location of the card in the assets folder, unless a base url
+    /// was set with [`GameData::set_image_base_url`](crate::cards::GameData::set_image_base_url).
+    #[cfg(feature = "images")]
     pub image_front_url: String,
     /// Url containing the relative location of the back of the card in the assets folder.
+    #[cfg(feature = "images")]
     pub image_back_url: Arc<String>,
 }
 
@@ -115,6 +142,21 @@ impl Liability {
             LiabilityType::Bonds => 3,
         }
     }
+
+    /// Gets the cash it costs to redeem this liability in round `round_number`. This is its value,
+    /// plus its rfr% for every round it was held if `config` enables
+    /// [`GameConfig::redemption_interest`].
+    pub fn redemption_cost(&self, round_number: u8, config: &GameConfig) -> u8 {
+        let rounds_held = self
+            .issued_round
+            .map_or(0, |issued| round_number.saturating_sub(issued));
+        match config.redemption_interest {
+            true => self
+                .value
+                .saturating_add(self.rfr_percentage().saturating_mul(rounds_held)),
+            false => self.value,
+        }
+    }
 }
 
 /// The liability type determines the cost of lending for that particular liability.
@@ -177,12 +219,26 @@ pub struct PlayerInfo {
     pub assets: Vec<Asset>,
     /// The liabilities this player has issued.
     pub liabilities: Vec<Liability>,
-    /// The amount of cash this player has.
-    pub cash: u8,
+    /// The amount of cash this player has, or only roughly how much if cash is hidden.
+    pub cash: PlayerCash,
     /// The character this player has chosen, if applicable.
     pub character: Option<Character>,
     /// This player is controlled by a human
     pub is_human: bool,
+    /// The total gold value of all assets this player has bought.
+    pub total_asset_gold: u8,
+    /// The total value of all liabilities this player has issued.
+    pub total_debt: u8,
+    /// The total value of all assets this player has bought in the current market, if there is
+    /// one. Note that this value can be negative.
+    pub market_value: Option<i16>,
+    /// The most liabilities this player may hold at once, if the game limits it. See
+    /// [`GameConfig::liability_cap`].
+    pub liability_cap: Option<u8>,
+    /// The total amount of cash this player still owes to bankers, see [`BankerDebt`].
+    pub banker_debt: u8,
+    /// Whether this player used the ability of their character this round.
+    pub has_used_ability: bool,
 }
 
 impl PlayerInfo {
@@ -194,6 +250,52 @@ impl PlayerInfo {
             })
             .collect()
     }
+
+    fn total_asset_gold(assets: &[Asset]) -> u8 {
+        assets.iter().map(|a| a.gold_value).sum()
+    }
+
+    fn total_debt(liabilities: &[Liability]) -> u8 {
+        liabilities.iter().map(|l| l.value).sum()
+    }
+
+    fn banker_debt(debts: &[BankerDebt]) -> u8 {
+        debts.iter().map(|d| d.amount).sum()
+    }
+
+    /// Replaces the exact cash of this player with a [`CashBracket`] if `config` hides cash.
+    pub fn redact(mut self, config: &GameConfig) -> Self {
+        if let PlayerCash::Exact(cash) = self.cash {
+            self.cash = PlayerCash::seen_by_others(cash, config);
+        }
+        self
+    }
+
+    /// Sets [`PlayerInfo::liability_cap`] to the cap `config` is played with.
+    pub fn with_liability_cap(mut self, config: &GameConfig) -> Self {
+        self.liability_cap = config.liability_cap;
+        self
+    }
+
+    /// Sets [`PlayerInfo::market_value`] to the value of the assets of this player in `market`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::player::PlayerInfo;
+    /// # use game::game::Market;
+    /// let info = PlayerInfo::default().with_market(&Market::default());
+    /// assert_eq!(info.market_value, Some(0));
+    /// ```
+    pub fn with_market(mut self, market: &Market) -> Self {
+        self.market_value = Some(
+            self.assets
+                .iter()
+                .map(|a| a.market_value(market) as i16)
+                .sum(),
+        );
+        self
+    }
 }
 
 impl Default for PlayerInfo {
@@ -204,9 +306,74 @@ impl Default for PlayerInfo {
             hand: Default::default(),
             assets: Default::default(),
             liabilities: Default::default(),
-            cash: Default::default(),
+            cash: PlayerCash::Exact(Cash::ZERO),
             character: Default::default(),
             is_human: Default::default(),
+            total_asset_gold: Default::default(),
+            total_debt: Default::default(),
+            market_value: Default::default(),
+            liability_cap: Default::default(),
+            banker_debt: Default::default(),
+            has_used_ability: Default::default(),
+        }
+    }
+}
+
+/// The cash of a player as seen by other players.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PlayerCash {
+    /// The exact amount of cash.
+    Exact(Cash),
+    /// Roughly how much cash, used when [`GameConfig::hide_cash`] is set.
+    Bracket(CashBracket),
+}
+
+impl PlayerCash {
+    /// Gets how other players see `cash`, which is only roughly if `config` hides cash.
+    pub fn seen_by_others(cash: Cash, config: &GameConfig) -> Self {
+        match config.hide_cash {
+            true => Self::Bracket(CashBracket::new(cash)),
+            false => Self::Exact(cash),
+        }
+    }
+}
+
+/// A coarse indication of how much cash a player has.
+#[allow(missing_docs)]
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CashBracket {
+    Low,
+    Medium,
+    High,
+}
+
+impl CashBracket {
+    /// The lowest amount of cash that counts as [`CashBracket::Medium`].
+    pub const MEDIUM: u8 = 5;
+    /// The lowest amount of cash that counts as [`CashBracket::High`].
+    pub const HIGH: u8 = 10;
+
+    /// Gets the bracket `cash` falls in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use game::player::CashBracket;
+    /// assert_eq!(CashBracket::new(1), CashBracket::Low);
+    /// assert_eq!(CashBracket::new(7), CashBracket::Medium);
+    /// assert_eq!(CashBracket::new(12), CashBracket::High);
+    /// ```
+    pub fn new(cash: impl Into<Cash>) -> Self {
+        let cash = cash.into();
+        match cash {
+            c if c >= Self::HIGH => Self::High,
+            c if c >= Self::MEDIUM => Self::Medium,
+            _ => Self::Low,
         }
     }
 }
@@ -215,7 +382,7 @@ impl Default for PlayerInfo {
 #[allow(missing_docs)]
 #[cfg_attr(feature = "ts", derive(TS))]
 #[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Color {
     Red,
     Green,
@@ -238,6 +405,47 @@ impl Color {
     pub fn is_divestable(&self) -> bool {
         !matches!(self, Self::Red | Self::Green) // note the !
     }
+
+    /// Gets the standard marker frontends draw on top of this color, see [`ColorMarker`]. Card data
+    /// can choose different markers, see [`GameData::colors`](crate::cards::GameData::colors).
+    pub fn marker(self) -> ColorMarker {
+        let (pattern, icon) = match self {
+            Self::Red => ("diagonal-stripes", "triangle"),
+            Self::Green => ("dots", "circle"),
+            Self::Purple => ("crosshatch", "diamond"),
+            Self::Yellow => ("horizontal-stripes", "star"),
+            Self::Blue => ("waves", "square"),
+        };
+
+        ColorMarker {
+            color: self,
+            pattern: pattern.to_owned(),
+            icon: icon.to_owned(),
+        }
+    }
+}
+
+/// A pattern and an icon that belong to a [`Color`], so players who cannot tell the colors apart
+/// can still recognize them. The game decides which markers belong to which color, so every
+/// frontend shows the same ones.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColorMarker {
+    /// The color these markers belong to.
+    pub color: Color,
+    /// The identifier of the pattern to fill cards of this color with, like `"dots"`.
+    pub pattern: String,
+    /// The identifier of the icon to show on cards of this color, like `"circle"`.
+    pub icon: String,
+}
+
+impl ColorMarker {
+    /// Gets the standard markers of every color, in the order of [`Color::COLORS`]. These are used
+    /// for card data that does not list its own colors.
+    pub fn all() -> Vec<ColorMarker> {
+        Color::COLORS.into_iter().map(Color::marker).collect()
+    }
 }
 
 /// Utility struct used to represent the amount of asset cards and liability cards a certain player
@@ -261,15 +469,48 @@ pub struct PayBankerPlayer {
     /// The total amount of gold paid to the banker.
     pub paid_amount: u8,
     /// The new cash balance of the banker.
-    pub new_banker_cash: u8,
+    pub new_banker_cash: Cash,
     /// The new cash balance of the player that was targeted by the banker.
-    pub new_target_cash: u8,
+    pub new_target_cash: Cash,
     /// The id of the player that was targeted by the banker.
     pub target_id: PlayerId,
     /// The id of the player who is the banker.
     pub banker_id: PlayerId,
     /// The selection of assets and liabilities to be played to pay the banker.
     pub selected_cards: SelectedAssetsAndLiabilities,
+    /// What the targeted player still owes the banker after this payment, which is only more than
+    /// zero if they could not pay in full. See [`BankerDebt`].
+    pub remaining_debt: u8,
+}
+
+/// Cash a player still owes the banker that targeted them, because they could not pay in full even
+/// after selling everything they could. The debt is paid off at the start of their next turns, as
+/// far as their cash allows.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BankerDebt {
+    /// The id of the player that was the banker when the debt was made.
+    pub banker_id: PlayerId,
+    /// The amount of cash still owed.
+    pub amount: u8,
+}
+
+/// A payment towards the [`BankerDebt`]s of a player, made at the start of their turn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BankerDebtPayment {
+    /// The id of the player that paid off (part of) their debt.
+    pub player_id: PlayerId,
+    /// The id of the player the debt was owed to.
+    pub banker_id: PlayerId,
+    /// The amount of cash that was paid.
+    pub paid_amount: u8,
+    /// The amount of cash still owed to the banker after this payment.
+    pub remaining_debt: u8,
+    /// The new cash balance of the player that paid.
+    pub new_player_cash: Cash,
+    /// The new cash balance of the banker.
+    pub new_banker_cash: Cash,
 }
 
 /// Utility struct used to represent each asset that can be divested from a player including the
@@ -298,6 +539,20 @@ pub struct DivestAsset {
     pub is_divestable: bool,
 }
 
+/// An asset the stakeholder picked to divest. It only changes hands once the stakeholder confirms
+/// it, so the player it belongs to can be told about it first.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDivest {
+    /// The id of the player the asset belongs to.
+    pub target_id: PlayerId,
+    /// The index of the asset among the assets of the target.
+    pub asset_idx: usize,
+    /// The gold the stakeholder pays to divest the asset.
+    pub gold_cost: u8,
+}
+
 /// An enum containing all characters currently in the game in the order in which they are called.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "ts", derive(TS))]
@@ -409,7 +664,8 @@ impl Character {
         }
     }
 
-    /// Gets the amount of cards this character is allowed to draw.
+    /// Gets the amount of cards this character is allowed to draw, unless the game is played with
+    /// other [`DrawRules`](crate::game::DrawRules).
     pub fn draws_n_cards(&self) -> u8 {
         // TODO: fix head rnd ability when ready
         match self {
@@ -418,6 +674,22 @@ impl Character {
         }
     }
 
+    /// Gets the amount of drawn cards this character gives back, unless the game is played with
+    /// other [`DrawRules`](crate::game::DrawRules).
+    pub fn gives_back_n_cards(&self) -> u8 {
+        // For every 3 cards drawn one needs to be given back
+        self.draws_n_cards() / 3
+    }
+
+    /// Returns `true` if this character has an ability that is used during their turn, instead of
+    /// one that applies on its own.
+    pub fn has_active_ability(&self) -> bool {
+        matches!(
+            self,
+            Self::Shareholder | Self::Banker | Self::Regulator | Self::Stakeholder
+        )
+    }
+
     /// Returns `true` if this character is allowed to redeem liabilities
     pub fn can_redeem_liabilities(&self) -> bool {
         matches!(self, Self::CFO)
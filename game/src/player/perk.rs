@@ -0,0 +1,159 @@
+//! File containing [`PerkDescriptor`], a description of what a [`Character`] is allowed to do that
+//! frontends can build their ability UI from. Descriptors are made from the same functions the game
+//! uses to check actions, so they never disagree with the rules.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+use crate::player::*;
+
+/// What a character is allowed to do on their turn.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerkDescriptor {
+    /// The character that has this perk.
+    pub character: Character,
+    /// The kind of perk.
+    pub kind: PerkKind,
+    /// The numbers and targets of the perk.
+    pub params: PerkParams,
+}
+
+/// The kind of perk a character has, which mostly decides which ability UI should be shown.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PerkKind {
+    /// The character can fire one of [`PerkParams::targets`].
+    FireCharacter,
+    /// The character can terminate the credit of one of [`PerkParams::targets`].
+    TerminateCredit,
+    /// The character can swap their hand with the hand of a player that has one of
+    /// [`PerkParams::targets`], or swap cards with the deck.
+    SwapCards,
+    /// The character can buy more assets than usual, see [`PerkParams::assets`].
+    BuyAssets,
+    /// The character can issue more liabilities than usual, or redeem liabilities instead.
+    ManageLiabilities,
+    /// The character can draw more cards than usual, see [`PerkParams::draws_cards`].
+    DrawCards,
+    /// The character can force a player that has one of [`PerkParams::targets`] to divest an
+    /// asset.
+    ForceDivest,
+}
+
+/// The numbers and targets that go with a [`PerkKind`]. Fields that do not matter for a perk have
+/// the values every character has.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerkParams {
+    /// The characters the perk can be used on. Empty if the perk has no target.
+    pub targets: Vec<Character>,
+    /// The most assets of each color the character can buy this turn.
+    pub assets: Vec<AssetLimit>,
+    /// The most liabilities the character can issue this turn.
+    pub liabilities: u8,
+    /// Whether the character can redeem liabilities.
+    pub redeems_liabilities: bool,
+    /// The amount of cards the character can draw this turn.
+    pub draws_cards: u8,
+    /// The amount of drawn cards the character has to give back this turn.
+    pub gives_back_cards: u8,
+    /// Whether the character becomes chairman in the next round.
+    pub becomes_chairman: bool,
+}
+
+/// The most assets of a color a character can buy in one turn.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetLimit {
+    /// The color of the assets.
+    pub color: Color,
+    /// The most assets of this color that can be bought.
+    pub max: u8,
+}
+
+impl Character {
+    /// Gets the [`PerkDescriptor`] of this character.
+    pub fn perk(self) -> PerkDescriptor {
+        let (kind, targets) = match self {
+            Self::Shareholder => (PerkKind::FireCharacter, Self::targets(|c| c.can_be_fired())),
+            Self::Banker => (
+                PerkKind::TerminateCredit,
+                Self::targets(|c| c.can_be_fired()),
+            ),
+            Self::Regulator => (PerkKind::SwapCards, Self::targets(|c| c != self)),
+            Self::CEO | Self::CSO => (PerkKind::BuyAssets, vec![]),
+            Self::CFO => (PerkKind::ManageLiabilities, vec![]),
+            Self::HeadRnD => (PerkKind::DrawCards, vec![]),
+            Self::Stakeholder => (
+                PerkKind::ForceDivest,
+                Self::targets(|c| c != self && c.can_be_forced_to_divest()),
+            ),
+        };
+
+        let playable = self.playable_assets();
+        let assets = Color::COLORS
+            .into_iter()
+            .map(|color| AssetLimit {
+                color,
+                max: playable.total() / playable.color_cost(color),
+            })
+            .collect();
+
+        PerkDescriptor {
+            character: self,
+            kind,
+            params: PerkParams {
+                targets,
+                assets,
+                liabilities: self.playable_liabilities(),
+                redeems_liabilities: self.can_redeem_liabilities(),
+                draws_cards: self.draws_n_cards(),
+                gives_back_cards: self.gives_back_n_cards(),
+                becomes_chairman: self == Self::CEO,
+            },
+        }
+    }
+
+    fn targets(f: impl Fn(Self) -> bool) -> Vec<Self> {
+        Self::CHARACTERS.into_iter().filter(|&c| f(c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perks_follow_the_rules() {
+        let cso = Character::CSO.perk();
+        assert_eq!(cso.kind, PerkKind::BuyAssets);
+        assert!(cso.params.assets.contains(&AssetLimit {
+            color: Color::Red,
+            max: 2
+        }));
+        assert!(cso.params.assets.contains(&AssetLimit {
+            color: Color::Blue,
+            max: 1
+        }));
+
+        let head_rnd = Character::HeadRnD.perk().params;
+        assert_eq!((head_rnd.draws_cards, head_rnd.gives_back_cards), (6, 2));
+
+        let shareholder = Character::Shareholder.perk().params;
+        assert!(!shareholder.targets.contains(&Character::Banker));
+        assert!(shareholder.targets.contains(&Character::CEO));
+
+        let stakeholder = Character::Stakeholder.perk().params;
+        assert!(!stakeholder.targets.contains(&Character::CSO));
+        assert!(!stakeholder.targets.contains(&Character::Stakeholder));
+
+        assert!(Character::CEO.perk().params.becomes_chairman);
+        assert!(Character::CFO.perk().params.redeems_liabilities);
+    }
+}
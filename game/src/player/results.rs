@@ -9,11 +9,12 @@ use crate::{game::*, player::*};
 
 /// The player type that corresponds to the [`Results`](crate::game::Results) stage of the game.
 /// During the results stage, each player can calculate and see their score.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ResultsPlayer {
     id: PlayerId,
     name: String,
-    cash: u8,
+    character: Character,
+    cash: Cash,
     assets: Vec<Asset>,
     liabilities: Vec<Liability>,
     hand: Vec<Either<Asset, Liability>>,
@@ -22,8 +23,10 @@ pub struct ResultsPlayer {
     old_silver_into_gold: Option<SilverIntoGoldData>,
     old_change_asset_color: Option<ChangeAssetColorData>,
     confirmed_asset_ability_idxs: Vec<usize>,
+    used_asset_abilities: Vec<UsedAssetAbility>,
     was_first_to_six_assets: bool,
     is_human: bool,
+    scoring: Scoring,
 }
 
 impl ResultsPlayer {
@@ -34,6 +37,7 @@ impl ResultsPlayer {
         Self {
             id: player.id,
             name: player.name,
+            character: player.character,
             cash: player.cash,
             assets: player.assets,
             liabilities: player.liabilities,
@@ -43,11 +47,19 @@ impl ResultsPlayer {
             old_silver_into_gold: None,
             old_change_asset_color: None,
             confirmed_asset_ability_idxs: vec![],
+            used_asset_abilities: vec![],
             was_first_to_six_assets: player.was_first_to_six_assets,
             is_human: player.is_human,
+            scoring: Scoring::default(),
         }
     }
 
+    /// Scores this player with `scoring` instead of the default [`Scoring::Wacc`].
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
     /// Gets the id of the player
     pub fn id(&self) -> PlayerId {
         self.id
@@ -58,8 +70,23 @@ impl ResultsPlayer {
         &self.name
     }
 
+    /// Gets the character the player held in the final round
+    pub fn character(&self) -> Character {
+        self.character
+    }
+
+    /// Gets the human state of this player
+    pub fn is_human(&self) -> bool {
+        self.is_human
+    }
+
+    /// Sets the human state of this player
+    pub fn set_is_human(&mut self, human: bool) {
+        self.is_human = human;
+    }
+
     /// Gets the amount of cash of the player
-    pub fn cash(&self) -> u8 {
+    pub fn cash(&self) -> Cash {
         self.cash
     }
 
@@ -83,6 +110,11 @@ impl ResultsPlayer {
         &self.market
     }
 
+    /// Gets the asset abilities this player confirmed so far, in the order they were confirmed.
+    pub fn used_asset_abilities(&self) -> &[UsedAssetAbility] {
+        &self.used_asset_abilities
+    }
+
     fn check_has_ability(&self, ability: AssetPowerup) -> Result<(), AssetAbilityError> {
         let has_ability = self
             .assets
@@ -110,13 +142,7 @@ impl ResultsPlayer {
 
         self.market = self.final_market.clone();
 
-        match color {
-            Color::Red => self.market.red.make_higher(),
-            Color::Green => self.market.green.make_higher(),
-            Color::Purple => self.market.purple.make_higher(),
-            Color::Yellow => self.market.yellow.make_higher(),
-            Color::Blue => self.market.blue.make_higher(),
-        };
+        self.market.color_condition_mut(color).make_higher();
 
         Ok(&self.market)
     }
@@ -243,32 +269,104 @@ impl ResultsPlayer {
         }
     }
 
+    /// Gets the score this player would have if they used `powerup` on what `params` points to,
+    /// without changing anything. Choices that were not confirmed yet are replaced the same way
+    /// toggling would replace them.
+    pub fn evaluate_powerup(
+        &self,
+        powerup: AssetPowerup,
+        params: PowerupParams,
+    ) -> Result<f64, GameError> {
+        self.check_has_ability(powerup)?;
+
+        let mut player = self.clone();
+        match (powerup, params) {
+            (AssetPowerup::MinusIntoPlus, PowerupParams::Color(color)) => {
+                player.toggle_minus_into_plus(color)?;
+            }
+            (AssetPowerup::SilverIntoGold, PowerupParams::Asset(asset_idx)) => {
+                // Toggling the asset that already has its silver turned into gold would undo it
+                if self
+                    .old_silver_into_gold
+                    .is_none_or(|old| old.asset_idx != asset_idx)
+                {
+                    player.toggle_silver_into_gold(asset_idx)?;
+                }
+            }
+            (AssetPowerup::CountAsAnyColor, PowerupParams::AssetColor(asset_idx, color)) => {
+                player.toggle_change_asset_color(asset_idx, color)?;
+            }
+            (powerup, _) => return Err(AssetAbilityError::InvalidPowerupParams(powerup).into()),
+        }
+
+        Ok(player.score())
+    }
+
     /// Asset abilities are toggleable by default. This function confirms the current configuration,
-    /// after which a player cannot toggle this particular index anymore.
-    pub fn confirm_asset_ability(&mut self, asset_idx: usize) -> Result<(), GameError> {
+    /// after which a player cannot toggle this particular index anymore. Returns what the ability
+    /// was used on, which is kept in [`ResultsPlayer::used_asset_abilities`].
+    pub fn confirm_asset_ability(
+        &mut self,
+        asset_idx: usize,
+    ) -> Result<UsedAssetAbility, GameError> {
         if self.confirmed_asset_ability_idxs.contains(&asset_idx) {
             return Err(AssetAbilityError::AlreadyConfirmedAssetIndex(asset_idx as u8).into());
         }
 
         if let Some(asset) = self.assets.get(asset_idx) {
-            match asset.ability {
+            let used_on = match asset.ability {
                 Some(AssetPowerup::MinusIntoPlus) => {
+                    let used_on = Color::COLORS
+                        .into_iter()
+                        .find(|&color| {
+                            self.market.color_condition(color)
+                                != self.final_market.color_condition(color)
+                        })
+                        .map_or(
+                            AssetAbilityUse::Unused {
+                                powerup: AssetPowerup::MinusIntoPlus,
+                            },
+                            |color| AssetAbilityUse::MinusIntoPlus {
+                                color,
+                                from: self.final_market.color_condition(color),
+                                to: self.market.color_condition(color),
+                            },
+                        );
                     self.final_market = self.market.clone();
+                    used_on
                 }
-                Some(AssetPowerup::SilverIntoGold) => {
-                    self.old_silver_into_gold = None;
-                }
+                Some(AssetPowerup::SilverIntoGold) => match self.old_silver_into_gold.take() {
+                    Some(old) => AssetAbilityUse::SilverIntoGold {
+                        asset_idx: old.asset_idx,
+                        silver_value: old.silver_value,
+                    },
+                    None => AssetAbilityUse::Unused {
+                        powerup: AssetPowerup::SilverIntoGold,
+                    },
+                },
                 Some(AssetPowerup::CountAsAnyColor) => {
-                    self.old_change_asset_color = None;
+                    match self.old_change_asset_color.take() {
+                        // PANIC: the index of the changed asset is always valid once it's set
+                        Some(old) => AssetAbilityUse::CountAsAnyColor {
+                            asset_idx: old.asset_idx,
+                            from: old.color,
+                            to: self.assets[old.asset_idx].color,
+                        },
+                        None => AssetAbilityUse::Unused {
+                            powerup: AssetPowerup::CountAsAnyColor,
+                        },
+                    }
                 }
                 None => {
                     return Err(AssetAbilityError::InvalidAbilityIndex(asset_idx).into());
                 }
-            }
+            };
 
+            let used = UsedAssetAbility { asset_idx, used_on };
             self.confirmed_asset_ability_idxs.push(asset_idx);
+            self.used_asset_abilities.push(used);
 
-            Ok(())
+            Ok(used)
         } else {
             Err(GameError::InvalidAssetIndex(asset_idx as u8))
         }
@@ -336,13 +434,7 @@ impl ResultsPlayer {
 
     /// Gets the value of all assets of a certain color this player has
     pub fn color_value(&self, color: Color) -> f64 {
-        let market_condition = self.market.color_condition(color);
-
-        let mul = match market_condition {
-            MarketCondition::Plus => 1.0,
-            MarketCondition::Minus => -1.0,
-            MarketCondition::Zero => 0.0,
-        };
+        let mul = self.market.color_condition(color).multiplier() as f64;
 
         self.assets
             .iter()
@@ -363,39 +455,20 @@ impl ResultsPlayer {
             .sum()
     }
 
-    /// Gets the final score for this player
+    /// Gets the final score for this player, using the [`Scoring`] of the game.
     pub fn score(&self) -> f64 {
-        let cash = self.cash() as f64;
-        let gold = self.total_gold() as f64;
-        let silver = self.total_silver() as f64;
-
-        let trade_credit = self.trade_credit() as f64;
-        let bank_loan = self.bank_loan() as f64;
-        let bonds = self.bonds() as f64;
-        let debt = trade_credit + bank_loan + bonds;
-
-        let asset_count_bonus = self.six_assets_bonus() as f64;
-        let all_five_colors_bonus = self.all_five_colors_bonus() as f64;
-        let bonuses = asset_count_bonus + all_five_colors_bonus;
-
-        let rfr = self.market.rfr as f64;
-        let mrp = self.market.mrp as f64;
-
-        let beta = silver / gold;
-
-        let drp = (trade_credit + bank_loan * 2.0 + bonds * 3.0) / (gold + cash);
-
-        let wacc = rfr + drp + beta * mrp;
+        self.score_with(self.scoring.strategy())
+    }
 
-        // beta == inf || fcf / wacc == inf
-        if gold == 0.0 || wacc == 0.0 {
-            // lim_wacc->inf fcf / wacc = 0 || fcf / 0 = inf
-            (debt / 3.0) + cash + bonuses
-        } else {
-            let fcf = self.fcf();
+    /// Gets the final score for this player as a [`FixedScore`], using the [`Scoring`] of the game.
+    /// Players are ranked by this score.
+    pub fn fixed_score(&self) -> FixedScore {
+        self.scoring.strategy().fixed_score(self)
+    }
 
-            (fcf / (0.1 * wacc)) + (debt / 3.0) + cash + bonuses
-        }
+    /// Gets the final score for this player as `strategy` would score them.
+    pub fn score_with(&self, strategy: &dyn ScoringStrategy) -> f64 {
+        strategy.score(self)
     }
 }
 
@@ -407,15 +480,22 @@ impl From<&ResultsPlayer> for PlayerInfo {
             hand: Self::hand(&player.hand),
             assets: player.assets.clone(),
             liabilities: player.liabilities.clone(),
-            cash: player.cash,
+            cash: PlayerCash::Exact(player.cash),
             character: None,
             is_human: player.is_human,
+            total_asset_gold: Self::total_asset_gold(&player.assets),
+            total_debt: Self::total_debt(&player.liabilities),
+            market_value: None,
+            liability_cap: None,
+            banker_debt: 0,
+            has_used_ability: false,
         }
+        .with_market(&player.market)
     }
 }
 
 /// The representation of the result of toggling with [`AssetPowerup::SilverIntoGold`].
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ToggleSilverIntoGold {
     /// The data for the new asset.
     pub old_asset_data: Option<SilverIntoGoldData>,
@@ -485,7 +565,7 @@ impl SilverIntoGoldData {
 }
 
 /// The representation of the result of toggling with [`AssetPowerup::CountAsAnyColor`].
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ToggleChangeAssetColor {
     /// The data for the new asset.
     pub old_asset_data: Option<ChangeAssetColorData>,
@@ -547,6 +627,69 @@ impl ChangeAssetColorData {
     }
 }
 
+/// What an [`AssetPowerup`] is used on, see [`ResultsPlayer::evaluate_powerup`].
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PowerupParams {
+    /// The market color [`AssetPowerup::MinusIntoPlus`] raises.
+    Color(Color),
+    /// The index of the asset [`AssetPowerup::SilverIntoGold`] turns the silver of into gold.
+    Asset(usize),
+    /// The index of the asset [`AssetPowerup::CountAsAnyColor`] changes, and the color it would
+    /// count as.
+    AssetColor(usize, Color),
+}
+
+/// An asset ability a player confirmed at the end of the game, together with what it was used on.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsedAssetAbility {
+    /// The index of the asset the ability belongs to.
+    pub asset_idx: usize,
+    /// What the ability was used on.
+    pub used_on: AssetAbilityUse,
+}
+
+/// What an [`AssetPowerup`] changed about the score of a player once it was confirmed.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = crate::SHARED_TS_DIR))]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "ability")]
+pub enum AssetAbilityUse {
+    /// The market condition of `color` was raised for this player.
+    MinusIntoPlus {
+        /// The color whose market condition was raised.
+        color: Color,
+        /// The market condition before it was raised.
+        from: MarketCondition,
+        /// The market condition after it was raised.
+        to: MarketCondition,
+    },
+    /// The silver value of one of the assets of this player was turned into gold.
+    SilverIntoGold {
+        /// The index of the asset that changed.
+        asset_idx: usize,
+        /// The silver value that was turned into gold.
+        silver_value: u8,
+    },
+    /// One of the assets of this player counts as another color.
+    CountAsAnyColor {
+        /// The index of the asset that changed.
+        asset_idx: usize,
+        /// The color of the asset before it changed.
+        from: Color,
+        /// The color the asset counts as.
+        to: Color,
+    },
+    /// The ability was confirmed without being used.
+    Unused {
+        /// The powerup that went unused.
+        powerup: AssetPowerup,
+    },
+}
+
 #[cfg(test)]
 pub(super) mod tests {
     use assert_approx_eq::assert_approx_eq;
@@ -586,7 +729,8 @@ pub(super) mod tests {
         ResultsPlayer {
             id: PlayerId(0),
             name: Default::default(),
-            cash,
+            character: Character::CEO,
+            cash: cash.into(),
             assets,
             liabilities,
             hand: vec![],
@@ -595,8 +739,10 @@ pub(super) mod tests {
             old_silver_into_gold: None,
             old_change_asset_color: None,
             confirmed_asset_ability_idxs: vec![],
+            used_asset_abilities: vec![],
             was_first_to_six_assets: false,
             is_human: true,
+            scoring: Scoring::default(),
         }
     }
 
@@ -613,7 +759,10 @@ pub(super) mod tests {
         Liability {
             value,
             rfr_type,
+            issued_round: None,
+            #[cfg(feature = "images")]
             image_front_url: Default::default(),
+            #[cfg(feature = "images")]
             image_back_url: Default::default(),
         }
     }
@@ -869,6 +1018,98 @@ pub(super) mod tests {
         assert_ability_error(&mut player);
     }
 
+    #[test]
+    fn evaluating_powerups_changes_nothing() {
+        let mut player = results_player(
+            0,
+            vec![asset(Color::Purple), asset(Color::Green)],
+            vec![],
+            Market {
+                green: MarketCondition::Minus,
+                ..Default::default()
+            },
+        );
+        player.assets[0].ability = Some(AssetPowerup::SilverIntoGold);
+        player.assets[1].silver_value = 3;
+        let before = player.clone();
+
+        let score = assert_ok!(
+            player.evaluate_powerup(AssetPowerup::SilverIntoGold, PowerupParams::Asset(1))
+        );
+        assert_eq!(player, before);
+        assert_matches!(
+            player.evaluate_powerup(
+                AssetPowerup::SilverIntoGold,
+                PowerupParams::Color(Color::Red)
+            ),
+            Err(GameError::CardAbility(
+                AssetAbilityError::InvalidPowerupParams(AssetPowerup::SilverIntoGold)
+            ))
+        );
+        assert_matches!(
+            player.evaluate_powerup(
+                AssetPowerup::MinusIntoPlus,
+                PowerupParams::Color(Color::Red)
+            ),
+            Err(GameError::CardAbility(
+                AssetAbilityError::PlayerDoesNotHaveAbility(AssetPowerup::MinusIntoPlus)
+            ))
+        );
+
+        assert_ok!(player.toggle_silver_into_gold(1));
+        assert_approx_eq!(score, player.score());
+        assert_approx_eq!(
+            score,
+            assert_ok!(
+                player.evaluate_powerup(AssetPowerup::SilverIntoGold, PowerupParams::Asset(1))
+            )
+        );
+    }
+
+    #[test]
+    fn confirming_records_used_abilities() {
+        let mut player = results_player(
+            0,
+            vec![asset(Color::Purple), asset(Color::Green), asset(Color::Red)],
+            vec![],
+            Market {
+                yellow: MarketCondition::Minus,
+                ..Default::default()
+            },
+        );
+        player.assets[0].ability = Some(AssetPowerup::CountAsAnyColor);
+        player.assets[1].ability = Some(AssetPowerup::MinusIntoPlus);
+        player.assets[2].ability = Some(AssetPowerup::SilverIntoGold);
+
+        assert_ok!(player.toggle_change_asset_color(2, Color::Blue));
+        assert_ok!(player.toggle_minus_into_plus(Color::Yellow));
+        let used = [
+            assert_ok!(player.confirm_asset_ability(0)),
+            assert_ok!(player.confirm_asset_ability(1)),
+            assert_ok!(player.confirm_asset_ability(2)),
+        ];
+
+        assert_eq!(
+            used.map(|used| used.used_on),
+            [
+                AssetAbilityUse::CountAsAnyColor {
+                    asset_idx: 2,
+                    from: Color::Red,
+                    to: Color::Blue,
+                },
+                AssetAbilityUse::MinusIntoPlus {
+                    color: Color::Yellow,
+                    from: MarketCondition::Minus,
+                    to: MarketCondition::Zero,
+                },
+                AssetAbilityUse::Unused {
+                    powerup: AssetPowerup::SilverIntoGold,
+                },
+            ]
+        );
+        assert_eq!(player.used_asset_abilities(), used);
+    }
+
     #[test]
     fn total_gold() {
         for i in 0..10 {
@@ -1033,11 +1274,14 @@ pub(super) mod tests {
                 };
 
                 let mul = match market_condition {
+                    MarketCondition::DoublePlus => 2.0,
                     MarketCondition::Plus => 1.0,
                     MarketCondition::Zero => 0.0,
                     MarketCondition::Minus => -1.0,
+                    MarketCondition::DoubleMinus => -2.0,
                 };
 
+                #[allow(clippy::filter_map_bool_then)]
                 let color_value = player
                     .assets
                     .iter()
@@ -1087,7 +1331,7 @@ pub(super) mod tests {
                 player
             })
             .for_each(|player| {
-                let cash = player.cash() as f64;
+                let cash = player.cash().get() as f64;
                 let gold = player.total_gold() as f64;
                 let silver = player.total_silver() as f64;
 
@@ -1125,6 +1369,30 @@ pub(super) mod tests {
                 };
 
                 assert_approx_eq!(score, player.score());
+                assert_approx_eq!(score, player.fixed_score().as_f64(), 0.005);
             });
     }
+
+    #[test]
+    fn scoring_can_be_swapped() {
+        let market = market(
+            MarketCondition::Plus,
+            MarketCondition::Zero,
+            MarketCondition::Minus,
+            MarketCondition::Zero,
+            MarketCondition::Plus,
+            2,
+            4,
+        );
+        let assets = vec![asset(Color::Red), asset(Color::Blue)];
+        let liabilities = vec![liability_with_type(3, LiabilityType::BankLoan)];
+        let player = results_player(5, assets, liabilities, market);
+
+        assert_approx_eq!(player.score(), player.score_with(&WaccScoring));
+
+        let simple = player.clone().with_scoring(Scoring::Simple);
+        assert_approx_eq!(simple.score(), 5.0 + player.fcf() - 3.0);
+        assert_approx_eq!(simple.score(), player.score_with(&SimpleScoring));
+        assert_approx_eq!(simple.score(), simple.fixed_score().as_f64());
+    }
 }
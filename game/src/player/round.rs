@@ -7,25 +7,28 @@ use crate::{errors::*, game::*, player::*};
 
 /// The player type that corresponds to the [`Round`](crate::game::Round) stage of the game. During
 /// the round stage, each player has selected a character.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RoundPlayer {
     pub(super) id: PlayerId,
     pub(super) name: String,
-    pub(super) cash: u8,
+    pub(super) cash: Cash,
     pub(super) assets: Vec<Asset>,
     pub(super) liabilities: Vec<Liability>,
     pub(super) character: Character,
     pub(super) hand: Vec<Either<Asset, Liability>>,
     pub(super) cards_drawn: Vec<usize>,
-    pub(super) bonus_draw_cards: u8,
+    pub(super) draw_rule: DrawRule,
     pub(super) assets_to_play: u8,
     pub(super) playable_assets: PlayableAssets,
     pub(super) liabilities_to_play: u8,
     pub(super) total_cards_drawn: u8,
     pub(super) total_cards_given_back: u8,
+    #[serde(default)]
+    pub(super) extra_draws: u8,
     pub(super) has_used_ability: bool,
     pub(super) has_gotten_bonus_cash: bool,
     pub(super) was_first_to_six_assets: bool,
+    pub(super) banker_debts: Vec<BankerDebt>,
     pub(super) is_human: bool,
 }
 
@@ -41,13 +44,20 @@ impl RoundPlayer {
     }
 
     /// Gets the amount of cash of the player
-    pub fn cash(&self) -> u8 {
+    pub fn cash(&self) -> Cash {
         self.cash
     }
 
     // TODO: Temporarily used in tests, remove when tests update
-    pub(crate) fn _set_cash(&mut self, cash: u8) {
-        self.cash = cash;
+    pub(crate) fn _set_cash(&mut self, cash: impl Into<Cash>) {
+        self.cash = cash.into();
+    }
+
+    /// Puts `card` in the hand of the player without counting it as drawn. Only used by the debug
+    /// commands of the `dev` feature.
+    #[cfg(feature = "dev")]
+    pub(crate) fn give_card(&mut self, card: Either<Asset, Liability>) {
+        self.hand.push(card);
     }
 
     /// Gets a list of bought assets of the player
@@ -70,6 +80,11 @@ impl RoundPlayer {
         &self.hand
     }
 
+    /// Gets what this player still owes to bankers.
+    pub fn banker_debts(&self) -> &[BankerDebt] {
+        &self.banker_debts
+    }
+
     /// The first player to get six assets gets a cash bonus of 2.
     pub(crate) fn enable_first_to_six_assets_bonus(&mut self) {
         self.was_first_to_six_assets = true;
@@ -90,6 +105,17 @@ impl RoundPlayer {
         self.has_used_ability
     }
 
+    /// Checks whether this player can still use the ability of their character this turn, see
+    /// [`Character::has_active_ability`].
+    pub fn can_use_ability(&self) -> bool {
+        self.character.has_active_ability() && !self.has_used_ability
+    }
+
+    /// Returns true if the player has already gotten their bonus cash this turn
+    pub fn has_gotten_bonus_cash(&self) -> bool {
+        self.has_gotten_bonus_cash
+    }
+
     /// Returns the amount of cards already drawn by the player
     pub fn total_cards_drawn(&self) -> u8 {
         self.total_cards_drawn
@@ -141,24 +167,34 @@ impl RoundPlayer {
         self.liabilities_to_play
     }
 
-    /// Redeems a liability for a player by paying for it in cash. If succesful, returns the
-    /// liability that was redeemed.
+    /// Checks whether this player holds fewer liabilities than `cap`, or whether there is no cap.
+    pub fn is_under_liability_cap(&self, cap: Option<u8>) -> bool {
+        cap.is_none_or(|cap| self.liabilities.len() < cap as usize)
+    }
+
+    /// Redeems a liability for a player by paying for it in cash, which costs its
+    /// [`Liability::redemption_cost`] in round `round_number`. If succesful, returns the liability
+    /// that was redeemed.
     pub(crate) fn redeem_liability(
         &mut self,
         liability_idx: usize,
+        round_number: u8,
+        config: &GameConfig,
     ) -> Result<Liability, RedeemLiabilityError> {
         if self.character.can_redeem_liabilities() {
             if self.can_play_liability() {
                 if let Some(liability) = self.liabilities.get(liability_idx) {
-                    if liability.value <= self.cash {
-                        self.liabilities_to_play -= 1;
-                        self.cash -= liability.value;
-                        Ok(self.liabilities.remove(liability_idx))
-                    } else {
-                        Err(RedeemLiabilityError::NotEnoughCash {
+                    let cost = liability.redemption_cost(round_number, config);
+                    match self.cash.checked_sub(cost) {
+                        Ok(cash) => {
+                            self.liabilities_to_play -= 1;
+                            self.cash = cash;
+                            Ok(self.liabilities.remove(liability_idx))
+                        }
+                        Err(_) => Err(RedeemLiabilityError::NotEnoughCash {
                             cash: self.cash,
-                            cost: liability.value,
-                        })
+                            cost,
+                        }),
                     }
                 } else {
                     Err(RedeemLiabilityError::InvalidLiabilityIndex(
@@ -219,29 +255,30 @@ impl RoundPlayer {
     }
 
     /// Swaps a list of card indexes `card_idxs` with the deck. Each asset that is swapped is put
-    /// back into the deck and each liability that is swapped is put back into the liability deck.
-    /// If succesful, returns the total number of assets (left) and liabilties (right) that were
-    /// removed from the player's hand. After this action, the player is able to draw the total
-    /// number of returned cards.
+    /// back into the deck and each liability that is swapped is put back into the liability deck,
+    /// after which the same number of assets and liabilities is drawn to replace them. If
+    /// succesful, returns the number of swapped cards and the replacements, which are added to the
+    /// end of the player's hand. Replacements do not count towards the cards drawn this turn.
     pub fn swap_with_deck(
         &mut self,
         mut card_idxs: Vec<usize>,
         asset_deck: &mut Deck<Asset>,
         liability_deck: &mut Deck<Liability>,
-    ) -> Result<AssetLiabilityCount, SwapError> {
+    ) -> Result<DeckSwap, SwapError> {
         if card_idxs.is_empty() {
-            return Ok(AssetLiabilityCount::new(0, 0)); // Zero assets, zero liabilities returned.
+            // Zero assets, zero liabilities returned.
+            return Ok(DeckSwap {
+                count: AssetLiabilityCount::new(0, 0),
+                new_cards: vec![],
+            });
         }
 
         if self.character == Character::Regulator {
             if !self.has_used_ability {
                 card_idxs.sort();
-                if card_idxs.last().copied().unwrap_or_default() <= self.hand.len()
+                if card_idxs.last().is_some_and(|&last| last < self.hand.len())
                     && card_idxs.iter().all_unique()
                 {
-                    let removed_card_len = card_idxs.len();
-
-                    // TODO: actually draw new cards for player?
                     let mut asset_count: usize = 0;
                     let mut liability_count: usize = 0;
                     for card_idx in card_idxs.into_iter().rev() {
@@ -259,9 +296,27 @@ impl RoundPlayer {
                             }
                         }
                     }
+
+                    // Cards are put back at the bottom of the deck, so the replacements are drawn
+                    // from the top like any other card.
+                    // PANIC: every replacement was just put back, so the decks cannot run out.
+                    let new_cards =
+                        std::iter::repeat_with(|| Either::Left(asset_deck.draw().unwrap()))
+                            .take(asset_count)
+                            .chain(
+                                std::iter::repeat_with(|| {
+                                    Either::Right(liability_deck.draw().unwrap())
+                                })
+                                .take(liability_count),
+                            )
+                            .collect::<Vec<_>>();
+                    self.hand.extend(new_cards.iter().cloned());
+
                     self.has_used_ability = true;
-                    self.bonus_draw_cards += removed_card_len as u8;
-                    Ok(AssetLiabilityCount::new(asset_count, liability_count))
+                    Ok(DeckSwap {
+                        count: AssetLiabilityCount::new(asset_count, liability_count),
+                        new_cards,
+                    })
                 } else {
                     Err(SwapError::InvalidCardIdxs)
                 }
@@ -307,20 +362,34 @@ impl RoundPlayer {
         player: &RoundPlayer,
         asset_idx: usize,
         market: &Market,
+    ) -> Result<u8, DivestAssetError> {
+        let cost = self.divest_cost(player, asset_idx, market)?;
+        self.cash = self
+            .cash
+            .checked_sub(cost)
+            .map_err(|_| DivestAssetError::NotEnoughCash)?;
+        self.has_used_ability = true;
+        Ok(cost)
+    }
+
+    /// Checks if this player could divest an asset at index `asset_idx` from the target player,
+    /// without divesting it. If they could, the cost of doing so is returned.
+    pub fn divest_cost(
+        &self,
+        player: &RoundPlayer,
+        asset_idx: usize,
+        market: &Market,
     ) -> Result<u8, DivestAssetError> {
         if self.character == Character::Stakeholder {
             if !self.has_used_ability {
                 if player.character.can_be_forced_to_divest() {
                     if asset_idx < player.assets.len() {
                         let asset = &player.assets[asset_idx];
-                        if asset.color != Color::Red && asset.color != Color::Green {
+                        if asset.color.is_divestable() {
                             let cost = asset.divest_cost(market);
-                            if cost <= self.cash {
-                                self.has_used_ability = true;
-                                self.cash -= cost;
-                                Ok(cost)
-                            } else {
-                                Err(DivestAssetError::NotEnoughCash)
+                            match self.cash.covers(cost) {
+                                true => Ok(cost),
+                                false => Err(DivestAssetError::NotEnoughCash),
                             }
                         } else {
                             Err(DivestAssetError::CantDivestAssetType)
@@ -340,41 +409,53 @@ impl RoundPlayer {
     }
 
     /// Plays card in players hand with index `card_idx`. If that index is valid and they are
-    /// allowed to play that card, it is returned.
+    /// allowed to play that card, it is returned. Issuing a liability fails with
+    /// [`GameError::CashOverflow`] if the player cannot hold the cash it gives, and otherwise marks
+    /// it as issued in round `round_number`.
     pub(crate) fn play_card(
         &mut self,
         card_idx: usize,
-    ) -> Result<Either<Asset, Liability>, PlayCardError> {
+        round_number: u8,
+    ) -> Result<Either<Asset, Liability>, GameError> {
         use PlayCardError::*;
 
         if let Some(card) = self.hand.get(card_idx) {
             match card {
                 Either::Left(a) if self.can_play_asset(a.color) && self.can_afford_asset(a) => {
+                    self.cash = self.cash.checked_sub(a.gold_value)?;
                     // PANIC: self.hand[card_idx] exists and has been verified to be an asset, so
                     // this is safe to unwrap
                     let asset = self.hand.remove(card_idx).left().unwrap();
-                    self.cash -= asset.gold_value;
                     self.assets_to_play -= self.playable_assets.color_cost(asset.color);
                     self.assets.push(asset.clone());
                     self.update_cards_drawn(card_idx);
+                    if let Some(effect) = asset.on_buy {
+                        self.trigger(effect);
+                    }
                     Ok(Either::Left(asset))
                 }
-                Either::Left(a) if !self.can_play_asset(a.color) => Err(ExceedsMaximumAssets),
+                Either::Left(a) if !self.can_play_asset(a.color) => {
+                    Err(ExceedsMaximumAssets.into())
+                }
                 Either::Left(a) if !self.can_afford_asset(a) => Err(CannotAffordAsset {
                     cash: self.cash,
                     cost: a.gold_value,
-                }),
-                Either::Right(_) if self.can_play_liability() => {
+                }
+                .into()),
+                Either::Right(l) if self.can_play_liability() => {
+                    self.cash = self.cash.checked_add(l.value)?;
                     // PANIC: self.hand[card_idx] exists and has been verified to be a liability, so
                     // this is safe to unwrap
-                    let liability = self.hand.remove(card_idx).right().unwrap();
-                    self.cash += liability.value;
+                    let mut liability = self.hand.remove(card_idx).right().unwrap();
+                    liability.issued_round = Some(round_number);
                     self.liabilities_to_play -= 1;
                     self.liabilities.push(liability.clone());
                     self.update_cards_drawn(card_idx);
                     Ok(Either::Right(liability))
                 }
-                Either::Right(_) if !self.can_play_liability() => Err(ExceedsMaximumLiabilities),
+                Either::Right(_) if !self.can_play_liability() => {
+                    Err(ExceedsMaximumLiabilities.into())
+                }
                 _ => {
                     // PANIC: the compiler cannot verify that all cases are covered, but we can:
                     // Left() if we can both play and buy asset is checked,
@@ -387,7 +468,17 @@ impl RoundPlayer {
                 }
             }
         } else {
-            Err(InvalidCardIndex(card_idx as u8))
+            Err(InvalidCardIndex(card_idx as u8).into())
+        }
+    }
+
+    /// Applies an [`AssetEffect`] that was triggered by buying an asset.
+    fn trigger(&mut self, effect: AssetEffect) {
+        match effect {
+            AssetEffect::DrawExtraCard => self.extra_draws = self.extra_draws.saturating_add(1),
+            AssetEffect::IssueExtraLiability => {
+                self.liabilities_to_play = self.liabilities_to_play.saturating_add(1)
+            }
         }
     }
 
@@ -404,7 +495,7 @@ impl RoundPlayer {
     /// asset is returned.
     pub(crate) fn draw_asset(&mut self, deck: &mut Deck<Asset>) -> Result<&Asset, DrawCardError> {
         if self.can_draw_cards() {
-            let asset = Either::Left(deck.draw());
+            let asset = Either::Left(deck.draw().ok_or(DrawCardError::EmptyDeck)?);
             let card = self.draw_card(asset);
 
             // PANIC: because we just drew an asset, we know this to be safe.
@@ -421,7 +512,7 @@ impl RoundPlayer {
         deck: &mut Deck<Liability>,
     ) -> Result<&Liability, DrawCardError> {
         if self.can_draw_cards() {
-            let liability = Either::Right(deck.draw());
+            let liability = Either::Right(deck.draw().ok_or(DrawCardError::EmptyDeck)?);
             let card = self.draw_card(liability);
 
             // PANIC: because we just drew a liability, we know this to be safe.
@@ -455,9 +546,9 @@ impl RoundPlayer {
 
     /// Checks whether or not this player should still give back cards.
     pub fn should_give_back_cards(&self) -> bool {
-        // For every 3 cards drawn one needs to give one back. Subtract any bonus drawing cards a
-        // player may draw.
-        match (self.total_cards_drawn.saturating_sub(self.bonus_draw_cards) / 3)
+        match self
+            .draw_rule
+            .owed_after(self.total_cards_drawn)
             .checked_sub(self.total_cards_given_back)
         {
             Some(v) => v > 0,
@@ -467,18 +558,29 @@ impl RoundPlayer {
 
     /// Checks whether or not this player can still draw any more cards
     pub fn can_draw_cards(&self) -> bool {
-        self.total_cards_drawn < self.draws_n_cards() + self.bonus_draw_cards
+        self.cards_left_to_draw() > 0
+    }
+
+    /// Gets the number of cards this player can still draw this turn.
+    pub fn cards_left_to_draw(&self) -> u8 {
+        self.draws_n_cards().saturating_sub(self.total_cards_drawn)
     }
 
     /// Gets the number of cards this player can draw in total
     pub fn draws_n_cards(&self) -> u8 {
-        self.character.draws_n_cards()
+        self.draw_rule.draws.saturating_add(self.extra_draws)
     }
 
     /// Gets the number of cards this player should give back in total.
     pub fn gives_back_n_cards(&self) -> u8 {
-        // Give back one card for every 3 drawn
-        self.draws_n_cards() / 3
+        self.draw_rule.gives_back
+    }
+
+    /// Makes this player draw and give back cards according to `rules` instead of the default
+    /// rules of their character.
+    pub(crate) fn with_draw_rules(mut self, rules: &DrawRules) -> Self {
+        self.draw_rule = rules.for_character(self.character);
+        self
     }
 
     /// Gets this player's [`PlayableAssets`], which is a representation of how many assets of each
@@ -510,15 +612,35 @@ impl RoundPlayer {
         }
     }
 
+    /// Gets the amount of cash this player gains from `event`: one for every asset of a color in
+    /// [`Event::plus_gold`], minus one for every asset of a color in [`Event::minus_gold`].
+    pub fn event_cash(&self, event: &Event) -> i16 {
+        self.assets
+            .iter()
+            .map(|a| {
+                i16::from(event.plus_gold.contains(&a.color))
+                    - i16::from(event.minus_gold.contains(&a.color))
+            })
+            .sum()
+    }
+
+    /// Gives this player the cash they gain from `event`, or takes what they lose, never taking
+    /// more than they have. Returns how much their cash actually changed.
+    pub(crate) fn apply_event(&mut self, event: &Event) -> i16 {
+        let before = self.cash;
+        let amount = self.event_cash(event);
+        self.cash = match u16::try_from(amount) {
+            Ok(gain) => self.cash.checked_add(Cash(gain)).unwrap_or(Cash::MAX),
+            Err(_) => Cash(self.cash.0.saturating_sub(amount.unsigned_abs())),
+        };
+        (i32::from(self.cash.0) - i32::from(before.0)) as i16
+    }
+
     /// Gets the amount of cash this player gets based on the character they chose and the market
     /// condition of the color of that character.
     pub fn market_condition_bonus(&self, current_market: &Market) -> i16 {
         match self.character.color() {
-            Some(color) => match current_market.color_condition(color) {
-                MarketCondition::Plus => 1,
-                MarketCondition::Zero => 0,
-                MarketCondition::Minus => -1,
-            },
+            Some(color) => current_market.color_condition(color).multiplier() as i16,
             None => 0,
         }
     }
@@ -529,15 +651,12 @@ impl RoundPlayer {
     }
 
     /// Get bonus gold a player can get on their turn based on their characters color and their bought assets
-    pub fn get_bonus_cash_character(
-        &mut self,
-        current_market: &Market,
-    ) -> Result<u8, GetBonusCashError> {
+    pub fn get_bonus_cash_character(&mut self, current_market: &Market) -> Result<u8, GameError> {
         if self.has_gotten_bonus_cash {
-            return Err(GetBonusCashError::AlreadyGottenBonusCashThisTurn);
+            return Err(GetBonusCashError::AlreadyGottenBonusCashThisTurn.into());
         }
         if self.character.color().is_none() {
-            return Err(GetBonusCashError::InvalidCharacter);
+            return Err(GetBonusCashError::InvalidCharacter.into());
         }
         let asset_bonus = self.asset_bonus();
         let market_condition_bonus = self.market_condition_bonus(current_market);
@@ -546,15 +665,81 @@ impl RoundPlayer {
             self.has_gotten_bonus_cash = true;
             Ok(0)
         } else {
+            self.cash = self.cash.checked_add(bonus_cash as u8)?;
             self.has_gotten_bonus_cash = true;
-            self.cash += bonus_cash as u8;
             Ok(bonus_cash as u8)
         }
     }
 
     /// Starts this player's turn by givinig them their turn gold.
-    pub(crate) fn start_turn(&mut self) {
-        self.cash += self.turn_cash();
+    pub(crate) fn start_turn(&mut self) -> Result<(), GameError> {
+        self.cash = self.cash.checked_add(self.turn_cash())?;
+        Ok(())
+    }
+
+    /// Pays off as much of what this player owes `banker` as their cash allows, if they owe them
+    /// anything. A debt that is paid in full is forgotten.
+    pub(crate) fn pay_banker_debt(
+        &mut self,
+        banker: &mut RoundPlayer,
+    ) -> Result<Option<BankerDebtPayment>, GameError> {
+        let Some(idx) = self
+            .banker_debts
+            .iter()
+            .position(|d| d.banker_id == banker.id)
+        else {
+            return Ok(None);
+        };
+
+        let debt = &mut self.banker_debts[idx];
+        let paid_amount = match self.cash.covers(debt.amount) {
+            true => debt.amount,
+            // The cash is less than the debt, which fits in a `u8`
+            false => self.cash.get() as u8,
+        };
+        self.cash = self.cash.checked_sub(paid_amount)?;
+        banker.cash = banker.cash.checked_add(paid_amount)?;
+        debt.amount -= paid_amount;
+        let remaining_debt = debt.amount;
+        if remaining_debt == 0 {
+            self.banker_debts.remove(idx);
+        }
+
+        Ok(Some(BankerDebtPayment {
+            player_id: self.id,
+            banker_id: banker.id,
+            paid_amount,
+            remaining_debt,
+            new_player_cash: self.cash,
+            new_banker_cash: banker.cash,
+        }))
+    }
+
+    /// Creates a player with `character` that has not done anything yet this turn, which is how
+    /// a puzzle sets up the player solving it.
+    pub(crate) fn at_turn_start(
+        id: PlayerId,
+        name: String,
+        character: Character,
+        cash: u8,
+        hand: Vec<Either<Asset, Liability>>,
+        assets: Vec<Asset>,
+        liabilities: Vec<Liability>,
+    ) -> Self {
+        let player = SelectingCharactersPlayer {
+            id,
+            name,
+            cash: cash.into(),
+            assets,
+            liabilities,
+            character: Some(character),
+            hand,
+            banker_debts: vec![],
+            is_human: true,
+        };
+
+        // PANIC: the player was just given a character, which is all that can fail.
+        player.try_into().unwrap()
     }
 }
 
@@ -574,15 +759,17 @@ impl TryFrom<SelectingCharactersPlayer> for RoundPlayer {
                     character,
                     hand: player.hand,
                     cards_drawn: Vec::new(),
+                    draw_rule: DrawRules::default().for_character(character),
                     assets_to_play: playable_assets.total(),
                     playable_assets,
                     liabilities_to_play: character.playable_liabilities(),
                     total_cards_drawn: 0,
-                    bonus_draw_cards: 0,
                     total_cards_given_back: 0,
+                    extra_draws: 0,
                     has_used_ability: false,
                     has_gotten_bonus_cash: false,
                     was_first_to_six_assets: false,
+                    banker_debts: player.banker_debts,
                     is_human: player.is_human,
                 })
             }
@@ -599,9 +786,15 @@ impl From<&RoundPlayer> for PlayerInfo {
             hand: Self::hand(&player.hand),
             assets: player.assets.clone(),
             liabilities: player.liabilities.clone(),
-            cash: player.cash,
+            cash: PlayerCash::Exact(player.cash),
             character: Some(player.character),
             is_human: player.is_human,
+            total_asset_gold: Self::total_asset_gold(&player.assets),
+            total_debt: Self::total_debt(&player.liabilities),
+            market_value: None,
+            liability_cap: None,
+            banker_debt: Self::banker_debt(&player.banker_debts),
+            has_used_ability: player.has_used_ability,
         }
     }
 }
@@ -616,8 +809,10 @@ impl From<&RoundPlayer> for BankerTargetPlayer {
             liabilities: player.liabilities.clone(),
             character: player.character(),
             hand: player.hand.clone(),
+            draw_rule: player.draw_rule,
             liabilities_to_play: player.liabilities_to_play,
             was_first_to_six_assets: player.was_first_to_six_assets,
+            banker_debts: player.banker_debts.clone(),
             is_human: player.is_human(),
         }
     }
@@ -635,15 +830,17 @@ impl From<&BankerTargetPlayer> for RoundPlayer {
             character: player.character,
             hand: player.hand.clone(),
             cards_drawn: vec![],
-            bonus_draw_cards: 0,
+            draw_rule: player.draw_rule,
             assets_to_play: playable_assets.total(),
             playable_assets,
             liabilities_to_play: player.liabilities_to_play,
             total_cards_drawn: 0,
             total_cards_given_back: 0,
+            extra_draws: 0,
             has_used_ability: false,
             has_gotten_bonus_cash: false,
             was_first_to_six_assets: player.was_first_to_six_assets,
+            banker_debts: player.banker_debts.clone(),
             is_human: true,
         }
     }
@@ -661,7 +858,10 @@ pub(super) mod tests {
             gold_value: 1,
             silver_value: 1,
             ability: None,
+            on_buy: None,
+            #[cfg(feature = "images")]
             image_front_url: Default::default(),
+            #[cfg(feature = "images")]
             image_back_url: Default::default(),
         }
     }
@@ -670,7 +870,10 @@ pub(super) mod tests {
         Liability {
             value,
             rfr_type: LiabilityType::BankLoan,
+            issued_round: None,
+            #[cfg(feature = "images")]
             image_front_url: Default::default(),
+            #[cfg(feature = "images")]
             image_back_url: Default::default(),
         }
     }
@@ -692,9 +895,10 @@ pub(super) mod tests {
             name: Default::default(),
             assets: Default::default(),
             liabilities: Default::default(),
-            cash,
+            cash: cash.into(),
             character,
             hand: Default::default(),
+            banker_debts: Default::default(),
             is_human: Default::default(),
         }
     }
@@ -1045,6 +1249,45 @@ pub(super) mod tests {
         }
     }
 
+    #[test]
+    fn give_back_cards_custom_rules() {
+        let rules = DrawRules {
+            default: DrawRule {
+                draws: 2,
+                gives_back: 0,
+            },
+            head_rnd: DrawRule {
+                draws: 6,
+                gives_back: 2,
+            },
+        };
+
+        let mut player = round_player(Character::CEO, 0).with_draw_rules(&rules);
+        assert_eq!(player.draws_n_cards(), 2);
+        assert_eq!(player.gives_back_n_cards(), 0);
+        let mut assets = Deck::new(vec![asset(Color::Blue); 2]);
+        for _ in 0..2 {
+            assert_ok!(player.draw_asset(&mut assets));
+            assert!(!player.should_give_back_cards());
+        }
+        assert_err!(player.draw_asset(&mut assets));
+
+        let mut player = round_player(Character::HeadRnD, 0).with_draw_rules(&rules);
+        let mut assets = Deck::new(vec![asset(Color::Blue); 6]);
+        for _ in 0..3 {
+            assert_ok!(player.draw_asset(&mut assets));
+        }
+        assert!(player.should_give_back_cards());
+        assert_ok!(player.give_back_card(0));
+        assert!(!player.should_give_back_cards());
+        for _ in 0..3 {
+            assert_ok!(player.draw_asset(&mut assets));
+        }
+        assert_ok!(player.give_back_card(0));
+        assert!(!player.should_give_back_cards());
+        assert_eq!(player.hand.len(), 4);
+    }
+
     #[test]
     fn should_give_back_cards() {
         let mut round_player = round_player(Character::HeadRnD, 0);
@@ -1086,12 +1329,33 @@ pub(super) mod tests {
         }
     }
 
+    #[test]
+    fn event_cash() {
+        let event = Event {
+            title: "Event".to_owned(),
+            description: String::new(),
+            plus_gold: [Color::Red, Color::Blue].into(),
+            minus_gold: [Color::Green].into(),
+            skip_turn: None,
+        };
+        let mut player = round_player(Character::CEO, 1);
+        player.assets = vec![asset(Color::Red), asset(Color::Blue), asset(Color::Yellow)];
+        assert_eq!(player.event_cash(&event), 2);
+        assert_eq!(player.apply_event(&event), 2);
+        assert_eq!(player.cash, 3);
+
+        player.assets = vec![asset(Color::Green); 5];
+        assert_eq!(player.event_cash(&event), -5);
+        assert_eq!(player.apply_event(&event), -3);
+        assert_eq!(player.cash, 0);
+    }
+
     #[test]
     fn market_condition_bonus() {
         use MarketCondition::*;
 
         for character in Character::CHARACTERS {
-            for condition in [Minus, Zero, Plus] {
+            for condition in [DoubleMinus, Minus, Zero, Plus, DoublePlus] {
                 let round_player = round_player(character, 100);
 
                 let mut market = Market::default();
@@ -1113,9 +1377,11 @@ pub(super) mod tests {
 
                 let bonus = match character.color() {
                     Some(color) => match market.color_condition(color) {
+                        MarketCondition::DoublePlus => 2,
                         MarketCondition::Plus => 1,
                         MarketCondition::Zero => 0,
                         MarketCondition::Minus => -1,
+                        MarketCondition::DoubleMinus => -2,
                     },
                     None => 0,
                 };
@@ -1145,12 +1411,12 @@ pub(super) mod tests {
                 .map(|v| (v[0], v[1]))
                 .for_each(|(c1, c2)| {
                     let mut player = round_player.clone();
-                    let cash = player.cash;
+                    let cash = player.cash.get();
 
                     player.hand = hand_asset(c1);
-                    assert_ok!(player.play_card(0));
+                    assert_ok!(player.play_card(0, 1));
 
-                    assert_eq!(player.cash, cash - 1);
+                    assert_eq!(player.cash.get(), cash - 1);
                     assert_eq!(player.hand.len(), 0);
                     assert_eq!(player.assets.len(), 1);
 
@@ -1158,10 +1424,10 @@ pub(super) mod tests {
 
                     player.hand = hand_asset(c2);
                     assert_matches!(
-                        player.play_card(0),
-                        Err(PlayCardError::ExceedsMaximumAssets)
+                        player.play_card(0, 1),
+                        Err(GameError::PlayCard(PlayCardError::ExceedsMaximumAssets))
                     );
-                    assert_eq!(player.cash, cash - 1);
+                    assert_eq!(player.cash.get(), cash - 1);
                     assert_eq!(player.hand.len(), 1);
                     assert_eq!(player.assets.len(), 1);
                 });
@@ -1183,7 +1449,7 @@ pub(super) mod tests {
 
                 for (i, c) in colors.into_iter().enumerate() {
                     player.hand = hand_asset(c);
-                    assert_ok!(player.play_card(0), "bought assets: {i}");
+                    assert_ok!(player.play_card(0, 1), "bought assets: {i}");
                     assert_eq!(player.assets.len(), i + 1);
                     assert_eq!(player.cash, STARTING_CASH - 1 - i as u8);
                 }
@@ -1192,8 +1458,8 @@ pub(super) mod tests {
 
                 player.hand = hand_asset(extra);
                 assert_matches!(
-                    player.play_card(0),
-                    Err(PlayCardError::ExceedsMaximumAssets)
+                    player.play_card(0, 1),
+                    Err(GameError::PlayCard(PlayCardError::ExceedsMaximumAssets))
                 );
                 assert_eq!(player.assets.len(), 3);
                 assert_eq!(player.cash, STARTING_CASH - 3);
@@ -1215,15 +1481,15 @@ pub(super) mod tests {
 
                 for (i, c) in colors.into_iter().enumerate() {
                     player.hand = hand_asset(c);
-                    assert_ok!(player.play_card(0));
+                    assert_ok!(player.play_card(0, 1));
                     assert_eq!(player.assets.len(), i + 1);
                     assert_eq!(player.cash, STARTING_CASH - 1 - i as u8);
                 }
 
                 player.hand = hand_asset(extra);
                 assert_matches!(
-                    player.play_card(0),
-                    Err(PlayCardError::ExceedsMaximumAssets)
+                    player.play_card(0, 1),
+                    Err(GameError::PlayCard(PlayCardError::ExceedsMaximumAssets))
                 );
                 assert_eq!(player.assets.len(), 2);
                 assert_eq!(player.cash, STARTING_CASH - 2);
@@ -1237,14 +1503,14 @@ pub(super) mod tests {
             .for_each(|(c1, c2)| {
                 let mut player = round_player.clone();
                 player.hand = hand_asset(c1);
-                assert_ok!(player.play_card(0));
+                assert_ok!(player.play_card(0, 1));
                 assert_eq!(player.assets.len(), 1);
                 assert_eq!(player.cash, STARTING_CASH - 1);
 
                 player.hand = hand_asset(c2);
                 assert_matches!(
-                    player.play_card(0),
-                    Err(PlayCardError::ExceedsMaximumAssets)
+                    player.play_card(0, 1),
+                    Err(GameError::PlayCard(PlayCardError::ExceedsMaximumAssets))
                 );
                 assert_eq!(player.assets.len(), 1);
                 assert_eq!(player.cash, STARTING_CASH - 1);
@@ -1274,13 +1540,14 @@ pub(super) mod tests {
                         liability(LIABILITY_VALUE),
                         liability(LIABILITY_VALUE),
                     ],
-                    cash: 100,
+                    cash: Cash(100),
                     character: Some(Character::CFO),
                     hand: vec![
                         Either::Right(liability(LIABILITY_VALUE)),
                         Either::Right(liability(LIABILITY_VALUE)),
                         Either::Right(liability(LIABILITY_VALUE)),
                     ],
+                    banker_debts: Default::default(),
                     is_human: Default::default(),
                 };
                 let mut player = RoundPlayer::try_from(selecting_player).unwrap();
@@ -1291,16 +1558,23 @@ pub(super) mod tests {
                     let liabilities_len = player.liabilities.len();
                     match ir {
                         IR::Issue => {
-                            let liability = assert_ok!(player.play_card(0)).right().unwrap();
+                            let liability = assert_ok!(player.play_card(0, 1)).right().unwrap();
                             assert_eq!(liability.value, LIABILITY_VALUE);
-                            assert_eq!(player.cash, player_cash + LIABILITY_VALUE);
+                            assert_eq!(
+                                player.cash,
+                                assert_ok!(player_cash.checked_add(LIABILITY_VALUE))
+                            );
                             assert_eq!(player.hand.len(), hand_len - 1);
                             assert_eq!(player.liabilities.len(), liabilities_len + 1);
                         }
                         IR::Redeem => {
-                            let liability = assert_ok!(player.redeem_liability(0));
+                            let liability =
+                                assert_ok!(player.redeem_liability(0, 1, &GameConfig::default()));
                             assert_eq!(liability.value, LIABILITY_VALUE);
-                            assert_eq!(player.cash, player_cash - LIABILITY_VALUE);
+                            assert_eq!(
+                                player.cash,
+                                assert_ok!(player_cash.checked_sub(LIABILITY_VALUE))
+                            );
                             assert_eq!(player.liabilities.len(), liabilities_len - 1);
                         }
                     }
@@ -1311,22 +1585,24 @@ pub(super) mod tests {
                         let player_cash = player.cash;
                         player.hand = vec![];
                         assert_matches!(
-                            player.play_card(0),
-                            Err(PlayCardError::InvalidCardIndex(_))
+                            player.play_card(0, 1),
+                            Err(GameError::PlayCard(PlayCardError::InvalidCardIndex(_)))
                         );
                         assert_eq!(player.cash, player_cash);
 
                         player.hand = hand_liability(LIABILITY_VALUE);
                         assert_matches!(
-                            player.play_card(0),
-                            Err(PlayCardError::ExceedsMaximumLiabilities)
+                            player.play_card(0, 1),
+                            Err(GameError::PlayCard(
+                                PlayCardError::ExceedsMaximumLiabilities
+                            ))
                         );
                         assert_eq!(player.cash, player_cash);
                     }
                     IR::Redeem => {
                         player.liabilities = vec![liability(LIABILITY_VALUE)];
                         assert_matches!(
-                            player.redeem_liability(0),
+                            player.redeem_liability(0, 1, &GameConfig::default()),
                             Err(RedeemLiabilityError::ExceedsMaximumLiabilities)
                         );
                     }
@@ -1334,6 +1610,41 @@ pub(super) mod tests {
             });
     }
 
+    #[test]
+    fn bought_assets_trigger_their_effect() {
+        let mut player = round_player(Character::CEO, 10);
+        let draws = player.draws_n_cards();
+        let liabilities = player.liabilities_to_play();
+        let mut drawing = asset(Color::Red);
+        drawing.on_buy = Some(AssetEffect::DrawExtraCard);
+        let mut issuing = asset(Color::Blue);
+        issuing.on_buy = Some(AssetEffect::IssueExtraLiability);
+        player.hand = vec![Either::Left(drawing), Either::Left(issuing)];
+
+        assert_ok!(player.play_card(0, 1));
+        assert_eq!(player.draws_n_cards(), draws + 1);
+        assert_eq!(player.liabilities_to_play(), liabilities);
+
+        assert_ok!(player.play_card(0, 1));
+        assert_eq!(player.draws_n_cards(), draws + 1);
+        assert_eq!(player.liabilities_to_play(), liabilities + 1);
+    }
+
+    #[test]
+    fn cash_cannot_overflow() {
+        let mut player = round_player(Character::CEO, 0);
+        player._set_cash(Cash::MAX);
+        player.hand = hand_liability(1);
+
+        assert_matches!(player.play_card(0, 1), Err(GameError::CashOverflow));
+        assert_eq!(player.cash, Cash::MAX);
+        assert_eq!(player.hand.len(), 1);
+        assert!(player.liabilities.is_empty());
+
+        assert_matches!(player.start_turn(), Err(GameError::CashOverflow));
+        assert_eq!(player.cash, Cash::MAX);
+    }
+
     #[test]
     fn issue_liabilities_default() {
         const LIABILITY_VALUE: u8 = 10;
@@ -1349,30 +1660,149 @@ pub(super) mod tests {
             let hand_len = player.hand.len();
             let liabilities_len = player.liabilities.len();
 
-            let liability = assert_ok!(player.play_card(0)).right().unwrap();
+            let liability = assert_ok!(player.play_card(0, 1)).right().unwrap();
 
             assert_eq!(liability.value, LIABILITY_VALUE);
-            assert_eq!(player.cash, player_cash + LIABILITY_VALUE);
+            assert_eq!(
+                player.cash,
+                assert_ok!(player_cash.checked_add(LIABILITY_VALUE))
+            );
             assert_eq!(player.hand.len(), hand_len - 1);
             assert_eq!(player.liabilities.len(), liabilities_len + 1);
 
             let player_cash = player.cash;
 
-            assert_matches!(player.play_card(0), Err(PlayCardError::InvalidCardIndex(_)));
+            assert_matches!(
+                player.play_card(0, 1),
+                Err(GameError::PlayCard(PlayCardError::InvalidCardIndex(_)))
+            );
             assert_eq!(player.cash, player_cash);
 
             player.hand = hand_liability(LIABILITY_VALUE);
             assert_matches!(
-                player.play_card(0),
-                Err(PlayCardError::ExceedsMaximumLiabilities)
+                player.play_card(0, 1),
+                Err(GameError::PlayCard(
+                    PlayCardError::ExceedsMaximumLiabilities
+                ))
             );
             assert_eq!(player.cash, player_cash);
 
             assert_matches!(
-                player.redeem_liability(0),
+                player.redeem_liability(0, 1, &GameConfig::default()),
                 Err(RedeemLiabilityError::NotAllowedToRedeemLiability(_))
             );
             assert_eq!(player.cash, player_cash);
         }
     }
+
+    #[test]
+    fn swap_with_deck_bounds() {
+        let mut player = round_player(Character::Regulator, 0);
+        player.hand = [hand_asset(Color::Red), hand_liability(1)].concat();
+        let mut assets = Deck::new(vec![]);
+        let mut liabilities = Deck::new(vec![]);
+
+        assert_eq!(
+            player
+                .clone()
+                .swap_with_deck(vec![2], &mut assets, &mut liabilities),
+            Err(SwapError::InvalidCardIdxs)
+        );
+        let swap = assert_ok!(player.swap_with_deck(vec![1, 0], &mut assets, &mut liabilities));
+        assert_eq!(swap.count, AssetLiabilityCount::new(1, 1));
+        assert_eq!(player.hand, swap.new_cards);
+        assert_eq!(
+            player.hand,
+            [hand_asset(Color::Red), hand_liability(1)].concat()
+        );
+        assert_eq!(player.total_cards_drawn, 0);
+    }
+
+    #[test]
+    fn player_info_totals() {
+        let mut player = round_player(Character::CEO, 0);
+        player.assets = vec![asset(Color::Red), asset(Color::Blue)];
+        player.liabilities = vec![liability(2), liability(3)];
+
+        let market = Market {
+            red: MarketCondition::Plus,
+            blue: MarketCondition::Minus,
+            ..Default::default()
+        };
+
+        let info = PlayerInfo::from(&player);
+        assert_eq!(info.total_asset_gold, 2);
+        assert_eq!(info.total_debt, 5);
+        assert_eq!(info.market_value, None);
+        assert_eq!(info.with_market(&market).market_value, Some(2));
+    }
+
+    #[test]
+    fn banker_debt_carries_over() {
+        let mut target = round_player(Character::CEO, 1);
+        target.id = PlayerId(1);
+        let mut banker = BankerTargetPlayer::from(&round_player(Character::Banker, 0));
+
+        let mut bankrupt = BankerTargetPlayer::from(&target);
+        let paid = assert_ok!(bankrupt.go_bankrupt_for_banker(
+            5,
+            &mut banker,
+            Market::default(),
+            &mut Deck::default(),
+            &mut Deck::default(),
+        ));
+        assert_eq!(paid.paid_amount, 1);
+        assert_eq!(paid.remaining_debt, 4);
+        assert_eq!(
+            bankrupt.banker_debts(),
+            [BankerDebt {
+                banker_id: PlayerId(0),
+                amount: 4
+            }]
+        );
+
+        let mut target = RoundPlayer::from(bankrupt);
+        let mut banker = RoundPlayer::from(banker);
+        assert_eq!(PlayerInfo::from(&target).banker_debt, 4);
+
+        target._set_cash(3);
+        let payment = assert_some!(assert_ok!(target.pay_banker_debt(&mut banker)));
+        assert_eq!(payment.paid_amount, 3);
+        assert_eq!(payment.remaining_debt, 1);
+        assert_eq!(payment.new_player_cash, Cash::ZERO);
+        assert_eq!(payment.new_banker_cash, Cash(4));
+
+        target._set_cash(10);
+        let payment = assert_some!(assert_ok!(target.pay_banker_debt(&mut banker)));
+        assert_eq!(payment.paid_amount, 1);
+        assert_eq!(payment.remaining_debt, 0);
+        assert_eq!(target.cash(), Cash(9));
+        assert!(target.banker_debts().is_empty());
+        assert_none!(assert_ok!(target.pay_banker_debt(&mut banker)));
+    }
+
+    #[test]
+    fn redemption_interest() {
+        let config = GameConfig {
+            redemption_interest: true,
+            ..Default::default()
+        };
+        let mut player = round_player(Character::CFO, 0);
+        player.hand = hand_liability(5);
+
+        let issued = assert_ok!(player.play_card(0, 2)).right().unwrap();
+        assert_eq!(issued.issued_round, Some(2));
+        assert_eq!(issued.redemption_cost(2, &config), 5);
+        assert_eq!(issued.redemption_cost(5, &config), 11);
+        assert_eq!(issued.redemption_cost(5, &GameConfig::default()), 5);
+
+        player._set_cash(10);
+        assert_matches!(
+            player.redeem_liability(0, 5, &config),
+            Err(RedeemLiabilityError::NotEnoughCash { cost: 11, .. })
+        );
+        let redeemed = assert_ok!(player.redeem_liability(0, 4, &config));
+        assert_eq!(redeemed.value, 5);
+        assert_eq!(player.cash(), Cash(1));
+    }
 }
@@ -5,15 +5,16 @@ use crate::player::*;
 /// The player type that corresponds to the
 /// [`SelectingCharacters`](crate::game::SelectingCharacters) stage of the game. In this stage,
 /// players may not have a selected character yet.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SelectingCharactersPlayer {
     pub(super) id: PlayerId,
     pub(super) name: String,
-    pub(super) cash: u8,
+    pub(super) cash: Cash,
     pub(super) assets: Vec<Asset>,
     pub(super) liabilities: Vec<Liability>,
     pub(super) character: Option<Character>,
     pub(super) hand: Vec<Either<Asset, Liability>>,
+    pub(super) banker_debts: Vec<BankerDebt>,
     pub(super) is_human: bool,
 }
 
@@ -29,7 +30,7 @@ impl SelectingCharactersPlayer {
     }
 
     /// Gets the amount of cash of the player
-    pub fn cash(&self) -> u8 {
+    pub fn cash(&self) -> Cash {
         self.cash
     }
 
@@ -53,6 +54,11 @@ impl SelectingCharactersPlayer {
         &self.hand
     }
 
+    /// Gets what this player still owes to bankers.
+    pub fn banker_debts(&self) -> &[BankerDebt] {
+        &self.banker_debts
+    }
+
     /// Gets the human state of this player
     pub fn is_human(&self) -> bool {
         self.is_human
@@ -69,7 +75,7 @@ impl SelectingCharactersPlayer {
         id: PlayerId,
         assets: [Asset; 2],
         liabilities: [Liability; 2],
-        cash: u8,
+        cash: Cash,
         is_human: bool,
     ) -> Self {
         let hand = assets
@@ -86,6 +92,7 @@ impl SelectingCharactersPlayer {
             liabilities: vec![],
             character: None,
             hand,
+            banker_debts: vec![],
             is_human,
         }
     }
@@ -116,6 +123,7 @@ impl From<RoundPlayer> for SelectingCharactersPlayer {
             liabilities: player.liabilities,
             character: None,
             hand: player.hand,
+            banker_debts: player.banker_debts,
             is_human: player.is_human,
         }
     }
@@ -129,9 +137,15 @@ impl From<&SelectingCharactersPlayer> for PlayerInfo {
             hand: Self::hand(&player.hand),
             assets: player.assets.clone(),
             liabilities: player.liabilities.clone(),
-            cash: player.cash,
+            cash: PlayerCash::Exact(player.cash),
             character: player.character,
             is_human: player.is_human,
+            total_asset_gold: Self::total_asset_gold(&player.assets),
+            total_debt: Self::total_debt(&player.liabilities),
+            market_value: None,
+            liability_cap: None,
+            banker_debt: Self::banker_debt(&player.banker_debts),
+            has_used_ability: false,
         }
     }
 }
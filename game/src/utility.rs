@@ -1,5 +1,91 @@
 //! This file contains utility code used throughout the project.
 
+/// Hashes the [`Debug`](std::fmt::Debug) representation of `value` with 64-bit FNV-1a, which
+/// unlike the hasher of the standard library is the same on every run. The hash is only stable
+/// between builds of the same version of the game, since derived `Debug` output may change.
+pub(crate) fn stable_hash(value: &impl std::fmt::Debug) -> u64 {
+    use std::fmt::Write;
+
+    struct Fnv(u64);
+
+    impl Write for Fnv {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            for byte in s.bytes() {
+                self.0 ^= byte as u64;
+                self.0 = self.0.wrapping_mul(0x100000001b3);
+            }
+            Ok(())
+        }
+    }
+
+    let mut hasher = Fnv(0xcbf29ce484222325);
+    // PANIC: writing to the hasher itself never fails, and every `Debug` implementation this is
+    // used with is derived, so this is safe to unwrap.
+    write!(hasher, "{value:?}").unwrap();
+    hasher.0
+}
+
+#[cfg(feature = "shuffle")]
+thread_local! {
+    static SEEDED_RNG: std::cell::RefCell<Option<rand::rngs::SmallRng>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Runs `f` with every random choice the game makes on this thread, like shuffling the decks,
+/// drawn from an rng seeded with `seed`. The same actions taken in the same order then always lead
+/// to exactly the same game. Without the `shuffle` feature the game makes no random choices, so
+/// `f` is simply called.
+///
+/// # Examples
+///
+/// ```
+/// # use game::{cards::GameData, game::GameState, utility::with_seed};
+/// let start = || {
+///     let mut state = GameState::new();
+///     let lobby = state.lobby_mut().unwrap();
+///     for i in 0..4 {
+///         lobby.join(format!("player {i}")).unwrap();
+///     }
+///     state.start_game("../assets/cards/boardgame.json").unwrap();
+///     state.state_hash()
+/// };
+///
+/// assert_eq!(with_seed(42, start), with_seed(42, start));
+/// ```
+pub fn with_seed<R>(seed: u64, f: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "shuffle")]
+    {
+        use rand::SeedableRng;
+
+        /// Puts back the rng that was used before, even if `f` panics.
+        struct Restore(Option<rand::rngs::SmallRng>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SEEDED_RNG.set(self.0.take());
+            }
+        }
+
+        let _restore = Restore(SEEDED_RNG.replace(Some(rand::rngs::SmallRng::seed_from_u64(seed))));
+        f()
+    }
+    #[cfg(not(feature = "shuffle"))]
+    {
+        let _ = seed;
+        f()
+    }
+}
+
+/// Calls `f` with the rng the game should make its random choices with, which is the seeded rng
+/// of [`with_seed`] if set.
+#[cfg(feature = "shuffle")]
+pub(crate) fn with_rng<R>(f: impl FnOnce(&mut dyn rand::RngCore) -> R) -> R {
+    SEEDED_RNG.with_borrow_mut(|seeded| match seeded {
+        Some(rng) => f(rng),
+        None => f(&mut rand::rng()),
+    })
+}
+
 pub mod serde_asset_liability {
     //! Includes a tagged wrapper around `Either<Asset, Liability>` to make the serialized json
     //! nicer to interact with.
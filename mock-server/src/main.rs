@@ -0,0 +1,34 @@
+use mock_server::{scenario::Scenario, server::serve};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+/// The port the mock server listens on, unless `MOCK_PORT` is set. It is the same port as the
+/// real server, so frontends can be pointed at either without changes.
+const DEFAULT_PORT: u16 = 3000;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| format!("{}=debug", env!("CARGO_CRATE_NAME")).into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let port = std::env::var("MOCK_PORT").map_or(DEFAULT_PORT, |port| {
+        port.parse()
+            .expect("ENV MOCK_PORT should be a valid port number")
+    });
+    let scenario = Scenario::from_env();
+
+    // PANIC: this crashes if the port is not available, in which case there is nothing to serve.
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+        .await
+        .unwrap();
+    // PANIC: since we know the listener to have a valid address, this cannot crash.
+    tracing::info!(
+        "mock server listening on {}",
+        listener.local_addr().unwrap()
+    );
+    serve(listener, scenario).await;
+}
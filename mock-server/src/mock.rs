@@ -0,0 +1,312 @@
+//! File containing [`MockGame`], the single game the mock server runs, with bots playing for
+//! every seat no person took.
+
+use game::{
+    bot::{GreedyStrategy, MAX_SIMULATED_ACTIONS, Strategy, acting_player},
+    errors::GameError,
+    game::{GameState, Round, normalize_username},
+    player::PlayerId,
+};
+use responses::*;
+use server::request_handler::*;
+
+use std::collections::HashMap;
+
+use crate::scenario::Scenario;
+
+/// The responses for each player that follow a request, in the order they are to be sent.
+pub type Outbox = Vec<(PlayerId, Vec<UniqueResponse>)>;
+
+/// A game played by whoever connects to the mock server, together with the bots of its
+/// [`Scenario`].
+#[derive(Debug)]
+pub struct MockGame {
+    game: GameState,
+    scenario: Scenario,
+    /// The usernames of the bots with the strategy they play with.
+    bots: Vec<(String, GreedyStrategy)>,
+    /// Whether the commands of the scenario were run already.
+    scripted: bool,
+}
+
+impl MockGame {
+    /// Creates a lobby that is set up for `scenario`.
+    pub fn new(scenario: Scenario) -> Self {
+        let mut game = GameState::new();
+        if let GameState::Lobby(lobby) = &mut game {
+            lobby.set_config(scenario.config);
+            load_game_data(lobby);
+        }
+
+        Self {
+            game,
+            scenario,
+            bots: Vec::new(),
+            scripted: false,
+        }
+    }
+
+    /// Gets the game that is being played.
+    pub fn state(&self) -> &GameState {
+        &self.game
+    }
+
+    /// Lets the player with `username` join the lobby, or rejoin the game if they left it. Bots
+    /// take their seats right after the first player joined.
+    pub fn join(&mut self, username: &str) -> Result<(PlayerId, Outbox), ResponseError> {
+        let username = normalize_username(username);
+
+        let id = match &mut self.game {
+            GameState::Lobby(lobby) => lobby.join(username.clone()).map_err(GameError::from)?.id(),
+            GameState::SelectingCharacters(selecting) => {
+                let id = selecting.player_by_name(&username)?.id();
+                selecting.rejoin(id)?.id()
+            }
+            GameState::Round(round) => {
+                let id = round.player_by_name(&username)?.id();
+                round.rejoin(id)?.id()
+            }
+            _ => return Err(ResponseError::GameAlreadyStarted),
+        };
+
+        if let GameState::Lobby(lobby) = &mut self.game {
+            while self.bots.len() < self.scenario.bots {
+                let name = format!("bot {}", self.bots.len() + 1);
+                if let Err(error) = lobby.join(name.clone()) {
+                    tracing::warn!(%error, "Could not seat {name}");
+                    break;
+                }
+                self.bots.push((name, GreedyStrategy::new()));
+            }
+        }
+
+        Ok((id, self.lobby_changed(username)))
+    }
+
+    /// Lets the player with `username` leave. They can join again later, unless the game was still
+    /// in the lobby.
+    pub fn leave(&mut self, username: &str) -> Outbox {
+        let Ok(id) = player_id(&self.game, username) else {
+            return Outbox::new();
+        };
+
+        let result = match &mut self.game {
+            GameState::Lobby(lobby) => {
+                lobby.leave(username);
+                return self.lobby_changed(username.to_owned());
+            }
+            GameState::SelectingCharacters(selecting) => selecting.leave(id),
+            GameState::Round(round) => round.leave(id),
+            _ => Ok(()),
+        };
+        if let Err(error) = result {
+            tracing::error!(%error, "Could not let {username} leave");
+        }
+
+        Outbox::new()
+    }
+
+    /// Handles `request` of the player with `username`, after which the bots take their turns.
+    /// Debug commands are always accepted, since nobody plays the mock server for real. Votes are
+    /// not supported.
+    pub fn request(
+        &mut self,
+        username: &str,
+        request: FrontendRequest,
+    ) -> Result<(DirectResponse, Outbox), ResponseError> {
+        let mut outbox = Outbox::new();
+
+        let Response(InternalResponse(players, _), direct) = match request {
+            FrontendRequest::Dev { command } => {
+                let player_id = player_id(&self.game, username)?;
+                command.run(&mut self.game)?;
+                outbox.extend(self.everyone(UniqueResponse::DevCommandUsed { player_id, command }));
+                resync(&self.game, player_id)?
+            }
+            FrontendRequest::ProposeVote { .. } | FrontendRequest::CastVote { .. } => {
+                return Err(ResponseError::InvalidData);
+            }
+            FrontendRequest::KickPlayer { target } => {
+                let id = player_id(&self.game, username)?;
+                let lobby = self.game.lobby_mut()?;
+                let kicked = lobby.kick(id, target)?.name().to_owned();
+                let lobby_changed = UniqueResponse::PlayersInLobby {
+                    changed_player: kicked.clone(),
+                    usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
+                    host_id: lobby.host().map(|p| p.id()),
+                };
+                outbox.extend(self.everyone(UniqueResponse::PlayerKicked {
+                    player_id: target,
+                    username: kicked,
+                }));
+                outbox.extend(self.everyone(lobby_changed));
+                Response(
+                    InternalResponse(HashMap::new(), vec![]),
+                    DirectResponse::YouKickedPlayer { player_id: target },
+                )
+            }
+            FrontendRequest::RequestHint => hint(&self.game, player_id(&self.game, username)?)?,
+            FrontendRequest::RequestFullState => {
+                full_state(&self.game, player_id(&self.game, username)?)?
+            }
+            request => {
+                let turn_nonce = request.turn_nonce();
+                match request.into_action() {
+                    Some(action) => handle_action(&mut self.game, username, action, turn_nonce)?,
+                    None => resync(&self.game, player_id(&self.game, username)?)?,
+                }
+            }
+        };
+        outbox.extend(players);
+        outbox.extend(self.play_bots());
+
+        Ok((direct, outbox))
+    }
+
+    /// Lets the bots act for as long as it is the turn of one of them, and runs the commands of the
+    /// scenario once the first round starts.
+    fn play_bots(&mut self) -> Outbox {
+        let mut outbox = self.run_script();
+
+        for _ in 0..MAX_SIMULATED_ACTIONS {
+            let Some(id) = acting_player(&self.game) else {
+                break;
+            };
+            let Ok(name) = player_name(&self.game, id) else {
+                break;
+            };
+            let Some((name, strategy)) = self.bots.iter_mut().find(|(bot, _)| bot == name) else {
+                break;
+            };
+            let Some(action) = strategy.next_action(&self.game, id) else {
+                break;
+            };
+
+            // Bots act on the turn they see
+            let name = name.clone();
+            let turn_nonce = self.game.round().ok().map(Round::turn_nonce);
+            match handle_action(&mut self.game, &name, action, turn_nonce) {
+                Ok(Response(InternalResponse(players, _), _)) => outbox.extend(players),
+                Err(error) => {
+                    tracing::error!(%error, "{name} could not take their action");
+                    break;
+                }
+            }
+            outbox.extend(self.run_script());
+        }
+
+        outbox
+    }
+
+    /// Runs the commands of the scenario if the first round just started, and lets everyone know
+    /// the game changed.
+    fn run_script(&mut self) -> Outbox {
+        if self.scripted || self.game.round().is_err() {
+            return Outbox::new();
+        }
+        self.scripted = true;
+
+        let Some(host) = self.game.round().ok().and_then(|round| round.host()) else {
+            return Outbox::new();
+        };
+        let mut outbox = Outbox::new();
+        for command in self.scenario.commands.clone() {
+            match command.run(&mut self.game) {
+                Ok(()) => outbox.extend(self.everyone(UniqueResponse::DevCommandUsed {
+                    player_id: host,
+                    command,
+                })),
+                Err(error) => tracing::error!(%error, "Could not run {command:?}"),
+            }
+        }
+
+        outbox
+    }
+
+    /// Lets everyone know who is in the lobby after `changed_player` joined or left.
+    fn lobby_changed(&self, changed_player: String) -> Outbox {
+        match &self.game {
+            GameState::Lobby(lobby) => self.everyone(UniqueResponse::PlayersInLobby {
+                changed_player,
+                usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
+                host_id: lobby.host().map(|p| p.id()),
+            }),
+            _ => Outbox::new(),
+        }
+    }
+
+    /// Sends `response` to every player.
+    fn everyone(&self, response: UniqueResponse) -> Outbox {
+        self.game
+            .player_ids()
+            .into_iter()
+            .map(|id| (id, vec![response.clone()]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+    use game::cards::GameData;
+
+    fn started(scenario: Scenario) -> (MockGame, PlayerId) {
+        let mut mock = MockGame::new(scenario);
+        let (id, outbox) = assert_ok!(mock.join("player"));
+        assert_eq!(outbox.len(), 4);
+        assert_ok!(mock.request("player", FrontendRequest::StartGame));
+        (mock, id)
+    }
+
+    #[test]
+    fn bots_play_until_player_acts() {
+        let (mut mock, id) = started(Scenario {
+            bots: 3,
+            ..Default::default()
+        });
+
+        while let Ok(selecting) = mock.state().selecting_characters() {
+            assert_eq!(acting_player(mock.state()), Some(id));
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(mock.request("player", FrontendRequest::SelectCharacter { character }));
+        }
+        assert_eq!(acting_player(mock.state()), Some(id));
+    }
+
+    #[test]
+    fn scenario_runs_when_round_starts() {
+        let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        let title = data
+            .market_deck
+            .deck
+            .iter()
+            .find_map(|card| card.as_ref().left())
+            .unwrap()
+            .title
+            .clone();
+        let (mut mock, id) = started(Scenario {
+            bots: 3,
+            commands: vec![DevRequest::ForceMarket {
+                title: title.clone(),
+            }],
+            ..Default::default()
+        });
+
+        let mut outbox = Outbox::new();
+        while let Ok(selecting) = mock.state().selecting_characters() {
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            let (_, sent) =
+                assert_ok!(mock.request("player", FrontendRequest::SelectCharacter { character }));
+            outbox.extend(sent);
+        }
+        assert!(outbox.iter().any(|(to, batch)| *to == id
+            && batch.iter().any(|response| matches!(
+                response,
+                UniqueResponse::DevCommandUsed {
+                    command: DevRequest::ForceMarket { title: forced },
+                    ..
+                } if *forced == title
+            ))));
+    }
+}
@@ -0,0 +1,83 @@
+//! Scripted setups for the mock server, so a frontend developer can jump straight to the situation
+//! they are working on.
+
+use game::game::GameConfig;
+use responses::DevRequest;
+use serde::Deserialize;
+
+/// How the game of the mock server is set up.
+///
+/// Scenarios are written as json, where every field can be left out:
+///
+/// ```json
+/// {
+///     "bots": 3,
+///     "config": { "hide_cash": true },
+///     "commands": [
+///         { "command": "SetCash", "data": { "player_id": 0, "cash": 40 } },
+///         { "command": "ForceMarket", "data": { "title": "Recession" } }
+///     ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    /// How many bots take a seat once the first player joined. The first player stays host, so
+    /// they decide when the game starts.
+    pub bots: usize,
+    /// The options the game is played with.
+    pub config: GameConfig,
+    /// Debug commands that are run as soon as the first round starts.
+    pub commands: Vec<DevRequest>,
+}
+
+impl Scenario {
+    /// Reads the scenario from the json file at `MOCK_SCENARIO`, if set. `MOCK_BOTS` overrides
+    /// how many bots take a seat.
+    pub fn from_env() -> Self {
+        let mut scenario = match std::env::var("MOCK_SCENARIO") {
+            Ok(path) => {
+                let json = std::fs::read_to_string(&path)
+                    .unwrap_or_else(|error| panic!("Could not read scenario '{path}': {error}"));
+                serde_json::from_str(&json)
+                    .unwrap_or_else(|error| panic!("Scenario '{path}' is not valid: {error}"))
+            }
+            Err(_) => Self::default(),
+        };
+
+        if let Ok(bots) = std::env::var("MOCK_BOTS") {
+            scenario.bots = bots
+                .parse()
+                .expect("ENV MOCK_BOTS should be a positive integer");
+        }
+
+        scenario
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::player::PlayerId;
+
+    #[test]
+    fn parses_scenario() {
+        let scenario: Scenario = serde_json::from_str(
+            r#"{
+                "bots": 2,
+                "commands": [{ "command": "SetCash", "data": { "player_id": 1, "cash": 40 } }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(scenario.bots, 2);
+        assert_eq!(scenario.config, GameConfig::default());
+        assert_eq!(
+            scenario.commands,
+            [DevRequest::SetCash {
+                player_id: PlayerId(1),
+                cash: 40
+            }]
+        );
+    }
+}
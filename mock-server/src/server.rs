@@ -0,0 +1,323 @@
+//! The websocket side of the mock server. Clients connect and send requests exactly like they do
+//! with the real server, except that every channel leads to the same [`MockGame`].
+
+use axum::{
+    Json, Router,
+    extract::{
+        Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+};
+use futures_util::{sink::SinkExt, stream::StreamExt};
+use game::{
+    game::{GameState, Lobby},
+    player::PlayerId,
+};
+use responses::*;
+use serde::Deserialize;
+use server::{
+    queue::Encoding,
+    request_handler::{load_game_data, player_name},
+    server::SUPPORTED_CAPABILITIES,
+};
+use tokio::{net::TcpListener, sync::mpsc};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    mock::{MockGame, Outbox},
+    scenario::Scenario,
+};
+
+/// Everything the connections to the mock server share.
+#[derive(Debug)]
+struct Mock {
+    game: MockGame,
+    /// The connected players by username, with how their messages are encoded, whether they
+    /// negotiated [`Capabilities::BATCHES`] and where to put them.
+    clients: HashMap<String, (Encoding, bool, mpsc::UnboundedSender<Message>)>,
+}
+
+impl Mock {
+    /// Queues every batch in `outbox` for the player it is meant for, if they are connected.
+    fn dispatch(&self, outbox: Outbox) {
+        for (id, batch) in outbox {
+            let Ok(name) = player_name(self.game.state(), id) else {
+                continue;
+            };
+            let Some((encoding, batching, tx)) = self.clients.get(name) else {
+                continue;
+            };
+            if *batching {
+                let _ = tx.send(encoding.encode(Batched::from(batch)));
+            } else {
+                for response in batch {
+                    let _ = tx.send(encoding.encode(response));
+                }
+            }
+        }
+    }
+}
+
+/// Serves a game set up for `scenario` on `listener`, at the same `/websocket` route as the real
+/// server. Made-up games can be fetched from `/fake`, see [`FakeQuery`].
+pub async fn serve(listener: TcpListener, scenario: Scenario) {
+    let mock = Arc::new(Mutex::new(Mock {
+        game: MockGame::new(scenario),
+        clients: HashMap::new(),
+    }));
+
+    let app = Router::new()
+        .route("/websocket", get(websocket_handler))
+        .route("/fake", get(fake_handler))
+        .with_state(mock);
+
+    // PANIC: Although this returns a result type, as specified by the axum documentation this will
+    // never actually complete or return an error
+    axum::serve(listener, app).await.unwrap();
+}
+
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    State(mock): State<Arc<Mutex<Mock>>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(|socket| websocket(socket, mock))
+}
+
+/// Which made-up game `/fake` responds with. Every field can be left out of the query.
+#[derive(Debug, Deserialize)]
+pub struct FakeQuery {
+    /// How many players the game has, 4 if left out.
+    #[serde(default = "default_fake_players")]
+    pub players: usize,
+    /// The seed the game is played with. The same seed always gives the same responses.
+    #[serde(default)]
+    pub seed: u64,
+    /// Only the responses sent to this player, instead of those sent to everyone.
+    pub player: Option<u8>,
+}
+
+fn default_fake_players() -> usize {
+    4
+}
+
+/// Plays a made-up game between bots, and responds with the json encoded list of every
+/// [`FakeResponse`] it produced.
+async fn fake_handler(
+    Query(query): Query<FakeQuery>,
+) -> Result<Json<Vec<FakeResponse>>, StatusCode> {
+    let mut lobby = Lobby::new();
+    load_game_data(&mut lobby);
+    let data = lobby
+        .game_data()
+        .cloned()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let game = FakeGame::new(data, query.players, query.seed).map_err(|error| {
+        tracing::debug!(%error, "Could not fake a game");
+        StatusCode::BAD_REQUEST
+    })?;
+    let responses = game
+        .filter(|fake| query.player.is_none_or(|id| fake.player_id == PlayerId(id)))
+        .collect();
+
+    Ok(Json(responses))
+}
+
+fn parse_request(message: &Message) -> Option<FrontendRequest> {
+    match message {
+        Message::Text(text) => serde_json::from_str(text).ok(),
+        Message::Binary(bytes) => from_msgpack(bytes).ok(),
+        _ => None,
+    }
+}
+
+async fn websocket(stream: WebSocket, mock: Arc<Mutex<Mock>>) {
+    let (mut sender, mut receiver) = stream.split();
+
+    let (username, channel, encoding, capabilities) = loop {
+        match receiver.next().await {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                Ok(Connect::Connect {
+                    username,
+                    channel,
+                    binary_frames,
+                    capabilities,
+                    ..
+                }) => {
+                    let capabilities = capabilities.map(|c| c & SUPPORTED_CAPABILITIES);
+                    let binary =
+                        capabilities.is_some_and(|c| c.contains(Capabilities::BINARY_FRAMES));
+                    let encoding = match binary_frames || binary {
+                        true => Encoding::MessagePack,
+                        false => Encoding::Json,
+                    };
+                    break (username, channel, encoding, capabilities);
+                }
+                // The mock server has no delayed feed, so it cannot be watched
+                Ok(Connect::Spectate { .. }) | Err(_) => {
+                    let error = DirectResponse::from(ResponseError::InvalidData);
+                    let _ = sender.send(Encoding::Json.encode(error)).await;
+                }
+            },
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+            Some(Ok(_)) => continue,
+        }
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let joined = {
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        let mut mock = mock.lock().unwrap();
+        mock.game.join(&username).map(|(id, outbox)| {
+            // PANIC: the player just joined, so they have a name
+            let username = player_name(mock.game.state(), id).unwrap().to_owned();
+            let _ = tx.send(encoding.encode(DirectResponse::YouJoinedGame {
+                username: username.clone(),
+                channel,
+            }));
+            if let Some(capabilities) = capabilities {
+                let negotiated = DirectResponse::YouNegotiatedCapabilities { capabilities };
+                let _ = tx.send(encoding.encode(negotiated));
+            }
+            if !matches!(mock.game.state(), GameState::Lobby(_)) {
+                let _ = tx.send(encoding.encode(DirectResponse::YouRejoined));
+            }
+            let batching = capabilities.is_some_and(|c| c.contains(Capabilities::BATCHES));
+            mock.clients
+                .insert(username.clone(), (encoding, batching, tx.clone()));
+            mock.dispatch(outbox);
+            username
+        })
+    };
+    let mut username = match joined {
+        Ok(username) => username,
+        Err(error) => {
+            let _ = sender
+                .send(encoding.encode(DirectResponse::from(error)))
+                .await;
+            return;
+        }
+    };
+    tracing::debug!("{username} connected");
+
+    let write_task = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            if sender.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(Ok(message)) = receiver.next().await {
+        if let Message::Close(_) = message {
+            break;
+        }
+        let Some(request) = parse_request(&message) else {
+            continue;
+        };
+        tracing::debug!("{username} sent {request:?}");
+
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        let mut mock = mock.lock().unwrap();
+        match mock.game.request(&username, request) {
+            Ok((direct, outbox)) => {
+                // Responses are dispatched by username, so the connection moves along with it
+                if let DirectResponse::YouChangedUsername { username: changed } = &direct
+                    && let Some(client) = mock.clients.remove(&username)
+                {
+                    username = changed.clone();
+                    mock.clients.insert(username.clone(), client);
+                }
+                let _ = tx.send(encoding.encode(direct));
+                mock.dispatch(outbox);
+            }
+            Err(error) => {
+                let _ = tx.send(encoding.encode(DirectResponse::from(error)));
+            }
+        }
+    }
+
+    tracing::debug!("{username} disconnected");
+    {
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        let mut mock = mock.lock().unwrap();
+        mock.clients.remove(&username);
+        let outbox = mock.game.leave(&username);
+        mock.dispatch(outbox);
+    }
+    write_task.abort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::{connect_async, tungstenite};
+
+    async fn receive(
+        stream: &mut (impl StreamExt<Item = Result<tungstenite::Message, tungstenite::Error>> + Unpin),
+    ) -> serde_json::Value {
+        let message = stream.next().await.unwrap().unwrap();
+        serde_json::from_str(&message.into_text().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fakes_games() {
+        let query = FakeQuery {
+            players: 4,
+            seed: 2,
+            player: Some(1),
+        };
+        let Json(responses) = fake_handler(Query(query)).await.unwrap();
+
+        assert!(!responses.is_empty());
+        assert!(responses.iter().all(|fake| fake.player_id == PlayerId(1)));
+    }
+
+    #[tokio::test]
+    async fn plays_against_bots() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/websocket", listener.local_addr().unwrap());
+        tokio::spawn(serve(
+            listener,
+            Scenario {
+                bots: 3,
+                ..Default::default()
+            },
+        ));
+
+        let (mut stream, _) = connect_async(url).await.unwrap();
+        let connect = serde_json::json!({
+            "action": "Connect",
+            "data": { "username": "player", "channel": "anything" }
+        });
+        stream.send(connect.to_string().into()).await.unwrap();
+
+        assert_eq!(receive(&mut stream).await["action"], "YouJoinedGame");
+        let lobby = receive(&mut stream).await;
+        assert_eq!(lobby["action"], "PlayersInLobby");
+        assert_eq!(
+            lobby["data"]["usernames"],
+            serde_json::json!(["player", "bot 1", "bot 2", "bot 3"])
+        );
+
+        stream
+            .send(
+                serde_json::json!({ "action": "StartGame" })
+                    .to_string()
+                    .into(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(receive(&mut stream).await["action"], "YouStartedGame");
+    }
+}
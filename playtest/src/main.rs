@@ -0,0 +1,203 @@
+//! A terminal client to playtest rule changes without the web frontend. It plays a local game
+//! against bots, where moves are typed in the same short form a [`Notation`] writes them down in.
+
+use std::{
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use game::{
+    bot::{GreedyStrategy, Strategy, acting_player},
+    cards::GameData,
+    errors::GameError,
+    game::{GameState, Notation, PlayerAction},
+    player::PlayerId,
+    utility::with_seed,
+};
+
+/// How many bots play along, unless `PLAYTEST_BOTS` is set.
+const DEFAULT_BOTS: usize = 3;
+
+/// The name of the player at the keyboard, who always sits in the first seat.
+const HUMAN: &str = "you";
+
+const HELP: &str = "\
+Type a move the way a notation writes it down, for example:
+  pick CEO                 ban CFO                  draw asset
+  return \"Cool Asset\"      buy \"Cool Asset\"         issue BankLoan:2
+  redeem BankLoan:2        ability                  bonus
+  fire CFO                 terminate CFO            swap P1
+  swap deck \"Cool Asset\"   divest P1 \"Cool Asset\"   confirm-divest
+  sell \"Cool Asset\"        raise TradeCredit:1      pay 5
+  end
+Or one of these commands:
+  show      shows the whole game again
+  hint      suggests a move
+  notation  prints the game so far
+  help      prints this text
+  quit      stops playing";
+
+/// A game between the player at the keyboard and bots, which is written down as it is played.
+struct Playtest {
+    state: GameState,
+    notation: Notation,
+    /// The strategy of every bot, by the id of the bot minus one.
+    bots: Vec<GreedyStrategy>,
+    /// The seed the game is played with, if any, so it can be played the same way again.
+    seed: Option<u64>,
+}
+
+impl Playtest {
+    fn new(data: GameData, bots: usize, seed: Option<u64>) -> Result<Self, GameError> {
+        let mut state = GameState::new();
+        let lobby = state.lobby_mut()?;
+        lobby.set_game_data(data);
+        lobby.join(HUMAN.to_owned())?;
+        for i in 1..=bots {
+            lobby.join(format!("bot {i}"))?;
+        }
+        let names = lobby.usernames().iter().map(ToString::to_string).collect();
+
+        let mut playtest = Self {
+            state,
+            notation: Notation::new(names),
+            bots: (0..bots).map(|_| GreedyStrategy::new()).collect(),
+            seed,
+        };
+        playtest.apply(PlayerId(0), PlayerAction::StartGame)?;
+        Ok(playtest)
+    }
+
+    /// Applies `action` for player `id`, and returns how it was written down.
+    fn apply(&mut self, id: PlayerId, action: PlayerAction) -> Result<&str, GameError> {
+        let state = &mut self.state;
+        let notation = &mut self.notation;
+        match self.seed {
+            // Every action gets a seed of its own, so shuffles later in the game differ
+            Some(seed) => with_seed(seed.wrapping_add(notation.moves().len() as u64), || {
+                notation.apply(state, id, action)
+            }),
+            None => notation.apply(state, id, action),
+        }?;
+
+        // PANIC: the action was just written down, so there is a last move
+        Ok(&self.notation.moves().last().unwrap().1)
+    }
+
+    /// Lets the bots act until it is the turn of the player at the keyboard, or the game ended.
+    fn play_bots(&mut self) {
+        while let Some(id) = acting_player(&self.state).filter(|id| id.0 > 0) {
+            let Some(action) = self.bots[id.0 as usize - 1].next_action(&self.state, id) else {
+                eprintln!("bot {} does not know what to do", id.0);
+                return;
+            };
+            match self.apply(id, action) {
+                Ok(notation) => println!("P{} {notation}", id.0),
+                Err(error) => {
+                    eprintln!("bot {} could not take their action: {error}", id.0);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Gets the move the greedy bot would make in place of the player at the keyboard.
+    fn hint(&self) -> Option<String> {
+        let hint = self.state.round().ok()?.suggest_action(PlayerId(0))?;
+        let notation = Notation::write_move(&self.state, PlayerId(0), hint.action).ok()?;
+        Some(format!("{notation} ({:?})", hint.reason))
+    }
+}
+
+fn main() {
+    let bots = std::env::var("PLAYTEST_BOTS").map_or(DEFAULT_BOTS, |bots| {
+        bots.parse()
+            .expect("ENV PLAYTEST_BOTS should be a positive integer")
+    });
+    let seed = std::env::var("PLAYTEST_SEED").ok().map(|seed| {
+        seed.parse()
+            .expect("ENV PLAYTEST_SEED should be a positive integer")
+    });
+    let assets_path = std::env::var("ASSETS_DIR")
+        .unwrap_or_else(|_| format!("{}/../assets/", env!("CARGO_MANIFEST_DIR")));
+    let path = PathBuf::from(assets_path).join("cards/boardgame.json");
+    let data = GameData::new(&path)
+        .unwrap_or_else(|error| panic!("Could not load cards from '{}': {error}", path.display()));
+
+    let mut playtest = Playtest::new(data, bots, seed)
+        .unwrap_or_else(|error| panic!("Could not start the game: {error}"));
+    println!("{HELP}\n");
+
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        playtest.play_bots();
+        if acting_player(&playtest.state).is_none() {
+            break;
+        }
+        println!("\n{}", playtest.state.render_text());
+
+        // Keep asking until a move is made, so the game is only shown again once it changed
+        loop {
+            print!("> ");
+            // A prompt that does not show is not worth stopping the game for
+            let _ = io::stdout().flush();
+            let Some(Ok(line)) = lines.next() else {
+                return;
+            };
+
+            match line.trim() {
+                "" => continue,
+                "quit" => return,
+                "help" => println!("{HELP}"),
+                "show" => println!("{}", playtest.state.render_text()),
+                "notation" => print!("{}", playtest.notation),
+                "hint" => match playtest.hint() {
+                    Some(hint) => println!("{hint}"),
+                    None => println!("There is no hint for this part of the game"),
+                },
+                notation => {
+                    let Some(action) = Notation::read_move(&playtest.state, PlayerId(0), notation)
+                    else {
+                        println!("'{notation}' is not a move right now, type help for examples");
+                        continue;
+                    };
+                    match playtest.apply(PlayerId(0), action) {
+                        Ok(_) => break,
+                        Err(error) => println!("{error}"),
+                    }
+                }
+            }
+        }
+    }
+
+    println!("\n{}", playtest.state.render_text());
+    print!("{}", playtest.notation);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_until_results_with_typed_moves() {
+        let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+        let mut playtest = Playtest::new(data, 3, Some(5)).unwrap();
+        let mut strategy = GreedyStrategy::new();
+
+        for _ in 0..game::bot::MAX_SIMULATED_ACTIONS {
+            playtest.play_bots();
+            if acting_player(&playtest.state).is_none() {
+                break;
+            }
+
+            // Type the move the greedy bot would make, like a player at the keyboard would
+            let action = strategy.next_action(&playtest.state, PlayerId(0)).unwrap();
+            let typed = Notation::write_move(&playtest.state, PlayerId(0), action).unwrap();
+            let action = Notation::read_move(&playtest.state, PlayerId(0), &typed).unwrap();
+            playtest.apply(PlayerId(0), action).unwrap();
+        }
+
+        assert!(playtest.state.results().is_ok());
+        assert!(playtest.state.render_text().starts_with("Results"));
+    }
+}
@@ -0,0 +1,496 @@
+//! Conversions from the [`ActionEffects`](game::game::ActionEffects) of the game into the responses
+//! sent to each player.
+
+use game::{
+    game::{ActorEffect, GameEffect},
+    player::Character,
+};
+
+use crate::{DirectResponse, UniqueResponse};
+
+impl From<ActorEffect> for DirectResponse {
+    fn from(effect: ActorEffect) -> Self {
+        use ActorEffect as E;
+
+        match effect {
+            E::StartedGame => Self::YouStartedGame,
+            E::TransferredHost { host_id } => Self::YouTransferredHost { host_id },
+            E::ChangedUsername { username } => Self::YouChangedUsername { username },
+            E::SetReady { ready } => Self::YouSetReady { ready },
+            E::SelectedCharacter { character } => Self::YouSelectedCharacter { character },
+            E::BannedCharacter { character } => Self::YouBannedCharacter { character },
+            E::FiredCharacter { character } => Self::YouFiredCharacter { character },
+            E::TerminatedCreditCharacter { character } => {
+                Self::YouTerminateCreditCharacter { character }
+            }
+            E::PaidBanker(paid) => Self::YouPaidBanker {
+                banker_id: paid.banker_id,
+                new_banker_cash: paid.new_banker_cash,
+                your_new_cash: paid.new_target_cash,
+                paid_amount: paid.paid_amount,
+                sold_assets: paid.selected_cards.sold_assets,
+                issued_liabilities: paid.selected_cards.issued_liabilities,
+                remaining_debt: paid.remaining_debt,
+            },
+            E::SelectedCardsBankerTarget(selected) => Self::YouSelectCardBankerTarget {
+                assets: selected.sold_assets,
+                liabilities: selected.issued_liabilities,
+            },
+            E::FiringOptions { characters } => Self::YouAreFiringSomeone {
+                characters,
+                character: Character::Shareholder,
+                perk: Character::Shareholder.perk(),
+            },
+            E::TerminationOptions { characters } => Self::YouAreTerminatingSomeone {
+                characters,
+                character: Character::Banker,
+                perk: Character::Banker.perk(),
+            },
+            E::RegulatorOptions { options } => Self::YouRegulatorOptions {
+                options,
+                character: Character::Regulator,
+                perk: Character::Regulator.perk(),
+            },
+            E::DivestOptions { options } => Self::YouAreDivesting {
+                options,
+                character: Character::Stakeholder,
+                perk: Character::Stakeholder.perk(),
+            },
+            E::CharacterAbility { character } => Self::YouCharacterAbility {
+                character,
+                perk: character.perk(),
+            },
+            E::SwappedWithDeck { new_cards } => Self::YouSwapDeck { new_cards },
+            E::SwappedWithPlayer {
+                new_cards,
+                target_player_id,
+            } => Self::YouSwapPlayer {
+                new_cards,
+                target_player_id,
+            },
+            E::DrewCard {
+                card,
+                can_draw_cards,
+                can_give_back_cards,
+            } => Self::YouDrewCard {
+                card,
+                can_draw_cards,
+                can_give_back_cards,
+            },
+            E::PutBackCard {
+                card_idx,
+                can_draw_cards,
+                can_give_back_cards,
+            } => Self::YouPutBackCard {
+                card_idx,
+                can_draw_cards,
+                can_give_back_cards,
+            },
+            E::BonusCash { cash } => Self::YouBonusCash { cash },
+            E::BoughtAsset {
+                asset,
+                card_idx,
+                market_change,
+                assets_to_play,
+                cash,
+                triggered_effect,
+            } => Self::YouBoughtAsset {
+                asset,
+                card_idx,
+                market_change,
+                assets_to_play,
+                cash,
+                triggered_effect,
+            },
+            E::IssuedLiability {
+                liability,
+                card_idx,
+            } => Self::YouIssuedLiability {
+                liability,
+                card_idx,
+            },
+            E::PickedAssetToDivest {
+                target_id,
+                asset_idx,
+                gold_cost,
+            } => Self::YouPickedAssetToDivest {
+                target_id,
+                asset_idx,
+                gold_cost,
+            },
+            E::DivestedAsset {
+                target_id,
+                asset_idx,
+                gold_cost,
+            } => Self::YouDivestedAnAsset {
+                target_id,
+                asset_idx,
+                gold_cost,
+            },
+            E::RedeemedLiability {
+                liability_idx,
+                cost,
+            } => Self::YouRedeemedLiability {
+                liability_idx,
+                cost,
+            },
+            E::EndedTurn => Self::YouEndedTurn,
+            E::ForcedEndTurn { player_id } => Self::YouForcedEndTurn { player_id },
+            E::MinusedIntoPlus {
+                color,
+                new_market,
+                new_score,
+            } => Self::YouMinusedIntoPlus {
+                color,
+                new_market,
+                new_score,
+            },
+            E::SilveredIntoGold {
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            } => Self::YouSilveredIntoGold {
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            },
+            E::ChangedAssetColor {
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            } => Self::YouChangedAssetColor {
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            },
+            E::ConfirmedAssetAbility {
+                asset_idx,
+                used_on,
+                breakdown,
+            } => Self::YouConfirmedAssetAbility {
+                asset_idx,
+                used_on,
+                breakdown,
+            },
+        }
+    }
+}
+
+impl From<GameEffect> for UniqueResponse {
+    fn from(effect: GameEffect) -> Self {
+        use GameEffect as E;
+
+        match effect {
+            E::HostChanged { host_id } => Self::HostChanged { host_id },
+            E::PlayerReady { player_id, ready } => Self::PlayerReady { player_id, ready },
+            E::PlayersInLobby {
+                changed_player,
+                usernames,
+                host_id,
+            } => Self::PlayersInLobby {
+                changed_player,
+                usernames,
+                host_id,
+            },
+            E::GameStarted {
+                id,
+                cash,
+                hand,
+                player_info,
+                initial_market,
+                data_checksum,
+                color_markers,
+                seats_shuffled,
+                config,
+            } => Self::StartGame {
+                id,
+                cash,
+                hand,
+                player_info,
+                initial_market,
+                data_checksum: format!("{data_checksum:016x}"),
+                color_markers,
+                seats_shuffled,
+                config,
+            },
+            E::SelectingCharacters {
+                chairman_id,
+                selectable_characters,
+                open_characters,
+                closed_character,
+                turn_order,
+                banned_characters,
+            } => Self::SelectingCharacters {
+                chairman_id,
+                selectable_characters,
+                open_characters,
+                closed_character,
+                turn_order,
+                banned_characters,
+            },
+            E::BanningCharacters {
+                chairman_id,
+                currently_banning_id,
+                bannable_characters,
+                banned_characters,
+                turn_order,
+            } => Self::BanningCharacters {
+                chairman_id,
+                currently_banning_id,
+                bannable_characters,
+                banned_characters,
+                turn_order,
+            },
+            E::CharacterBanned {
+                player_id,
+                character,
+            } => Self::CharacterBanned {
+                player_id,
+                character,
+            },
+            E::SelectedCharacter {
+                currently_picking_id,
+                selectable_characters,
+                closed_character,
+            } => Self::SelectedCharacter {
+                currently_picking_id,
+                selectable_characters,
+                closed_character,
+            },
+            E::TurnStarts {
+                player_turn,
+                player_turn_cash,
+                draws_n_cards,
+                gives_back_n_cards,
+                playable_assets,
+                playable_liabilities,
+                player_character,
+                can_use_ability,
+                skipped_characters,
+                skip_reasons,
+                time_bank_ms,
+                turn_time_ms,
+                phase,
+                events,
+                turn_nonce,
+            } => Self::TurnStarts {
+                player_turn,
+                player_turn_cash,
+                draws_n_cards,
+                gives_back_n_cards,
+                playable_assets,
+                playable_liabilities,
+                player_character,
+                can_use_ability,
+                skipped_characters,
+                skip_reasons,
+                time_bank_ms,
+                turn_time_ms,
+                phase,
+                events,
+                turn_nonce,
+            },
+            E::PlayerTargetedByBanker {
+                player_turn,
+                cash_to_be_paid,
+                credit_termination,
+                is_possible_to_pay_banker,
+            } => Self::PlayerTargetedByBanker {
+                player_turn,
+                cash_to_be_paid,
+                credit_termination,
+                is_possible_to_pay_banker,
+            },
+            E::YourCreditTerminated(termination) => Self::YourCreditTerminated {
+                base_gold: termination.base_gold,
+                colors: termination.colors,
+                total_gold: termination.total_gold,
+            },
+            E::SelectedCardsBankerTarget {
+                assets,
+                liability_count,
+            } => Self::SelectedCardsBankerTarget {
+                assets,
+                liability_count,
+            },
+            E::DrewCard {
+                player_id,
+                card_type,
+                deck_sizes,
+            } => Self::DrewCard {
+                player_id,
+                card_type,
+                deck_sizes,
+            },
+            E::TurnForcedToEnd { player_id } => Self::TurnForcedToEnd { player_id },
+            E::PutBackCard {
+                player_id,
+                card_type,
+                deck_sizes,
+            } => Self::PutBackCard {
+                player_id,
+                card_type,
+                deck_sizes,
+            },
+            E::BoughtAsset {
+                player_id,
+                asset,
+                card_idx,
+                market_change,
+                assets_to_play,
+                cash,
+                triggered_effect,
+            } => Self::BoughtAsset {
+                player_id,
+                asset,
+                card_idx,
+                market_change,
+                assets_to_play,
+                cash,
+                triggered_effect,
+            },
+            E::IssuedLiability {
+                player_id,
+                liability,
+                card_idx,
+            } => Self::IssuedLiability {
+                player_id,
+                liability,
+                card_idx,
+            },
+            E::RedeemedLiability {
+                player_id,
+                liability_idx,
+                cost,
+            } => Self::RedeemedLiability {
+                player_id,
+                liability_idx,
+                cost,
+            },
+            E::PlayerGotBonusCash { player_id, cash } => {
+                Self::PlayerGotBonusCash { player_id, cash }
+            }
+            E::FiredCharacter {
+                player_id,
+                character,
+            } => Self::FiredCharacter {
+                player_id,
+                character,
+            },
+            E::TerminatedCreditCharacter {
+                player_id,
+                character,
+            } => Self::TerminatedCreditCharacter {
+                player_id,
+                character,
+            },
+            E::PlayerPaidBanker(paid) => Self::PlayerPaidBanker {
+                banker_id: paid.banker_id,
+                player_id: paid.target_id,
+                new_banker_cash: paid.new_banker_cash,
+                new_target_cash: paid.new_target_cash,
+                paid_amount: paid.paid_amount,
+                sold_assets: paid.selected_cards.sold_assets,
+                issued_liabilities: paid.selected_cards.issued_liabilities,
+                remaining_debt: paid.remaining_debt,
+            },
+            E::PlayerPaidBankerDebt(payment) => Self::PlayerPaidBankerDebt {
+                player_id: payment.player_id,
+                banker_id: payment.banker_id,
+                paid_amount: payment.paid_amount,
+                remaining_debt: payment.remaining_debt,
+                new_player_cash: payment.new_player_cash,
+                new_banker_cash: payment.new_banker_cash,
+            },
+            E::YourTurnSkipped { reason } => Self::YourTurnSkipped { reason },
+            E::RegulatorSwappedYourCards { new_cards } => {
+                Self::RegulatorSwappedYourCards { new_cards }
+            }
+            E::SwappedWithPlayer {
+                regulator_id,
+                target_id,
+            } => Self::SwappedWithPlayer {
+                regulator_id,
+                target_id,
+            },
+            E::SwappedWithDeck {
+                asset_count,
+                liability_count,
+            } => Self::SwappedWithDeck {
+                asset_count,
+                liability_count,
+            },
+            E::BeingDivested {
+                stakeholder_id,
+                asset_idx,
+                gold_cost,
+            } => Self::YouAreBeingDivested {
+                stakeholder_id,
+                asset_idx,
+                gold_cost,
+            },
+            E::AssetDivested {
+                player_id,
+                target_id,
+                asset_idx,
+                paid_gold,
+            } => Self::AssetDivested {
+                player_id,
+                target_id,
+                asset_idx,
+                paid_gold,
+            },
+            E::GameEnded {
+                scores,
+                boards,
+                final_market,
+            } => Self::GameEnded {
+                scores,
+                boards,
+                final_market,
+            },
+            E::MinusedIntoPlus {
+                player_id,
+                new_market,
+                new_score,
+            } => Self::MinusedIntoPlus {
+                player_id,
+                new_market,
+                new_score,
+            },
+            E::SilveredIntoGold {
+                player_id,
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            } => Self::SilveredIntoGold {
+                player_id,
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            },
+            E::ChangedAssetColor {
+                player_id,
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            } => Self::ChangedAssetColor {
+                player_id,
+                old_asset_data,
+                new_asset_data,
+                new_score,
+            },
+            E::ConfirmedAssetAbility {
+                player_id,
+                asset_idx,
+                used_on,
+                breakdown,
+            } => Self::ConfirmedAssetAbility {
+                player_id,
+                asset_idx,
+                used_on,
+                breakdown,
+            },
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Made-up games for clients to be developed and tested against. A [`FakeGame`] is played by bots
+//! from start to finish, and yields the [`UniqueResponse`]s every player would have been sent
+//! along the way.
+
+use std::collections::VecDeque;
+
+use game::{
+    bot::{GreedyStrategy, MAX_SIMULATED_ACTIONS, Strategy, acting_player},
+    cards::GameData,
+    errors::GameError,
+    game::*,
+    player::PlayerId,
+    utility::with_seed,
+};
+use serde::Serialize;
+
+use crate::UniqueResponse;
+
+/// A response a player of a [`FakeGame`] was sent.
+#[derive(Debug, Clone, Serialize)]
+pub struct FakeResponse {
+    /// The id of the player the response was sent to.
+    pub player_id: PlayerId,
+    /// The response itself.
+    pub response: UniqueResponse,
+}
+
+/// A game between bots that yields every response its players are sent, in order. The same
+/// `seed` always plays out the same game, so the responses can be used as fixtures.
+///
+/// ```
+/// # use game::{cards::GameData, player::PlayerId};
+/// # use responses::FakeGame;
+/// let data = GameData::new("../assets/cards/boardgame.json").unwrap();
+/// let game = FakeGame::new(data, 4, 7).unwrap();
+///
+/// let seen_by_first = game.filter(|fake| fake.player_id == PlayerId(0)).count();
+/// assert!(seen_by_first > 0);
+/// ```
+#[derive(Debug)]
+pub struct FakeGame {
+    state: GameState,
+    strategy: GreedyStrategy,
+    seed: u64,
+    /// How many actions were taken so far.
+    actions: usize,
+    pending: VecDeque<FakeResponse>,
+}
+
+impl FakeGame {
+    /// Seats `players` bots named `player 0`, `player 1` and so on, and starts a game with `data`.
+    pub fn new(data: GameData, players: usize, seed: u64) -> Result<Self, GameError> {
+        let mut state = GameState::new();
+        let mut pending = VecDeque::new();
+
+        let lobby = state.lobby_mut()?;
+        lobby.set_game_data(data);
+        for i in 0..players {
+            let username = format!("player {i}");
+            lobby.join(username.clone())?;
+            let joined = UniqueResponse::PlayersInLobby {
+                changed_player: username,
+                usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
+                host_id: lobby.host().map(|p| p.id()),
+            };
+            pending.extend(lobby.players().iter().map(|p| FakeResponse {
+                player_id: p.id(),
+                response: joined.clone(),
+            }));
+        }
+
+        let mut fake = Self {
+            state,
+            strategy: GreedyStrategy::new(),
+            seed,
+            actions: 0,
+            pending,
+        };
+        fake.apply(PlayerId(0), PlayerAction::StartGame)?;
+        Ok(fake)
+    }
+
+    /// Gets the game as it is after the responses yielded so far, and the ones still pending.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Applies `action` for player `id` and queues the responses it produces for everyone.
+    fn apply(&mut self, id: PlayerId, action: PlayerAction) -> Result<(), GameError> {
+        // Every action gets a seed of its own, so shuffles later in the game differ from the first
+        let seed = self.seed.wrapping_add(self.actions as u64);
+        let effects = with_seed(seed, || self.state.apply(id, action))?;
+        self.actions += 1;
+
+        for player_id in self.state.player_ids() {
+            let responses = effects.for_player(player_id).into_iter();
+            self.pending.extend(responses.map(|effect| FakeResponse {
+                player_id,
+                response: effect.into(),
+            }));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for FakeGame {
+    type Item = FakeResponse;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && self.actions < MAX_SIMULATED_ACTIONS {
+            let id = acting_player(&self.state)?;
+            let action = self.strategy.next_action(&self.state, id)?;
+            self.apply(id, action).ok()?;
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> GameData {
+        GameData::new("../assets/cards/boardgame.json").unwrap()
+    }
+
+    #[test]
+    fn plays_until_results() {
+        let mut game = FakeGame::new(data(), 4, 1).unwrap();
+        let responses = game.by_ref().collect::<Vec<_>>();
+
+        assert!(game.state().results().is_ok());
+        assert!(matches!(
+            responses.first(),
+            Some(FakeResponse {
+                player_id: PlayerId(0),
+                response: UniqueResponse::PlayersInLobby { .. }
+            })
+        ));
+        assert!(responses.iter().all(|fake| fake.player_id.0 < 4));
+    }
+
+    #[test]
+    fn same_seed_same_responses() {
+        let json = |seed| {
+            let game = FakeGame::new(data(), 5, seed).unwrap();
+            serde_json::to_string(&game.collect::<Vec<_>>()).unwrap()
+        };
+
+        assert_eq!(json(3), json(3));
+    }
+}
@@ -0,0 +1,97 @@
+//! Generates typescript type guards for every variant of the responses, like
+//! `isYouDrewCard(response): response is ...`, so the frontend can dispatch on messages without
+//! writing a guard by hand for each of them. The guards are written next to the types exported by
+//! `ts-rs` when the tests are run with the `ts` feature.
+
+use ts_rs::TS;
+
+use std::{fmt::Write, path::Path};
+
+use crate::{DirectResponse, UniqueResponse};
+
+/// Gets the values of `tag` of every member of the union declared by `decl`. Only tags of the
+/// members themselves are picked up, not those of nested types or inside doc comments.
+fn variant_names(decl: &str, tag: &str) -> Vec<String> {
+    let pattern = format!("\"{tag}\": \"");
+    let mut names = Vec::new();
+    let mut depth = 0;
+    let mut rest = decl;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.split_once("*/").map_or("", |(_, after)| after);
+            continue;
+        }
+        if depth == 1
+            && let Some(name) = rest.strip_prefix(&pattern)
+            && let Some((name, after)) = name.split_once('"')
+        {
+            names.push(name.to_owned());
+            rest = after;
+            continue;
+        }
+
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    names
+}
+
+/// Generates a type guard for every variant of `T`, an enum which serde tags with `tag`.
+pub fn type_guards<T: TS>(tag: &str) -> String {
+    let ty = T::name();
+    variant_names(&T::decl(), tag)
+        .into_iter()
+        .fold(String::new(), |mut guards, name| {
+            // PANIC: writing to a string cannot fail.
+            writeln!(
+                guards,
+                "export function is{name}(response: {ty}): response is Extract<{ty}, {{ {tag}: \"{name}\" }}> {{\n    return response.{tag} === \"{name}\";\n}}\n"
+            )
+            .unwrap();
+            guards
+        })
+}
+
+/// Writes the type guards of [`DirectResponse`] and [`UniqueResponse`] to `guards.ts`, next to
+/// the file the types themselves are exported to.
+pub fn export_type_guards() -> std::io::Result<()> {
+    let path = Path::new(game::SHARED_TS_DIR).with_file_name("guards.ts");
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let contents = format!(
+        "// This file was generated by the responses crate. Do not edit this file manually.\nimport type {{ {}, {} }} from \"./index\";\n\n{}{}",
+        DirectResponse::name(),
+        UniqueResponse::name(),
+        type_guards::<DirectResponse>("action"),
+        type_guards::<UniqueResponse>("action"),
+    );
+    std::fs::write(path, contents.trim_end().to_owned() + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_nested_tags_and_comments() {
+        let decl = r#"type A = { "action": "First", "data": { /** { "action": "Comment" } */ b: { "action": "Nested" }, } } | { "action": "Second" };"#;
+        assert_eq!(variant_names(decl, "action"), ["First", "Second"]);
+    }
+
+    #[test]
+    fn export_bindings_guards() {
+        let guards = type_guards::<DirectResponse>("action");
+        assert!(guards.contains(
+            "export function isYouDrewCard(response: DirectResponse): response is Extract<DirectResponse, { action: \"YouDrewCard\" }> {"
+        ));
+        export_type_guards().expect("guards.ts should be writable");
+    }
+}
@@ -3,13 +3,32 @@
 #![warn(missing_docs)]
 
 use either::Either;
-use game::{errors::GameError, game::*, player::*, utility::serde_asset_liability};
+use game::{
+    bot::{Hint, acting_player},
+    errors::GameError,
+    game::*,
+    player::*,
+    utility::serde_asset_liability,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[cfg(feature = "ts")]
 use ts_rs::TS;
 
+mod effects;
+mod fake;
+#[cfg(feature = "ts")]
+mod guards;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+
+pub use fake::*;
+#[cfg(feature = "ts")]
+pub use guards::*;
+#[cfg(feature = "msgpack")]
+pub use msgpack::*;
+
 /// The connect response. The very first thing a client should send is this request.
 #[cfg_attr(feature = "ts", derive(TS))]
 #[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
@@ -23,52 +42,185 @@ pub enum Connect {
         username: String,
         /// The channel code of the player who wants to connect.
         channel: String,
+        /// Whether this client wants to receive binary frames containing MessagePack encoded
+        /// responses instead of json text frames.
+        #[serde(default)]
+        binary_frames: bool,
+        /// The language card titles and descriptions are sent in, like `"nl"`. Cards without a
+        /// translation into this language are sent as they appear in the card data.
+        #[serde(default)]
+        locale: Option<String>,
+        /// The optional protocol features this client understands. If sent, the server answers
+        /// with [`DirectResponse::YouNegotiatedCapabilities`] right after joining. Clients that
+        /// leave this out are only sent what every client understands.
+        #[serde(default)]
+        capabilities: Option<Capabilities>,
+    },
+    /// Watch the game in a channel without playing in it. The server answers with a
+    /// [`DirectResponse::SpectatorView`], after which spectators are sent everything that happens
+    /// publicly in the game after a delay. Spectators cannot make requests.
+    Spectate {
+        /// The username of the spectator.
+        username: String,
+        /// The channel code of the game to watch.
+        channel: String,
+        /// The language card titles and descriptions are sent in, see [`Connect::Connect`].
+        #[serde(default)]
+        locale: Option<String>,
+        /// The optional protocol features this client understands, see [`Connect::Connect`].
+        #[serde(default)]
+        capabilities: Option<Capabilities>,
     },
 }
 
+/// A set of optional protocol features, sent as a bitset. Clients tell the server which of these
+/// they understand when connecting, and the server acknowledges the ones it will use, so new
+/// features can be rolled out without breaking older clients.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Capabilities(pub u32);
+
+impl Capabilities {
+    /// No optional features at all.
+    pub const NONE: Self = Self(0);
+    /// Responses are sent as MessagePack in binary frames, the same as setting `binary_frames`
+    /// when connecting.
+    pub const BINARY_FRAMES: Self = Self(1 << 0);
+    /// Changes to the game are sent as deltas instead of complete states.
+    pub const DELTAS: Self = Self(1 << 1);
+    /// Frames are compressed.
+    pub const COMPRESSION: Self = Self(1 << 2);
+    /// Spectators are sent what happens after a delay, instead of right away.
+    pub const SPECTATOR_DELAY: Self = Self(1 << 3);
+    /// Several responses may be sent in a single frame, as a json array or MessagePack list, see
+    /// [`Batched`]. Clients that leave this out get every response in a frame of its own.
+    pub const BATCHES: Self = Self(1 << 4);
+
+    /// Checks whether every feature in `other` is in this set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Gets every feature that is in this set or in `other`, the same as `self | other` in a
+    /// constant.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl std::ops::BitOr for Capabilities {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl std::ops::BitAnd for Capabilities {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
 /// Requests that are sent from the frontend, to be handled by the backend.
 #[cfg_attr(feature = "ts", derive(TS))]
 #[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
 #[cfg_attr(feature = "ts", ts(export))]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "action", content = "data")]
 pub enum FrontendRequest {
     /// Tries to start the game.
     StartGame,
+    /// Tries to make another player the host of the lobby, see
+    /// [`Lobby::host`](game::game::Lobby::host).
+    TransferHost {
+        /// The id of the player that becomes the host.
+        target: PlayerId,
+    },
+    /// Tries to kick another player out of the lobby as the host, see
+    /// [`Lobby::kick`](game::game::Lobby::kick). Kicked players cannot join the room again.
+    KickPlayer {
+        /// The id of the player to kick.
+        target: PlayerId,
+    },
+    /// Says whether this player is ready for the game to start, which is only possible in the
+    /// lobby. See [`GameConfig::require_ready`].
+    SetReady {
+        /// Whether this player is ready.
+        ready: bool,
+    },
+    /// Tries to change the username of this player, which is only possible in the lobby. The
+    /// username is checked the same way as when joining.
+    ChangeUsername {
+        /// The username to change to.
+        username: String,
+    },
     /// Tries to select a character for this player.
     SelectCharacter {
         /// The character the player wants to select.
         character: Character,
     },
+    /// Tries to ban a character from this round, see
+    /// [`CharacterBans`](game::game::CharacterBans).
+    BanCharacter {
+        /// The character the player wants to ban.
+        character: Character,
+    },
     /// Tries to draw a card for this player.
     DrawCard {
         /// The [`CardType`] the player wants to draw.
         card_type: CardType,
+        /// The nonce of the turn this request is meant for, as sent in
+        /// [`UniqueResponse::TurnStarts`]. The request is rejected if that turn already ended.
+        turn_nonce: u32,
     },
     /// Tries to put back a card for this player.
     PutBackCard {
         /// The index of the card this player is trying to put back.
         card_idx: usize,
+        /// The nonce of the turn this request is meant for, as sent in
+        /// [`UniqueResponse::TurnStarts`]. The request is rejected if that turn already ended.
+        turn_nonce: u32,
     },
     /// Tries to buy an asset for this player.
     BuyAsset {
         /// The index of the card the player wants to buy.
         card_idx: usize,
+        /// The nonce of the turn this request is meant for, as sent in
+        /// [`UniqueResponse::TurnStarts`]. The request is rejected if that turn already ended.
+        turn_nonce: u32,
     },
     /// Tries to issue a liability for this player.
     IssueLiability {
         /// The index of the card the player wants to issue.
         card_idx: usize,
+        /// The nonce of the turn this request is meant for, as sent in
+        /// [`UniqueResponse::TurnStarts`]. The request is rejected if that turn already ended.
+        turn_nonce: u32,
     },
     /// Tries to redeem a liability for this player.
     RedeemLiability {
         /// The index of the issued liability the player wanst to redeem.
         liability_idx: usize,
+        /// The nonce of the turn this request is meant for, as sent in
+        /// [`UniqueResponse::TurnStarts`]. The request is rejected if that turn already ended.
+        turn_nonce: u32,
     },
     /// Tries to use the ability for this player.
-    UseAbility,
+    UseAbility {
+        /// The nonce of the turn this request is meant for, as sent in
+        /// [`UniqueResponse::TurnStarts`]. The request is rejected if that turn already ended.
+        turn_nonce: u32,
+    },
     /// Get characters bonus gold only once per turn,
-    GetBonusCash,
+    GetBonusCash {
+        /// The nonce of the turn this request is meant for, as sent in
+        /// [`UniqueResponse::TurnStarts`]. The request is rejected if that turn already ended.
+        turn_nonce: u32,
+    },
     /// Tries to fire a particular character by this player.
     FireCharacter {
         /// The character that is to be fired.
@@ -115,18 +267,53 @@ pub enum FrontendRequest {
         /// The id of the player which is to be swapped with.
         target_player_id: PlayerId,
     },
-    /// Tries to force another player to divest one of their assets at market value minus one, which
-    /// is to be paid by this player.
+    /// Tries to pick an asset of another player to divest at market value minus one, which is to be
+    /// paid by this player. The asset is only divested once the pick is confirmed with
+    /// [`FrontendRequest::ConfirmDivest`].
     DivestAsset {
         /// The id of the player which would be forced to divest their asset.
         target_player_id: PlayerId,
         /// The index of the asset that is to be divested.
         card_idx: usize,
     },
+    /// Tries to divest the asset this player picked with [`FrontendRequest::DivestAsset`].
+    ConfirmDivest,
     /// Tries to end the turn of this player.
-    EndTurn,
+    EndTurn {
+        /// The nonce of the turn to end, as sent in [`UniqueResponse::TurnStarts`]. The request is
+        /// rejected if that turn already ended.
+        turn_nonce: u32,
+    },
+    /// Tries to end the turn of the current player as the host, for when they stopped playing.
+    ForceEndTurn {
+        /// The nonce of the turn to end, as sent in [`UniqueResponse::TurnStarts`]. The request is
+        /// rejected if that turn already ended.
+        turn_nonce: u32,
+    },
     /// Request a resync packet containing the current gamestate
     Resync,
+    /// Asks for everything this player is allowed to see of the game, in any phase of the game.
+    /// Answered with [`DirectResponse::FullState`].
+    RequestFullState,
+    /// Asks the game what would be a good next move during this player's turn.
+    RequestHint,
+    /// Starts a vote everyone in the room can take part in. The player that proposes the vote
+    /// votes in favor of it.
+    ProposeVote {
+        /// What the vote is about.
+        kind: VoteKind,
+    },
+    /// Votes on the vote that is currently running.
+    CastVote {
+        /// Whether this player is in favor.
+        approve: bool,
+    },
+    /// Runs a debug command which changes the game without playing, see [`DevRequest`]. Only
+    /// servers built with the `dev` feature accept these.
+    Dev {
+        /// The command to run.
+        command: DevRequest,
+    },
     /// Tries to turn minus into zero or zero into plus for the player's market at the end of the
     /// game. Related to [`AssetPowerup::MinusIntoPlus`](game::player::AssetPowerup::MinusIntoPlus).
     MinusIntoPlus {
@@ -154,6 +341,155 @@ pub enum FrontendRequest {
     },
 }
 
+impl FrontendRequest {
+    /// Gets the [`PlayerAction`] this request represents, if any. Returns `None` for requests that
+    /// do not change the game, like [`FrontendRequest::Resync`].
+    pub fn into_action(self) -> Option<PlayerAction> {
+        use PlayerAction as A;
+
+        let action = match self {
+            Self::StartGame => A::StartGame,
+            Self::TransferHost { target } => A::TransferHost { target },
+            Self::SetReady { ready } => A::SetReady { ready },
+            Self::ChangeUsername { username } => A::ChangeUsername { username },
+            Self::SelectCharacter { character } => A::SelectCharacter { character },
+            Self::BanCharacter { character } => A::BanCharacter { character },
+            Self::DrawCard { card_type, .. } => A::DrawCard { card_type },
+            Self::PutBackCard { card_idx, .. } => A::PutBackCard { card_idx },
+            Self::BuyAsset { card_idx, .. } => A::BuyAsset { card_idx },
+            Self::IssueLiability { card_idx, .. } => A::IssueLiability { card_idx },
+            Self::RedeemLiability { liability_idx, .. } => A::RedeemLiability { liability_idx },
+            Self::UseAbility { .. } => A::UseAbility,
+            Self::GetBonusCash { .. } => A::GetBonusCash,
+            Self::FireCharacter { character } => A::FireCharacter { character },
+            Self::TerminateCreditCharacter { character } => {
+                A::TerminateCreditCharacter { character }
+            }
+            Self::SelectAssetToDivest { asset_id } => A::SelectAssetToDivest { asset_id },
+            Self::UnselectAssetToDivest { asset_id } => A::UnselectAssetToDivest { asset_id },
+            Self::SelectLiabilityToIssue { liability_id } => {
+                A::SelectLiabilityToIssue { liability_id }
+            }
+            Self::UnselectLiabilityToIssue { liability_id } => {
+                A::UnselectLiabilityToIssue { liability_id }
+            }
+            Self::PayBanker { cash } => A::PayBanker { cash },
+            Self::SwapWithDeck { card_idxs } => A::SwapWithDeck { card_idxs },
+            Self::SwapWithPlayer { target_player_id } => A::SwapWithPlayer { target_player_id },
+            Self::DivestAsset {
+                target_player_id,
+                card_idx,
+            } => A::DivestAsset {
+                target_player_id,
+                card_idx,
+            },
+            Self::ConfirmDivest => A::ConfirmDivest,
+            Self::EndTurn { .. } => A::EndTurn,
+            Self::ForceEndTurn { .. } => A::ForceEndTurn,
+            Self::KickPlayer { .. }
+            | Self::Resync
+            | Self::RequestFullState
+            | Self::RequestHint
+            | Self::ProposeVote { .. }
+            | Self::CastVote { .. }
+            | Self::Dev { .. } => return None,
+            Self::MinusIntoPlus { color } => A::MinusIntoPlus { color },
+            Self::SilverIntoGold { asset_idx } => A::SilverIntoGold { asset_idx },
+            Self::ChangeAssetColor { asset_idx, color } => A::ChangeAssetColor { asset_idx, color },
+            Self::ConfirmAssetAbility { asset_idx } => A::ConfirmAssetAbility { asset_idx },
+        };
+
+        Some(action)
+    }
+
+    /// Gets the nonce of the turn this request is meant for, if it belongs to a turn. See
+    /// [`GameState::apply_in_turn`].
+    pub fn turn_nonce(&self) -> Option<u32> {
+        match self {
+            Self::DrawCard { turn_nonce, .. }
+            | Self::PutBackCard { turn_nonce, .. }
+            | Self::BuyAsset { turn_nonce, .. }
+            | Self::IssueLiability { turn_nonce, .. }
+            | Self::RedeemLiability { turn_nonce, .. }
+            | Self::UseAbility { turn_nonce }
+            | Self::GetBonusCash { turn_nonce }
+            | Self::EndTurn { turn_nonce }
+            | Self::ForceEndTurn { turn_nonce } => Some(*turn_nonce),
+            _ => None,
+        }
+    }
+}
+
+impl FrontendRequest {
+    /// Gets the request that takes `action` in `game`. Requests that belong to a turn get the nonce
+    /// of the turn that is being played, see [`Round::turn_nonce`](game::game::Round::turn_nonce).
+    pub fn from_action(action: PlayerAction, game: &GameState) -> Self {
+        use PlayerAction as A;
+
+        let turn_nonce = game.round().map_or(0, Round::turn_nonce);
+
+        match action {
+            A::StartGame => Self::StartGame,
+            A::TransferHost { target } => Self::TransferHost { target },
+            A::SetReady { ready } => Self::SetReady { ready },
+            A::ChangeUsername { username } => Self::ChangeUsername { username },
+            A::SelectCharacter { character } => Self::SelectCharacter { character },
+            A::BanCharacter { character } => Self::BanCharacter { character },
+            A::DrawCard { card_type } => Self::DrawCard {
+                card_type,
+                turn_nonce,
+            },
+            A::PutBackCard { card_idx } => Self::PutBackCard {
+                card_idx,
+                turn_nonce,
+            },
+            A::BuyAsset { card_idx } => Self::BuyAsset {
+                card_idx,
+                turn_nonce,
+            },
+            A::IssueLiability { card_idx } => Self::IssueLiability {
+                card_idx,
+                turn_nonce,
+            },
+            A::RedeemLiability { liability_idx } => Self::RedeemLiability {
+                liability_idx,
+                turn_nonce,
+            },
+            A::UseAbility => Self::UseAbility { turn_nonce },
+            A::GetBonusCash => Self::GetBonusCash { turn_nonce },
+            A::FireCharacter { character } => Self::FireCharacter { character },
+            A::TerminateCreditCharacter { character } => {
+                Self::TerminateCreditCharacter { character }
+            }
+            A::SelectAssetToDivest { asset_id } => Self::SelectAssetToDivest { asset_id },
+            A::UnselectAssetToDivest { asset_id } => Self::UnselectAssetToDivest { asset_id },
+            A::SelectLiabilityToIssue { liability_id } => {
+                Self::SelectLiabilityToIssue { liability_id }
+            }
+            A::UnselectLiabilityToIssue { liability_id } => {
+                Self::UnselectLiabilityToIssue { liability_id }
+            }
+            A::PayBanker { cash } => Self::PayBanker { cash },
+            A::SwapWithDeck { card_idxs } => Self::SwapWithDeck { card_idxs },
+            A::SwapWithPlayer { target_player_id } => Self::SwapWithPlayer { target_player_id },
+            A::DivestAsset {
+                target_player_id,
+                card_idx,
+            } => Self::DivestAsset {
+                target_player_id,
+                card_idx,
+            },
+            A::ConfirmDivest => Self::ConfirmDivest,
+            A::EndTurn => Self::EndTurn { turn_nonce },
+            A::ForceEndTurn => Self::ForceEndTurn { turn_nonce },
+            A::MinusIntoPlus { color } => Self::MinusIntoPlus { color },
+            A::SilverIntoGold { asset_idx } => Self::SilverIntoGold { asset_idx },
+            A::ChangeAssetColor { asset_idx, color } => Self::ChangeAssetColor { asset_idx, color },
+            A::ConfirmAssetAbility { asset_idx } => Self::ConfirmAssetAbility { asset_idx },
+        }
+    }
+}
+
 /// A response type that a player receives after performing an action. Can either be an error or
 /// a confirmation that the action was succesful, including the data needed to update the UI
 /// accordingly.
@@ -172,11 +508,37 @@ pub enum DirectResponse {
     },
     /// Confirmation that this player started the game.
     YouStartedGame,
+    /// Confirmation that this player made another player the host of the lobby.
+    YouTransferredHost {
+        /// The id of the new host.
+        host_id: PlayerId,
+    },
+    /// Confirmation that this player said whether they are ready for the game to start.
+    YouSetReady {
+        /// Whether this player is ready.
+        ready: bool,
+    },
+    /// Confirmation that this player, as the host, kicked another player out of the lobby.
+    YouKickedPlayer {
+        /// The id the kicked player had.
+        player_id: PlayerId,
+    },
+    /// Confirmation that this player changed their username. Requests sent from now on are made
+    /// under this username.
+    YouChangedUsername {
+        /// The new, normalized username.
+        username: String,
+    },
     /// Confirmation that this player selected a character.
     YouSelectedCharacter {
         /// The character this player selected.
         character: Character,
     },
+    /// Confirmation that this player banned a character from this round.
+    YouBannedCharacter {
+        /// The character this player banned.
+        character: Character,
+    },
     /// Confirmation that this player fired a character.
     YouFiredCharacter {
         /// The character that was fired.
@@ -192,15 +554,18 @@ pub enum DirectResponse {
         /// The id of the player who is the banker.
         banker_id: PlayerId,
         /// The new cash balance of the banker.
-        new_banker_cash: u8,
+        new_banker_cash: Cash,
         /// The new cash balance of the player that was targeted by the banker.
-        your_new_cash: u8,
+        your_new_cash: Cash,
         /// The amount of gold paid.
         paid_amount: u8,
         /// A list of assets to be sold to pay off the banker.
         sold_assets: Vec<SoldAssetToPayBanker>,
         /// A list of liabilities to be issued to pay off the banker.
         issued_liabilities: Vec<IssuedLiabilityToPayBanker>,
+        /// What you still owe the banker because you could not pay in full. It is paid off at the
+        /// start of your next turns.
+        remaining_debt: u8,
     },
     /// Confirmation that you selected someone as target as the banker.
     YouSelectCardBankerTarget {
@@ -215,13 +580,19 @@ pub enum DirectResponse {
         options: Vec<RegulatorSwapPlayer>,
         /// Always [`Character::Regulator`]
         character: Character,
-        /// A string containing information about what this character is allowed to do.
-        perk: String,
+        /// What this character is allowed to do.
+        perk: PerkDescriptor,
     },
-    /// Confirmation that this player can proceed swapping with the deck.
+    /// Confirmation that this player swapped cards with the deck.
     YouSwapDeck {
-        /// The amount of cards this player may draw from the deck.
-        cards_to_draw: usize,
+        /// The cards this player drew to replace the swapped cards, which were added to the end of
+        /// their hand.
+        #[cfg_attr(
+            feature = "ts",
+            ts(as = "Vec<serde_asset_liability::EitherAssetLiability>")
+        )]
+        #[serde(with = "serde_asset_liability::vec")]
+        new_cards: Vec<Either<Asset, Liability>>,
     },
     /// Confirmation that this player was succesful in swapping with a player.
     YouSwapPlayer {
@@ -241,8 +612,8 @@ pub enum DirectResponse {
         options: Vec<DivestPlayer>,
         /// Always [`Character::Stakeholder`]
         character: Character,
-        /// A string containing information about what this character is allowed to do.
-        perk: String,
+        /// What this character is allowed to do.
+        perk: PerkDescriptor,
     },
     /// Confirmation that this player drew a card.
     YouDrewCard {
@@ -268,8 +639,8 @@ pub enum DirectResponse {
     YouCharacterAbility {
         /// The character of the player.
         character: Character,
-        /// A string containing information about what this player is allowed to do.
-        perk: String,
+        /// What this character is allowed to do.
+        perk: PerkDescriptor,
     },
     /// Confirmation you received your bonus cash and how much.
     YouBonusCash {
@@ -284,6 +655,13 @@ pub enum DirectResponse {
         card_idx: usize,
         /// If the market changed, a list of events and a new market is returned.
         market_change: Option<MarketChange>,
+        /// The units of assets this player can still buy this turn, see [`PlayableAssets`].
+        assets_to_play: u8,
+        /// The cash this player has left.
+        cash: Cash,
+        /// The [`AssetEffect`] buying the asset triggered, if any.
+        #[serde(default)]
+        triggered_effect: Option<AssetEffect>,
     },
     /// Confirmation that this player issued a liability.
     YouIssuedLiability {
@@ -298,8 +676,19 @@ pub enum DirectResponse {
         characters: Vec<Character>,
         /// Always [`Character::Shareholder`]
         character: Character,
-        /// A string containing information on what this character is allowed to do.
-        perk: String,
+        /// What this character is allowed to do.
+        perk: PerkDescriptor,
+    },
+    /// Confirmation that this player picked an asset of another player to divest. The player it
+    /// belongs to is told with [`UniqueResponse::YouAreBeingDivested`], and it is divested once
+    /// this player sends [`FrontendRequest::ConfirmDivest`].
+    YouPickedAssetToDivest {
+        /// The id of the player who is forced to divest one of their assets.
+        target_id: PlayerId,
+        /// The index of the asset they are forced to divest.
+        asset_idx: usize,
+        /// The amount of gold it costs to divest this asset.
+        gold_cost: u8,
     },
     /// Confirmation that this player divested an asset of another player.
     YouDivestedAnAsset {
@@ -316,16 +705,24 @@ pub enum DirectResponse {
         characters: Vec<Character>,
         /// Always [`Character::Banker`]
         character: Character,
-        /// A string containing information on what this character is allowed to do.
-        perk: String,
+        /// What this character is allowed to do.
+        perk: PerkDescriptor,
     },
     /// Confirmation that this player redeemed a liability.
     YouRedeemedLiability {
         /// The index of the liability that was redeemed.
         liability_idx: usize,
+        /// The cash it cost to redeem the liability, which includes interest if the game is played
+        /// with [`GameConfig::redemption_interest`](game::game::GameConfig::redemption_interest).
+        cost: u8,
     },
     /// Confirmation that this player ended their turn.
     YouEndedTurn,
+    /// Confirmation that this player, as the host, ended the turn of another player.
+    YouForcedEndTurn {
+        /// Id of the player whose turn was ended.
+        player_id: PlayerId,
+    },
 
     /// Confirmation that you joined the lobby.
     YouJoinedGame {
@@ -334,15 +731,27 @@ pub enum DirectResponse {
         /// The channel you're connected to.
         channel: String,
     },
+    /// Confirms which of the optional protocol features the client asked for when connecting the
+    /// server will use. Features the client did not ask for are never used.
+    YouNegotiatedCapabilities {
+        /// The features that are used for this connection.
+        capabilities: Capabilities,
+    },
     /// Inform the client that they are rejoining
     YouRejoined,
 
+    /// A suggestion for this player's next move, or `None` if it is not their turn.
+    YouGotHint {
+        /// The suggested move and why it was suggested.
+        hint: Option<Hint>,
+    },
+
     /// Deliver data to the client to sync them back to the game state
     YouResynced {
         /// This player's personal id.
         id: PlayerId,
         /// The amount of cash this player has.
-        cash: u8,
+        cash: Cash,
         /// The player's hand.
         #[cfg_attr(
             feature = "ts",
@@ -358,9 +767,37 @@ pub enum DirectResponse {
         player_info: Vec<PlayerInfo>,
         /// The current market.
         market: Market,
+        /// Every event that happened so far this game, oldest first.
+        events: Vec<Event>,
         /// A response containing the current gamestate
         phase: ResyncData,
     },
+    /// Everything this player is allowed to see of the game, sent when they ask for it with
+    /// [`FrontendRequest::RequestFullState`]. Unlike [`DirectResponse::YouResynced`], this can be
+    /// sent in any phase of the game.
+    FullState {
+        /// Public info about this player, with their exact cash.
+        you: PlayerInfo,
+        /// The player's hand.
+        #[cfg_attr(
+            feature = "ts",
+            ts(as = "Vec<serde_asset_liability::EitherAssetLiability>")
+        )]
+        #[serde(with = "serde_asset_liability::vec")]
+        hand: Vec<Either<Asset, Liability>>,
+        /// Public info about every other player.
+        player_info: Vec<PlayerInfo>,
+        /// The current market, or the one this player ended the game in. `None` in the lobby.
+        market: Option<Market>,
+        /// Every event that happened so far this game, oldest first.
+        events: Vec<Event>,
+        /// The id of the player that is expected to act, if any.
+        current_player: Option<PlayerId>,
+        /// How many cards are left in the asset and liability decks, while the game is played.
+        deck_sizes: Option<DeckSizes>,
+        /// The data specific to the phase the game is in.
+        phase: ResyncData,
+    },
     /// Confirms that this player changed one of their market colors.
     YouMinusedIntoPlus {
         /// The market color that was changed,
@@ -396,6 +833,34 @@ pub enum DirectResponse {
     YouConfirmedAssetAbility {
         /// The asset the player confirmed their choice for.
         asset_idx: usize,
+        /// What the ability was used on.
+        used_on: AssetAbilityUse,
+        /// How the score of the player is made up now that the choice is final.
+        breakdown: ScoreBreakdown,
+    },
+    /// Confirms that this player started a vote.
+    YouProposedVote {
+        /// What the vote is about.
+        kind: VoteKind,
+    },
+    /// Confirms that this player voted.
+    YouVoted {
+        /// Whether this player voted in favor.
+        approve: bool,
+    },
+    /// Everything a spectator is allowed to see of the game, sent when they start watching and
+    /// whenever they want to sync back up. Nobody's hand or closed character is in it.
+    SpectatorView {
+        /// Public info about every player, see [`GameState::spectator_info`].
+        player_info: Vec<PlayerInfo>,
+        /// The current market, or the one the game ended in. `None` in the lobby.
+        market: Option<Market>,
+        /// Every event that happened so far this game, oldest first.
+        events: Vec<Event>,
+        /// The id of the player that is expected to act, if any.
+        current_player: Option<PlayerId>,
+        /// The usernames of everyone watching the game.
+        spectators: Vec<String>,
     },
 }
 
@@ -417,6 +882,29 @@ impl From<GameError> for DirectResponse {
     }
 }
 
+impl DirectResponse {
+    /// Creates a [`DirectResponse::SpectatorView`] of `game` as it is right now.
+    pub fn spectator_view(game: &GameState) -> Self {
+        let (market, events) = match game {
+            GameState::Lobby(_) => (None, [].as_slice()),
+            GameState::SelectingCharacters(s) => (Some(s.current_market()), s.current_events()),
+            GameState::Round(round) => (Some(round.current_market()), round.current_events()),
+            GameState::BankerTarget(round) => {
+                (Some(round.current_market()), round.current_events())
+            }
+            GameState::Results(results) => (Some(results.final_market()), results.final_events()),
+        };
+
+        DirectResponse::SpectatorView {
+            player_info: game.spectator_info(),
+            market: market.cloned(),
+            events: events.to_vec(),
+            current_player: acting_player(game),
+            spectators: game.spectators().usernames().to_vec(),
+        }
+    }
+}
+
 /// A response type that is meant for every other player when one player performs an action.
 #[cfg_attr(feature = "ts", derive(TS))]
 #[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
@@ -424,20 +912,43 @@ impl From<GameError> for DirectResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "action", content = "data")]
 pub enum UniqueResponse {
-    /// When someone joins or leaves, this response is sent which includes information on the
+    /// When someone joins, leaves or changes their username, this response is sent which includes information on the
     /// updated list of players.
     PlayersInLobby {
-        /// The name of the player that joined/left.
+        /// The name of the player that joined/left, or their new name if they changed it.
         changed_player: String,
         /// The new list of usernames.
         usernames: Vec<String>,
+        /// The id of the host of the lobby, if anyone is in it.
+        host_id: Option<PlayerId>,
+    },
+    /// Sent when someone else became the host of the lobby, either because the host made them
+    /// host or because the host left.
+    HostChanged {
+        /// The id of the new host.
+        host_id: PlayerId,
+    },
+    /// Sent when someone in the lobby said whether they are ready for the game to start.
+    PlayerReady {
+        /// The id of the player.
+        player_id: PlayerId,
+        /// Whether they are ready.
+        ready: bool,
+    },
+    /// Sent when the host kicked a player out of the lobby. The updated list of players follows
+    /// in [`UniqueResponse::PlayersInLobby`].
+    PlayerKicked {
+        /// The id the kicked player had.
+        player_id: PlayerId,
+        /// The username of the kicked player.
+        username: String,
     },
     /// Sent when the game was started.
     StartGame {
         /// This player's personal id.
         id: PlayerId,
         /// The amount of cash this player gets.
-        cash: u8,
+        cash: Cash,
         /// The player's hand.
         #[cfg_attr(
             feature = "ts",
@@ -449,6 +960,19 @@ pub enum UniqueResponse {
         player_info: Vec<PlayerInfo>,
         /// The market at the start of the game.
         initial_market: Market,
+        /// The checksum of the cards the game is played with as a hexadecimal string, see
+        /// [`GameData::checksum`](game::cards::GameData::checksum).
+        data_checksum: String,
+        /// The markers to draw on top of each color, so players who cannot tell the colors apart
+        /// can still recognize them.
+        color_markers: Vec<ColorMarker>,
+        /// Whether players were seated in a random order, so ids and the first chairman do not
+        /// follow the order players joined in. See
+        /// [`GameConfig::shuffle_seats`](game::game::GameConfig::shuffle_seats).
+        seats_shuffled: bool,
+        /// The rules the game is played with, such as when it ends, the time bank and the variants
+        /// that are enabled.
+        config: GameConfig,
     },
     /// Sent when a [`SelectingCharacters`](game::game::SelectingCharacters) stage begins.
     SelectingCharacters {
@@ -462,6 +986,30 @@ pub enum UniqueResponse {
         closed_character: Option<Character>,
         /// The order each player selects a character in.
         turn_order: Vec<PlayerId>,
+        /// A list of characters that were banned from this round.
+        banned_characters: Vec<Character>,
+    },
+    /// Sent when a new selecting characters phase starts or someone banned a character, while
+    /// characters are still being banned. Characters are dealt once the last one is banned, which
+    /// is followed by [`UniqueResponse::SelectingCharacters`].
+    BanningCharacters {
+        /// The id of the chairman, or the person who bans a character first.
+        chairman_id: PlayerId,
+        /// The id of the player that's currently banning.
+        currently_banning_id: PlayerId,
+        /// If it's this player's turn, a list of characters that can be banned.
+        bannable_characters: Option<Vec<Character>>,
+        /// A list of characters that were banned from this round so far.
+        banned_characters: Vec<Character>,
+        /// The order each player bans a character in.
+        turn_order: Vec<PlayerId>,
+    },
+    /// Sent when someone banned a character from this round.
+    CharacterBanned {
+        /// The id of the player that banned the character.
+        player_id: PlayerId,
+        /// The character that was banned.
+        character: Character,
     },
     /// Sent when someone selected a character.
     SelectedCharacter {
@@ -489,18 +1037,47 @@ pub enum UniqueResponse {
         playable_liabilities: u8,
         /// The character of this player.
         player_character: Character,
+        /// Whether this player has an ability they can use this turn.
+        can_use_ability: bool,
         /// A list of characters which were called but were not available.
         skipped_characters: Vec<Character>,
+        /// Why each of the skipped characters that a player selected was skipped.
+        #[serde(default)]
+        skip_reasons: Vec<(Character, SkipReason)>,
+        /// The time in milliseconds this player has left for the rest of the game, if the game is
+        /// played with a time bank.
+        time_bank_ms: Option<u64>,
+        /// The time in milliseconds this player has for this turn, if turns are limited.
+        turn_time_ms: Option<u64>,
+        /// The phase the turn of this player starts in.
+        phase: TurnPhase,
+        /// Every event that happened so far this game, oldest first.
+        events: Vec<Event>,
+        /// The nonce of this turn, which has to be sent along to end it, see
+        /// [`Round::turn_nonce`](game::game::Round::turn_nonce).
+        turn_nonce: u32,
     },
     /// Sent when a player is targed by the banker on their turn
     PlayerTargetedByBanker {
         /// Id of the player whose turn it is.
         player_turn: PlayerId,
-        /// Amount of cash to be paid to banker.
+        /// Amount of cash to be paid to banker. Paying any other amount is rejected.
         cash_to_be_paid: u8,
+        /// How the amount of cash to be paid to the banker is made up.
+        credit_termination: CreditTermination,
         /// Amount of cash to be paid to banker.
         is_possible_to_pay_banker: bool,
     },
+    /// Sent to the target of the banker when their credit line is terminated, with how the amount
+    /// they owe the banker is made up. It is sent before [`UniqueResponse::PlayerTargetedByBanker`].
+    YourCreditTerminated {
+        /// The gold owed no matter which assets this player owns.
+        base_gold: u8,
+        /// Every color of asset this player owns, which each add one gold.
+        colors: Vec<Color>,
+        /// The total amount of gold owed to the banker.
+        total_gold: u8,
+    },
     /// Sent when a player selects or unselects an asset to sell or liability to issue when paying
     /// off the banker.
     SelectedCardsBankerTarget {
@@ -515,6 +1092,8 @@ pub enum UniqueResponse {
         player_id: PlayerId,
         /// The type of card this player drew.
         card_type: CardType,
+        /// How many cards are left in the asset and liability decks afterwards.
+        deck_sizes: DeckSizes,
     },
     /// Sent when someone put back a card.
     PutBackCard {
@@ -522,6 +1101,8 @@ pub enum UniqueResponse {
         player_id: PlayerId,
         /// The type of card this player put back.
         card_type: CardType,
+        /// How many cards are left in the asset and liability decks afterwards.
+        deck_sizes: DeckSizes,
     },
     /// Sent when a player bought an asset.
     BoughtAsset {
@@ -534,6 +1115,13 @@ pub enum UniqueResponse {
         /// If buying the asset changed the market, sends a list of events as well as the new
         /// market.
         market_change: Option<MarketChange>,
+        /// The units of assets this player can still buy this turn, see [`PlayableAssets`].
+        assets_to_play: u8,
+        /// The cash this player has left, which is only roughly known if the game hides cash.
+        cash: PlayerCash,
+        /// The [`AssetEffect`] buying the asset triggered, if any.
+        #[serde(default)]
+        triggered_effect: Option<AssetEffect>,
     },
     /// Sent when a player issued a liability.
     IssuedLiability {
@@ -550,6 +1138,8 @@ pub enum UniqueResponse {
         player_id: PlayerId,
         /// The index of the liability this player redeemed.
         liability_idx: usize,
+        /// The cash it cost to redeem the liability.
+        cost: u8,
     },
     /// Player got their characters bonus gold.
     PlayerGotBonusCash {
@@ -581,15 +1171,39 @@ pub enum UniqueResponse {
         /// The id of the player who is the banker.
         player_id: PlayerId,
         /// The new cash balance of the banker.
-        new_banker_cash: u8,
+        new_banker_cash: Cash,
         /// The new cash balance of the player that was targeted by the banker.
-        new_target_cash: u8,
+        new_target_cash: Cash,
         /// The amount of gold paid.
         paid_amount: u8,
         /// A list of assets to be sold to pay off the banker.
         sold_assets: Vec<SoldAssetToPayBanker>,
         /// A list of liabilities to be issued to pay off the banker.
         issued_liabilities: Vec<IssuedLiabilityToPayBanker>,
+        /// What the player still owes the banker because they could not pay in full.
+        remaining_debt: u8,
+    },
+    /// Sent when a player paid off (part of) what they still owed a banker, at the start of their
+    /// turn.
+    PlayerPaidBankerDebt {
+        /// The id of the player that paid.
+        player_id: PlayerId,
+        /// The id of the player the debt was owed to.
+        banker_id: PlayerId,
+        /// The amount of gold paid.
+        paid_amount: u8,
+        /// The amount of gold still owed to the banker after this payment.
+        remaining_debt: u8,
+        /// The new cash balance of the player that paid.
+        new_player_cash: Cash,
+        /// The new cash balance of the banker.
+        new_banker_cash: Cash,
+    },
+    /// Sent to a player when they will not get a turn this round, because they were fired or
+    /// because of an event.
+    YourTurnSkipped {
+        /// Why this player's turn is skipped.
+        reason: SkipReason,
     },
     /// Sent when the regulator swapped their hand with this player.
     RegulatorSwappedYourCards {
@@ -615,6 +1229,16 @@ pub enum UniqueResponse {
         /// The amount of liabilities the regulator drew from the deck.
         liability_count: usize,
     },
+    /// Sent to a player when the stakeholder picked one of their assets to divest. It is divested
+    /// once the stakeholder confirms it, which is announced with [`UniqueResponse::AssetDivested`].
+    YouAreBeingDivested {
+        /// The id of the stakeholder.
+        stakeholder_id: PlayerId,
+        /// The index of the asset that is picked to be divested.
+        asset_idx: usize,
+        /// The amount of gold the stakeholder would pay to divest this asset.
+        gold_cost: u8,
+    },
     /// Sent when the stakeholder forced another player to divest an asset.
     AssetDivested {
         /// The id of the stakeholder.
@@ -633,14 +1257,45 @@ pub enum UniqueResponse {
     },
     /// Sent when the game ended.
     GameEnded {
-        /// A list of player scores.
+        /// A list of player scores, from the highest to the lowest.
         scores: Vec<PlayerScore>,
+        /// What every player ended the game with and how their score is made up, in the order of
+        /// their ids.
+        boards: Vec<FinalBoard>,
+        /// The market the game ended in.
+        final_market: Market,
     },
     /// Sent when rejoin request is acknowledged
     Rejoined {
         /// Id of the rejoining player
         player_id: PlayerId,
     },
+    /// Sent when a player's connection was lost or timed out during the game.
+    PlayerDisconnected {
+        /// Id of the disconnected player
+        player_id: PlayerId,
+        /// Whether the turns of this player end by themselves while they are away, because their
+        /// time bank keeps running, see [`UniqueResponse::TurnEndedOutOfTime`]. Otherwise the
+        /// game waits for them, unless the host ends their turn.
+        turns_auto_skipped: bool,
+    },
+    /// Sent when a player that disconnected during the game connected again.
+    PlayerReconnected {
+        /// Id of the reconnected player
+        player_id: PlayerId,
+    },
+    /// Sent when a player ran out of time in their time bank, and their turn was ended for them.
+    /// Any cards they still had to give back were put back automatically.
+    TurnEndedOutOfTime {
+        /// Id of the player that ran out of time
+        player_id: PlayerId,
+    },
+    /// Sent when the host ended the turn of a player. Any cards they still had to give back were
+    /// put back automatically, which is sent before this as [`UniqueResponse::PutBackCard`].
+    TurnForcedToEnd {
+        /// Id of the player whose turn was ended
+        player_id: PlayerId,
+    },
     /// Confirms that a player changed one of their market colors.
     MinusedIntoPlus {
         /// The id of the player which changed one of their market colors.
@@ -682,6 +1337,54 @@ pub enum UniqueResponse {
         player_id: PlayerId,
         /// The asset the player confirmed their choice for.
         asset_idx: usize,
+        /// What the ability was used on.
+        used_on: AssetAbilityUse,
+        /// How the score of the player is made up now that the choice is final.
+        breakdown: ScoreBreakdown,
+    },
+    /// Sent when a player started a vote.
+    VoteProposed {
+        /// The id of the player that started the vote.
+        proposer_id: PlayerId,
+        /// What the vote is about.
+        kind: VoteKind,
+        /// How many players have to vote in favor for the vote to pass.
+        votes_needed: usize,
+        /// How many seconds players have to vote before the vote fails.
+        seconds_left: u64,
+    },
+    /// Sent when a player voted on the vote that is currently running.
+    VoteCast {
+        /// The id of the player that voted.
+        player_id: PlayerId,
+        /// Whether the player voted in favor.
+        approve: bool,
+        /// How many players voted in favor so far.
+        votes_for: usize,
+        /// How many players voted against so far.
+        votes_against: usize,
+    },
+    /// Sent when the vote that was running ended.
+    VoteEnded {
+        /// What the vote was about.
+        kind: VoteKind,
+        /// Whether the vote passed.
+        passed: bool,
+    },
+    /// Sent to players and spectators when someone started watching the game.
+    SpectatorJoined {
+        /// The username of the new spectator.
+        username: String,
+        /// The usernames of everyone watching the game, listed apart from the players.
+        spectators: Vec<String>,
+    },
+    /// Sent when a player changed the game with a debug command. Clients should resync, since the
+    /// change is not described any further.
+    DevCommandUsed {
+        /// The id of the player that used the command.
+        player_id: PlayerId,
+        /// The command that was used.
+        command: DevRequest,
     },
     /// Used internally to gracefully let everyone know a room closed for any reason.
     #[serde(skip)]
@@ -704,6 +1407,107 @@ pub enum RoomCloseReason {
     GameEnded,
     /// Used when it encountered a fatal error
     FatalError,
+    /// Used when the players voted to abort the game.
+    Aborted,
+    /// Used to close the connection of a player that was voted out of the room.
+    Kicked,
+}
+
+/// What players can vote on, see [`FrontendRequest::ProposeVote`].
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "vote", content = "data")]
+pub enum VoteKind {
+    /// Remove a player from the room. They cannot vote on their own removal, and cannot join the
+    /// room again.
+    Kick {
+        /// The id of the player to remove.
+        target: PlayerId,
+    },
+    /// Stop the game and close the room for everyone.
+    Abort,
+    /// Stop the time bank of every player, and reject any action until the game is resumed.
+    Pause,
+    /// Continue a paused game.
+    Resume,
+}
+
+/// Debug commands that put a game in any situation during a round, so edge cases can be
+/// reproduced without playing up to them. See [`FrontendRequest::Dev`].
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", content = "data")]
+pub enum DevRequest {
+    /// Sets the cash of a player.
+    SetCash {
+        /// The id of the player whose cash is set.
+        player_id: PlayerId,
+        /// The cash the player will have.
+        cash: u16,
+    },
+    /// Takes an asset out of the deck and puts it in the hand of a player.
+    GiveCard {
+        /// The id of the player who gets the asset.
+        player_id: PlayerId,
+        /// The title of the asset.
+        title: String,
+    },
+    /// Makes a market the current market.
+    ForceMarket {
+        /// The title of the market.
+        title: String,
+    },
+}
+
+impl DevRequest {
+    /// Runs this command on the round that is being played in `state`. Only available with the
+    /// `dev` feature.
+    #[cfg(feature = "dev")]
+    pub fn run(&self, state: &mut GameState) -> Result<(), GameError> {
+        let round = state.round_mut()?;
+        match self {
+            DevRequest::SetCash { player_id, cash } => {
+                round.dev_set_cash(*player_id, Cash(*cash))?
+            }
+            DevRequest::GiveCard { player_id, title } => {
+                round.dev_give_card(*player_id, title)?;
+            }
+            DevRequest::ForceMarket { title } => {
+                round.dev_force_market(title)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors that can happen when proposing a vote or voting.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
+#[derive(Debug, PartialEq, Error, Serialize, Deserialize)]
+pub enum VoteError {
+    /// Another vote is still running.
+    #[error("Another vote is still running")]
+    VoteInProgress,
+    /// There is no vote to vote on.
+    #[error("There is no vote running")]
+    NoVoteInProgress,
+    /// The player already voted.
+    #[error("Player already voted")]
+    AlreadyVoted,
+    /// The player is not allowed to vote on this vote.
+    #[error("Player cannot vote on this vote")]
+    CannotVote,
+    /// A player tried to start a vote to kick themselves.
+    #[error("Player cannot vote to kick themselves")]
+    CannotKickSelf,
+    /// The vote would not change anything, like pausing a game that is already paused.
+    #[error("Vote would not change anything")]
+    NothingToChange,
+    /// The player was voted out of the room before.
+    #[error("Player was kicked from this room")]
+    Kicked,
 }
 
 /// The general error type that can be sent back in a response.
@@ -723,6 +1527,23 @@ pub enum ResponseError {
     /// An error sent when the data the player sent in invalid.
     #[error("Data is not valid for this state")]
     InvalidData,
+    /// An error sent when a frame the player sent is not a request the server understands, for
+    /// example because it was sent by an outdated client.
+    #[error("Request could not be read")]
+    UnreadableRequest,
+    /// An error sent when a player sends more requests than the server allows.
+    #[error("Too many requests, slow down")]
+    RateLimited,
+    /// An error sent when a player sent the exact same request twice in quick succession, for
+    /// example by double clicking a button. The first request was already handled.
+    #[error("Duplicate request ignored")]
+    DuplicateRequest,
+    /// A [`VoteError`]
+    #[error(transparent)]
+    Vote(#[from] VoteError),
+    /// An error sent when a player tries to take an action while the game is paused.
+    #[error("Game is paused")]
+    GamePaused,
 }
 
 /// Custom data used for resyncing a client
@@ -734,12 +1555,17 @@ pub enum ResyncData {
     SelectingCharacters {
         /// The id of the chairman, or the person who selects a character first.
         chairman_id: PlayerId,
-        /// The id of the player currently selecting a character
+        /// The id of the player currently selecting a character, or banning one while characters
+        /// are still being banned.
         currently_picking_id: PlayerId,
         /// If it's this player's turn, a list of characters that can be selected.
         selectable_characters: Option<Vec<Character>>,
+        /// If it's this player's turn to ban, a list of characters that can be banned.
+        bannable_characters: Option<Vec<Character>>,
         /// A list of characters that cannot be selected by anyone.
         open_characters: Vec<Character>,
+        /// A list of characters that were banned from this round so far.
+        banned_characters: Vec<Character>,
         /// A character that only the chairman can see, but not select.
         closed_character: Option<Character>,
         /// The order each player selects a character in.
@@ -777,5 +1603,67 @@ pub enum ResyncData {
 
         /// The amount of liabilities this player can play.
         playable_liabilities: u8,
+
+        /// The nonce of this turn, which has to be sent along to end it.
+        turn_nonce: u32,
+    },
+
+    /// When the game has not started yet this will be sent
+    Lobby {
+        /// The id of the host of the lobby, if anyone is in it.
+        host_id: Option<PlayerId>,
+        /// The usernames of everyone watching the lobby.
+        spectators: Vec<String>,
+        /// The ids of the players that are ready for the game to start.
+        ready: Vec<PlayerId>,
     },
+
+    /// When a player is paying the banker this will be sent
+    PayingBanker {
+        /// The player that has to pay the banker.
+        current_player_id: PlayerId,
+        /// The amount of cash that has to be paid.
+        gold_to_be_paid: u8,
+    },
+
+    /// When the game has ended this will be sent
+    Results {
+        /// The score of every player, in the order of their ids.
+        scores: Vec<PlayerScore>,
+    },
+}
+
+/// One or more responses that are sent in a single websocket frame, to clients that negotiated
+/// [`Capabilities::BATCHES`]. The reply to a request and all responses that are queued for a
+/// player when their connection gets around to sending are coalesced into one frame, in which case
+/// they are sent as an array in the order they were produced, the reply first.
+#[cfg_attr(feature = "ts", derive(TS))]
+#[cfg_attr(feature = "ts", ts(export_to = game::SHARED_TS_DIR))]
+#[cfg_attr(feature = "ts", ts(export))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Batched<T> {
+    /// A single response.
+    One(T),
+    /// Multiple responses, in the order they were produced.
+    Many(Vec<T>),
+}
+
+impl<T> Batched<T> {
+    /// Gets all responses in this batch, in the order they were produced.
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(response) => vec![response],
+            Self::Many(responses) => responses,
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Batched<T> {
+    fn from(mut responses: Vec<T>) -> Self {
+        match responses.len() {
+            1 => Self::One(responses.remove(0)),
+            _ => Self::Many(responses),
+        }
+    }
 }
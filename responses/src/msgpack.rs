@@ -0,0 +1,15 @@
+//! Helpers to encode requests and responses as MessagePack, which clients can opt into to get
+//! smaller messages sent as binary websocket frames.
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Encodes `msg` as MessagePack. Structs are encoded as maps, so the result has the same shape
+/// as the json representation.
+pub fn to_msgpack(msg: &impl Serialize) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    rmp_serde::to_vec_named(msg)
+}
+
+/// Decodes a MessagePack encoded message, see [`to_msgpack`].
+pub fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, rmp_serde::decode::Error> {
+    rmp_serde::from_slice(bytes)
+}
@@ -0,0 +1,75 @@
+//! Every player in a room has their own channel for the responses only they should see. Channels
+//! are indexed by player id, and follow their player when the seats are shuffled at the start of a
+//! game, so a connection keeps receiving the responses for the same player.
+
+use game::{game::MAX_PLAYERS, player::PlayerId};
+use responses::UniqueResponse;
+use tokio::sync::broadcast;
+
+use std::sync::{Arc, Mutex};
+
+/// The channels of the players of a single room, indexed by player id. Clones share the same
+/// channels.
+#[derive(Debug, Clone)]
+pub struct PlayerChannels(Arc<Mutex<[broadcast::Sender<Vec<UniqueResponse>>; MAX_PLAYERS]>>);
+
+impl PlayerChannels {
+    /// Creates a channel for every seat, which can each hold `capacity` batches of responses.
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(std::array::from_fn(|_| {
+            broadcast::channel(capacity).0
+        }))))
+    }
+
+    /// Sends `responses` to player `id` as a single batch.
+    pub fn send(&self, id: PlayerId, responses: Vec<UniqueResponse>) {
+        if let Some(tx) = self.lock().get(usize::from(id)) {
+            let _ = tx.send(responses);
+        }
+    }
+
+    /// Receives the responses sent to player `id`. Panics if `id` is not a valid seat.
+    pub fn subscribe(&self, id: PlayerId) -> broadcast::Receiver<Vec<UniqueResponse>> {
+        assert!(usize::from(id) < MAX_PLAYERS, "{id:?} is not a valid seat");
+        self.lock()[usize::from(id)].subscribe()
+    }
+
+    /// Moves the channel of every player to their new seat, where `seats[i]` is the id the player
+    /// that now has id `i` had before. Seats that are not in `seats` keep their channel.
+    pub fn reseat(&self, seats: &[PlayerId]) {
+        let mut channels = self.lock();
+        let old = channels.clone();
+        for (new, &old_id) in seats.iter().enumerate() {
+            channels[new] = old[usize::from(old_id)].clone();
+        }
+    }
+
+    fn lock(
+        &self,
+    ) -> std::sync::MutexGuard<'_, [broadcast::Sender<Vec<UniqueResponse>>; MAX_PLAYERS]> {
+        // PANIC: a mutex can only poison if a thread panicked while holding it, and none of the
+        // methods above can panic while the lock is held.
+        self.0.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    #[test]
+    fn channels_follow_their_player() {
+        let channels = PlayerChannels::new(8);
+        let mut rx = channels.subscribe(PlayerId(0));
+
+        channels
+            .clone()
+            .reseat(&[PlayerId(2), PlayerId(1), PlayerId(0)]);
+        channels.send(PlayerId(0), vec![]);
+        assert_err!(rx.try_recv());
+
+        channels.send(PlayerId(2), vec![]);
+        assert_ok!(rx.try_recv());
+    }
+}
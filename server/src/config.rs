@@ -0,0 +1,63 @@
+//! How the server is configured. Everything is read from environment variables once when the
+//! server starts, so a misconfigured server refuses to start instead of failing every connection
+//! or room that reads the same variable later on.
+
+use thiserror::Error;
+
+use std::{str::FromStr, time::Duration};
+
+/// Errors in how the server is configured, which are found when it starts.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// Error indicating that an environment variable should have been a positive number.
+    #[error("ENV {name} should be a positive number, not '{value}'")]
+    InvalidNumber { name: &'static str, value: String },
+    /// Error indicating that `BACKPRESSURE_POLICY` is not a
+    /// [`BackpressurePolicy`](crate::queue::BackpressurePolicy).
+    #[error("ENV BACKPRESSURE_POLICY should be resync or disconnect, not '{0}'")]
+    InvalidPolicy(String),
+}
+
+/// Reads the environment variable `name` as a positive number, if it is set.
+pub fn env_number<T: FromStr>(name: &'static str) -> Result<Option<T>, ConfigError> {
+    parse_number(name, std::env::var(name).ok())
+}
+
+/// Reads the environment variable `name` as a number of seconds, if it is set.
+pub fn env_seconds(name: &'static str) -> Result<Option<Duration>, ConfigError> {
+    Ok(env_number(name)?.map(Duration::from_secs))
+}
+
+/// Parses `value` of the environment variable `name` as a positive number, if it is set.
+fn parse_number<T: FromStr>(
+    name: &'static str,
+    value: Option<String>,
+) -> Result<Option<T>, ConfigError> {
+    value
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| ConfigError::InvalidNumber { name, value })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    #[test]
+    fn numbers_are_parsed() {
+        assert_none!(assert_ok!(parse_number::<u64>("TEST", None)));
+        assert_some_eq!(assert_ok!(parse_number::<u64>("TEST", Some("3".into()))), 3);
+        assert_some_eq!(
+            assert_ok!(parse_number::<f64>("TEST", Some("2.5".into()))),
+            2.5
+        );
+        assert_matches!(
+            parse_number::<u64>("TEST", Some("-3".into())),
+            Err(ConfigError::InvalidNumber { name: "TEST", value }) if value == "-3"
+        );
+    }
+}
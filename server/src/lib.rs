@@ -1,3 +1,11 @@
+pub mod channels;
+pub mod config;
+pub mod middleware;
+pub mod queue;
 pub mod request_handler;
 pub mod rooms;
 pub mod server;
+pub mod spectators;
+pub mod storage;
+pub mod telemetry;
+pub mod votes;
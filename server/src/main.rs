@@ -1,6 +1,6 @@
-use server::server::setupsocket;
+use server::{config::ConfigError, server::setupsocket};
 
 #[tokio::main]
-async fn main() {
-    setupsocket().await;
+async fn main() -> Result<(), ConfigError> {
+    setupsocket().await
 }
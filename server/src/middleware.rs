@@ -0,0 +1,1270 @@
+//! Requests are handled by passing them through a chain of [`Layer`]s before they reach the game.
+//! Each layer handles a single concern, like rate limiting or sending responses out, and decides
+//! whether the request may continue to the next layer. The default chain runs
+//! auth → rate limit → dedup → validation → audit → telemetry → fan-out → votes → kicks → dev →
+//! game dispatch, where the audit, telemetry and fan-out layers act on the result of the game once
+//! dispatch returns. Votes, kicks and debug commands are handled by their own layers instead of
+//! the game. The vote and kick layers share what the players decided with the room through
+//! [`RoomControl`].
+
+use game::{
+    errors::GameError,
+    game::{GameState, PlayerAction, SelectingCharacters, normalize_username},
+    player::{Character, PlayerId},
+};
+use responses::*;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, RandomState},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    channels::PlayerChannels,
+    request_handler::*,
+    rooms::RoomConfig,
+    storage::{AuditEntry, AuditLog, Storage},
+    telemetry::{CharacterPlay, TelemetryEvent, TelemetrySink},
+    votes::{Vote, Votes, voters},
+};
+
+const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_millis(250);
+
+/// A request of a single player as it passes through the chain.
+#[derive(Debug)]
+pub struct RequestContext<'a> {
+    /// The username of the player that sent the request.
+    pub username: &'a str,
+    /// The request itself.
+    pub request: FrontendRequest,
+    /// The state of the game the request is for.
+    pub game: &'a mut GameState,
+}
+
+/// A single step in handling a request. A layer can reject the request by returning an error,
+/// pass it on by calling [`Next::run`], and look at or change the response on the way back.
+pub trait Layer: Send {
+    /// Handles `ctx`, calling `next` to continue down the chain.
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError>;
+}
+
+/// The remainder of the chain after the current layer.
+pub struct Next<'a> {
+    layers: &'a mut [Box<dyn Layer>],
+}
+
+impl Next<'_> {
+    /// Runs the rest of the chain for `ctx`, ending with the request being applied to the game.
+    pub fn run(self, ctx: RequestContext<'_>) -> Result<Response, ResponseError> {
+        match self.layers.split_first_mut() {
+            Some((layer, layers)) => layer.handle(ctx, Next { layers }),
+            None => dispatch(ctx).map_err(Into::into),
+        }
+    }
+}
+
+/// An ordered list of layers every request of a room passes through.
+#[derive(Default)]
+pub struct Chain {
+    layers: Vec<Box<dyn Layer>>,
+}
+
+impl Chain {
+    /// Creates a chain without any layers, which applies each request directly to the game.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the chain used by the room with `channel` and `config`, sending the responses of
+    /// the game over `player_tx` and sharing what the players decide with the room through
+    /// `control`. If `storage` is set, every accepted action is written to its audit log. If
+    /// `telemetry` is set, anonymized events about the game are sent to it.
+    pub fn standard(
+        channel: &str,
+        config: &RoomConfig,
+        player_tx: PlayerChannels,
+        control: Arc<Mutex<RoomControl>>,
+        storage: Option<Arc<dyn Storage>>,
+        telemetry: Option<Arc<dyn TelemetrySink>>,
+    ) -> Self {
+        let chain = Self::new()
+            .layer(AuthLayer)
+            .layer(RateLimitLayer::new(
+                config.rate_limit,
+                config.rate_limit_burst,
+            ))
+            .layer(DedupLayer::new(DEFAULT_DEDUP_WINDOW))
+            .layer(ValidationLayer);
+        let chain = match storage {
+            Some(storage) => chain.layer(AuditLayer::new(channel.to_owned(), storage)),
+            None => chain,
+        };
+        let chain = match telemetry {
+            Some(sink) => chain.layer(TelemetryLayer::new(sink)),
+            None => chain,
+        };
+
+        chain
+            .layer(FanOutLayer::new(player_tx))
+            .layer(VoteLayer::new(control.clone()))
+            .layer(KickLayer::new(control))
+            .layer(DevLayer)
+    }
+
+    /// Adds `layer` to the end of the chain, right before the game itself.
+    pub fn layer(mut self, layer: impl Layer + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    /// Passes `ctx` through every layer of the chain.
+    pub fn handle(&mut self, ctx: RequestContext<'_>) -> Result<Response, ResponseError> {
+        Next {
+            layers: &mut self.layers,
+        }
+        .run(ctx)
+    }
+}
+
+impl std::fmt::Debug for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Chain")
+            .field("layers", &self.layers.len())
+            .finish()
+    }
+}
+
+/// Rejects requests of anyone who is not a player in the game.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuthLayer;
+
+impl Layer for AuthLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        let is_player = match &*ctx.game {
+            GameState::Lobby(lobby) => lobby.usernames().contains(&ctx.username),
+            GameState::SelectingCharacters(s) => s.player_by_name(ctx.username).is_ok(),
+            GameState::Round(r) => r.player_by_name(ctx.username).is_ok(),
+            GameState::BankerTarget(b) => b.player_by_name(ctx.username).is_ok(),
+            GameState::Results(r) => r.player_by_name(ctx.username).is_ok(),
+        };
+
+        match is_player {
+            true => next.run(ctx),
+            false => Err(GameError::InvalidPlayerName(ctx.username.to_owned()).into()),
+        }
+    }
+}
+
+/// Limits how many requests each player can send using a token bucket. Every player can send a
+/// burst of requests at once, after which they are limited to a steady rate.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    per_second: f64,
+    burst: f64,
+    buckets: HashMap<String, (f64, Instant)>,
+}
+
+impl RateLimitLayer {
+    /// Creates a new rate limit which allows `burst` requests at once, refilling at `per_second`
+    /// requests per second.
+    pub fn new(per_second: f64, burst: f64) -> Self {
+        Self {
+            per_second,
+            burst,
+            buckets: HashMap::new(),
+        }
+    }
+
+    fn try_take(&mut self, username: &str, now: Instant) -> bool {
+        let (tokens, last) = self
+            .buckets
+            .entry(username.to_owned())
+            .or_insert((self.burst, now));
+
+        let refilled = now.duration_since(*last).as_secs_f64() * self.per_second;
+        *tokens = (*tokens + refilled).min(self.burst);
+        *last = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Layer for RateLimitLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        match self.try_take(ctx.username, Instant::now()) {
+            true => next.run(ctx),
+            false => Err(ResponseError::RateLimited),
+        }
+    }
+}
+
+/// Drops a request if the same player sent the exact same request within a short window, which
+/// mostly happens when a button is clicked twice. Only requests that can never be meant to happen
+/// twice in a row are dropped.
+#[derive(Debug, Clone)]
+pub struct DedupLayer {
+    window: Duration,
+    last: HashMap<String, (FrontendRequest, Instant)>,
+}
+
+impl DedupLayer {
+    /// Creates a new dedup layer which drops identical requests sent within `window`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last: HashMap::new(),
+        }
+    }
+
+    fn is_duplicate(&mut self, username: &str, request: &FrontendRequest, now: Instant) -> bool {
+        if !matches!(
+            request,
+            FrontendRequest::StartGame
+                | FrontendRequest::SelectCharacter { .. }
+                | FrontendRequest::BanCharacter { .. }
+                | FrontendRequest::UseAbility { .. }
+                | FrontendRequest::GetBonusCash { .. }
+                | FrontendRequest::PayBanker { .. }
+        ) {
+            return false;
+        }
+
+        let duplicate = self
+            .last
+            .get(username)
+            .is_some_and(|(last, at)| last == request && now.duration_since(*at) < self.window);
+        self.last
+            .insert(username.to_owned(), (request.clone(), now));
+
+        duplicate
+    }
+}
+
+impl Layer for DedupLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        match self.is_duplicate(ctx.username, &ctx.request, Instant::now()) {
+            true => Err(ResponseError::DuplicateRequest),
+            false => next.run(ctx),
+        }
+    }
+}
+
+/// Rejects requests that cannot be handled in the current phase of the game before they reach
+/// it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidationLayer;
+
+impl ValidationLayer {
+    fn validate(request: &FrontendRequest, game: &GameState) -> Result<(), GameError> {
+        use FrontendRequest as R;
+
+        match request {
+            R::StartGame
+            | R::TransferHost { .. }
+            | R::SetReady { .. }
+            | R::ChangeUsername { .. } => game.lobby().map(drop),
+            R::SelectCharacter { .. } | R::BanCharacter { .. } => {
+                game.selecting_characters().map(drop)
+            }
+            R::SelectAssetToDivest { .. }
+            | R::UnselectAssetToDivest { .. }
+            | R::SelectLiabilityToIssue { .. }
+            | R::UnselectLiabilityToIssue { .. } => game.bankertarget().map(drop),
+            R::PayBanker { cash } => game
+                .bankertarget()?
+                .check_payment(*cash)
+                .map_err(Into::into),
+            R::MinusIntoPlus { .. }
+            | R::SilverIntoGold { .. }
+            | R::ChangeAssetColor { .. }
+            | R::ConfirmAssetAbility { .. } => game.results().map(drop),
+            R::RequestHint => game.round().map(drop),
+            R::KickPlayer { target } => {
+                game.lobby()?;
+                player_name(game, *target).map(drop)
+            }
+            R::ProposeVote {
+                kind: VoteKind::Kick { target },
+            } => player_name(game, *target).map(drop),
+            // Whether a vote is running is up to the vote layer, votes can be taken in any phase
+            R::ProposeVote { .. } | R::CastVote { .. } => Ok(()),
+            R::Dev { .. } if cfg!(feature = "dev") => game.round().map(drop),
+            R::Dev { .. } => Err(game::errors::DevError::Disabled.into()),
+            R::Resync => match game {
+                GameState::Round(_) | GameState::SelectingCharacters(_) => Ok(()),
+                _ => Err(GameError::NotRoundState),
+            },
+            R::RequestFullState => Ok(()),
+            R::DrawCard { .. }
+            | R::PutBackCard { .. }
+            | R::BuyAsset { .. }
+            | R::IssueLiability { .. }
+            | R::RedeemLiability { .. }
+            | R::UseAbility { .. }
+            | R::GetBonusCash { .. }
+            | R::FireCharacter { .. }
+            | R::TerminateCreditCharacter { .. }
+            | R::SwapWithDeck { .. }
+            | R::SwapWithPlayer { .. }
+            | R::DivestAsset { .. }
+            | R::ConfirmDivest
+            | R::EndTurn { .. }
+            | R::ForceEndTurn { .. } => game.round().map(drop),
+        }
+    }
+}
+
+impl Layer for ValidationLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        Self::validate(&ctx.request, ctx.game)?;
+        next.run(ctx)
+    }
+}
+
+/// Writes every action the game accepted to the audit log of the room, together with who took it
+/// and a hash of the state it resulted in. Resyncs and hints are not actions, so they are not
+/// logged. The log is written in the background, see [`AuditLog`].
+#[derive(Debug, Clone)]
+pub struct AuditLayer {
+    log: AuditLog,
+}
+
+impl AuditLayer {
+    /// Creates a new audit layer which writes to the log of `channel` in `storage`. Has to be
+    /// called from within a tokio runtime.
+    pub fn new(channel: String, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            log: AuditLog::spawn(channel, storage),
+        }
+    }
+}
+
+impl Layer for AuditLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        let RequestContext {
+            username,
+            request,
+            game,
+        } = ctx;
+
+        let action = match request {
+            FrontendRequest::Resync
+            | FrontendRequest::RequestFullState
+            | FrontendRequest::RequestHint => None,
+            _ => Some(request.clone()),
+        };
+        let response = next.run(RequestContext {
+            username,
+            request,
+            game: &mut *game,
+        })?;
+
+        if let Some(action) = action {
+            let entry = AuditEntry::now(username.to_owned(), action, game.state_hash());
+            self.log.append(entry);
+        }
+
+        Ok(response)
+    }
+}
+
+/// Keeps track of how a game is played and sends anonymized [`TelemetryEvent`]s about it to a
+/// [`TelemetrySink`]. Games of lobbies that opted out with
+/// [`GameConfig::telemetry_opt_out`](game::game::GameConfig::telemetry_opt_out) are not tracked.
+#[derive(Debug)]
+pub struct TelemetryLayer {
+    sink: Arc<dyn TelemetrySink>,
+    game: Option<GameStats>,
+}
+
+/// What the [`TelemetryLayer`] knows about the game that is running.
+#[derive(Debug)]
+struct GameStats {
+    id: u64,
+    started: Instant,
+    action_counts: BTreeMap<String, u32>,
+    /// The player and character of every turn so far, with how long the turn took.
+    turns: Vec<(PlayerId, Character, Duration)>,
+    /// When the last turn of `turns` started.
+    turn_started: Instant,
+}
+
+impl TelemetryLayer {
+    /// Creates a new telemetry layer which sends its events to `sink`.
+    pub fn new(sink: Arc<dyn TelemetrySink>) -> Self {
+        Self { sink, game: None }
+    }
+
+    /// Gets the player whose turn it is and the character they play, if it is anyone's turn.
+    fn current_turn(game: &GameState) -> Option<(PlayerId, Character)> {
+        match game {
+            GameState::Round(round) => {
+                let player = round.current_player();
+                Some((player.id(), player.character()))
+            }
+            GameState::BankerTarget(round) => {
+                let player = round.current_player();
+                Some((player.id(), player.character()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Updates the stats of the running game after `action` was applied to `game`, and sends the
+    /// results once the game is over.
+    fn track(&mut self, action: Option<PlayerAction>, game: &GameState) {
+        let Some(stats) = &mut self.game else {
+            return;
+        };
+        let now = Instant::now();
+
+        if let Some(action) = action
+            && let Ok(value) = serde_json::to_value(&action)
+            && let Some(name) = value["action"].as_str()
+        {
+            *stats.action_counts.entry(name.to_owned()).or_default() += 1;
+        }
+
+        let turn = Self::current_turn(game);
+        let last_turn = stats
+            .turns
+            .last()
+            .map(|&(id, character, _)| (id, character));
+        if turn != last_turn {
+            if let Some((_, _, duration)) = stats.turns.last_mut() {
+                *duration = now - stats.turn_started;
+            }
+            if let Some((id, character)) = turn {
+                stats.turns.push((id, character, Duration::ZERO));
+            }
+            stats.turn_started = now;
+        }
+
+        let GameState::Results(results) = game else {
+            return;
+        };
+        // PANIC: we just checked there is a game running.
+        let stats = self.game.take().unwrap();
+        let winning_score = results.ranking().first().map(|p| p.fixed_score());
+        let winners = results
+            .ranking()
+            .into_iter()
+            .filter(|p| Some(p.fixed_score()) == winning_score)
+            .map(|p| p.id())
+            .collect::<Vec<_>>();
+
+        self.sink.emit(TelemetryEvent::GameEnded {
+            game_id: stats.id,
+            duration_secs: stats.started.elapsed().as_secs_f64(),
+            action_counts: stats.action_counts,
+            characters: stats
+                .turns
+                .into_iter()
+                .map(|(id, character, duration)| CharacterPlay {
+                    character,
+                    turn_secs: duration.as_secs_f64(),
+                    won: winners.contains(&id),
+                })
+                .collect(),
+            character_stats: results.character_stats().clone(),
+        });
+    }
+}
+
+impl Layer for TelemetryLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        let RequestContext {
+            username,
+            request,
+            game,
+        } = ctx;
+
+        let opted_in = game
+            .lobby()
+            .is_ok_and(|lobby| !lobby.config().telemetry_opt_out);
+        let action = request.clone().into_action();
+        let response = next.run(RequestContext {
+            username,
+            request,
+            game: &mut *game,
+        })?;
+
+        if opted_in && let Ok(selecting) = game.selecting_characters() {
+            let game_id = RandomState::new().hash_one(Instant::now());
+            self.game = Some(GameStats {
+                id: game_id,
+                started: Instant::now(),
+                action_counts: BTreeMap::new(),
+                turns: Vec::new(),
+                turn_started: Instant::now(),
+            });
+            self.sink.emit(TelemetryEvent::GameStarted {
+                game_id,
+                players: selecting.players().len(),
+            });
+        }
+        self.track(action, game);
+
+        Ok(response)
+    }
+}
+
+/// Sends the responses the game produced for each player to that player, leaving only the direct
+/// response for the player that made the request and the responses for spectators.
+///
+/// If starting the game shuffled the seats, the channels are moved along with their players before
+/// anything is sent, see [`PlayerChannels::reseat`].
+#[derive(Debug, Clone)]
+pub struct FanOutLayer {
+    player_tx: PlayerChannels,
+}
+
+impl FanOutLayer {
+    /// Creates a new fan-out layer which sends responses over `player_tx`, indexed by player id.
+    pub fn new(player_tx: PlayerChannels) -> Self {
+        Self { player_tx }
+    }
+
+    /// Moves the channels of the players that were seated in the order of `usernames` to the seats
+    /// they got when `selecting` started.
+    fn reseat(&self, usernames: &[String], selecting: &SelectingCharacters) {
+        let seats = selecting
+            .players()
+            .iter()
+            .map(|p| usernames.iter().position(|name| name == p.name()))
+            .map(|seat| seat.map(|seat| PlayerId(seat as u8)))
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(seats) = seats {
+            self.player_tx.reseat(&seats);
+        }
+    }
+}
+
+impl Layer for FanOutLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        let RequestContext {
+            username,
+            request,
+            game,
+        } = ctx;
+
+        let usernames = game.lobby().ok().map(|lobby| {
+            lobby
+                .usernames()
+                .into_iter()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        });
+        let Response(InternalResponse(players, spectators), direct) = next.run(RequestContext {
+            username,
+            request,
+            game: &mut *game,
+        })?;
+
+        if let Some(usernames) = usernames
+            && let Ok(selecting) = game.selecting_characters()
+        {
+            self.reseat(&usernames, selecting);
+        }
+
+        for (id, responses) in players {
+            self.player_tx.send(id, responses);
+        }
+
+        // Spectators are sent their responses by the room, after a delay
+        Ok(Response(
+            InternalResponse(HashMap::new(), spectators),
+            direct,
+        ))
+    }
+}
+
+/// What the players of a room decided about the room itself, shared between the room and the
+/// layers of its chain.
+#[derive(Debug)]
+pub struct RoomControl {
+    /// The vote the players are taking, if any.
+    pub votes: Votes,
+    /// Whether the players voted to pause the game.
+    pub paused: bool,
+    /// The votes that passed while handling the last request, which the room still has to act on.
+    pub passed: Vec<Vote>,
+    /// The usernames of the players that were kicked out of the room, who cannot join again.
+    pub kicked: Vec<String>,
+    /// The usernames of the players the host kicked while handling the last request, which the
+    /// room still has to remove.
+    pub kicks: Vec<String>,
+}
+
+impl RoomControl {
+    /// Creates the control of a room that is not paused, where votes run for `votes`.
+    pub fn new(votes: Votes) -> Self {
+        Self {
+            votes,
+            paused: false,
+            passed: Vec::new(),
+            kicked: Vec::new(),
+            kicks: Vec::new(),
+        }
+    }
+}
+
+/// Handles requests to propose a vote or to vote on the vote that is running, and lets everyone
+/// know. Votes that passed are left in the [`RoomControl`] for the room to act on. Actions are
+/// rejected while the players paused the game.
+#[derive(Debug, Clone)]
+pub struct VoteLayer {
+    control: Arc<Mutex<RoomControl>>,
+}
+
+impl VoteLayer {
+    /// Creates a new vote layer which keeps the votes in `control`.
+    pub fn new(control: Arc<Mutex<RoomControl>>) -> Self {
+        Self { control }
+    }
+}
+
+impl Layer for VoteLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        let RequestContext {
+            username,
+            request,
+            game,
+        } = ctx;
+        let now = Instant::now();
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        let mut control = self.control.lock().unwrap();
+
+        let (direct, announced, (vote, passed)) = match request {
+            FrontendRequest::ProposeVote { kind } => {
+                let proposer_id = player_id(game, username)?;
+                let target = match kind {
+                    VoteKind::Kick { target } => Some(player_name(game, target)?.to_owned()),
+                    VoteKind::Pause if control.paused => Err(VoteError::NothingToChange)?,
+                    VoteKind::Resume if !control.paused => Err(VoteError::NothingToChange)?,
+                    VoteKind::Abort | VoteKind::Pause | VoteKind::Resume => None,
+                };
+                let result = control
+                    .votes
+                    .propose(username, kind, target, voters(game), now)?;
+
+                let proposed = UniqueResponse::VoteProposed {
+                    proposer_id,
+                    kind,
+                    votes_needed: result.0.votes_needed(),
+                    seconds_left: result.0.time_left(now).as_secs(),
+                };
+                (DirectResponse::YouProposedVote { kind }, proposed, result)
+            }
+            FrontendRequest::CastVote { approve } => {
+                let player_id = player_id(game, username)?;
+                let result = control.votes.cast(username, approve)?;
+
+                let cast = UniqueResponse::VoteCast {
+                    player_id,
+                    approve,
+                    votes_for: result.0.votes_for(),
+                    votes_against: result.0.votes_against(),
+                };
+                (DirectResponse::YouVoted { approve }, cast, result)
+            }
+            request if control.paused && request.clone().into_action().is_some() => {
+                return Err(ResponseError::GamePaused);
+            }
+            request => {
+                drop(control);
+                return next.run(RequestContext {
+                    username,
+                    request,
+                    game,
+                });
+            }
+        };
+
+        let mut announced = vec![announced];
+        if let Some(passed) = passed {
+            tracing::debug!("Vote ended: {:?}, passed: {passed}", vote.kind());
+            announced.push(UniqueResponse::VoteEnded {
+                kind: vote.kind(),
+                passed,
+            });
+            if passed {
+                control.passed.push(vote);
+            }
+        }
+        Ok(announce(game, announced, direct))
+    }
+}
+
+/// Handles requests of the host to kick a player out of the lobby, and lets everyone know. The
+/// kicked players are left in the [`RoomControl`] for the room to remove. Players that were kicked
+/// cannot come back by taking their username either.
+#[derive(Debug, Clone)]
+pub struct KickLayer {
+    control: Arc<Mutex<RoomControl>>,
+}
+
+impl KickLayer {
+    /// Creates a new kick layer which keeps the kicked players in `control`.
+    pub fn new(control: Arc<Mutex<RoomControl>>) -> Self {
+        Self { control }
+    }
+}
+
+impl Layer for KickLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        let mut control = self.control.lock().unwrap();
+
+        match &ctx.request {
+            &FrontendRequest::KickPlayer { target } => {
+                let id = player_id(ctx.game, ctx.username)?;
+                let kicked = ctx.game.lobby()?.check_kick(id, target)?.name().to_owned();
+                control.kicks.push(kicked.clone());
+
+                let announced = vec![UniqueResponse::PlayerKicked {
+                    player_id: target,
+                    username: kicked,
+                }];
+                let direct = DirectResponse::YouKickedPlayer { player_id: target };
+                Ok(announce(ctx.game, announced, direct))
+            }
+            FrontendRequest::ChangeUsername { username }
+                if control.kicked.contains(&normalize_username(username)) =>
+            {
+                Err(VoteError::Kicked.into())
+            }
+            _ => {
+                drop(control);
+                next.run(ctx)
+            }
+        }
+    }
+}
+
+/// Runs debug commands and lets everyone know the game changed. The player that used one is
+/// resynced right away. Debug commands are rejected unless the server was built with the `dev`
+/// feature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DevLayer;
+
+impl Layer for DevLayer {
+    fn handle(
+        &mut self,
+        ctx: RequestContext<'_>,
+        next: Next<'_>,
+    ) -> Result<Response, ResponseError> {
+        let FrontendRequest::Dev { command } = ctx.request else {
+            return next.run(ctx);
+        };
+        Self::run(ctx.username, command, ctx.game).map_err(Into::into)
+    }
+}
+
+impl DevLayer {
+    #[cfg(feature = "dev")]
+    fn run(
+        username: &str,
+        command: DevRequest,
+        game: &mut GameState,
+    ) -> Result<Response, GameError> {
+        let player_id = player_id(game, username)?;
+        command.run(game)?;
+        tracing::debug!("Dev command used by {username}: {command:?}");
+
+        let Response(_, direct) = resync(game, player_id)?;
+        let announced = vec![UniqueResponse::DevCommandUsed { player_id, command }];
+        Ok(announce(game, announced, direct))
+    }
+
+    #[cfg(not(feature = "dev"))]
+    fn run(_: &str, _: DevRequest, _: &mut GameState) -> Result<Response, GameError> {
+        Err(game::errors::DevError::Disabled.into())
+    }
+}
+
+/// Creates the response that lets every player of `game` as well as spectators know about
+/// `announced`, answering the player that made the request with `direct`.
+fn announce(game: &GameState, announced: Vec<UniqueResponse>, direct: DirectResponse) -> Response {
+    let players = game
+        .player_ids()
+        .into_iter()
+        .map(|id| (id, announced.clone()))
+        .collect();
+    Response(InternalResponse(players, announced), direct)
+}
+
+/// Applies the request to the game, which is where every chain ends.
+fn dispatch(ctx: RequestContext<'_>) -> Result<Response, GameError> {
+    let RequestContext {
+        username,
+        request,
+        game,
+    } = ctx;
+
+    if request == FrontendRequest::RequestHint {
+        let player_id = player_id(game, username)?;
+        return hint(game, player_id);
+    }
+    if request == FrontendRequest::RequestFullState {
+        let player_id = player_id(game, username)?;
+        return full_state(game, player_id);
+    }
+
+    let turn_nonce = request.turn_nonce();
+    match request.into_action() {
+        Some(action) => handle_action(game, username, action, turn_nonce),
+        None => match game {
+            GameState::Round(_) | GameState::SelectingCharacters(_) => {
+                let player_id = player_id(game, username)?;
+                resync(game, player_id)
+            }
+            _ => Err(GameError::NotRoundState),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+    use game::game::GameConfig;
+
+    fn lobby(players: &[&str]) -> GameState {
+        let mut game = GameState::new();
+        for player in players {
+            assert_ok!(game.lobby_mut().unwrap().join(player.to_string()));
+        }
+        game
+    }
+
+    fn control() -> Arc<Mutex<RoomControl>> {
+        Arc::new(Mutex::new(RoomControl::new(Votes::new(
+            Duration::from_secs(60),
+        ))))
+    }
+
+    fn handle(
+        chain: &mut Chain,
+        game: &mut GameState,
+        username: &str,
+        request: FrontendRequest,
+    ) -> Result<Response, ResponseError> {
+        chain.handle(RequestContext {
+            username,
+            request,
+            game,
+        })
+    }
+
+    #[test]
+    fn rejects_unknown_players() {
+        let mut game = lobby(&["player"]);
+        let mut chain = Chain::new().layer(AuthLayer);
+
+        assert_matches!(
+            handle(&mut chain, &mut game, "stranger", FrontendRequest::EndTurn { turn_nonce: 0 }),
+            Err(ResponseError::Game(GameError::InvalidPlayerName(name))) if name == "stranger"
+        );
+        assert_matches!(
+            handle(
+                &mut chain,
+                &mut game,
+                "player",
+                FrontendRequest::EndTurn { turn_nonce: 0 }
+            ),
+            Err(ResponseError::Game(GameError::NotRoundState))
+        );
+    }
+
+    #[test]
+    fn rate_limit_refills() {
+        let mut limit = RateLimitLayer::new(1.0, 2.0);
+        let now = Instant::now();
+
+        assert!(limit.try_take("player", now));
+        assert!(limit.try_take("player", now));
+        assert!(!limit.try_take("player", now));
+        assert!(limit.try_take("other", now));
+        assert!(limit.try_take("player", now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn dedup_only_drops_repeats_within_window() {
+        let mut dedup = DedupLayer::new(Duration::from_millis(250));
+        let now = Instant::now();
+        let later = now + Duration::from_secs(1);
+        let ability = FrontendRequest::UseAbility { turn_nonce: 0 };
+        let draw = FrontendRequest::DrawCard {
+            card_type: game::player::CardType::Asset,
+            turn_nonce: 0,
+        };
+        // The turn nonce already keeps a repeated request from ending the next turn
+        let end_turn = FrontendRequest::EndTurn { turn_nonce: 0 };
+
+        assert!(!dedup.is_duplicate("player", &ability, now));
+        assert!(dedup.is_duplicate("player", &ability, now));
+        assert!(!dedup.is_duplicate("other", &ability, now));
+        assert!(!dedup.is_duplicate("player", &ability, later));
+        assert!(!dedup.is_duplicate("player", &draw, now));
+        assert!(!dedup.is_duplicate("player", &draw, now));
+        assert!(!dedup.is_duplicate("player", &end_turn, now));
+        assert!(!dedup.is_duplicate("player", &end_turn, now));
+    }
+
+    #[test]
+    fn validation_checks_phase() {
+        let game = lobby(&["player"]);
+
+        assert_ok!(ValidationLayer::validate(
+            &FrontendRequest::StartGame,
+            &game
+        ));
+        assert_eq!(
+            ValidationLayer::validate(&FrontendRequest::PayBanker { cash: 1 }, &game),
+            Err(GameError::NotBankerTargetState)
+        );
+        assert_eq!(
+            ValidationLayer::validate(&FrontendRequest::Resync, &game),
+            Err(GameError::NotRoundState)
+        );
+        assert_eq!(
+            ValidationLayer::validate(&FrontendRequest::RequestHint, &game),
+            Err(GameError::NotRoundState)
+        );
+        assert_ok!(ValidationLayer::validate(
+            &FrontendRequest::KickPlayer {
+                target: PlayerId(0)
+            },
+            &game
+        ));
+        assert_err!(ValidationLayer::validate(
+            &FrontendRequest::KickPlayer {
+                target: PlayerId(1)
+            },
+            &game
+        ));
+        assert_err!(ValidationLayer::validate(
+            &FrontendRequest::ProposeVote {
+                kind: VoteKind::Kick {
+                    target: PlayerId(1)
+                }
+            },
+            &game
+        ));
+        assert_err!(ValidationLayer::validate(
+            &FrontendRequest::Dev {
+                command: DevRequest::ForceMarket {
+                    title: "market".to_owned()
+                }
+            },
+            &game
+        ));
+    }
+
+    #[test]
+    fn stale_turn_nonce_is_rejected() {
+        let mut game = lobby(&["1", "2", "3", "4"]);
+        assert_ok!(dispatch(RequestContext {
+            username: "1",
+            request: FrontendRequest::StartGame,
+            game: &mut game,
+        }));
+        while let Ok(selecting) = game.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let characters = assert_ok!(selecting.player_get_selectable_characters(id));
+            assert_ok!(game.player_select_character(id, characters[0]));
+        }
+        let turn_nonce = game.round().unwrap().turn_nonce();
+
+        assert_matches!(
+            dispatch(RequestContext {
+                username: "1",
+                request: FrontendRequest::ForceEndTurn {
+                    turn_nonce: turn_nonce + 1
+                },
+                game: &mut game,
+            }),
+            Err(GameError::StaleTurnNonce(n)) if n == turn_nonce + 1
+        );
+        assert_ok!(dispatch(RequestContext {
+            username: "1",
+            request: FrontendRequest::ForceEndTurn { turn_nonce },
+            game: &mut game,
+        }));
+        assert_ne!(game.round().unwrap().turn_nonce(), turn_nonce);
+    }
+
+    #[tokio::test]
+    async fn audit_logs_accepted_actions() {
+        let mut game = lobby(&["1", "2", "3", "4"]);
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let mut chain = Chain::new().layer(AuditLayer::new("test".to_owned(), storage.clone()));
+
+        assert_err!(handle(
+            &mut chain,
+            &mut game,
+            "1",
+            FrontendRequest::EndTurn { turn_nonce: 0 }
+        ));
+        assert_ok!(handle(
+            &mut chain,
+            &mut game,
+            "1",
+            FrontendRequest::StartGame
+        ));
+        assert_ok!(handle(&mut chain, &mut game, "1", FrontendRequest::Resync));
+
+        let actions = storage.wait_for_actions("test", 1).await;
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].actor, "1");
+        assert_eq!(actions[0].action, FrontendRequest::StartGame);
+        assert_eq!(actions[0].state_hash, game.state_hash());
+    }
+
+    #[tokio::test]
+    async fn votes_pass_through_the_chain() {
+        let mut game = lobby(&["1", "2", "3"]);
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let control = control();
+        let mut chain = Chain::standard(
+            "test",
+            &RoomConfig::default(),
+            PlayerChannels::new(8),
+            control.clone(),
+            Some(storage.clone()),
+            None,
+        );
+        let pause = FrontendRequest::ProposeVote {
+            kind: VoteKind::Pause,
+        };
+
+        assert_matches!(
+            handle(&mut chain, &mut game, "nobody", pause.clone()),
+            Err(ResponseError::Game(GameError::InvalidPlayerName(_)))
+        );
+        let Response(InternalResponse(players, spectators), direct) =
+            assert_ok!(handle(&mut chain, &mut game, "1", pause.clone()));
+        assert_matches!(direct, DirectResponse::YouProposedVote { .. });
+        assert!(players.is_empty());
+        assert_matches!(spectators.as_slice(), [UniqueResponse::VoteProposed { .. }]);
+        assert_matches!(
+            handle(&mut chain, &mut game, "1", pause.clone()),
+            Err(ResponseError::Vote(VoteError::VoteInProgress))
+        );
+        assert_ok!(handle(
+            &mut chain,
+            &mut game,
+            "2",
+            FrontendRequest::CastVote { approve: true }
+        ));
+
+        let passed = std::mem::take(&mut control.lock().unwrap().passed);
+        assert_matches!(passed.as_slice(), [vote] if vote.kind() == VoteKind::Pause);
+        let actions = storage.wait_for_actions("test", 2).await;
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].action, pause);
+    }
+
+    #[test]
+    fn full_state_is_sent_in_every_phase() {
+        let mut game = lobby(&["1", "2", "3", "4"]);
+        let mut chain = Chain::new().layer(ValidationLayer);
+
+        let response = handle(
+            &mut chain,
+            &mut game,
+            "2",
+            FrontendRequest::RequestFullState,
+        );
+        assert_matches!(
+            response,
+            Ok(Response(_, DirectResponse::FullState {
+                you,
+                phase: ResyncData::Lobby { host_id: Some(PlayerId(0)), .. },
+                ..
+            })) if you.id == PlayerId(1)
+        );
+
+        assert_ok!(handle(
+            &mut chain,
+            &mut game,
+            "1",
+            FrontendRequest::StartGame
+        ));
+        let hand = game.selecting_characters().unwrap().players()[1]
+            .hand()
+            .len();
+        let response = handle(
+            &mut chain,
+            &mut game,
+            "2",
+            FrontendRequest::RequestFullState,
+        );
+        assert_matches!(
+            response,
+            Ok(Response(_, DirectResponse::FullState {
+                hand: full_hand,
+                player_info,
+                phase: ResyncData::SelectingCharacters { .. },
+                ..
+            })) if full_hand.len() == hand && player_info.len() == 3
+        );
+    }
+
+    #[test]
+    fn telemetry_respects_opt_out() {
+        let sink = Arc::new(crate::telemetry::MemorySink::default());
+        let mut chain = Chain::new().layer(TelemetryLayer::new(sink.clone()));
+
+        let mut game = lobby(&["1", "2", "3", "4"]);
+        assert_ok!(handle(
+            &mut chain,
+            &mut game,
+            "1",
+            FrontendRequest::StartGame
+        ));
+        assert_matches!(
+            sink.events().as_slice(),
+            [TelemetryEvent::GameStarted { players: 4, .. }]
+        );
+
+        let mut game = lobby(&["1", "2", "3", "4"]);
+        game.lobby_mut().unwrap().set_config(GameConfig {
+            telemetry_opt_out: true,
+            ..Default::default()
+        });
+        assert_ok!(handle(
+            &mut chain,
+            &mut game,
+            "1",
+            FrontendRequest::StartGame
+        ));
+        assert_eq!(sink.events().len(), 1);
+    }
+
+    #[test]
+    fn fan_out_sends_internal_responses() {
+        let mut game = lobby(&["1", "2", "3", "4"]);
+        let player_tx = PlayerChannels::new(8);
+        let mut rx = player_tx.subscribe(PlayerId(0));
+        let mut chain = Chain::standard(
+            "test",
+            &RoomConfig::default(),
+            player_tx,
+            control(),
+            None,
+            None,
+        );
+
+        let Response(internal, direct) = assert_ok!(handle(
+            &mut chain,
+            &mut game,
+            "1",
+            FrontendRequest::StartGame
+        ));
+        assert!(internal.into_inner().is_empty());
+        assert_matches!(direct, DirectResponse::YouStartedGame);
+        assert_matches!(
+            rx.try_recv(),
+            Ok(responses) if matches!(responses[0], UniqueResponse::StartGame { .. })
+        );
+
+        assert_matches!(
+            handle(&mut chain, &mut game, "1", FrontendRequest::StartGame),
+            Err(ResponseError::DuplicateRequest)
+        );
+    }
+
+    #[test]
+    fn fan_out_follows_shuffled_seats() {
+        let usernames = ["1", "2", "3", "4"];
+        let mut game = lobby(&usernames);
+        game.lobby_mut().unwrap().set_config(GameConfig {
+            shuffle_seats: true,
+            ..Default::default()
+        });
+        let player_tx = PlayerChannels::new(8);
+        let mut rxs = (0..4u8)
+            .map(|id| player_tx.subscribe(PlayerId(id)))
+            .collect::<Vec<_>>();
+        let mut chain = Chain::standard(
+            "test",
+            &RoomConfig::default(),
+            player_tx,
+            control(),
+            None,
+            None,
+        );
+
+        assert_ok!(handle(
+            &mut chain,
+            &mut game,
+            "1",
+            FrontendRequest::StartGame
+        ));
+
+        let selecting = game.selecting_characters().unwrap();
+        for (name, rx) in usernames.into_iter().zip(&mut rxs) {
+            let responses = assert_ok!(rx.try_recv());
+            let UniqueResponse::StartGame {
+                id, seats_shuffled, ..
+            } = responses[0]
+            else {
+                panic!("Expected the game to start, got {responses:?}");
+            };
+            assert!(seats_shuffled);
+            assert_eq!(selecting.player(id).unwrap().name(), name);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use axum::extract::ws::Message;
+use game::cards::CardTranslations;
+use responses::to_msgpack;
+use serde::Serialize;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use crate::config::{ConfigError, env_number};
+
+const DEFAULT_SEND_QUEUE_SIZE: usize = 64;
+
+/// What to do with a client that does not read its messages fast enough to keep up with the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// Drop messages that do not fit in the queue and send the client a full resync as soon as it
+    /// has caught up.
+    #[default]
+    Resync,
+    /// Disconnect the client.
+    Disconnect,
+}
+
+impl BackpressurePolicy {
+    /// Parses `value` of the `BACKPRESSURE_POLICY` environment variable, which is either `resync`
+    /// or `disconnect`, or gets the default policy if it is not set.
+    fn parse(value: Option<String>) -> Result<Self, ConfigError> {
+        match value {
+            None => Ok(Self::Resync),
+            Some(value) => match value.as_str() {
+                "resync" => Ok(Self::Resync),
+                "disconnect" => Ok(Self::Disconnect),
+                _ => Err(ConfigError::InvalidPolicy(value)),
+            },
+        }
+    }
+}
+
+/// How many messages can wait to be sent to each client, and what happens when a client falls
+/// further behind than that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueConfig {
+    pub size: usize,
+    pub policy: BackpressurePolicy,
+}
+
+impl QueueConfig {
+    /// Reads the size of each client's send queue from the `SEND_QUEUE_SIZE` environment variable
+    /// and the policy from `BACKPRESSURE_POLICY`, or uses defaults if they are not set.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            size: env_number("SEND_QUEUE_SIZE")?.unwrap_or(DEFAULT_SEND_QUEUE_SIZE),
+            policy: BackpressurePolicy::parse(std::env::var("BACKPRESSURE_POLICY").ok())?,
+        })
+    }
+}
+
+/// How messages are encoded for a client, which they choose when connecting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// Json in text frames.
+    #[default]
+    Json,
+    /// MessagePack in binary frames.
+    MessagePack,
+}
+
+impl Encoding {
+    /// Encodes `msg` into a websocket message.
+    pub fn encode(&self, msg: impl Serialize) -> Message {
+        // PANIC: both serde_json and rmp_serde can only fail to serialize if the implementation of
+        // `Serialize` fails for the given type, or if the type contains a map with non-string
+        // keys. Since neither of those things are true, this as safe to unwrap.
+        match self {
+            Self::Json => Message::Text(serde_json::to_string(&msg).unwrap().into()),
+            Self::MessagePack => Message::Binary(to_msgpack(&msg).unwrap().into()),
+        }
+    }
+}
+
+/// A bounded queue of messages waiting to be sent to a single client. Pushing never waits, so a
+/// slow client can never stall the tasks that broadcast to the rest of the room. When the queue is
+/// full, the [`BackpressurePolicy`] decides what happens.
+#[derive(Debug, Clone)]
+pub struct ClientQueue {
+    tx: mpsc::Sender<Message>,
+    policy: BackpressurePolicy,
+    encoding: Encoding,
+    translations: Option<Arc<CardTranslations>>,
+    needs_resync: Arc<AtomicBool>,
+}
+
+impl ClientQueue {
+    /// Creates a new queue that holds at most `size` messages encoded with `encoding`, as well as
+    /// the receiving end the writer of the connection reads from.
+    pub fn new(
+        size: usize,
+        policy: BackpressurePolicy,
+        encoding: Encoding,
+    ) -> (Self, mpsc::Receiver<Message>) {
+        let (tx, rx) = mpsc::channel(size);
+        let queue = Self {
+            tx,
+            policy,
+            encoding,
+            translations: None,
+            needs_resync: Arc::new(AtomicBool::new(false)),
+        };
+
+        (queue, rx)
+    }
+
+    /// Translates the cards in every message with `translations`, in the language this client
+    /// chose when connecting.
+    pub fn with_translations(mut self, translations: Option<Arc<CardTranslations>>) -> Self {
+        self.translations = translations;
+        self
+    }
+
+    /// Encodes `msg` for this client, translating its cards if the client chose a language.
+    pub fn encode(&self, msg: impl Serialize) -> Message {
+        let Some(translations) = &self.translations else {
+            return self.encoding.encode(msg);
+        };
+
+        // PANIC: see `Encoding::encode`, since serializing to json can fail for the same reasons.
+        let mut value = serde_json::to_value(msg).unwrap();
+        translations.localize(&mut value);
+        self.encoding.encode(value)
+    }
+
+    /// Queues `msg` in the encoding of this client. Returns `false` if the connection should be
+    /// closed.
+    pub fn push(&self, msg: impl Serialize) -> bool {
+        self.push_message(self.encode(msg))
+    }
+
+    /// Queues `msg` as the reply to a request of this client, waiting for room in the queue
+    /// instead of dropping it, since a resync would not tell the client what became of its request.
+    /// Only the task reading this client's requests waits, never the rest of the room. Returns
+    /// `false` if the connection should be closed.
+    pub async fn push_reply(&self, msg: impl Serialize) -> bool {
+        self.tx.send(self.encode(msg)).await.is_ok()
+    }
+
+    /// Queues a raw websocket message. Returns `false` if the connection should be closed.
+    pub fn push_message(&self, msg: Message) -> bool {
+        match self.tx.try_send(msg) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => self.overflowed(),
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// Applies the policy for when messages for this client had to be dropped. Returns `false` if
+    /// the connection should be closed.
+    pub fn overflowed(&self) -> bool {
+        match self.policy {
+            BackpressurePolicy::Resync => {
+                self.needs_resync.store(true, Ordering::Relaxed);
+                true
+            }
+            BackpressurePolicy::Disconnect => false,
+        }
+    }
+
+    /// Checks whether messages were dropped since the last time this was called, meaning the client
+    /// should be sent a resync.
+    pub fn take_needs_resync(&self) -> bool {
+        self.needs_resync.swap(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    #[test]
+    fn policy_is_parsed() {
+        assert_ok_eq!(BackpressurePolicy::parse(None), BackpressurePolicy::Resync);
+        assert_ok_eq!(
+            BackpressurePolicy::parse(Some("disconnect".into())),
+            BackpressurePolicy::Disconnect
+        );
+        assert_matches!(
+            BackpressurePolicy::parse(Some("drop".into())),
+            Err(ConfigError::InvalidPolicy(policy)) if policy == "drop"
+        );
+    }
+
+    #[test]
+    fn full_queue_resyncs() {
+        let (queue, mut rx) = ClientQueue::new(1, BackpressurePolicy::Resync, Encoding::Json);
+
+        assert!(queue.push("first"));
+        assert!(!queue.take_needs_resync());
+        assert!(queue.push("second"));
+        assert!(queue.take_needs_resync());
+        assert!(!queue.take_needs_resync());
+
+        assert!(matches!(rx.try_recv(), Ok(Message::Text(text)) if text == "\"first\""));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn replies_wait_for_room() {
+        let (queue, mut rx) = ClientQueue::new(1, BackpressurePolicy::Disconnect, Encoding::Json);
+        assert!(queue.push("first"));
+
+        let reply = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.push_reply("reply").await }
+        });
+        assert!(matches!(rx.recv().await, Some(Message::Text(text)) if text == "\"first\""));
+        assert!(reply.await.unwrap());
+        assert!(matches!(rx.recv().await, Some(Message::Text(text)) if text == "\"reply\""));
+        assert!(!queue.take_needs_resync());
+    }
+
+    #[test]
+    fn full_queue_disconnects() {
+        let (queue, rx) = ClientQueue::new(1, BackpressurePolicy::Disconnect, Encoding::Json);
+
+        assert!(queue.push("first"));
+        assert!(!queue.push("second"));
+
+        drop(rx);
+        assert!(!queue.push("third"));
+    }
+
+    #[test]
+    fn encodes_binary_frames() {
+        let (queue, mut rx) =
+            ClientQueue::new(1, BackpressurePolicy::Resync, Encoding::MessagePack);
+
+        assert!(queue.push("msg"));
+        let Ok(Message::Binary(bytes)) = rx.try_recv() else {
+            panic!("Expected a binary frame");
+        };
+        assert_eq!(responses::from_msgpack::<String>(&bytes).unwrap(), "msg");
+    }
+
+    #[test]
+    fn translates_cards() {
+        let translations = CardTranslations {
+            titles: [("Stable Market".to_owned(), "Stabiele Markt".to_owned())].into(),
+            ..Default::default()
+        };
+        let (queue, mut rx) = ClientQueue::new(1, BackpressurePolicy::Resync, Encoding::Json);
+        let queue = queue.with_translations(Some(Arc::new(translations)));
+
+        assert!(queue.push(serde_json::json!({ "title": "Stable Market" })));
+        let Ok(Message::Text(text)) = rx.try_recv() else {
+            panic!("Expected a text frame");
+        };
+        assert_eq!(text.as_str(), r#"{"title":"Stabiele Markt"}"#);
+    }
+}
@@ -1,14 +1,21 @@
-use either::Either;
-use game::{errors::*, game::*, player::*};
+use game::{cards::GameData, errors::*, game::*, player::*};
 use responses::*;
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+/// How long a room waits for its cards to download before it falls back to the bundled ones,
+/// since nobody can join the room in the meantime.
+pub const REMOTE_GAME_DATA_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug)]
 pub struct Response(pub InternalResponse, pub DirectResponse);
 
+/// The responses for each player, followed by the responses for spectators.
 #[derive(Clone, Debug)]
-pub struct InternalResponse(pub HashMap<PlayerId, Vec<UniqueResponse>>);
+pub struct InternalResponse(
+    pub HashMap<PlayerId, Vec<UniqueResponse>>,
+    pub Vec<UniqueResponse>,
+);
 
 impl InternalResponse {
     pub fn get_responses(&self, id: PlayerId) -> Option<&[UniqueResponse]> {
@@ -20,754 +27,142 @@ impl InternalResponse {
     }
 }
 
-pub fn start_game(state: &mut GameState) -> Result<Response, GameError> {
-    let assets_path = std::env::var("ASSETS_DIR")
-        .unwrap_or_else(|_| format!("{}/../assets/", env!("CARGO_MANIFEST_DIR")));
-    let path = PathBuf::from(assets_path).join("cards/boardgame.json");
-
-    state.start_game(path)?;
-
-    tracing::debug!("Started Game");
-
-    let selecting = state.selecting_characters()?;
-
-    let internal = selecting
-        .players()
-        .iter()
-        .map(|p| {
-            (
-                p.id(),
-                vec![
-                    UniqueResponse::StartGame {
-                        id: p.id(),
-                        hand: p.hand().to_vec(),
-                        cash: p.cash(),
-                        player_info: selecting.player_info(p.id()),
-                        initial_market: selecting.current_market().clone(),
-                    },
-                    UniqueResponse::SelectingCharacters {
-                        chairman_id: selecting.chairman_id(),
-                        selectable_characters: selecting
-                            .player_get_selectable_characters(p.id())
-                            .ok(),
-                        open_characters: selecting.open_characters().to_vec(),
-                        closed_character: selecting.player_get_closed_character(p.id()).ok(),
-                        turn_order: selecting.turn_order(),
-                    },
-                ],
-            )
-        })
-        .collect();
-
-    Ok(Response(
-        InternalResponse(internal),
-        DirectResponse::YouStartedGame,
-    ))
-}
-
-pub fn use_ability(state: &mut GameState, player_id: PlayerId) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-    let player = round.player(player_id)?;
-    match player.character() {
-        Character::Shareholder if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            DirectResponse::YouAreFiringSomeone {
-                characters: round.player_get_fireble_characters(),
-                 character: Character::Shareholder,
-                perk: "You can fire a character \n- A fired character skips their turs ".to_string(),
-            },
-        )),
-        Character::Banker if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            DirectResponse::YouAreTerminatingSomeone {
-                characters: round.player_get_fireble_characters(),
-                 character: Character::Banker,
-                perk: "You can force a player to give you cash based on the amount of different color assets they have +1".to_string(),
-            },
-        )),
-        Character::Regulator if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            DirectResponse::YouRegulatorOptions {
-                options: round.player_get_regulator_swap_players(),
-                character: Character::Regulator,
-                perk: "You can swap your hand with another player or swap any number of cards with the deck".to_string(),
-             }
-        )),
-        Character::CEO if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            DirectResponse::YouCharacterAbility {
-                character: Character::CEO,
-                perk: "- You can buy up to 3 assets \n- Next turn you become chairman".to_string(),
-            },
-        )),
-        Character::CFO if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            DirectResponse::YouCharacterAbility {
-                character: Character::CFO,
-                perk: "You can issue or redeem 3 liabilities".to_string(),
-            },
-        )),
-        Character::CSO if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            DirectResponse::YouCharacterAbility {
-                character: Character::CSO,
-                perk: "You can buy up to 2 red or green assets".to_string(),
-            },
-        )),
-        Character::HeadRnD if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            DirectResponse::YouCharacterAbility {
-                character: Character::HeadRnD,
-                perk: "You can draw six cards and only have to put 2 back".to_string(),
-            },
-        )),
-        Character::Stakeholder if round.current_player().id() == player.id() => Ok(Response(
-            InternalResponse(std::collections::HashMap::new()),
-            //TODO send other players divest message
-            DirectResponse::YouAreDivesting {
-                options: round.get_divest_assets(player_id)?,
-                character: Character::Stakeholder,
-                perk: "you can force a player to divest from an asset by spending the assets market value -1".to_string(),
-            },
-        )),
-        _ => Err(GameError::InvalidPlayerIndex(0)),
+impl Response {
+    /// Turns the effects of an action into the responses for each of `players`.
+    pub fn from_effects(effects: ActionEffects, players: &[PlayerId]) -> Self {
+        let internal = players
+            .iter()
+            .filter_map(|&id| {
+                let responses = effects
+                    .for_player(id)
+                    .into_iter()
+                    .map(UniqueResponse::from)
+                    .collect::<Vec<_>>();
+                (!responses.is_empty()).then_some((id, responses))
+            })
+            .collect();
+
+        let spectators = effects
+            .for_spectators()
+            .into_iter()
+            .map(UniqueResponse::from)
+            .collect();
+
+        Response(
+            InternalResponse(internal, spectators),
+            effects.to_actor.into(),
+        )
     }
 }
 
-pub fn get_bonus_cash(state: &mut GameState, player_id: PlayerId) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-    let bonus_cash = round.player_get_bonus_cash_character(player_id)?;
-
-    let internal = round
-        .players()
-        .iter()
-        .filter(|p| p.id() != player_id)
-        .map(|p| {
-            (
-                p.id(),
-                vec![UniqueResponse::PlayerGotBonusCash {
-                    player_id,
-                    cash: bonus_cash,
-                }],
-            )
-        })
-        .collect();
-
-    Ok(Response(
-        InternalResponse(internal),
-        DirectResponse::YouBonusCash { cash: bonus_cash },
-    ))
-}
-
-pub fn draw_card(
-    state: &mut GameState,
-    card_type: CardType,
-    player_id: PlayerId,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-    let card = round.player_draw_card(player_id, card_type)?.cloned();
-    let player = round.player(player_id)?;
-
-    let internal = round
-        .players()
-        .iter()
-        .filter(|p| p.id() != player_id)
-        .map(|p| {
-            (
-                p.id(),
-                vec![UniqueResponse::DrewCard {
-                    player_id,
-                    card_type,
-                }],
-            )
-        })
-        .collect();
-
-    Ok(Response(
-        InternalResponse(internal),
-        DirectResponse::YouDrewCard {
-            card,
-            can_draw_cards: player.can_draw_cards(),
-            can_give_back_cards: player.should_give_back_cards(),
-        },
-    ))
-}
-
-pub fn put_back_card(
+/// Applies `action` which the player with `player_name` sent during the turn with `turn_nonce`,
+/// and gets the responses for everyone. See [`GameState::apply_in_turn`].
+pub fn handle_action(
     state: &mut GameState,
-    card_idx: usize,
-    player_id: PlayerId,
+    player_name: &str,
+    action: PlayerAction,
+    turn_nonce: Option<u32>,
 ) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-    let card_type = round.player_give_back_card(player_id, card_idx)?;
-    let player = round.player(player_id)?;
-
-    let internal = round
-        .players()
-        .iter()
-        .filter(|p| p.id() != player_id)
-        .map(|p| {
-            (
-                p.id(),
-                vec![UniqueResponse::PutBackCard {
-                    player_id,
-                    card_type,
-                }],
-            )
-        })
-        .collect();
-
-    Ok(Response(
-        InternalResponse(internal),
-        DirectResponse::YouPutBackCard {
-            card_idx,
-            can_draw_cards: player.can_draw_cards(),
-            can_give_back_cards: player.should_give_back_cards(),
-        },
-    ))
-}
-
-pub fn play_card(
-    state: &mut GameState,
-    card_idx: usize,
-    player_id: PlayerId,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-    let played_card = round.player_play_card(player_id, card_idx)?;
-
-    match played_card.used_card {
-        Either::Left(asset) => {
-            let internal = round
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::BoughtAsset {
-                            player_id,
-                            card_idx,
-                            asset: asset.clone(),
-                            market_change: played_card.market.clone(),
-                        }],
-                    )
-                })
-                .collect();
-
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouBoughtAsset {
-                    asset,
-                    card_idx,
-                    market_change: played_card.market,
-                },
-            ))
-        }
-        Either::Right(liability) => {
-            let internal = round
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::IssuedLiability {
-                            player_id,
-                            card_idx,
-                            liability: liability.clone(),
-                        }],
-                    )
-                })
-                .collect();
-
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouIssuedLiability {
-                    liability,
-                    card_idx,
-                },
-            ))
-        }
+    if action == PlayerAction::StartGame
+        && let GameState::Lobby(lobby) = state
+        && lobby.game_data().is_none()
+    {
+        load_game_data(lobby);
     }
-}
-
-pub fn redeem_liability(
-    state: &mut GameState,
-    liability_idx: usize,
-    player_id: PlayerId,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
 
-    round.player_redeem_liability(player_id, liability_idx)?;
+    let player_id = player_id(state, player_name)?;
+    let effects = state.apply_in_turn(player_id, action, turn_nonce)?;
 
-    let internal = round
-        .players()
-        .iter()
-        .filter(|p| p.id() != player_id)
-        .map(|p| {
-            (
-                p.id(),
-                vec![UniqueResponse::RedeemedLiability {
-                    player_id,
-                    liability_idx,
-                }],
-            )
-        })
-        .collect();
-
-    Ok(Response(
-        InternalResponse(internal),
-        DirectResponse::YouRedeemedLiability { liability_idx },
-    ))
-}
-
-fn turn_starts(round: &Round) -> UniqueResponse {
-    let current_player = round.current_player();
-
-    UniqueResponse::TurnStarts {
-        player_turn: current_player.id(),
-        player_turn_cash: current_player.turn_cash(),
-        player_character: current_player.character(),
-        draws_n_cards: current_player.draws_n_cards(),
-        gives_back_n_cards: current_player.gives_back_n_cards(),
-        playable_assets: current_player.playable_assets(),
-        playable_liabilities: current_player.playable_liabilities(),
-        skipped_characters: round.skipped_characters(),
-    }
-}
-
-pub fn select_character(
-    state: &mut GameState,
-    player_id: PlayerId,
-    character: Character,
-) -> Result<Response, GameError> {
-    match state.player_select_character(player_id, character) {
-        Ok(_) => {
-            match state {
-                GameState::Lobby(_) => Err(GameError::NotAvailableInLobbyState),
-                GameState::BankerTarget(_) => Err(GameError::NotAvailableInBankerTargetState),
-                GameState::SelectingCharacters(selecting) => {
-                    let internal = selecting
-                        .players()
-                        .iter()
-                        .map(|p| {
-                            (
-                                p.id(),
-                                vec![UniqueResponse::SelectedCharacter {
-                                    currently_picking_id: Some(selecting.currently_selecting_id()),
-                                    selectable_characters: selecting
-                                        .player_get_selectable_characters(p.id())
-                                        .ok(),
-                                    closed_character: selecting
-                                        .player_get_closed_character(p.id())
-                                        .ok(),
-                                }],
-                            )
-                        })
-                        .collect();
-
-                    Ok(Response(
-                        InternalResponse(internal),
-                        DirectResponse::YouSelectedCharacter { character },
-                    ))
-                }
-                GameState::Round(round) => {
-                    // TODO: turn is the same for everyone. Simplify maybe
-                    let internal = round
-                        .players()
-                        .iter()
-                        .map(|p| (p.id(), vec![turn_starts(round)]))
-                        .collect();
-
-                    Ok(Response(
-                        InternalResponse(internal),
-                        DirectResponse::YouSelectedCharacter { character },
-                    ))
-                }
-                GameState::Results(_) => Err(GameError::NotAvailableInResultsState),
-            }
-        }
-        Err(e) => Err(e),
-    }
-}
-
-pub fn fire_character(
-    state: &mut GameState,
-    player_id: PlayerId,
-    character: Character,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-
-    match round.player_fire_character(player_id, character) {
-        Ok(_c) => {
-            let internal = round
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::FiredCharacter {
-                            player_id,
-                            character,
-                        }],
-                    )
-                })
-                .collect();
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouFiredCharacter { character },
-            ))
-        }
-        Err(e) => Err(e),
+    if let ActorEffect::StartedGame = effects.to_actor {
+        tracing::debug!("Started Game");
     }
-}
-
-pub fn terminate_credit_character(
-    state: &mut GameState,
-    player_id: PlayerId,
-    character: Character,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
 
-    match round.player_terminate_credit_character(player_id, character) {
-        Ok(_c) => {
-            let internal = round
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::TerminatedCreditCharacter {
-                            player_id,
-                            character,
-                        }],
-                    )
-                })
-                .collect();
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouTerminateCreditCharacter { character },
-            ))
-        }
-        Err(e) => Err(e),
-    }
+    Ok(Response::from_effects(effects, &state.player_ids()))
 }
 
-pub fn select_divest_asset(
-    state: &mut GameState,
-    player_id: PlayerId,
-    asset_id: usize,
-) -> Result<Response, GameError> {
-    let btround = state.bankertarget_mut()?;
-    match btround.player_select_divest_asset(player_id, asset_id) {
-        Ok(selected) => Ok(create_selected_cards_response(btround, selected, player_id)),
-        Err(e) => Err(e),
-    }
-}
+/// Loads the cards from `ASSETS_DIR` into `lobby`, so the game can be started. Only used if the
+/// cards were not downloaded when the room was created, see [`load_remote_game_data`].
+pub fn load_game_data(lobby: &mut Lobby) {
+    let assets_path = std::env::var("ASSETS_DIR")
+        .unwrap_or_else(|_| format!("{}/../assets/", env!("CARGO_MANIFEST_DIR")));
+    let path = PathBuf::from(assets_path).join("cards/boardgame.json");
 
-pub fn unselect_divest_asset(
-    state: &mut GameState,
-    player_id: PlayerId,
-    asset_id: usize,
-) -> Result<Response, GameError> {
-    let btround = state.bankertarget_mut()?;
-    match btround.player_unselect_divest_asset(player_id, asset_id) {
-        Ok(selected) => Ok(create_selected_cards_response(btround, selected, player_id)),
-        Err(e) => Err(e),
+    match GameData::new(&path) {
+        Ok(data) => lobby.set_game_data(with_image_base_url(data)),
+        Err(error) => tracing::error!(%error, "Could not load cards from '{}'", path.display()),
     }
 }
 
-pub fn select_issue_liability(
-    state: &mut GameState,
-    player_id: PlayerId,
-    liability_id: usize,
-) -> Result<Response, GameError> {
-    let btround = state.bankertarget_mut()?;
-    match btround.player_select_issue_liability(player_id, liability_id) {
-        Ok(selected) => Ok(create_selected_cards_response(btround, selected, player_id)),
-        Err(e) => Err(e),
+/// Downloads the cards from `CARDS_URL` into `lobby`, if it is set. If they cannot be downloaded
+/// within [`REMOTE_GAME_DATA_TIMEOUT`], the cards from `ASSETS_DIR` are used once the game starts
+/// instead.
+pub async fn load_remote_game_data(lobby: &mut Lobby) {
+    let Ok(url) = std::env::var("CARDS_URL") else {
+        return;
+    };
+
+    match tokio::time::timeout(REMOTE_GAME_DATA_TIMEOUT, GameData::from_url(&url)).await {
+        Ok(Ok(data)) => lobby.set_game_data(with_image_base_url(data)),
+        Ok(Err(error)) => tracing::error!(%error, "Could not load cards from '{url}'"),
+        Err(_) => tracing::error!(
+            "Loading cards from '{url}' took longer than {REMOTE_GAME_DATA_TIMEOUT:?}"
+        ),
     }
 }
 
-pub fn unselect_issue_liability(
-    state: &mut GameState,
-    player_id: PlayerId,
-    liability_id: usize,
-) -> Result<Response, GameError> {
-    let btround = state.bankertarget_mut()?;
-    match btround.player_unselect_issue_liability(player_id, liability_id) {
-        Ok(selected) => Ok(create_selected_cards_response(btround, selected, player_id)),
-        Err(e) => Err(e),
+/// Points the card images in `data` to `IMAGE_BASE_URL`, if it is set, so they can be served from
+/// a CDN instead of the assets folder of the frontend.
+fn with_image_base_url(mut data: GameData) -> GameData {
+    if let Ok(base_url) = std::env::var("IMAGE_BASE_URL") {
+        data.set_image_base_url(&base_url);
     }
+    data
 }
 
-fn create_selected_cards_response(
-    btround: &mut BankerTargetRound,
-    selected: SelectedAssetsAndLiabilities,
-    player_id: PlayerId,
-) -> Response {
-    let internal = btround
-        .players()
-        .iter()
-        .filter(|p| p.id() != player_id)
-        .map(|p| {
-            (
-                p.id(),
-                vec![UniqueResponse::SelectedCardsBankerTarget {
-                    assets: selected.sold_assets.clone(),
-                    liability_count: selected.issued_liabilities.len(),
-                }],
-            )
-        })
-        .collect();
-    Response(
-        InternalResponse(internal),
-        DirectResponse::YouSelectCardBankerTarget {
-            assets: selected.sold_assets,
-            liabilities: selected.issued_liabilities,
-        },
-    )
+/// Gets the id of the player with `player_name`.
+pub fn player_id(state: &GameState, player_name: &str) -> Result<PlayerId, GameError> {
+    let id = match state {
+        GameState::Lobby(lobby) => lobby
+            .players()
+            .iter()
+            .find(|p| p.name() == player_name)
+            .map(|p| p.id())
+            .ok_or_else(|| GameError::InvalidPlayerName(player_name.to_owned()))?,
+        GameState::SelectingCharacters(s) => s.player_by_name(player_name)?.id(),
+        GameState::Round(round) => round.player_by_name(player_name)?.id(),
+        GameState::BankerTarget(round) => round.player_by_name(player_name)?.id(),
+        GameState::Results(results) => results.player_by_name(player_name)?.id(),
+    };
+
+    Ok(id)
 }
 
-pub fn pay_banker(
-    state: &mut GameState,
-    player_id: PlayerId,
-    cash: u8,
-) -> Result<Response, GameError> {
-    let btround = state.bankertarget_mut()?;
-    match btround.player_pay_banker(player_id, cash) {
-        Ok(pbp) => {
-            let internal = btround
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::PlayerPaidBanker {
-                            banker_id: pbp.banker_id,
-                            player_id: pbp.target_id,
-                            new_banker_cash: pbp.new_banker_cash,
-                            new_target_cash: pbp.new_target_cash,
-                            paid_amount: pbp.paid_amount,
-                            sold_assets: pbp.selected_cards.sold_assets.clone(),
-                            issued_liabilities: pbp.selected_cards.issued_liabilities.clone(),
-                        }],
-                    )
-                })
-                .collect();
-            *state = GameState::Round(btround.into());
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouPaidBanker {
-                    banker_id: pbp.banker_id,
-                    new_banker_cash: pbp.new_banker_cash,
-                    your_new_cash: pbp.new_target_cash,
-                    paid_amount: pbp.paid_amount,
-                    sold_assets: pbp.selected_cards.sold_assets,
-                    issued_liabilities: pbp.selected_cards.issued_liabilities,
-                },
-            ))
-        }
-        Err(e) => Err(e),
-    }
-}
-
-pub fn swap_with_deck(
-    state: &mut GameState,
-    player_id: PlayerId,
-    card_idxs: Vec<usize>,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-
-    match round.player_swap_with_deck(player_id, card_idxs) {
-        Ok(AssetLiabilityCount {
-            asset_count,
-            liability_count,
-        }) => {
-            let internal = round
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::SwappedWithDeck {
-                            asset_count,
-                            liability_count,
-                        }],
-                    )
-                })
-                .collect();
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouSwapDeck {
-                    cards_to_draw: asset_count + liability_count,
-                },
-            ))
-        }
-        Err(e) => Err(e),
-    }
+/// Gets the name of player `player_id`.
+pub fn player_name(state: &GameState, player_id: PlayerId) -> Result<&str, GameError> {
+    let name = match state {
+        GameState::Lobby(lobby) => lobby
+            .player(player_id)
+            .ok_or(GameError::InvalidPlayerIndex(player_id.0))?
+            .name(),
+        GameState::SelectingCharacters(s) => s.player(player_id)?.name(),
+        GameState::Round(round) => round.player(player_id)?.name(),
+        GameState::BankerTarget(round) => round.player(player_id)?.name(),
+        GameState::Results(results) => results.player(player_id)?.name(),
+    };
+
+    Ok(name)
 }
 
-pub fn swap_with_player(
-    state: &mut GameState,
-    player_id: PlayerId,
-    target_player_id: PlayerId,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-
-    let hands = round.player_swap_with_player(player_id, target_player_id)?;
-
-    let internal = round
-        .players()
-        .iter()
-        .filter(|p| ![player_id, target_player_id].contains(&p.id()))
-        .map(|p| {
-            (
-                p.id(),
-                vec![UniqueResponse::SwappedWithPlayer {
-                    regulator_id: player_id,
-                    target_id: target_player_id,
-                }],
-            )
-        })
-        .chain(std::iter::once((
-            target_player_id,
-            vec![UniqueResponse::RegulatorSwappedYourCards {
-                new_cards: hands.target_new_hand,
-            }],
-        )))
-        .collect();
+/// Suggests a next move to player `player_id`, which is only answered to them.
+pub fn hint(state: &GameState, player_id: PlayerId) -> Result<Response, GameError> {
+    let hint = state.round()?.suggest_action(player_id);
 
     Ok(Response(
-        InternalResponse(internal),
-        DirectResponse::YouSwapPlayer {
-            new_cards: hands.regulator_new_hand,
-            target_player_id,
-        },
+        InternalResponse(HashMap::new(), vec![]),
+        DirectResponse::YouGotHint { hint },
     ))
 }
 
-pub fn divest_asset(
-    state: &mut GameState,
-    stakeholder_id: PlayerId,
-    target_id: PlayerId,
-    asset_idx: usize,
-) -> Result<Response, GameError> {
-    let round = state.round_mut()?;
-
-    match round.player_divest_asset(stakeholder_id, target_id, asset_idx) {
-        Ok(gold_cost) => {
-            let internal = round
-                .players()
-                .iter()
-                .filter(|p| p.id() != stakeholder_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::AssetDivested {
-                            player_id: stakeholder_id,
-                            target_id,
-                            asset_idx,
-                            paid_gold: gold_cost,
-                        }],
-                    )
-                })
-                .collect();
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouDivestedAnAsset {
-                    target_id,
-                    asset_idx,
-                    gold_cost,
-                },
-            ))
-        }
-        Err(e) => Err(e),
-    }
-}
-
-pub fn end_turn(state: &mut GameState, player_id: PlayerId) -> Result<Response, GameError> {
-    state.end_player_turn(player_id)?;
-
-    match state {
-        GameState::Lobby(_) => Err(GameError::NotAvailableInLobbyState),
-        GameState::BankerTarget(_) => Err(GameError::NotAvailableInBankerTargetState),
-        GameState::SelectingCharacters(selecting) => {
-            let internal = selecting
-                .players()
-                .iter()
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::SelectingCharacters {
-                            chairman_id: selecting.chairman_id(),
-                            selectable_characters: selecting
-                                .player_get_selectable_characters(p.id())
-                                .ok(),
-                            open_characters: selecting.open_characters().to_vec(),
-                            closed_character: selecting.player_get_closed_character(p.id()).ok(),
-                            turn_order: selecting.turn_order(),
-                        }],
-                    )
-                })
-                .collect();
-
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouEndedTurn,
-            ))
-        }
-        GameState::Round(round) => {
-            let mut internal: HashMap<PlayerId, Vec<UniqueResponse>> = round
-                .players()
-                .iter()
-                .map(|p| (p.id(), vec![turn_starts(round)]))
-                .collect();
-
-            if round.banker_target() == Some(round.current_player().character()) {
-                *state = GameState::BankerTarget(round.into());
-                for value in internal.values_mut() {
-                    value.push(UniqueResponse::PlayerTargetedByBanker {
-                        player_turn: state.bankertarget()?.current_player().id(),
-                        cash_to_be_paid: state.bankertarget()?.gold_to_be_paid(),
-                        is_possible_to_pay_banker: state.bankertarget()?.can_pay_banker(),
-                    });
-                }
-            }
-
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouEndedTurn,
-            ))
-        }
-        GameState::Results(results) => {
-            let scores = results.player_scores();
-
-            let internal = results
-                .players()
-                .iter()
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::GameEnded {
-                            scores: scores.clone(),
-                        }],
-                    )
-                })
-                .collect();
-
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouEndedTurn,
-            ))
-        }
-    }
-}
-
 /// Facilitates a client resync by providing a packet containing the full gamestate
 /// Contains data specific to the current gamestate
 pub fn resync(state: &GameState, player_id: PlayerId) -> Result<Response, GameError> {
@@ -776,7 +171,7 @@ pub fn resync(state: &GameState, player_id: PlayerId) -> Result<Response, GameEr
         GameState::Lobby(_) => Err(GameError::NotAvailableInLobbyState),
         GameState::Results(_) => Err(GameError::NotAvailableInResultsState),
         // Banker phase has to be implemented yet
-        GameState::BankerTarget(_) => todo!(),
+        GameState::BankerTarget(_) => Err(GameError::NotAvailableInBankerTargetState),
         // During the playing round we need to notify the player of the actions that they can still take
         GameState::Round(round) => {
             let player = round.player(player_id)?;
@@ -787,20 +182,7 @@ pub fn resync(state: &GameState, player_id: PlayerId) -> Result<Response, GameEr
                 .map(|p| (p.id(), vec![UniqueResponse::Rejoined { player_id }]))
                 .collect();
             // Create the resync data specific to the playing round
-            let round_data = ResyncData::PlayingRound {
-                current_player_id: round.current_player().id(),
-                player_character: round.current_player().character(),
-                had_turn: round.played_characters(),
-                draws_n_cards: round.current_player().draws_n_cards(),
-                cards_drawn: round.current_player().total_cards_drawn(),
-                gives_back_n_cards: round.current_player().gives_back_n_cards(),
-                cards_returned: round.current_player().total_cards_given_back(),
-                drawn_cards: round.current_player().cards_drawn().to_vec(),
-                used_ability: round.current_player().has_used_ability(),
-                playable_assets: round.current_player().playable_assets(),
-                play_credits_remaining: round.current_player().assets_to_play(),
-                playable_liabilities: round.current_player().liabilities_to_play(),
-            };
+            let round_data = playing_round_data(round);
             // Create the response
             let response = DirectResponse::YouResynced {
                 id: player.id(),
@@ -809,10 +191,11 @@ pub fn resync(state: &GameState, player_id: PlayerId) -> Result<Response, GameEr
                 assets: player.assets().to_vec(),
                 liabilities: player.liabilities().to_vec(),
                 market: round.current_market().clone(),
+                events: round.current_events().to_vec(),
                 player_info: round.player_info(player_id),
                 phase: round_data,
             };
-            Ok(Response(InternalResponse(internal), response))
+            Ok(Response(InternalResponse(internal, vec![]), response))
         }
         GameState::SelectingCharacters(round) => {
             let player = round.player(player_id)?;
@@ -823,16 +206,9 @@ pub fn resync(state: &GameState, player_id: PlayerId) -> Result<Response, GameEr
                 .map(|p| (p.id(), vec![UniqueResponse::Rejoined { player_id }]))
                 .collect();
             // Create the resync data specific to the selecting phase
-            let character_select_data = ResyncData::SelectingCharacters {
-                chairman_id: round.chairman_id(),
-                currently_picking_id: round.currently_selecting_id(),
-                selectable_characters: round.player_get_selectable_characters(player_id).ok(),
-                open_characters: round.open_characters().to_vec(),
-                closed_character: round.player_get_closed_character(player_id).ok(),
-                turn_order: round.turn_order(),
-            };
+            let character_select_data = selecting_data(round, player_id);
             Ok(Response(
-                InternalResponse(internal),
+                InternalResponse(internal, vec![]),
                 DirectResponse::YouResynced {
                     id: player.id(),
                     cash: player.cash(),
@@ -840,6 +216,7 @@ pub fn resync(state: &GameState, player_id: PlayerId) -> Result<Response, GameEr
                     assets: player.assets().to_vec(),
                     liabilities: player.liabilities().to_vec(),
                     market: round.current_market().clone(),
+                    events: round.current_events().to_vec(),
                     player_info: round.player_info(player_id),
                     phase: character_select_data,
                 },
@@ -848,171 +225,165 @@ pub fn resync(state: &GameState, player_id: PlayerId) -> Result<Response, GameEr
     }
 }
 
-pub fn minus_into_plus(
-    state: &mut GameState,
-    player_id: PlayerId,
-    color: Color,
-) -> Result<Response, GameError> {
-    let results = state.results_mut()?;
-
-    match results.toggle_minus_into_plus(player_id, color) {
-        Ok(new_market) => {
-            let player = results.player(player_id)?;
-            let new_score = player.score();
-
-            let internal = results
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    let new_market = new_market.clone();
-                    (
-                        p.id(),
-                        vec![UniqueResponse::MinusedIntoPlus {
-                            player_id,
-                            new_market,
-                            new_score,
-                        }],
-                    )
-                })
-                .collect();
-
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouMinusedIntoPlus {
-                    color,
-                    new_market,
-                    new_score,
+/// Gets everything player `player_id` is allowed to see of the game, in any phase of the game.
+/// Unlike [`resync`], nobody else is told about it.
+pub fn full_state(state: &GameState, player_id: PlayerId) -> Result<Response, GameError> {
+    let direct = match state {
+        GameState::Lobby(lobby) => DirectResponse::FullState {
+            you: lobby
+                .player(player_id)
+                .ok_or(GameError::InvalidPlayerIndex(player_id.0))?
+                .into(),
+            hand: Vec::new(),
+            player_info: lobby.player_info(player_id),
+            market: None,
+            events: Vec::new(),
+            current_player: None,
+            deck_sizes: None,
+            phase: ResyncData::Lobby {
+                host_id: lobby.host().map(|p| p.id()),
+                spectators: lobby.spectators().usernames().to_vec(),
+                ready: lobby
+                    .players()
+                    .iter()
+                    .filter(|p| p.is_ready())
+                    .map(|p| p.id())
+                    .collect(),
+            },
+        },
+        GameState::SelectingCharacters(selecting) => {
+            let player = selecting.player(player_id)?;
+            DirectResponse::FullState {
+                you: PlayerInfo::from(player)
+                    .with_market(selecting.current_market())
+                    .with_liability_cap(&selecting.config()),
+                hand: player.hand().to_vec(),
+                player_info: selecting.player_info(player_id),
+                market: Some(selecting.current_market().clone()),
+                events: selecting.current_events().to_vec(),
+                current_player: Some(selecting.currently_selecting_id()),
+                deck_sizes: state.deck_sizes(),
+                phase: selecting_data(selecting, player_id),
+            }
+        }
+        GameState::Round(round) => {
+            let player = round.player(player_id)?;
+            DirectResponse::FullState {
+                you: PlayerInfo::from(player)
+                    .with_market(round.current_market())
+                    .with_liability_cap(&round.config()),
+                hand: player.hand().to_vec(),
+                player_info: round.player_info(player_id),
+                market: Some(round.current_market().clone()),
+                events: round.current_events().to_vec(),
+                current_player: Some(round.current_player().id()),
+                deck_sizes: state.deck_sizes(),
+                phase: playing_round_data(round),
+            }
+        }
+        GameState::BankerTarget(round) => {
+            let player = round.player(player_id)?;
+            DirectResponse::FullState {
+                you: PlayerInfo::from(player)
+                    .with_market(round.current_market())
+                    .with_liability_cap(&round.config()),
+                hand: player.hand().to_vec(),
+                player_info: round.player_info(player_id),
+                market: Some(round.current_market().clone()),
+                events: round.current_events().to_vec(),
+                current_player: Some(round.current_player().id()),
+                deck_sizes: state.deck_sizes(),
+                phase: ResyncData::PayingBanker {
+                    current_player_id: round.current_player().id(),
+                    gold_to_be_paid: round.gold_to_be_paid(),
                 },
-            ))
+            }
         }
-        Err(e) => Err(e),
-    }
-}
-
-pub fn silver_into_gold(
-    state: &mut GameState,
-    player_id: PlayerId,
-    asset_idx: usize,
-) -> Result<Response, GameError> {
-    let results = state.results_mut()?;
-
-    match results.toggle_silver_into_gold(player_id, asset_idx) {
-        Ok(ToggleSilverIntoGold {
-            old_asset_data,
-            new_asset_data,
-        }) => {
+        GameState::Results(results) => {
             let player = results.player(player_id)?;
-            let new_score = player.score();
-
-            let internal = results
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::SilveredIntoGold {
-                            player_id,
-                            old_asset_data,
-                            new_asset_data,
-                            new_score,
-                        }],
-                    )
-                })
-                .collect();
-
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouSilveredIntoGold {
-                    old_asset_data,
-                    new_asset_data,
-                    new_score,
+            DirectResponse::FullState {
+                you: player.into(),
+                hand: player.hand().to_vec(),
+                player_info: results.player_info(player_id),
+                market: Some(player.market().clone()),
+                events: results.final_events().to_vec(),
+                current_player: None,
+                deck_sizes: None,
+                phase: ResyncData::Results {
+                    scores: results.player_scores(),
                 },
-            ))
+            }
         }
-        Err(e) => Err(e),
-    }
-}
-
-pub fn change_asset_color(
-    state: &mut GameState,
-    player_id: PlayerId,
-    asset_idx: usize,
-    color: Color,
-) -> Result<Response, GameError> {
-    let results = state.results_mut()?;
+    };
 
-    match results.toggle_change_asset_color(player_id, asset_idx, color) {
-        Ok(ToggleChangeAssetColor {
-            old_asset_data,
-            new_asset_data,
-        }) => {
-            let player = results.player(player_id)?;
-            let new_score = player.score();
-
-            let internal = results
-                .players()
-                .iter()
-                .filter(|p| p.id() != player_id)
-                .map(|p| {
-                    (
-                        p.id(),
-                        vec![UniqueResponse::ChangedAssetColor {
-                            player_id,
-                            old_asset_data,
-                            new_asset_data,
-                            new_score,
-                        }],
-                    )
-                })
-                .collect();
+    Ok(Response(InternalResponse(HashMap::new(), vec![]), direct))
+}
 
-            Ok(Response(
-                InternalResponse(internal),
-                DirectResponse::YouChangedAssetColor {
-                    old_asset_data,
-                    new_asset_data,
-                    new_score,
-                },
-            ))
-        }
-        Err(e) => Err(e),
+/// Gets the resync data of the round being played, which is about the player whose turn it is.
+fn playing_round_data(round: &Round) -> ResyncData {
+    let current = round.current_player();
+    ResyncData::PlayingRound {
+        current_player_id: current.id(),
+        player_character: current.character(),
+        had_turn: round.played_characters(),
+        draws_n_cards: current.draws_n_cards(),
+        cards_drawn: current.total_cards_drawn(),
+        gives_back_n_cards: current.gives_back_n_cards(),
+        cards_returned: current.total_cards_given_back(),
+        drawn_cards: current.cards_drawn().to_vec(),
+        used_ability: current.has_used_ability(),
+        playable_assets: current.playable_assets(),
+        play_credits_remaining: current.assets_to_play(),
+        playable_liabilities: current.liabilities_to_play(),
+        turn_nonce: round.turn_nonce(),
     }
 }
 
-pub fn confirm_asset_ability(
-    state: &mut GameState,
-    player_id: PlayerId,
-    asset_idx: usize,
-) -> Result<Response, GameError> {
-    let results = state.results_mut()?;
-    results.confirm_asset_ability(player_id, asset_idx)?;
-
-    let internal = results
-        .players()
-        .iter()
-        .filter(|p| p.id() != player_id)
-        .map(|p| {
-            (
-                p.id(),
-                vec![UniqueResponse::ConfirmedAssetAbility {
-                    player_id,
-                    asset_idx,
-                }],
-            )
-        })
-        .collect();
-
-    Ok(Response(
-        InternalResponse(internal),
-        DirectResponse::YouConfirmedAssetAbility { asset_idx },
-    ))
+/// Gets the resync data of selecting characters, as player `player_id` sees it.
+fn selecting_data(selecting: &SelectingCharacters, player_id: PlayerId) -> ResyncData {
+    ResyncData::SelectingCharacters {
+        chairman_id: selecting.chairman_id(),
+        currently_picking_id: selecting.currently_selecting_id(),
+        selectable_characters: selecting.player_get_selectable_characters(player_id).ok(),
+        bannable_characters: selecting.player_get_bannable_characters(player_id).ok(),
+        open_characters: selecting.open_characters().to_vec(),
+        banned_characters: selecting.banned_characters().to_vec(),
+        closed_character: selecting.player_get_closed_character(player_id).ok(),
+        turn_order: selecting.turn_order(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use claim::*;
+
+    #[test]
+    fn resync_is_not_available_while_targeted_by_banker() {
+        let mut game = GameState::new();
+        let lobby = game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("user {i}")));
+        }
+        assert_ok!(handle_action(
+            &mut game,
+            "user 0",
+            PlayerAction::StartGame,
+            None
+        ));
+        while let Ok(selecting) = game.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(game.apply(id, PlayerAction::SelectCharacter { character }));
+        }
+        let round = game.round_mut().unwrap();
+        game = GameState::BankerTarget(BankerTargetRound::from(round));
+
+        assert_matches!(
+            resync(&game, PlayerId(0)),
+            Err(GameError::NotAvailableInBankerTargetState)
+        );
+    }
 
     #[test]
     fn fmt() {
@@ -1020,6 +391,7 @@ mod tests {
 
         let action2 = FrontendRequest::DrawCard {
             card_type: CardType::Asset,
+            turn_nonce: 0,
         };
 
         let json = serde_json::to_string(&action).unwrap();
@@ -1038,4 +410,23 @@ mod tests {
 
         println!("send json: {sjson}");
     }
+
+    #[tokio::test]
+    async fn cards_are_downloaded() {
+        let path = format!(
+            "{}/../assets/cards/boardgame.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let json = std::fs::read_to_string(&path).unwrap();
+        let app = axum::Router::new().route("/cards", axum::routing::get(|| async { json }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let data = GameData::from_url(&format!("http://{addr}/cards")).await;
+        assert_eq!(data.unwrap(), GameData::new(&path).unwrap());
+
+        let missing = GameData::from_url(&format!("http://{addr}/missing")).await;
+        assert!(missing.is_err());
+    }
 }
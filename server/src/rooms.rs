@@ -1,181 +1,1393 @@
-use game::{errors::GameError, game::GameState};
+use game::{
+    bot::acting_player,
+    cards::CardTranslations,
+    errors::GameError,
+    game::{GameConfig, GameState, PlayerAction, normalize_username},
+    player::PlayerId,
+};
 use responses::*;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, oneshot};
 
 use std::{
-    sync::{Arc, Mutex},
-    time::Instant,
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    channels::PlayerChannels,
+    config::{ConfigError, env_number, env_seconds},
+    middleware::*,
+    request_handler::*,
+    spectators::SpectatorFeed,
+    storage::Storage,
+    telemetry::TelemetrySink,
+    votes::{Vote, Votes},
 };
 
-use crate::request_handler::*;
+const DEFAULT_INACTIVITY_TIMEOUT: Duration = Duration::from_secs(300); // 5 min
+const DEFAULT_CLEANUP_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_VOTE_DURATION: Duration = Duration::from_secs(60);
+const DEFAULT_SPECTATOR_DELAY: Duration = Duration::from_secs(30);
+const DEFAULT_RATE_LIMIT: f64 = 10.0;
+const DEFAULT_RATE_LIMIT_BURST: f64 = 20.0;
+
+/// All rooms on this server, keyed by the name of their channel.
+pub type Rooms = Arc<Mutex<HashMap<String, RoomHandle>>>;
+
+/// Commands a connection sends to the task that runs its room.
+#[derive(Debug)]
+pub enum RoomCommand {
+    /// Join the room, or rejoin it if the game has already started.
+    Join {
+        username: String,
+        locale: Option<String>,
+        reply: oneshot::Sender<Result<Joined, ResponseError>>,
+    },
+    /// Handle a request of the player with `username`.
+    Request {
+        username: String,
+        request: FrontendRequest,
+        reply: oneshot::Sender<DirectResponse>,
+    },
+    /// Get the full state of the game for a player that fell behind, see [`full_state`].
+    FullState {
+        username: String,
+        reply: oneshot::Sender<Option<DirectResponse>>,
+    },
+    /// The connection of the player with `username` was closed.
+    Leave { username: String },
+    /// Start watching the room as a spectator.
+    Spectate {
+        username: String,
+        locale: Option<String>,
+        reply: oneshot::Sender<Result<Spectating, ResponseError>>,
+    },
+}
+
+/// Everything a connection needs after successfully joining a room.
+#[derive(Debug)]
+pub struct Joined {
+    /// The username of the player that joined.
+    pub username: String,
+    /// The id of the player that joined.
+    pub id: PlayerId,
+    /// Whether the player rejoined a game that already started.
+    pub rejoined: bool,
+    /// Receives responses meant for everyone in the room.
+    pub rx: broadcast::Receiver<UniqueResponse>,
+    /// Receives responses meant for this player.
+    pub player_rx: broadcast::Receiver<Vec<UniqueResponse>>,
+    /// The translations of the cards in the language the player chose, if there are any.
+    pub translations: Option<Arc<CardTranslations>>,
+}
+
+/// Everything a connection needs after it started watching a room.
+#[derive(Debug)]
+pub struct Spectating {
+    /// The username of the spectator.
+    pub username: String,
+    /// What the spectator may see of the game right now, see [`DirectResponse::spectator_view`].
+    pub view: DirectResponse,
+    /// Receives the delayed feed of the room.
+    pub rx: broadcast::Receiver<UniqueResponse>,
+    /// The translations of the cards in the language the spectator chose, if there are any.
+    pub translations: Option<Arc<CardTranslations>>,
+}
+
+/// A handle to a running room, which can be cheaply cloned. Every room runs in its own task which
+/// owns its [`GameState`], so rooms never contend over a lock and a crash in one room does not
+/// affect any other room.
+#[derive(Clone, Debug)]
+pub struct RoomHandle {
+    commands: mpsc::Sender<RoomCommand>,
+}
+
+impl RoomHandle {
+    /// Joins the room with `username`, who wants to see cards in `locale`. Returns `None` if the
+    /// room is no longer running.
+    pub async fn join(
+        &self,
+        username: String,
+        locale: Option<String>,
+    ) -> Option<Result<Joined, ResponseError>> {
+        let (reply, response) = oneshot::channel();
+        self.send(RoomCommand::Join {
+            username,
+            locale,
+            reply,
+        })
+        .await?;
+
+        response.await.ok()
+    }
+
+    /// Handles `request` of the player with `username`. Returns `None` if the room is no longer
+    /// running.
+    pub async fn request(
+        &self,
+        username: String,
+        request: FrontendRequest,
+    ) -> Option<DirectResponse> {
+        let (reply, response) = oneshot::channel();
+        self.send(RoomCommand::Request {
+            username,
+            request,
+            reply,
+        })
+        .await?;
+
+        response.await.ok()
+    }
+
+    /// Gets the [`DirectResponse::FullState`] of the player with `username`, which works in every
+    /// phase of the game. Players are looked up by name, since their id changes if the seats are
+    /// shuffled. Returns `None` if there is no such player or the room is no longer running.
+    pub async fn full_state(&self, username: String) -> Option<DirectResponse> {
+        let (reply, response) = oneshot::channel();
+        self.send(RoomCommand::FullState { username, reply })
+            .await?;
+
+        response.await.ok().flatten()
+    }
+
+    /// Starts watching the room as a spectator with `username`, who wants to see cards in
+    /// `locale`. Returns `None` if the room is no longer running.
+    pub async fn spectate(
+        &self,
+        username: String,
+        locale: Option<String>,
+    ) -> Option<Result<Spectating, ResponseError>> {
+        let (reply, response) = oneshot::channel();
+        self.send(RoomCommand::Spectate {
+            username,
+            locale,
+            reply,
+        })
+        .await?;
+
+        response.await.ok()
+    }
+
+    /// Lets the room know that the connection of the player or spectator with `username` was
+    /// closed.
+    pub async fn leave(&self, username: String) {
+        let _ = self.send(RoomCommand::Leave { username }).await;
+    }
+
+    /// Checks whether the room stopped running, either because it was closed or because it
+    /// crashed.
+    pub fn is_closed(&self) -> bool {
+        self.commands.is_closed()
+    }
+
+    async fn send(&self, command: RoomCommand) -> Option<()> {
+        self.commands.send(command).await.ok()
+    }
+}
+
+/// How every room on this server is run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoomConfig {
+    /// The seconds each player has for all of their turns combined, if limited.
+    pub time_bank: Option<u32>,
+    /// The seconds each turn may take, if limited.
+    pub turn_time: Option<u32>,
+    /// How long a room stays open without any activity.
+    pub inactivity_timeout: Duration,
+    /// How often a room checks whether it has been inactive for too long.
+    pub cleanup_interval: Duration,
+    /// How long players have to vote.
+    pub vote_duration: Duration,
+    /// How long spectators are held back.
+    pub spectator_delay: Duration,
+    /// How many requests each player can send per second.
+    pub rate_limit: f64,
+    /// How many requests each player can send at once.
+    pub rate_limit_burst: f64,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        Self {
+            time_bank: None,
+            turn_time: None,
+            inactivity_timeout: DEFAULT_INACTIVITY_TIMEOUT,
+            cleanup_interval: DEFAULT_CLEANUP_INTERVAL,
+            vote_duration: DEFAULT_VOTE_DURATION,
+            spectator_delay: DEFAULT_SPECTATOR_DELAY,
+            rate_limit: DEFAULT_RATE_LIMIT,
+            rate_limit_burst: DEFAULT_RATE_LIMIT_BURST,
+        }
+    }
+}
+
+impl RoomConfig {
+    /// Reads the config from the environment, where `TIME_BANK`, `TURN_TIME`,
+    /// `INACTIVITY_TIMEOUT`, `CLEANUP_INTERVAL`, `VOTE_DURATION` and `SPECTATOR_DELAY` are in
+    /// seconds, and `RATE_LIMIT` and `RATE_LIMIT_BURST` in requests. Defaults are used for any
+    /// that are not set.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let default = Self::default();
+        Ok(Self {
+            time_bank: env_number("TIME_BANK")?,
+            turn_time: env_number("TURN_TIME")?,
+            inactivity_timeout: env_seconds("INACTIVITY_TIMEOUT")?
+                .unwrap_or(default.inactivity_timeout),
+            cleanup_interval: env_seconds("CLEANUP_INTERVAL")?.unwrap_or(default.cleanup_interval),
+            vote_duration: env_seconds("VOTE_DURATION")?.unwrap_or(default.vote_duration),
+            spectator_delay: env_seconds("SPECTATOR_DELAY")?.unwrap_or(default.spectator_delay),
+            rate_limit: env_number("RATE_LIMIT")?.unwrap_or(default.rate_limit),
+            rate_limit_burst: env_number("RATE_LIMIT_BURST")?.unwrap_or(default.rate_limit_burst),
+        })
+    }
+}
+
+/// Spawns a new room for `channel`, which removes itself from `rooms` once it has been inactive
+/// for too long. Every action the room accepts is logged to `storage`, if set, and anonymized
+/// events about its games are sent to `telemetry`, if set.
+pub fn spawn_room(
+    channel: String,
+    rooms: Rooms,
+    config: RoomConfig,
+    storage: Option<Arc<dyn Storage>>,
+    telemetry: Option<Arc<dyn TelemetrySink>>,
+) -> RoomHandle {
+    let (commands, receiver) = mpsc::channel(64);
+    let handle = RoomHandle { commands };
+
+    let room = Room::new(channel.clone(), config, storage, telemetry);
+    tokio::spawn(room.run(receiver, handle.clone(), rooms));
+
+    tracing::debug!("Created room with channel '{channel}'");
+
+    handle
+}
+
+/// Creates the game of a new room, which is played with the time limits of `config`.
+fn new_game(config: &RoomConfig) -> GameState {
+    let mut game = GameState::new();
+    if let GameState::Lobby(lobby) = &mut game {
+        lobby.set_config(GameConfig {
+            time_bank: config.time_bank,
+            turn_time: config.turn_time,
+            ..Default::default()
+        });
+    }
+    game
+}
 
 /// All-encompassing state each room has access to
-pub struct RoomState {
+struct Room {
+    /// The name of the channel of this room
+    channel: String,
+    /// How this room is run
+    config: RoomConfig,
     /// Internal broadcast that can be received by any connected thread
-    pub tx: broadcast::Sender<UniqueResponse>,
-    /// Internal broadcast channels to send responses specific to each player
-    pub player_tx: [broadcast::Sender<UniqueResponse>; 7],
+    tx: broadcast::Sender<UniqueResponse>,
+    /// Internal broadcast channels to send responses specific to each player. All responses a
+    /// single action produces for a player are sent as one batch.
+    player_tx: PlayerChannels,
     /// Per-room gamestate
-    pub game: Mutex<GameState>,
+    game: GameState,
     /// Timestamp of last activity used for cleanup.
-    pub last_activity: Arc<Mutex<Instant>>,
-    /// A task that periodically checks if the room has been inactive and should be closed.
-    pub cleanup_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    last_activity: Instant,
+    /// The layers every request passes through before reaching the game.
+    chain: Chain,
+    /// The player whose turn is being timed and since when, if the game is in a round.
+    turn_clock: Option<(PlayerId, Instant)>,
+    /// Everything that happens publicly in this room, held back for spectators.
+    spectators: SpectatorFeed,
+    /// The translations of the cards the game is played with, keyed by locale.
+    translations: HashMap<String, Arc<CardTranslations>>,
+    /// The votes of the players and what they decided, shared with the chain.
+    control: Arc<Mutex<RoomControl>>,
+    /// Why the room should close once the current command is handled, if it should.
+    closing: Option<RoomCloseReason>,
 }
 
-impl RoomState {
-    pub fn new() -> Self {
+impl Room {
+    fn new(
+        channel: String,
+        config: RoomConfig,
+        storage: Option<Arc<dyn Storage>>,
+        telemetry: Option<Arc<dyn TelemetrySink>>,
+    ) -> Self {
+        let player_tx = PlayerChannels::new(64);
+        let control = Arc::new(Mutex::new(RoomControl::new(Votes::new(
+            config.vote_duration,
+        ))));
+
         Self {
+            chain: Chain::standard(
+                &channel,
+                &config,
+                player_tx.clone(),
+                control.clone(),
+                storage,
+                telemetry,
+            ),
+            channel,
+            config,
             tx: broadcast::channel(64).0,
-            player_tx: [
-                broadcast::channel(64).0,
-                broadcast::channel(64).0,
-                broadcast::channel(64).0,
-                broadcast::channel(64).0,
-                broadcast::channel(64).0,
-                broadcast::channel(64).0,
-                broadcast::channel(64).0,
-            ],
-            game: Mutex::new(GameState::new()),
-            last_activity: Arc::new(Mutex::new(Instant::now())),
-            cleanup_handle: Mutex::new(None),
-        }
-    }
-
-    pub fn handle_request(
-        &self,
-        msg: FrontendRequest,
-        player_name: &str,
-    ) -> Result<Response, GameError> {
-        self.touch();
+            player_tx,
+            game: new_game(&config),
+            last_activity: Instant::now(),
+            turn_clock: None,
+            spectators: SpectatorFeed::new(config.spectator_delay),
+            translations: HashMap::new(),
+            control,
+            closing: None,
+        }
+    }
+
+    async fn run(
+        mut self,
+        mut commands: mpsc::Receiver<RoomCommand>,
+        handle: RoomHandle,
+        rooms: Rooms,
+    ) {
+        let RoomConfig {
+            inactivity_timeout,
+            cleanup_interval,
+            ..
+        } = self.config;
+
+        if let GameState::Lobby(lobby) = &mut self.game {
+            load_remote_game_data(lobby).await;
+            if lobby.game_data().is_none() {
+                load_game_data(lobby);
+            }
 
+            // The cards are known before anyone joins, so each player can get the language they
+            // asked for
+            if let Some(data) = lobby.game_data() {
+                self.translations = data
+                    .translations
+                    .iter()
+                    .map(|(locale, t)| (locale.clone(), Arc::new(t.clone())))
+                    .collect();
+            }
+        }
+
+        let mut cleanup = tokio::time::interval_at(
+            tokio::time::Instant::now() + cleanup_interval,
+            cleanup_interval,
+        );
+        let mut clock = tokio::time::interval(Duration::from_secs(1));
+
+        loop {
+            let release = self.spectators.next_release();
+
+            tokio::select! {
+                command = commands.recv() => match command {
+                    Some(command) => {
+                        self.handle_command(command);
+                        if let Some(reason) = self.closing.take() {
+                            tracing::info!("Closing room with channel name '{}'", self.channel);
+                            self.close(reason, &rooms, &handle).await;
+                            break;
+                        }
+                    }
+                    None => break,
+                },
+                _ = clock.tick() => {
+                    self.tick_clock();
+                    self.expire_vote();
+                }
+                // The future is created even when the branch is disabled, so it needs some deadline
+                _ = tokio::time::sleep_until(release.unwrap_or_else(Instant::now).into()),
+                    if release.is_some() =>
+                {
+                    self.spectators.release(Instant::now());
+                }
+                _ = cleanup.tick() => {
+                    let elapsed = self.last_activity.elapsed();
+                    if elapsed > inactivity_timeout {
+                        tracing::info!(
+                            "Room with channel name '{}' inactive for {:?}, closing",
+                            self.channel,
+                            elapsed
+                        );
+
+                        self.close(RoomCloseReason::Inactive, &rooms, &handle).await;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Lets everyone know the room is closing because of `reason`, and removes it from `rooms`.
+    async fn close(&mut self, reason: RoomCloseReason, rooms: &Rooms, handle: &RoomHandle) {
+        let msg = UniqueResponse::RoomClosed {
+            channel: self.channel.clone(),
+            reason,
+        };
+        if let Err(e) = self.tx.send(msg) {
+            tracing::error!(%e);
+        }
+
+        // Give the messages a little bit of time to be sent out and received
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        // Remove from HashMap to drop the room, which closes its connected channels and cleans up
+        // the connected user tasks.
         // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
         // this cannot happen, unwrapping is safe.
-        let state = &mut *self.game.lock().unwrap();
-
-        match msg {
-            FrontendRequest::StartGame => start_game(state),
-            FrontendRequest::SelectCharacter { character } => {
-                // TODO: do something about this lookup madness
-                let player_id = state
-                    .selecting_characters()?
-                    .player_by_name(player_name)?
-                    .id();
-                select_character(state, player_id, character)
-            }
-            FrontendRequest::DrawCard { card_type } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                draw_card(state, card_type, player_id)
-            }
-            FrontendRequest::PutBackCard { card_idx } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                put_back_card(state, card_idx, player_id)
-            }
-            FrontendRequest::BuyAsset { card_idx } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                play_card(state, card_idx, player_id)
-            }
-            FrontendRequest::IssueLiability { card_idx } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                play_card(state, card_idx, player_id)
-            }
-            FrontendRequest::RedeemLiability { liability_idx } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                redeem_liability(state, liability_idx, player_id)
-            }
-            FrontendRequest::UseAbility => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                use_ability(state, player_id)
-            }
-            FrontendRequest::GetBonusCash => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                get_bonus_cash(state, player_id)
-            }
-            FrontendRequest::FireCharacter { character } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                fire_character(state, player_id, character)
-            }
-            FrontendRequest::TerminateCreditCharacter { character } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                terminate_credit_character(state, player_id, character)
-            }
-            FrontendRequest::SelectAssetToDivest { asset_id } => {
-                let player_id = state.bankertarget()?.player_by_name(player_name)?.id();
-                select_divest_asset(state, player_id, asset_id)
-            }
-            FrontendRequest::UnselectAssetToDivest { asset_id } => {
-                let player_id = state.bankertarget()?.player_by_name(player_name)?.id();
-                unselect_divest_asset(state, player_id, asset_id)
-            }
-            FrontendRequest::SelectLiabilityToIssue { liability_id } => {
-                let player_id = state.bankertarget()?.player_by_name(player_name)?.id();
-                select_issue_liability(state, player_id, liability_id)
-            }
-            FrontendRequest::UnselectLiabilityToIssue { liability_id } => {
-                let player_id = state.bankertarget()?.player_by_name(player_name)?.id();
-                unselect_issue_liability(state, player_id, liability_id)
-            }
-            FrontendRequest::PayBanker { cash } => {
-                let player_id = state.bankertarget()?.player_by_name(player_name)?.id();
-                pay_banker(state, player_id, cash)
-            }
-            FrontendRequest::SwapWithDeck { card_idxs } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                swap_with_deck(state, player_id, card_idxs)
-            }
-            FrontendRequest::SwapWithPlayer { target_player_id } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                swap_with_player(state, player_id, target_player_id)
-            }
-            FrontendRequest::DivestAsset {
-                target_player_id,
-                card_idx,
+        let mut rooms = rooms.lock().unwrap();
+        if rooms
+            .get(&self.channel)
+            .is_some_and(|room| room.commands.same_channel(&handle.commands))
+        {
+            rooms.remove(&self.channel);
+        }
+    }
+
+    fn handle_command(&mut self, command: RoomCommand) {
+        match command {
+            RoomCommand::Join {
+                username,
+                locale,
+                reply,
             } => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                divest_asset(state, player_id, target_player_id, card_idx)
+                let _ = reply.send(self.join(username, locale));
             }
-            FrontendRequest::EndTurn => {
-                let player_id = state.round()?.player_by_name(player_name)?.id();
-                end_turn(state, player_id)
-            }
-            FrontendRequest::Resync => match state {
-                GameState::Round(round) => {
-                    let player_id = round.player_by_name(player_name)?.id();
-                    resync(state, player_id)
+            RoomCommand::Request {
+                username,
+                request,
+                reply,
+            } => {
+                self.last_activity = Instant::now();
+
+                self.tick_clock();
+
+                let ctx = RequestContext {
+                    username: &username,
+                    request,
+                    game: &mut self.game,
+                };
+                let direct = match self.chain.handle(ctx) {
+                    Ok(Response(InternalResponse(_, spectators), direct)) => {
+                        self.spectators.push(spectators, Instant::now());
+                        direct
+                    }
+                    Err(e) => e.into(),
+                };
+                let _ = reply.send(direct);
+
+                let (passed, kicks) = {
+                    let mut control = self.control();
+                    let control = &mut *control;
+                    (
+                        std::mem::take(&mut control.passed),
+                        std::mem::take(&mut control.kicks),
+                    )
+                };
+                for vote in passed {
+                    self.act_on_vote(vote);
+                }
+                for username in kicks {
+                    self.kick(&username);
                 }
-                GameState::SelectingCharacters(round) => {
-                    let player_id = round.player_by_name(player_name)?.id();
-                    resync(state, player_id)
+                self.pick_for_absent_players();
+                self.tick_clock();
+            }
+            RoomCommand::FullState { username, reply } => {
+                let direct = player_id(&self.game, &username)
+                    .and_then(|player_id| full_state(&self.game, player_id))
+                    .ok()
+                    .map(|Response(_, direct)| direct);
+                let _ = reply.send(direct);
+            }
+            RoomCommand::Leave { username } => self.leave(&username),
+            RoomCommand::Spectate {
+                username,
+                locale,
+                reply,
+            } => {
+                let _ = reply.send(self.spectate(username, locale));
+            }
+        }
+    }
+
+    fn join(&mut self, username: String, locale: Option<String>) -> Result<Joined, ResponseError> {
+        // Players are stored under their normalized username, see `Lobby::join`
+        let username = normalize_username(&username);
+        if self.control().kicked.contains(&username) {
+            return Err(VoteError::Kicked.into());
+        }
+
+        let (id, rejoined) = match &mut self.game {
+            GameState::Lobby(lobby) => match lobby.join(username.clone()) {
+                Ok(player) => {
+                    debug_assert_eq!(player.name(), username);
+                    (player.id(), false)
                 }
-                _ => Err(GameError::NotRoundState),
+                Err(e) => return Err(GameError::from(e).into()),
             },
-            FrontendRequest::MinusIntoPlus { color } => {
-                let player_id = state.results()?.player_by_name(player_name)?.id();
-                minus_into_plus(state, player_id, color)
+            // If the game is already running check and see if the player that is trying to
+            // connect had previously disconnected, if they are allow them to rejoin.
+            GameState::Round(round) => {
+                let id = round.player_by_name(&username)?.id();
+                round.rejoin(id)?;
+                tracing::debug!("Player rejoined: {:?}", id);
+                (id, true)
+            }
+            GameState::SelectingCharacters(round) => {
+                let id = round.player_by_name(&username)?.id();
+                round.rejoin(id)?;
+                tracing::debug!("Player rejoined: {:?}", id);
+                (id, true)
+            }
+            _ => return Err(ResponseError::GameAlreadyStarted),
+        };
+
+        // Subscribe before announcing the join, so the player that joined receives it as well
+        let rx = self.tx.subscribe();
+        let player_rx = self.player_tx.subscribe(id);
+
+        if rejoined {
+            let players = self.game.player_ids();
+            self.announce_presence(
+                &players,
+                id,
+                UniqueResponse::PlayerReconnected { player_id: id },
+            );
+        }
+        if let GameState::Lobby(lobby) = &self.game {
+            let internal = UniqueResponse::PlayersInLobby {
+                changed_player: username.clone(),
+                usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
+                host_id: lobby.host().map(|p| p.id()),
+            };
+            tracing::debug!("Global Response: {:?}", internal);
+            self.spectators.push([internal.clone()], Instant::now());
+            let _ = self.tx.send(internal);
+        }
+
+        Ok(Joined {
+            username,
+            id,
+            rejoined,
+            rx,
+            player_rx,
+            translations: locale.and_then(|locale| self.translations.get(&locale).cloned()),
+        })
+    }
+
+    /// Lets `username` watch the game, and lets everyone know.
+    fn spectate(
+        &mut self,
+        username: String,
+        locale: Option<String>,
+    ) -> Result<Spectating, ResponseError> {
+        if self
+            .control()
+            .kicked
+            .contains(&normalize_username(&username))
+        {
+            return Err(VoteError::Kicked.into());
+        }
+        let username = self
+            .game
+            .spectate(username)
+            .map_err(GameError::from)?
+            .to_owned();
+        tracing::debug!("Spectator joined: {username}");
+
+        // Subscribe before announcing the join, so the spectator receives it as well
+        let rx = self.spectators.subscribe();
+        self.announce(UniqueResponse::SpectatorJoined {
+            username: username.clone(),
+            spectators: self.game.spectators().usernames().to_vec(),
+        });
+
+        Ok(Spectating {
+            view: DirectResponse::spectator_view(&self.game),
+            username,
+            rx,
+            translations: locale.and_then(|locale| self.translations.get(&locale).cloned()),
+        })
+    }
+
+    fn leave(&mut self, username: &str) {
+        if self.game.stop_spectating(username) {
+            tracing::debug!("Spectator left: {username}");
+            return;
+        }
+
+        if let GameState::Lobby(lobby) = &mut self.game {
+            // remove username on disconnect, and move the channels of the players after them
+            // along with their ids
+            let was_host = lobby.host().is_some_and(|p| p.name() == username);
+            if let Some(pos) = lobby.players().iter().position(|p| p.name() == username) {
+                lobby.leave(username);
+                let seats = (0..=lobby.len() as u8)
+                    .filter(|&id| usize::from(id) != pos)
+                    .map(PlayerId)
+                    .collect::<Vec<_>>();
+                self.player_tx.reseat(&seats);
+            }
+
+            // send updated list to everyone, and who took over if the host left
+            let host_id = lobby.host().map(|p| p.id());
+            let mut internal = vec![UniqueResponse::PlayersInLobby {
+                changed_player: username.to_owned(),
+                usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
+                host_id,
+            }];
+            if was_host && let Some(host_id) = host_id {
+                tracing::debug!("Host left, new host: {:?}", host_id);
+                internal.push(UniqueResponse::HostChanged { host_id });
+            }
+            for i in 0..lobby.len() {
+                self.player_tx.send(PlayerId(i as u8), internal.clone());
+            }
+            self.spectators.push(internal, Instant::now());
+            return;
+        }
+
+        // If we are outside of the lobby state then the game will already have started
+        // We need to modify the player object and let the other players in that room know
+        // that the player has disconnected. This also marks them as available for reconnecting.
+        let Ok(id) = player_id(&self.game, username) else {
+            tracing::debug!("A disconnect happened but no connected player could be found.");
+            return;
+        };
+        self.disconnect(id);
+
+        if matches!(self.game, GameState::SelectingCharacters(_)) {
+            // Nobody would pick a character for them otherwise, stalling the game
+            self.pick_for_absent_players();
+        }
+    }
+
+    /// Marks `id` as disconnected from a game that has started, and lets everyone else know
+    /// whether their turns will be skipped.
+    fn disconnect(&mut self, id: PlayerId) {
+        // This can fail but we just continue silently if it does
+        let (_, config) = match &mut self.game {
+            // Players leaving the lobby are removed instead
+            GameState::Lobby(_) => return,
+            GameState::SelectingCharacters(game) => (game.leave(id), Some(game.config())),
+            GameState::Round(game) => (game.leave(id), Some(game.config())),
+            GameState::BankerTarget(game) => (game.leave(id), Some(game.config())),
+            // There are no turns left to skip once the game is over
+            GameState::Results(game) => (game.leave(id), None),
+        };
+        tracing::debug!("Player left: {:?}", id);
+
+        let turns_auto_skipped =
+            config.is_some_and(|config| config.time_bank.is_some() || config.turn_time.is_some());
+        let players = self.game.player_ids();
+        self.announce_disconnect(&players, id, turns_auto_skipped);
+    }
+
+    /// Gets the votes of the players and what they decided.
+    fn control(&self) -> MutexGuard<'_, RoomControl> {
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        self.control.lock().unwrap()
+    }
+
+    /// Ends the vote that is running if its time is up, which means it did not pass, and lets
+    /// everyone know.
+    fn expire_vote(&mut self) {
+        let expired = self.control().votes.expire(Instant::now());
+        if let Some(vote) = expired {
+            tracing::debug!("Vote ended: {:?}, passed: false", vote.kind());
+            self.announce(UniqueResponse::VoteEnded {
+                kind: vote.kind(),
+                passed: false,
+            });
+        }
+    }
+
+    /// Acts on `vote`, which passed.
+    fn act_on_vote(&mut self, vote: Vote) {
+        match vote.kind() {
+            VoteKind::Kick { .. } => {
+                if let Some(target) = vote.target() {
+                    self.kick(target);
+                }
+            }
+            VoteKind::Abort => self.closing = Some(RoomCloseReason::Aborted),
+            VoteKind::Pause => {
+                // Charge the time up to now before the clock stops
+                self.tick_clock();
+                self.control().paused = true;
+                self.turn_clock = None;
             }
-            FrontendRequest::SilverIntoGold { asset_idx } => {
-                let player_id = state.results()?.player_by_name(player_name)?.id();
-                silver_into_gold(state, player_id, asset_idx)
+            VoteKind::Resume => {
+                self.control().paused = false;
+                self.tick_clock();
+            }
+        }
+    }
+
+    /// Removes the player with `username` from the room, closes their connection and keeps them
+    /// from joining again.
+    fn kick(&mut self, username: &str) {
+        tracing::debug!("Player kicked: {username}");
+        self.control().kicked.push(username.to_owned());
+
+        if let Ok(id) = player_id(&self.game, username) {
+            self.player_tx.send(
+                id,
+                vec![UniqueResponse::RoomClosed {
+                    channel: self.channel.clone(),
+                    reason: RoomCloseReason::Kicked,
+                }],
+            );
+        }
+        self.leave(username);
+    }
+
+    /// Sends `response` to everyone in the room, including spectators.
+    fn announce(&mut self, response: UniqueResponse) {
+        self.spectators.push([response.clone()], Instant::now());
+        let _ = self.tx.send(response);
+    }
+
+    /// Charges the time since the last tick to the player whose turn it is, and ends the turns of
+    /// players that ran out of time in their time bank.
+    fn tick_clock(&mut self) {
+        if self.control().paused {
+            self.turn_clock = None;
+            return;
+        }
+
+        if let Some((id, since)) = self.turn_clock.take() {
+            self.game.spend_time(id, since.elapsed());
+        }
+
+        while let Some(id) = self.game.out_of_time() {
+            if let Err(error) = self.end_turn_out_of_time(id) {
+                tracing::error!(%error, "Could not end the turn of {id:?} after running out of time");
+                break;
             }
-            FrontendRequest::ChangeAssetColor { asset_idx, color } => {
-                let player_id = state.results()?.player_by_name(player_name)?.id();
-                change_asset_color(state, player_id, asset_idx, color)
+        }
+
+        self.turn_clock = match &self.game {
+            GameState::Round(_) | GameState::BankerTarget(_) => {
+                acting_player(&self.game).map(|id| (id, Instant::now()))
             }
-            FrontendRequest::ConfirmAssetAbility { asset_idx } => {
-                let player_id = state.results()?.player_by_name(player_name)?.id();
-                confirm_asset_ability(state, player_id, asset_idx)
+            _ => None,
+        };
+    }
+
+    /// Ends the turn of player `id`, putting back the cards they drew last if they still had to
+    /// give back any, whatever phase their turn is in.
+    fn end_turn_out_of_time(&mut self, id: PlayerId) -> Result<(), GameError> {
+        let effects = self.game.end_turn_out_of_time(id)?;
+        let players = self.game.player_ids();
+        let response = Response::from_effects(effects, &players);
+
+        let ended = UniqueResponse::TurnEndedOutOfTime { player_id: id };
+        for other in players {
+            self.player_tx.send(other, vec![ended.clone()]);
+        }
+        self.spectators.push([ended], Instant::now());
+        self.send_internal(response.0);
+
+        Ok(())
+    }
+
+    /// Selects a character for every player that left while it is their turn to select one, and
+    /// lets everyone know which character was picked for them.
+    fn pick_for_absent_players(&mut self) {
+        while let GameState::SelectingCharacters(selecting) = &self.game
+            && let Some((id, character)) = selecting.absent_pick()
+        {
+            tracing::debug!("Selecting {character:?} for absent player {id:?}");
+            let name = match selecting.player(id) {
+                Ok(player) => player.name().to_owned(),
+                Err(_) => break,
+            };
+            match handle_action(
+                &mut self.game,
+                &name,
+                PlayerAction::SelectCharacter { character },
+                None,
+            ) {
+                Ok(response) => self.send_internal(response.0),
+                Err(error) => {
+                    tracing::error!(%error, "Could not select a character for {id:?}");
+                    break;
+                }
             }
         }
     }
 
-    /// Updates the timestamp the last action was taken in.
-    pub fn touch(&self) {
-        *self.last_activity.lock().unwrap() = Instant::now();
+    /// Sends the responses in `internal` to each of their players, and holds back the responses
+    /// for spectators.
+    fn send_internal(&mut self, InternalResponse(players, spectators): InternalResponse) {
+        for (id, responses) in players {
+            self.player_tx.send(id, responses);
+        }
+        self.spectators.push(spectators, Instant::now());
+    }
+
+    /// Lets every player in `players` except `id`, as well as spectators, know that `id`
+    /// disconnected.
+    fn announce_disconnect(
+        &mut self,
+        players: &[PlayerId],
+        id: PlayerId,
+        turns_auto_skipped: bool,
+    ) {
+        let disconnected = UniqueResponse::PlayerDisconnected {
+            player_id: id,
+            turns_auto_skipped,
+        };
+        self.announce_presence(players, id, disconnected);
+    }
+
+    /// Sends `response` about the connection of `id` to every player in `players` except `id`, as
+    /// well as spectators.
+    fn announce_presence(&mut self, players: &[PlayerId], id: PlayerId, response: UniqueResponse) {
+        for &other in players.iter().filter(|&&other| other != id) {
+            self.player_tx.send(other, vec![response.clone()]);
+        }
+        self.spectators.push([response], Instant::now());
     }
 }
 
-impl Default for RoomState {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::votes::voters;
+    use claim::*;
+    use game::{
+        game::{BankerTargetRound, TurnPhase},
+        player::CardType,
+    };
+
+    fn request(room: &mut Room, username: &str, request: FrontendRequest) -> DirectResponse {
+        let (reply, mut response) = oneshot::channel();
+        room.handle_command(RoomCommand::Request {
+            username: username.to_owned(),
+            request,
+            reply,
+        });
+        assert_ok!(response.try_recv())
+    }
+
+    #[test]
+    fn spectators_see_public_responses() {
+        let mut room = Room::new(
+            "spectator-test".to_owned(),
+            RoomConfig::default(),
+            None,
+            None,
+        );
+        room.spectators = SpectatorFeed::new(Duration::ZERO);
+        let mut rx = room.spectators.subscribe();
+
+        assert_ok!(room.join("user 1".to_owned(), None));
+        assert_err!(rx.try_recv());
+
+        room.spectators.release(Instant::now());
+        assert_matches!(
+            rx.try_recv(),
+            Ok(UniqueResponse::PlayersInLobby { usernames, .. }) if usernames == ["user 1"]
+        );
+    }
+
+    #[test]
+    fn spectators_are_announced() {
+        let mut room = Room::new(
+            "spectate-test".to_owned(),
+            RoomConfig::default(),
+            None,
+            None,
+        );
+        assert_ok!(room.join("user 1".to_owned(), None));
+        let mut rx = room.tx.subscribe();
+
+        let spectating = assert_ok!(room.spectate(" viewer ".to_owned(), None));
+        assert_eq!(spectating.username, "viewer");
+        assert_matches!(
+            spectating.view,
+            DirectResponse::SpectatorView { spectators, .. } if spectators == ["viewer"]
+        );
+        assert_matches!(
+            rx.try_recv(),
+            Ok(UniqueResponse::SpectatorJoined { username, .. }) if username == "viewer"
+        );
+        assert_err!(room.spectate("user 1".to_owned(), None));
+
+        room.leave("viewer");
+        assert!(room.game.spectators().is_empty());
+        assert_eq!(assert_ok!(room.game.lobby()).usernames(), vec!["user 1"]);
+    }
+
+    #[test]
+    fn host_leaving_announces_new_host() {
+        let mut room = Room::new("host-test".to_owned(), RoomConfig::default(), None, None);
+        assert_ok!(room.join("user 1".to_owned(), None));
+        assert_ok!(room.join("user 2".to_owned(), None));
+        let mut rx = room.player_tx.subscribe(PlayerId(1));
+
+        room.leave("user 1");
+
+        let responses = assert_ok!(rx.try_recv());
+        assert_matches!(
+            responses.as_slice(),
+            [
+                UniqueResponse::PlayersInLobby {
+                    host_id: Some(PlayerId(0)),
+                    ..
+                },
+                UniqueResponse::HostChanged {
+                    host_id: PlayerId(0)
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_username_is_announced() {
+        let mut room = Room::new("rename-test".to_owned(), RoomConfig::default(), None, None);
+        assert_ok!(room.join("user 1".to_owned(), None));
+        assert_ok!(room.join("user 2".to_owned(), None));
+        room.control().kicked.push("kicked".to_owned());
+        let mut rx = room.player_tx.subscribe(PlayerId(0));
+
+        let rename = |room: &mut Room, username: &str| {
+            let (reply, mut response) = oneshot::channel();
+            room.handle_command(RoomCommand::Request {
+                username: "user 2".to_owned(),
+                request: FrontendRequest::ChangeUsername {
+                    username: username.to_owned(),
+                },
+                reply,
+            });
+            assert_ok!(response.try_recv())
+        };
+
+        assert_matches!(rename(&mut room, "kicked"), DirectResponse::Error { .. });
+        assert_matches!(rename(&mut room, "User 1"), DirectResponse::Error { .. });
+        assert_matches!(
+            rename(&mut room, " alice "),
+            DirectResponse::YouChangedUsername { username } if username == "alice"
+        );
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayersInLobby { changed_player, usernames, .. }]
+                if changed_player == "alice" && usernames == &["user 1", "alice"]
+        );
+
+        room.leave("alice");
+        assert_eq!(assert_ok!(room.game.lobby()).usernames(), vec!["user 1"]);
+    }
+
+    #[test]
+    fn host_kicks_from_lobby() {
+        let mut room = Room::new("kick-test".to_owned(), RoomConfig::default(), None, None);
+        for i in 0..3 {
+            assert_ok!(room.join(format!("user {i}"), None));
+        }
+        let mut kicked_rx = room.player_tx.subscribe(PlayerId(1));
+        let mut moved_rx = room.player_tx.subscribe(PlayerId(2));
+
+        let kick = |target| FrontendRequest::KickPlayer { target };
+        assert_matches!(
+            request(&mut room, "user 1", kick(PlayerId(2))),
+            DirectResponse::Error { .. }
+        );
+        assert_matches!(
+            request(&mut room, "user 0", kick(PlayerId(0))),
+            DirectResponse::Error { .. }
+        );
+        assert_matches!(
+            request(&mut room, "user 0", kick(PlayerId(1))),
+            DirectResponse::YouKickedPlayer {
+                player_id: PlayerId(1)
+            }
+        );
+
+        assert_matches!(
+            assert_ok!(kicked_rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayerKicked { player_id: PlayerId(1), username }] if username == "user 1"
+        );
+        assert_matches!(
+            assert_ok!(kicked_rx.try_recv()).as_slice(),
+            [UniqueResponse::RoomClosed {
+                reason: RoomCloseReason::Kicked,
+                ..
+            }]
+        );
+        assert_matches!(
+            assert_ok!(moved_rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayerKicked { .. }]
+        );
+        // The player after the kicked one moved up a seat, along with their channel
+        assert_matches!(
+            assert_ok!(moved_rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayersInLobby { usernames, .. }] if usernames == &["user 0", "user 2"]
+        );
+        assert_eq!(room.control().kicked, ["user 1"]);
+        assert_err!(room.join("user 1".to_owned(), None));
+    }
+
+    #[test]
+    fn vote_pauses_game() {
+        let mut room = Room::new("vote-test".to_owned(), RoomConfig::default(), None, None);
+        for i in 0..3 {
+            assert_ok!(room.join(format!("user {i}"), None));
+        }
+        let mut rx = room.player_tx.subscribe(PlayerId(2));
+
+        let propose = |kind| FrontendRequest::ProposeVote { kind };
+        assert_matches!(
+            request(&mut room, "user 0", propose(VoteKind::Resume)),
+            DirectResponse::Error {
+                source: ResponseError::Vote(VoteError::NothingToChange),
+                ..
+            }
+        );
+        assert_matches!(
+            request(&mut room, "nobody", propose(VoteKind::Pause)),
+            DirectResponse::Error { .. }
+        );
+        assert_matches!(
+            request(&mut room, "user 0", propose(VoteKind::Pause)),
+            DirectResponse::YouProposedVote {
+                kind: VoteKind::Pause
+            }
+        );
+        assert!(!room.control().paused);
+        assert_matches!(
+            request(
+                &mut room,
+                "user 1",
+                FrontendRequest::CastVote { approve: true }
+            ),
+            DirectResponse::YouVoted { approve: true }
+        );
+        assert!(room.control().paused);
+        assert_matches!(
+            request(&mut room, "user 0", FrontendRequest::StartGame),
+            DirectResponse::Error {
+                source: ResponseError::GamePaused,
+                ..
+            }
+        );
+
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::VoteProposed {
+                votes_needed: 2,
+                ..
+            }]
+        );
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [
+                UniqueResponse::VoteCast { votes_for: 2, .. },
+                UniqueResponse::VoteEnded {
+                    kind: VoteKind::Pause,
+                    passed: true
+                }
+            ]
+        );
+    }
+
+    #[cfg(feature = "dev")]
+    #[tokio::test]
+    async fn dev_command_resyncs_player() {
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let mut room = Room::new(
+            "dev-test".to_owned(),
+            RoomConfig::default(),
+            Some(storage.clone()),
+            None,
+        );
+        for i in 0..4 {
+            assert_ok!(room.join(format!("user {i}"), None));
+        }
+        assert_ok!(handle_action(
+            &mut room.game,
+            "user 0",
+            PlayerAction::StartGame,
+            None
+        ));
+        let command = DevRequest::SetCash {
+            player_id: PlayerId(0),
+            cash: 30,
+        };
+        let dev = |command| FrontendRequest::Dev { command };
+        assert_matches!(
+            request(&mut room, "user 0", dev(command.clone())),
+            DirectResponse::Error {
+                source: ResponseError::Game(GameError::NotRoundState),
+                ..
+            }
+        );
+
+        while let Ok(selecting) = room.game.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(
+                room.game
+                    .apply(id, PlayerAction::SelectCharacter { character })
+            );
+        }
+        let mut rx = room.player_tx.subscribe(PlayerId(1));
+
+        assert_matches!(
+            request(&mut room, "user 0", dev(command.clone())),
+            DirectResponse::YouResynced { cash, .. } if cash.get() == 30
+        );
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::DevCommandUsed {
+                player_id: PlayerId(0),
+                ..
+            }]
+        );
+        let actions = storage.wait_for_actions("dev-test", 1).await;
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action, dev(command));
+    }
+
+    #[test]
+    fn out_of_time_ends_turn() {
+        let mut room = Room::new("clock-test".to_owned(), RoomConfig::default(), None, None);
+        let lobby = room.game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("user {i}")));
+        }
+        lobby.set_config(GameConfig {
+            time_bank: Some(1),
+            ..Default::default()
+        });
+        assert_ok!(handle_action(
+            &mut room.game,
+            "user 0",
+            PlayerAction::StartGame,
+            None
+        ));
+
+        while let Ok(selecting) = room.game.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(
+                room.game
+                    .apply(id, PlayerAction::SelectCharacter { character })
+            );
+        }
+
+        let id = room.game.round().unwrap().current_player().id();
+        let mut rx = room.player_tx.subscribe(id);
+        room.game.spend_time(id, Duration::from_secs(1));
+        room.tick_clock();
+
+        assert_ne!(acting_player(&room.game), Some(id));
+        let responses = assert_ok!(rx.try_recv());
+        assert_matches!(
+            responses.as_slice(),
+            [UniqueResponse::TurnEndedOutOfTime { player_id }] if *player_id == id
+        );
+    }
+
+    #[test]
+    fn out_of_time_ends_turn_before_drawing() {
+        let mut room = Room::new(
+            "strict-clock-test".to_owned(),
+            RoomConfig::default(),
+            None,
+            None,
+        );
+        let lobby = room.game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("user {i}")));
+        }
+        lobby.set_config(GameConfig {
+            time_bank: Some(1),
+            strict_phases: true,
+            ..Default::default()
+        });
+        assert_ok!(handle_action(
+            &mut room.game,
+            "user 0",
+            PlayerAction::StartGame,
+            None
+        ));
+
+        while let Ok(selecting) = room.game.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(
+                room.game
+                    .apply(id, PlayerAction::SelectCharacter { character })
+            );
+        }
+
+        let id = room.game.round().unwrap().current_player().id();
+        assert_ok!(room.game.apply(
+            id,
+            PlayerAction::DrawCard {
+                card_type: CardType::Asset
+            }
+        ));
+        assert_eq!(room.game.round().unwrap().turn_phase(), TurnPhase::Draw);
+        let mut rx = room.player_tx.subscribe(id);
+        room.game.spend_time(id, Duration::from_secs(1));
+        room.tick_clock();
+
+        assert_ne!(acting_player(&room.game), Some(id));
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::TurnEndedOutOfTime { player_id }] if *player_id == id
+        );
+    }
+
+    #[test]
+    fn leaving_while_selecting_picks_a_character() {
+        let mut room = Room::new("leave-test".to_owned(), RoomConfig::default(), None, None);
+        let lobby = room.game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("user {i}")));
+        }
+        assert_ok!(handle_action(
+            &mut room.game,
+            "user 0",
+            PlayerAction::StartGame,
+            None
+        ));
+
+        let selecting = room.game.selecting_characters().unwrap();
+        let id = selecting.currently_selecting_id();
+        let name = selecting.player(id).unwrap().name().to_owned();
+        let other = PlayerId((id.0 + 1) % 4);
+        let mut rx = room.player_tx.subscribe(other);
+
+        room.leave(&name);
+
+        let selecting = room.game.selecting_characters().unwrap();
+        assert_ne!(selecting.currently_selecting_id(), id);
+        assert_some!(selecting.player(id).unwrap().character());
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayerDisconnected { player_id, turns_auto_skipped: false }]
+                if *player_id == id
+        );
+        assert_ok!(rx.try_recv());
+    }
+
+    #[test]
+    fn leaving_while_paying_the_banker_is_announced() {
+        let mut room = Room::new(
+            "banker-leave-test".to_owned(),
+            RoomConfig::default(),
+            None,
+            None,
+        );
+        let lobby = room.game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("user {i}")));
+        }
+        assert_ok!(handle_action(
+            &mut room.game,
+            "user 0",
+            PlayerAction::StartGame,
+            None
+        ));
+        while let Ok(selecting) = room.game.selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let character = selecting.player_get_selectable_characters(id).unwrap()[0];
+            assert_ok!(
+                room.game
+                    .apply(id, PlayerAction::SelectCharacter { character })
+            );
+        }
+        let round = room.game.round_mut().unwrap();
+        room.game = GameState::BankerTarget(BankerTargetRound::from(round));
+        let mut rx = room.player_tx.subscribe(PlayerId(0));
+
+        room.leave("user 1");
+
+        let banker_target = assert_ok!(room.game.bankertarget());
+        assert!(!banker_target.player(PlayerId(1)).unwrap().is_human());
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayerDisconnected {
+                player_id: PlayerId(1),
+                ..
+            }]
+        );
+        assert!(!voters(&room.game).contains(&"user 1".to_owned()));
+    }
+
+    #[test]
+    fn rejoining_is_announced() {
+        let mut room = Room::new("rejoin-test".to_owned(), RoomConfig::default(), None, None);
+        let lobby = room.game.lobby_mut().unwrap();
+        for i in 0..4 {
+            assert_ok!(lobby.join(format!("user {i}")));
+        }
+        assert_ok!(handle_action(
+            &mut room.game,
+            "user 0",
+            PlayerAction::StartGame,
+            None
+        ));
+        let mut rx = room.player_tx.subscribe(PlayerId(0));
+
+        room.leave("user 1");
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayerDisconnected {
+                player_id: PlayerId(1),
+                ..
+            }]
+        );
+        // A character may have been picked for them in the meantime
+        while rx.try_recv().is_ok() {}
+        let joined = assert_ok!(room.join("user 1".to_owned(), None));
+        assert!(joined.rejoined);
+        assert_matches!(
+            assert_ok!(rx.try_recv()).as_slice(),
+            [UniqueResponse::PlayerReconnected {
+                player_id: PlayerId(1)
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn room_runs_commands() {
+        let room = spawn_room(
+            "rooms-test".to_owned(),
+            Rooms::default(),
+            RoomConfig::default(),
+            None,
+            None,
+        );
+
+        let mut joined = assert_ok!(assert_some!(room.join("user 1".to_owned(), None).await));
+        assert_eq!(joined.id, PlayerId(0));
+        assert!(!joined.rejoined);
+        assert_matches!(
+            joined.rx.recv().await,
+            Ok(UniqueResponse::PlayersInLobby { usernames, .. }) if usernames == ["user 1"]
+        );
+
+        assert_err!(assert_some!(room.join("user 1".to_owned(), None).await));
+
+        let direct = assert_some!(
+            room.request("user 1".to_owned(), FrontendRequest::StartGame)
+                .await
+        );
+        assert_matches!(direct, DirectResponse::Error { .. });
+        // The full state can be sent in the lobby as well
+        assert_matches!(
+            room.full_state("user 1".to_owned()).await,
+            Some(DirectResponse::FullState { .. })
+        );
+        assert_none!(room.full_state("nobody".to_owned()).await);
+        assert!(!room.is_closed());
     }
 }
@@ -1,7 +1,12 @@
-use game::{errors::GameError, game::GameState};
 use responses::*;
 
-use crate::{request_handler::Response, rooms::RoomState};
+use crate::{
+    config::{ConfigError, env_seconds},
+    queue::{ClientQueue, Encoding, QueueConfig},
+    rooms::{Joined, RoomConfig, RoomHandle, Rooms, Spectating, spawn_room},
+    storage::{Storage, storage_from_env},
+    telemetry::{TelemetrySink, telemetry_from_env},
+};
 
 use axum::{
     Router,
@@ -14,21 +19,88 @@ use axum::{
 };
 use futures_util::{
     sink::SinkExt,
-    stream::{SplitSink, StreamExt},
+    stream::{SplitSink, SplitStream, StreamExt},
 };
 use serde::Serialize;
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
-    time::Duration,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{Mutex as TokioMutex, broadcast, mpsc}, // async mutex for shared sink
+    task::JoinHandle,
 };
-use tokio::sync::{Mutex as TokioMutex, broadcast}; // async mutex for shared sink
 
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// The optional protocol features this server can use, see [`Capabilities`]. Clients are only
+/// acknowledged the features they asked for that are in here.
+pub const SUPPORTED_CAPABILITIES: Capabilities =
+    Capabilities::BINARY_FRAMES.union(Capabilities::BATCHES);
+
+/// A response that is sent to a single client, which is either the reply to one of their requests
+/// or something that happened in the game. Both are sent the same way they are on their own.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Outgoing {
+    Direct(Box<DirectResponse>),
+    Unique(UniqueResponse),
+}
+
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// How often clients are pinged, and how long they can stay silent before their connection is
+/// closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl HeartbeatConfig {
+    /// Reads the seconds between pings from the `HEARTBEAT_INTERVAL` environment variable and the
+    /// seconds a client can stay silent from `HEARTBEAT_TIMEOUT`, or uses defaults if they are not
+    /// set.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            interval: env_seconds("HEARTBEAT_INTERVAL")?.unwrap_or(DEFAULT_HEARTBEAT_INTERVAL),
+            timeout: env_seconds("HEARTBEAT_TIMEOUT")?.unwrap_or(DEFAULT_HEARTBEAT_TIMEOUT),
+        })
+    }
+}
+
 pub struct AppState {
     /// Keys are the name of the channel
-    rooms: Arc<Mutex<HashMap<String, Arc<RoomState>>>>,
+    rooms: Rooms,
+    /// Where rooms write their audit logs, if anywhere
+    storage: Option<Arc<dyn Storage>>,
+    /// Where rooms send anonymized gameplay events, if anywhere
+    telemetry: Option<Arc<dyn TelemetrySink>>,
+    /// How connections check that their client is still there
+    heartbeat: HeartbeatConfig,
+    /// How connections queue the messages for their client
+    queue: QueueConfig,
+    /// How rooms are run
+    room: RoomConfig,
+}
+
+impl AppState {
+    /// Creates the state of a server without any rooms, configured by the environment.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            rooms: Arc::new(Mutex::new(HashMap::new())),
+            storage: storage_from_env(),
+            telemetry: telemetry_from_env(),
+            heartbeat: HeartbeatConfig::from_env()?,
+            queue: QueueConfig::from_env()?,
+            room: RoomConfig::from_env()?,
+        })
+    }
 }
 
 async fn websocket_handler(
@@ -38,7 +110,9 @@ async fn websocket_handler(
     ws.on_upgrade(|socket| websocket(socket, state))
 }
 
-pub async fn setupsocket() {
+/// Runs the server until it crashes. Returns an error right away if the server is not configured
+/// correctly.
+pub async fn setupsocket() -> Result<(), ConfigError> {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
@@ -47,9 +121,7 @@ pub async fn setupsocket() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let app_state = Arc::new(AppState {
-        rooms: Arc::new(Mutex::new(HashMap::new())),
-    });
+    let app_state = Arc::new(AppState::from_env()?);
 
     let app = Router::new()
         .route("/websocket", get(websocket_handler))
@@ -63,18 +135,66 @@ pub async fn setupsocket() {
     // PANIC: Although this returns a result type, as specified by the axum documentation this will
     // never actually complete or return an error
     axum::serve(listener, app).await.unwrap();
+    Ok(())
 }
 
 async fn send_external(
     msg: impl Serialize,
+    encoding: Encoding,
     sender: Arc<TokioMutex<SplitSink<WebSocket, Message>>>,
 ) -> Result<(), axum::Error> {
-    // PANIC: the documentation of `serde_json::to_string` specifies that it can return an error if
-    // the implementation of `Serialize` fails for the given type, or if the type contains a map
-    // with non-string keys. Since neither of those things are true, this as safe to unwrap.
-    let msg = serde_json::to_string(&msg).unwrap();
+    let msg = encoding.encode(msg);
     let mut s = sender.lock().await;
-    s.send(Message::Text(msg.into())).await
+    s.send(msg).await
+}
+
+/// Creates the message that closes a connection because of `reason`.
+fn close_message(reason: RoomCloseReason) -> Message {
+    Message::Close(Some(CloseFrame {
+        code: reason as u16,
+        reason: format!("{reason:?}").into(),
+    }))
+}
+
+/// Queues the responses in `frame` for a client, as a single frame if the client negotiated
+/// [`Capabilities::BATCHES`] and one frame per response otherwise. Frames with the reply to a
+/// request are never dropped, see [`ClientQueue::push_reply`]. Returns `false` if the connection
+/// should be closed.
+async fn send_frame(
+    queue: &ClientQueue,
+    frame: Vec<Outgoing>,
+    batching: bool,
+    has_reply: bool,
+) -> bool {
+    if frame.is_empty() {
+        return true;
+    }
+    if batching {
+        return match has_reply {
+            true => queue.push_reply(Batched::from(frame)).await,
+            false => queue.push(Batched::from(frame)),
+        };
+    }
+
+    for response in frame {
+        let queued = match response {
+            Outgoing::Direct(_) => queue.push_reply(response).await,
+            Outgoing::Unique(_) => queue.push(response),
+        };
+        if !queued {
+            return false;
+        }
+    }
+    true
+}
+
+fn parse_request(message: &Message) -> Result<FrontendRequest, ResponseError> {
+    let request = match message {
+        Message::Text(text) => serde_json::from_str(text).ok(),
+        Message::Binary(bytes) => from_msgpack(bytes).ok(),
+        _ => None,
+    };
+    request.ok_or(ResponseError::UnreadableRequest)
 }
 
 async fn websocket(stream: WebSocket, state: Arc<AppState>) {
@@ -83,246 +203,315 @@ async fn websocket(stream: WebSocket, state: Arc<AppState>) {
     // wrap sink in an async mutex so multiple tasks can send safely
     let sender = Arc::new(TokioMutex::new(sender));
 
-    let mut channel_idx = 8; // invalid id to start
-    let mut username = String::new();
-    let mut channel = String::new();
+    let mut encoding = Encoding::Json;
 
     // receive initial username message
-    while let Some(Ok(message)) = receiver.next().await {
+    let (room, joined, channel, capabilities) = loop {
+        let Some(Ok(message)) = receiver.next().await else {
+            return;
+        };
+
         match message {
             Message::Text(text) => {
-                let (connect_username, connect_channel) = match serde_json::from_str(&text) {
-                    Ok(Connect::Connect { username, channel }) => (username, channel),
-                    Err(error) => {
-                        tracing::error!(%error);
-                        let _ = send_external(
-                            DirectResponse::from(ResponseError::InvalidData),
-                            sender.clone(),
-                        )
-                        .await;
-                        continue;
-                    }
-                };
+                let (connect_username, connect_channel, connect_locale, requested, spectate) =
+                    match serde_json::from_str(&text) {
+                        Ok(Connect::Connect {
+                            username,
+                            channel,
+                            binary_frames,
+                            locale,
+                            capabilities,
+                        }) => {
+                            if binary_frames {
+                                encoding = Encoding::MessagePack;
+                            }
+                            (username, channel, locale, capabilities, false)
+                        }
+                        Ok(Connect::Spectate {
+                            username,
+                            channel,
+                            locale,
+                            capabilities,
+                        }) => (username, channel, locale, capabilities, true),
+                        Err(error) => {
+                            tracing::error!(%error);
+                            let _ = send_external(
+                                DirectResponse::from(ResponseError::InvalidData),
+                                encoding,
+                                sender.clone(),
+                            )
+                            .await;
+                            continue;
+                        }
+                    };
+                let capabilities = requested.map(|c| c & SUPPORTED_CAPABILITIES);
+                if capabilities.is_some_and(|c| c.contains(Capabilities::BINARY_FRAMES)) {
+                    encoding = Encoding::MessagePack;
+                }
 
-                let error_response = {
+                let room = {
                     // PANIC: a mutex can only poison if any other thread that has access to it
                     // crashes. Since this cannot happen, unwrapping is safe.
                     let mut rooms = state.rooms.lock().unwrap();
-                    channel = connect_channel.clone();
-                    let room = rooms
-                        .entry(connect_channel)
-                        .or_insert_with(|| create_room(channel.clone(), state.rooms.clone()));
+                    let room = rooms.entry(connect_channel.clone()).or_insert_with(|| {
+                        spawn_room(
+                            connect_channel.clone(),
+                            state.rooms.clone(),
+                            state.room,
+                            state.storage.clone(),
+                            state.telemetry.clone(),
+                        )
+                    });
+
+                    // A room that crashed is replaced by a fresh one
+                    if room.is_closed() {
+                        *room = spawn_room(
+                            connect_channel.clone(),
+                            state.rooms.clone(),
+                            state.room,
+                            state.storage.clone(),
+                            state.telemetry.clone(),
+                        );
+                    }
 
-                    // PANIC: a mutex can only poison if any other thread that has access to it
-                    // crashes. Since this cannot happen, unwrapping is safe.
-                    match &mut *room.game.lock().unwrap() {
-                        GameState::Lobby(lobby) => match lobby.join(connect_username.clone()) {
-                            Ok(player) => {
-                                debug_assert_eq!(player.name(), connect_username);
-                                username = player.name().to_owned();
-                                channel_idx = player.id().into();
-                                break;
-                            }
-                            Err(e) => DirectResponse::from(GameError::from(e)),
-                        },
-                        // If the game is already running check and see if the player that is trying to connect had previously
-                        // disconnected, if they are allow them to rejoin, and notify the other players that someone
-                        // rejoined.
-                        GameState::Round(round) => match round.player_by_name(&connect_username) {
-                            Ok(player) => {
-                                debug_assert_eq!(player.name(), connect_username);
-                                match round.rejoin(player.id()) {
-                                    Ok(p) => {
-                                        username = p.name().to_owned();
-                                        channel_idx = p.id().into();
-                                        tracing::debug!("Player rejoined: {:?}", p.id());
-                                        break;
-                                    }
-                                    Err(e) => DirectResponse::from(e),
-                                }
-                            }
-                            Err(e) => DirectResponse::from(e),
-                        },
-                        GameState::SelectingCharacters(round) => {
-                            match round.player_by_name(&connect_username) {
-                                Ok(player) => {
-                                    debug_assert_eq!(player.name(), connect_username);
-                                    match round.rejoin(player.id()) {
-                                        Ok(p) => {
-                                            username = p.name().to_owned();
-                                            channel_idx = p.id().into();
-                                            tracing::debug!("Player rejoined: {:?}", p.id());
-                                            break;
-                                        }
-                                        Err(e) => DirectResponse::from(e),
-                                    }
-                                }
-                                Err(e) => DirectResponse::from(e),
-                            }
+                    room.clone()
+                };
+
+                if spectate {
+                    match room.spectate(connect_username, connect_locale).await {
+                        Some(Ok(spectating)) => {
+                            watch(
+                                room,
+                                spectating,
+                                capabilities,
+                                encoding,
+                                &state,
+                                sender,
+                                receiver,
+                            )
+                            .await
                         }
-                        _ => DirectResponse::from(ResponseError::GameAlreadyStarted),
+                        Some(Err(error)) => {
+                            let error_response = DirectResponse::from(error);
+                            let _ = send_external(error_response, encoding, sender.clone()).await;
+                        }
+                        None => close_after_crash(&sender).await,
                     }
-                };
+                    return;
+                }
 
-                let _ = send_external(error_response, sender.clone()).await;
+                match room.join(connect_username, connect_locale).await {
+                    Some(Ok(joined)) => {
+                        break (room, joined, connect_channel, capabilities);
+                    }
+                    Some(Err(error)) => {
+                        let error_response = DirectResponse::from(error);
+                        let _ = send_external(error_response, encoding, sender.clone()).await;
+                    }
+                    None => close_after_crash(&sender).await,
+                }
                 return;
             }
             Message::Close(_) => return,
             _ => continue,
         }
-    }
-
-    let room = {
-        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
-        // this cannot happen, unwrapping is safe.
-        let maybe_room = {
-            let rooms = state.rooms.lock().unwrap();
-            rooms.get(&channel).cloned()
-        };
-        if let Some(room) = maybe_room {
-            room
-        } else {
-            let reason = RoomCloseReason::FatalError;
-            let frame = CloseFrame {
-                code: reason as u16,
-                reason: format!("{reason:?}").into(),
-            };
-            if sender
-                .lock()
-                .await
-                .send(Message::Close(Some(frame)))
-                .await
-                .is_err()
-            {
-                tracing::error!("Couldn't send close frame when fatal crash was encountered")
-            };
-            return;
-        }
     };
 
-    let tx = room.tx.clone();
-    // subscribe to broadcast channel
-    let mut rx = tx.subscribe();
-
-    let mut player_rx = room.player_tx[channel_idx].subscribe();
+    let Joined {
+        username,
+        id: _,
+        rejoined,
+        rx,
+        mut player_rx,
+        translations,
+    } = joined;
 
     let confirm = DirectResponse::YouJoinedGame {
         username: username.clone(),
         channel: channel.clone(),
     };
     tracing::debug!("Targeted Response: {:?}", confirm);
-    let _ = send_external(confirm, sender.clone()).await;
-    let mut rejoin_message: Option<DirectResponse> = None;
-    // announce join to everyone
-    // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since this
-    // cannot happen, unwrapping is safe.
-    match &*room.game.lock().unwrap() {
-        GameState::Lobby(lobby) => {
-            let internal = UniqueResponse::PlayersInLobby {
-                changed_player: username.clone(),
-                usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
-            };
-            tracing::debug!("Global Response: {:?}", internal);
-            let _ = room.tx.send(internal);
-        }
-        GameState::Round(_) | GameState::SelectingCharacters(_) => {
-            rejoin_message = Some(DirectResponse::YouRejoined)
-        }
-        // TODO: handle joins after game starts
-        _ => return,
+    let _ = send_external(confirm, encoding, sender.clone()).await;
+
+    if let Some(capabilities) = capabilities {
+        let message = DirectResponse::YouNegotiatedCapabilities { capabilities };
+        tracing::debug!("Negotiated capabilities: {:?}", message);
+        let _ = send_external(message, encoding, sender.clone()).await;
     }
 
-    if let Some(message) = &rejoin_message {
+    if rejoined {
+        let message = DirectResponse::YouRejoined;
         tracing::debug!("Sending rejoin message: {:?}", message);
-        let _ = send_external(message, sender.clone()).await;
+        let _ = send_external(message, encoding, sender.clone()).await;
     }
 
-    // task: forward broadcast messages to this client
-    let mut send_task = {
+    // the room knows the player by their username, which they can change while in the lobby
+    let username = Arc::new(Mutex::new(username));
+
+    // every message for this client goes through a bounded queue, so a slow client cannot stall
+    // the rest of the room
+    let (queue, mut queue_rx) = ClientQueue::new(state.queue.size, state.queue.policy, encoding);
+    let queue = queue.with_translations(translations);
+
+    // task: write queued messages to this client
+    let mut write_task = {
         let sender = sender.clone();
+        let queue = queue.clone();
+        let room = room.clone();
+        let name = username.clone();
 
         tokio::spawn(async move {
-            loop {
-                match rx.recv().await {
-                    Ok(UniqueResponse::RoomClosed { reason, .. }) => {
-                        let frame = CloseFrame {
-                            code: reason as u16,
-                            reason: format!("{reason:?}").into(),
-                        };
+            while let Some(msg) = queue_rx.recv().await {
+                let is_close = matches!(msg, Message::Close(_));
+                if sender.lock().await.send(msg).await.is_err() || is_close {
+                    break;
+                }
 
-                        let mut s = sender.lock().await;
-                        if s.send(Message::Close(Some(frame))).await.is_err() {
-                            break;
-                        }
-                    }
-                    Ok(msg) => {
-                        tracing::debug!("unique send: {msg:?}");
-                        if send_external(msg, sender.clone()).await.is_err() {
-                            break;
-                        }
+                // Once the client caught up, replace everything it missed with the full state
+                if queue_rx.is_empty()
+                    && queue.take_needs_resync()
+                    && let Some(direct) = room.full_state(current_name(&name)).await
+                {
+                    tracing::debug!("resyncing slow client: {}", current_name(&name));
+                    if sender
+                        .lock()
+                        .await
+                        .send(queue.encode(direct))
+                        .await
+                        .is_err()
+                    {
+                        break;
                     }
-                    // If we lagged behind, just continue
-                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
-                    // channel closed
-                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
         })
     };
 
-    // task: forward player messages to this client
+    // task: forward broadcast messages to this client
+    let mut send_task = spawn_forward_task(rx, queue.clone());
+
+    // replies to this client's requests are handed to the task forwarding player messages, so
+    // they can be sent in the same frame as the responses the request caused
+    let (reply_tx, mut reply_rx) = mpsc::channel::<DirectResponse>(1);
+    let awaiting_reply = Arc::new(AtomicBool::new(false));
+    let batching = capabilities.is_some_and(|c| c.contains(Capabilities::BATCHES));
+
+    // task: forward replies and player messages to this client
     let mut player_send_task = {
-        let sender = sender.clone();
+        let queue = queue.clone();
+        let awaiting_reply = awaiting_reply.clone();
 
         tokio::spawn(async move {
             loop {
-                match player_rx.recv().await {
-                    Ok(msg) => {
-                        tracing::debug!("unique send: {msg:?}");
-                        if send_external(msg, sender.clone()).await.is_err() {
-                            break;
+                let (mut reply, mut batch) = tokio::select! {
+                    reply = reply_rx.recv() => match reply {
+                        Some(reply) => (Some(reply), Vec::new()),
+                        None => break,
+                    },
+                    batch = player_rx.recv() => match batch {
+                        Ok(batch) => (None, batch),
+                        // Messages were dropped because we lagged behind
+                        Err(broadcast::error::RecvError::Lagged(_)) => {
+                            if !queue.overflowed() {
+                                break;
+                            }
+                            continue;
                         }
+                        // channel closed
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+
+                // The room sends the responses a request caused before replying to it, so while a
+                // request is being handled its reply is waited for
+                if reply.is_none() {
+                    reply = match awaiting_reply.load(Ordering::Acquire) {
+                        true => reply_rx.recv().await,
+                        false => reply_rx.try_recv().ok(),
+                    };
+                }
+
+                // Coalesce every batch that was queued in the meantime into the same frame
+                let mut lagged = false;
+                loop {
+                    match player_rx.try_recv() {
+                        Ok(next) => batch.extend(next),
+                        Err(broadcast::error::TryRecvError::Lagged(_)) => lagged = true,
+                        Err(_) => break,
                     }
-                    // If we lagged behind, just continue
-                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
-                    // channel closed
-                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+                if lagged && !queue.overflowed() {
+                    break;
+                }
+
+                // The room closes the connection of a single player, for example when they
+                // were kicked, by sending them the reason in their own channel
+                let closed = batch.iter().find_map(|response| match response {
+                    UniqueResponse::RoomClosed { reason, .. } => Some(*reason),
+                    _ => None,
+                });
+                if let Some(reason) = closed {
+                    let _ = queue.push_message(close_message(reason));
+                    break;
+                }
+
+                tracing::debug!("unique send: {reply:?} {batch:?}");
+                let has_reply = reply.is_some();
+                let frame = reply
+                    .map(|reply| Outgoing::Direct(Box::new(reply)))
+                    .into_iter()
+                    .chain(batch.into_iter().map(Outgoing::Unique))
+                    .collect::<Vec<_>>();
+                if !send_frame(&queue, frame, batching, has_reply).await {
+                    break;
                 }
             }
         })
     };
 
+    // timestamp of the last message this client sent, including pongs
+    let last_seen = Arc::new(Mutex::new(Instant::now()));
+
     // task: read client messages, broadcast them, and send a custom reply to the sender only
     let mut recv_task = {
-        let sender = sender.clone();
         let name = username.clone();
         let room = room.clone();
+        let last_seen = last_seen.clone();
 
         tokio::spawn(async move {
             while let Some(Ok(message)) = receiver.next().await {
+                // PANIC: a mutex can only poison if any other thread that has access to it
+                // crashes. Since this cannot happen, unwrapping is safe.
+                *last_seen.lock().unwrap() = Instant::now();
+
                 match message {
-                    Message::Text(text) => {
-                        if let Ok(json) = serde_json::from_str::<FrontendRequest>(&text) {
-                            tracing::debug!("incoming request: {json:?}");
-
-                            let direct = match room.handle_request(json, &name) {
-                                Ok(Response(internal, direct)) => {
-                                    for (id, responses) in internal.into_inner() {
-                                        let idx = usize::from(id);
-                                        for r in responses {
-                                            let _ = room.player_tx[idx].send(r.clone());
-                                        }
-                                    }
-
-                                    direct
-                                }
-                                Err(e) => e.into(),
-                            };
-                            tracing::debug!("direct response: {direct:?}");
-
-                            if send_external(direct, sender.clone()).await.is_err() {
-                                break;
+                    Message::Text(_) | Message::Binary(_) => {
+                        awaiting_reply.store(true, Ordering::Release);
+                        let direct = match parse_request(&message) {
+                            Ok(request) => {
+                                tracing::debug!("incoming request: {request:?}");
+
+                                // The room stopped running, so there is nothing left to do
+                                let Some(direct) = room.request(current_name(&name), request).await
+                                else {
+                                    break;
+                                };
+                                direct
                             }
+                            Err(error) => error.into(),
+                        };
+                        tracing::debug!("direct response: {direct:?}");
+
+                        if let DirectResponse::YouChangedUsername { username } = &direct {
+                            // PANIC: a mutex can only poison if any other thread that has access
+                            // to it crashes. Since this cannot happen, unwrapping is safe.
+                            *name.lock().unwrap() = username.clone();
+                        }
+
+                        let sent = reply_tx.send(direct).await.is_ok();
+                        awaiting_reply.store(false, Ordering::Release);
+                        if !sent {
+                            break;
                         }
                     }
                     Message::Close(_) => break,
@@ -332,171 +521,225 @@ async fn websocket(stream: WebSocket, state: Arc<AppState>) {
         })
     };
 
+    // task: close the connection if the client stopped responding
+    let mut heartbeat_task = spawn_heartbeat_task(last_seen, queue, state.heartbeat);
+
     // if any task finishes, abort the others
     tokio::select! {
         _ = &mut send_task => {
             recv_task.abort();
             player_send_task.abort();
+            write_task.abort();
+            heartbeat_task.abort();
         },
         _ = &mut recv_task => {
             send_task.abort();
             player_send_task.abort();
+            write_task.abort();
+            heartbeat_task.abort();
         }
         _ = &mut player_send_task => {
             recv_task.abort();
             send_task.abort();
+            write_task.abort();
+            heartbeat_task.abort();
+        },
+        _ = &mut write_task => {
+            recv_task.abort();
+            send_task.abort();
+            player_send_task.abort();
+            heartbeat_task.abort();
+        },
+        _ = &mut heartbeat_task => {
+            tracing::debug!("Connection of {} timed out", current_name(&username));
+            recv_task.abort();
+            send_task.abort();
+            player_send_task.abort();
+            write_task.abort();
         },
     };
 
     // announce leave
-    // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since this
-    // cannot happen, unwrapping is safe.
-    match &mut *room.game.lock().unwrap() {
-        GameState::Lobby(lobby) => {
-            // remove username on disconnect
-            lobby.leave(&username);
-
-            // send updated list to everyone
-            for i in 0..lobby.len() {
-                let _ = room.player_tx[i].send(UniqueResponse::PlayersInLobby {
-                    changed_player: username.clone(),
-                    usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
-                });
-            }
-        }
-        // If we are outside of the lobby state then the game will already have started
-        // We need to modify the player object and let the other players in that room know
-        // that the player has disconnected. This also marks them as available for reconnecting.
-        GameState::Round(game) => {
-            let p = game.player_by_name(&username);
-            match p {
-                Ok(player) => {
-                    let id = player.id();
-                    let _ = game.leave(id); // This can fail but we just continue silently if it does
-                    tracing::debug!("Player left: {:?}", id);
-                }
-                Err(_) => {
-                    tracing::debug!(
-                        "A disconnect happened but no connected player could be found."
-                    );
-                }
-            }
-        }
-        GameState::SelectingCharacters(game) => {
-            let p = game.player_by_name(&username);
-            match p {
-                Ok(player) => {
-                    let id = player.id();
-                    let _ = game.leave(id);
-                    tracing::debug!("Player left: {:?}", id);
-                }
-                Err(_) => {
-                    tracing::debug!(
-                        "A disconnect happened but no connected player could be found."
-                    );
-                }
-            }
-        }
-        _ => (),
-    }
-    // if let Ok(lobby) = room.game.lock().unwrap().lobby_mut() {
-    //     // remove username on disconnect
-    //     lobby.leave(&username);
-
-    //     // send updated list to everyone
-    //     for i in 0..lobby.len() {
-    //         let _ = room.player_tx[i].send(UniqueResponse::PlayersInLobby {
-    //             changed_player: username.clone(),
-    //             usernames: lobby.usernames().iter().map(ToString::to_string).collect(),
-    //         });
-    //     }
-    // }
+    room.leave(current_name(&username)).await;
 }
 
-pub fn create_room(
-    channel: String,
-    rooms: Arc<Mutex<HashMap<String, Arc<RoomState>>>>,
-) -> Arc<RoomState> {
-    let room = Arc::new(RoomState::new());
+/// Sends what the spectator that started `spectating` `room` may see of it, followed by its
+/// delayed feed until the connection closes or stops responding to the heartbeat configured in
+/// `state`, acknowledging the `capabilities` they negotiated. Spectators cannot make requests, so
+/// anything they send is answered with an error.
+async fn watch(
+    room: RoomHandle,
+    spectating: Spectating,
+    capabilities: Option<Capabilities>,
+    encoding: Encoding,
+    state: &AppState,
+    sender: Arc<TokioMutex<SplitSink<WebSocket, Message>>>,
+    mut receiver: SplitStream<WebSocket>,
+) {
+    let Spectating {
+        username,
+        view,
+        rx,
+        translations,
+    } = spectating;
+
+    let (queue, mut queue_rx) = ClientQueue::new(state.queue.size, state.queue.policy, encoding);
+    let queue = queue.with_translations(translations);
+
+    tracing::debug!("Spectator view: {:?}", view);
+    let _ = queue.push_reply(view).await;
+    if let Some(capabilities) = capabilities {
+        let message = DirectResponse::YouNegotiatedCapabilities { capabilities };
+        tracing::debug!("Negotiated capabilities: {:?}", message);
+        let _ = queue.push_reply(message).await;
+    }
 
-    let cleanup_handle = spawn_cleanup_task(channel.clone(), rooms.clone());
+    // task: write queued messages to this client. A spectator that fell behind is not resynced,
+    // since the feed is held back and the game as it is right now is not.
+    let mut write_task = tokio::spawn(async move {
+        while let Some(msg) = queue_rx.recv().await {
+            let is_close = matches!(msg, Message::Close(_));
+            if sender.lock().await.send(msg).await.is_err() || is_close {
+                break;
+            }
+        }
+    });
 
-    *room.cleanup_handle.lock().unwrap() = Some(cleanup_handle);
+    // task: forward the delayed feed to this client
+    let mut send_task = spawn_forward_task(rx, queue.clone());
 
-    tracing::debug!("Created room with channel '{channel}'");
+    // timestamp of the last message this client sent, including pongs
+    let last_seen = Arc::new(Mutex::new(Instant::now()));
 
-    room
-}
+    // task: read client messages, to notice when the client is gone
+    let mut recv_task = {
+        let queue = queue.clone();
+        let last_seen = last_seen.clone();
+        let username = username.clone();
 
-fn spawn_cleanup_task(
-    channel: String,
-    rooms: Arc<Mutex<HashMap<String, Arc<RoomState>>>>,
-) -> tokio::task::JoinHandle<()> {
-    tokio::spawn(async move {
-        const DEFAULT_INACTIVITY_TIMEOUT: Duration = Duration::from_secs(300); // 5 min
-        const DEFAULT_CLEANUP_INTERVAL: Duration = Duration::from_secs(30);
-
-        let inactivity_timeout = if let Ok(timeout) = std::env::var("INACTIVITY_TIMEOUT") {
-            Duration::from_secs(
-                timeout
-                    .parse()
-                    .expect("ENV INACTIVITY_TIMEOUT should be a positive integer"),
-            )
-        } else {
-            DEFAULT_INACTIVITY_TIMEOUT
-        };
-        let cleanup_interval = if let Ok(interval) = std::env::var("CLEANUP_INTERVAL") {
-            Duration::from_secs(
-                interval
-                    .parse()
-                    .expect("ENV CLEANUP_INTERVAL should be a positive integer"),
-            )
-        } else {
-            DEFAULT_CLEANUP_INTERVAL
-        };
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = receiver.next().await {
+                // PANIC: a mutex can only poison if any other thread that has access to it
+                // crashes. Since this cannot happen, unwrapping is safe.
+                *last_seen.lock().unwrap() = Instant::now();
 
-        loop {
-            tokio::time::sleep(cleanup_interval).await;
+                match message {
+                    Message::Text(_) | Message::Binary(_) => {
+                        let error = game::errors::GameError::InvalidPlayerName(username.clone());
+                        if !queue.push_reply(DirectResponse::from(error)).await {
+                            break;
+                        }
+                    }
+                    Message::Close(_) => break,
+                    _ => continue,
+                }
+            }
+        })
+    };
 
-            let elapsed = match rooms.lock().unwrap().get(&channel) {
-                Some(room) => room.last_activity.lock().unwrap().elapsed(),
-                None => break,
-            };
+    // task: close the connection if the client stopped responding
+    let mut heartbeat_task = spawn_heartbeat_task(last_seen, queue, state.heartbeat);
 
-            if elapsed > inactivity_timeout {
-                tracing::info!(
-                    "Room with channel name '{}' inactive for {:?}, closing",
-                    channel,
-                    elapsed
-                );
+    // if any task finishes, abort the others
+    tokio::select! {
+        _ = &mut send_task => {
+            recv_task.abort();
+            write_task.abort();
+            heartbeat_task.abort();
+        },
+        _ = &mut recv_task => {
+            send_task.abort();
+            write_task.abort();
+            heartbeat_task.abort();
+        },
+        _ = &mut write_task => {
+            recv_task.abort();
+            send_task.abort();
+            heartbeat_task.abort();
+        },
+        _ = &mut heartbeat_task => {
+            tracing::debug!("Connection of spectator {username} timed out");
+            recv_task.abort();
+            send_task.abort();
+            write_task.abort();
+        },
+    };
 
-                let msg = UniqueResponse::RoomClosed {
-                    channel: channel.clone(),
-                    reason: RoomCloseReason::Inactive,
-                };
+    // stop watching
+    room.leave(username).await;
+}
 
-                match rooms.lock().unwrap().get(&channel) {
-                    Some(room) => {
-                        if let Err(e) = room.tx.send(msg) {
-                            tracing::error!(%e);
-                        }
-                    }
-                    None => break,
-                };
+/// Closes the connection in `sender` after the room it wanted to join crashed.
+async fn close_after_crash(sender: &TokioMutex<SplitSink<WebSocket, Message>>) {
+    if sender
+        .lock()
+        .await
+        .send(close_message(RoomCloseReason::FatalError))
+        .await
+        .is_err()
+    {
+        tracing::error!("Couldn't send close frame when fatal crash was encountered")
+    };
+}
 
-                // Give the messages a little bit of time to be sent out and received
-                tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+/// Forwards everything `rx` receives to `queue`, and closes the connection once the room closes.
+fn spawn_forward_task(
+    mut rx: broadcast::Receiver<UniqueResponse>,
+    queue: ClientQueue,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(UniqueResponse::RoomClosed { reason, .. }) => {
+                    if !queue.push_message(close_message(reason)) {
+                        break;
+                    }
+                }
+                Ok(msg) => {
+                    tracing::debug!("unique send: {msg:?}");
+                    if !queue.push(msg) {
+                        break;
+                    }
+                }
+                // Messages were dropped because we lagged behind
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    if !queue.overflowed() {
+                        break;
+                    }
+                }
+                // channel closed
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
 
-                // if let Err(e) = room.tx.send(UniqueResponse::ShutdownConnection) {
-                //     tracing::error!(%e);
-                // }
+/// Gets the username the connection currently goes by, see
+/// [`DirectResponse::YouChangedUsername`].
+fn current_name(username: &Mutex<String>) -> String {
+    // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since this
+    // cannot happen, unwrapping is safe.
+    username.lock().unwrap().clone()
+}
 
-                // tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+/// Pings the client every `heartbeat` interval and finishes once the client has not sent
+/// anything, not even a pong, for longer than its timeout.
+fn spawn_heartbeat_task(
+    last_seen: Arc<Mutex<Instant>>,
+    queue: ClientQueue,
+    heartbeat: HeartbeatConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(heartbeat.interval).await;
 
-                // Remove from HashMap to drop RoomState and close connected channels which cleans
-                // up both the room as well as its connected user threads.
-                rooms.lock().unwrap().remove(&channel);
+            // PANIC: a mutex can only poison if any other thread that has access to it crashes.
+            // Since this cannot happen, unwrapping is safe.
+            if last_seen.lock().unwrap().elapsed() > heartbeat.timeout {
+                break;
+            }
 
+            if !queue.push_message(Message::Ping(Default::default())) {
                 break;
             }
         }
@@ -505,30 +748,60 @@ fn spawn_cleanup_task(
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{collections::VecDeque, sync::OnceLock, time::Duration};
 
     use super::*;
     use claim::*;
-    use futures_util::stream::SplitStream;
+    use game::errors::GameError;
     use serde::Deserialize;
-    use tokio::sync::OnceCell;
     use tokio_tungstenite::{WebSocketStream, connect_async};
     use tungstenite::{Message, protocol::CloseFrame};
 
-    pub async fn receive<T, S>(reader: &mut SplitStream<WebSocketStream<S>>) -> T
+    /// Reads responses from a websocket one at a time, unpacking frames that contain a batch of
+    /// responses.
+    pub struct Reader<S> {
+        stream: SplitStream<WebSocketStream<S>>,
+        pending: VecDeque<serde_json::Value>,
+    }
+
+    impl<S> Reader<S>
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        pub fn new(stream: SplitStream<WebSocketStream<S>>) -> Self {
+            Self {
+                stream,
+                pending: VecDeque::new(),
+            }
+        }
+
+        pub async fn next(&mut self) -> Option<Result<Message, tungstenite::Error>> {
+            self.stream.next().await
+        }
+    }
+
+    pub async fn receive<T, S>(reader: &mut Reader<S>) -> T
     where
         for<'a> T: Deserialize<'a>,
         S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
     {
-        let msg = reader
-            .next()
-            .await
-            .expect("Stream ended")
-            .expect("Failed to read message")
-            .into_text()
-            .expect("Message was not text");
+        if reader.pending.is_empty() {
+            let msg = reader
+                .next()
+                .await
+                .expect("Stream ended")
+                .expect("Failed to read message")
+                .into_text()
+                .expect("Message was not text");
+
+            match serde_json::from_str(&msg).unwrap() {
+                serde_json::Value::Array(batch) => reader.pending.extend(batch),
+                response => reader.pending.push_back(response),
+            }
+        }
 
-        serde_json::from_str::<T>(&msg).unwrap()
+        // PANIC: batches are never sent empty, so there is at least one pending response.
+        serde_json::from_value::<T>(reader.pending.pop_front().unwrap()).unwrap()
     }
 
     pub async fn send<S>(
@@ -553,17 +826,27 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(milliseconds)).await
     }
 
-    static SERVER: OnceCell<()> = OnceCell::const_new();
+    static SERVER: OnceLock<()> = OnceLock::new();
 
     // #[fixture]
     async fn server_url() -> &'static str {
-        SERVER
-            .get_or_init(|| async {
-                tokio::spawn(async {
-                    setupsocket().await;
-                });
-            })
-            .await;
+        // Each test gets its own runtime, so the server lives on a dedicated thread to outlive
+        // whichever test happened to start it.
+        SERVER.get_or_init(|| {
+            // The server reads its config once when it starts, so rooms time out quickly for
+            // `room_timeout`. Setting these is safe, as the server thread does not exist yet.
+            unsafe {
+                std::env::set_var("INACTIVITY_TIMEOUT", "5");
+                std::env::set_var("CLEANUP_INTERVAL", "1");
+            };
+
+            std::thread::spawn(|| {
+                tokio::runtime::Runtime::new()
+                    .expect("could not build server runtime")
+                    .block_on(setupsocket())
+                    .expect("could not configure server");
+            });
+        });
 
         sleep(250).await;
 
@@ -592,23 +875,23 @@ mod tests {
         let (write4, read4) = ws_stream4.split();
 
         let mut writers = [write1, write2, write3, write4];
-        let mut readers = [read1, read2, read3, read4];
+        let mut readers = [read1, read2, read3, read4].map(Reader::new);
 
-        for (i, writer) in writers.iter_mut().enumerate() {
+        // Join one by one so that the player ids match the index of each reader and writer.
+        for (i, (reader, writer)) in readers.iter_mut().zip(&mut writers).enumerate() {
             send(
                 writer,
                 Connect::Connect {
                     channel: "server-test".to_string(),
                     username: format!("user {}", i),
+                    binary_frames: false,
+                    locale: None,
+                    capabilities: None,
                 },
             )
             .await
             .unwrap();
-        }
-
-        sleep(100).await;
 
-        for reader in readers.iter_mut() {
             let response = receive(reader).await;
             assert_matches!(response, DirectResponse::YouJoinedGame { .. })
         }
@@ -711,7 +994,286 @@ mod tests {
         }
     }
 
-    pub async fn test_response_messages<S>(reader: &mut SplitStream<WebSocketStream<S>>)
+    #[tokio::test]
+    async fn binary_frames() {
+        let url = server_url().await;
+
+        let (ws_stream, _) = connect_async(url).await.unwrap();
+        let (mut write, mut read) = ws_stream.split();
+
+        send(
+            &mut write,
+            Connect::Connect {
+                username: "user 1".to_owned(),
+                channel: "binary-test".to_owned(),
+                binary_frames: true,
+                locale: None,
+                capabilities: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let msg = read.next().await.unwrap().unwrap();
+        let Message::Binary(bytes) = msg else {
+            panic!("Expected a binary frame, got {msg:?}");
+        };
+        assert_matches!(
+            from_msgpack(&bytes),
+            Ok(DirectResponse::YouJoinedGame { .. })
+        );
+
+        let msg = read.next().await.unwrap().unwrap();
+        let Message::Binary(bytes) = msg else {
+            panic!("Expected a binary frame, got {msg:?}");
+        };
+        assert_matches!(
+            from_msgpack(&bytes),
+            Ok(UniqueResponse::PlayersInLobby { .. })
+        );
+
+        // Requests can be sent as MessagePack as well
+        let request = to_msgpack(&FrontendRequest::StartGame).unwrap();
+        write.send(Message::Binary(request)).await.unwrap();
+
+        let msg = read.next().await.unwrap().unwrap();
+        let Message::Binary(bytes) = msg else {
+            panic!("Expected a binary frame, got {msg:?}");
+        };
+        assert_matches!(from_msgpack(&bytes), Ok(DirectResponse::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn negotiates_capabilities() {
+        let url = server_url().await;
+
+        let (ws_stream, _) = connect_async(url).await.unwrap();
+        let (mut write, mut read) = ws_stream.split();
+
+        send(
+            &mut write,
+            Connect::Connect {
+                username: "user 1".to_owned(),
+                channel: "capabilities-test".to_owned(),
+                binary_frames: false,
+                locale: None,
+                capabilities: Some(Capabilities::BINARY_FRAMES | Capabilities::DELTAS),
+            },
+        )
+        .await
+        .unwrap();
+
+        let msg = read.next().await.unwrap().unwrap();
+        let Message::Binary(bytes) = msg else {
+            panic!("Expected a binary frame, got {msg:?}");
+        };
+        assert_matches!(
+            from_msgpack(&bytes),
+            Ok(DirectResponse::YouJoinedGame { .. })
+        );
+
+        // Only the features the server supports are acknowledged
+        let msg = read.next().await.unwrap().unwrap();
+        let Message::Binary(bytes) = msg else {
+            panic!("Expected a binary frame, got {msg:?}");
+        };
+        assert_matches!(
+            from_msgpack(&bytes),
+            Ok(DirectResponse::YouNegotiatedCapabilities { capabilities })
+                if capabilities == Capabilities::BINARY_FRAMES
+        );
+    }
+
+    #[tokio::test]
+    async fn unreadable_requests_are_answered() {
+        let url = server_url().await;
+
+        let (ws_stream, _) = connect_async(url).await.unwrap();
+        let (mut write, read) = ws_stream.split();
+        let mut read = Reader::new(read);
+
+        send(
+            &mut write,
+            Connect::Connect {
+                username: "user 1".to_owned(),
+                channel: "unreadable-test".to_owned(),
+                binary_frames: false,
+                locale: None,
+                capabilities: None,
+            },
+        )
+        .await
+        .unwrap();
+        test_response_messages(&mut read).await;
+
+        // Ending a turn without its nonce is how outdated clients ask for it
+        write.send(r#"{"action":"EndTurn"}"#.into()).await.unwrap();
+        let response = receive(&mut read).await;
+        assert_matches!(
+            response,
+            DirectResponse::Error {
+                source: ResponseError::UnreadableRequest,
+                ..
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn joins_as_spectator() {
+        let url = server_url().await;
+
+        let (player_stream, _) = connect_async(url).await.unwrap();
+        let (mut player_write, player_read) = player_stream.split();
+        let mut player_read = Reader::new(player_read);
+        send(
+            &mut player_write,
+            Connect::Connect {
+                username: "user 1".to_owned(),
+                channel: "spectate-test".to_owned(),
+                binary_frames: false,
+                locale: None,
+                capabilities: None,
+            },
+        )
+        .await
+        .unwrap();
+        test_response_messages(&mut player_read).await;
+
+        let (ws_stream, _) = connect_async(url).await.unwrap();
+        let (mut write, read) = ws_stream.split();
+        let mut read = Reader::new(read);
+        send(
+            &mut write,
+            Connect::Spectate {
+                username: "viewer".to_owned(),
+                channel: "spectate-test".to_owned(),
+                locale: None,
+                capabilities: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let response = receive(&mut read).await;
+        assert_matches!(
+            response,
+            DirectResponse::SpectatorView { player_info, spectators, .. }
+                if player_info.len() == 1 && spectators == ["viewer"]
+        );
+        let response = receive(&mut player_read).await;
+        assert_matches!(
+            response,
+            UniqueResponse::SpectatorJoined { username, .. } if username == "viewer"
+        );
+
+        // Spectators cannot take the username of a player
+        let (ws_stream, _) = connect_async(url).await.unwrap();
+        let (mut write, read) = ws_stream.split();
+        let mut read = Reader::new(read);
+        send(
+            &mut write,
+            Connect::Spectate {
+                username: "user 1".to_owned(),
+                channel: "spectate-test".to_owned(),
+                locale: None,
+                capabilities: None,
+            },
+        )
+        .await
+        .unwrap();
+        let response = receive(&mut read).await;
+        assert_matches!(response, DirectResponse::Error { .. });
+    }
+
+    #[tokio::test]
+    async fn spectators_cannot_make_requests() {
+        let url = server_url().await;
+
+        let (ws_stream, _) = connect_async(url).await.unwrap();
+        let (mut write, read) = ws_stream.split();
+        let mut read = Reader::new(read);
+
+        send(
+            &mut write,
+            Connect::Spectate {
+                username: "viewer".to_owned(),
+                channel: "spectate-request-test".to_owned(),
+                locale: None,
+                capabilities: None,
+            },
+        )
+        .await
+        .unwrap();
+        let response = receive(&mut read).await;
+        assert_matches!(response, DirectResponse::SpectatorView { .. });
+
+        send(&mut write, FrontendRequest::StartGame).await.unwrap();
+        let response = receive(&mut read).await;
+        assert_matches!(
+            response,
+            DirectResponse::Error {
+                source: ResponseError::Game(GameError::InvalidPlayerName(_)),
+                ..
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn batches_only_when_negotiated() {
+        let url = server_url().await;
+
+        for batching in [true, false] {
+            let (ws_stream, _) = connect_async(url).await.unwrap();
+            let (mut write, mut read) = ws_stream.split();
+
+            send(
+                &mut write,
+                Connect::Connect {
+                    username: "user 1".to_owned(),
+                    channel: format!("batch-test-{batching}"),
+                    binary_frames: false,
+                    locale: None,
+                    capabilities: Some(match batching {
+                        true => Capabilities::BATCHES,
+                        false => Capabilities::NONE,
+                    }),
+                },
+            )
+            .await
+            .unwrap();
+
+            let mut next_frame = async || {
+                let text = read.next().await.unwrap().unwrap().into_text().unwrap();
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            };
+            for _ in 0..3 {
+                assert!(next_frame().await.is_object());
+            }
+
+            // Changing usernames is confirmed to the player and announced to everyone
+            send(
+                &mut write,
+                FrontendRequest::ChangeUsername {
+                    username: "user 2".to_owned(),
+                },
+            )
+            .await
+            .unwrap();
+
+            let frame = next_frame().await;
+            if batching {
+                let responses = assert_some!(frame.as_array());
+                assert_eq!(responses.len(), 2);
+                assert_eq!(responses[0]["action"], "YouChangedUsername");
+                assert_eq!(responses[1]["action"], "PlayersInLobby");
+            } else {
+                assert_eq!(frame["action"], "YouChangedUsername");
+                assert_eq!(next_frame().await["action"], "PlayersInLobby");
+            }
+        }
+    }
+
+    pub async fn test_response_messages<S>(reader: &mut Reader<S>)
     where
         S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
     {
@@ -724,17 +1286,11 @@ mod tests {
 
     #[tokio::test]
     async fn room_timeout() {
-        // Safe in single-threaded programs. No other threads are spun up by this point, so
-        // this should be fine.
-        unsafe {
-            std::env::set_var("INACTIVITY_TIMEOUT", "5");
-            std::env::set_var("CLEANUP_INTERVAL", "1");
-        };
-
         let url = server_url().await;
 
         let (ws_stream1, _) = connect_async(url).await.unwrap();
-        let (mut write1, mut read1) = ws_stream1.split();
+        let (mut write1, read1) = ws_stream1.split();
+        let mut read1 = Reader::new(read1);
 
         // room is created. Now it should take 5 seconds to be shut down for inactivity.
         send(
@@ -742,6 +1298,9 @@ mod tests {
             Connect::Connect {
                 channel: "timeout-test".to_owned(),
                 username: "user 1".to_owned(),
+                binary_frames: false,
+                locale: None,
+                capabilities: None,
             },
         )
         .await
@@ -754,7 +1313,8 @@ mod tests {
         for i in 0..4 {
             tokio::spawn(async move {
                 let (ws_stream1, _) = connect_async(url).await.unwrap();
-                let (mut write1, mut read1) = ws_stream1.split();
+                let (mut write1, read1) = ws_stream1.split();
+                let mut read1 = Reader::new(read1);
 
                 // The it should be possible to join other rooms too
                 send(
@@ -762,6 +1322,9 @@ mod tests {
                     Connect::Connect {
                         channel: format!("{i}-timeout-test"),
                         username: "user 1".to_owned(),
+                        binary_frames: false,
+                        locale: None,
+                        capabilities: None,
                     },
                 )
                 .await
@@ -780,7 +1343,8 @@ mod tests {
         assert!(matches!(msg, Message::Close(Some(CloseFrame { .. }))));
 
         let (ws_stream1, _) = connect_async(url).await.unwrap();
-        let (mut write1, mut read1) = ws_stream1.split();
+        let (mut write1, read1) = ws_stream1.split();
+        let mut read1 = Reader::new(read1);
 
         // room was destroyed. It should be possible to rejoin it again.
         send(
@@ -788,6 +1352,9 @@ mod tests {
             Connect::Connect {
                 channel: "timeout-test".to_owned(),
                 username: "user 1".to_owned(),
+                binary_frames: false,
+                locale: None,
+                capabilities: None,
             },
         )
         .await
@@ -0,0 +1,99 @@
+//! Spectators of a room receive a copy of everything that happens publicly in it. When a game is
+//! streamed, this copy is held back for a while, so spectators cannot relay information like deck
+//! counts or how long someone took to the players.
+
+use responses::UniqueResponse;
+use tokio::sync::broadcast;
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// The delayed feed of a single room. Responses are only sent out once their delay has passed,
+/// which the room checks with [`SpectatorFeed::next_release`].
+#[derive(Debug)]
+pub struct SpectatorFeed {
+    delay: Duration,
+    buffer: VecDeque<(Instant, UniqueResponse)>,
+    tx: broadcast::Sender<UniqueResponse>,
+}
+
+impl SpectatorFeed {
+    /// Creates a new feed which holds every response back for `delay`.
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            buffer: VecDeque::new(),
+            tx: broadcast::channel(64).0,
+        }
+    }
+
+    /// Receives the responses of this feed once their delay has passed.
+    pub fn subscribe(&self) -> broadcast::Receiver<UniqueResponse> {
+        self.tx.subscribe()
+    }
+
+    /// Holds `responses`, which happened at `now`, back until the delay has passed.
+    pub fn push(&mut self, responses: impl IntoIterator<Item = UniqueResponse>, now: Instant) {
+        let release = now + self.delay;
+        self.buffer
+            .extend(responses.into_iter().map(|response| (release, response)));
+    }
+
+    /// Gets when the next response should be sent out, if any are held back.
+    pub fn next_release(&self) -> Option<Instant> {
+        self.buffer.front().map(|(release, _)| *release)
+    }
+
+    /// Sends out every response whose delay has passed at `now`.
+    pub fn release(&mut self, now: Instant) {
+        while let Some((release, _)) = self.buffer.front()
+            && *release <= now
+        {
+            // PANIC: we just checked there is a front element.
+            let (_, response) = self.buffer.pop_front().unwrap();
+            let _ = self.tx.send(response);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+    use game::player::PlayerId;
+
+    fn disconnected(id: u8) -> UniqueResponse {
+        UniqueResponse::PlayerDisconnected {
+            player_id: PlayerId(id),
+            turns_auto_skipped: false,
+        }
+    }
+
+    #[test]
+    fn holds_responses_back() {
+        let mut feed = SpectatorFeed::new(Duration::from_secs(10));
+        let mut rx = feed.subscribe();
+        let start = Instant::now();
+
+        feed.push([disconnected(0)], start);
+        feed.push([disconnected(1)], start + Duration::from_secs(5));
+        assert_eq!(feed.next_release(), Some(start + Duration::from_secs(10)));
+
+        feed.release(start + Duration::from_secs(9));
+        assert_err!(rx.try_recv());
+
+        feed.release(start + Duration::from_secs(10));
+        assert_matches!(
+            rx.try_recv(),
+            Ok(UniqueResponse::PlayerDisconnected { player_id, .. }) if player_id == PlayerId(0)
+        );
+        assert_err!(rx.try_recv());
+        assert_eq!(feed.next_release(), Some(start + Duration::from_secs(15)));
+
+        feed.release(start + Duration::from_secs(15));
+        assert_ok!(rx.try_recv());
+        assert_none!(feed.next_release());
+    }
+}
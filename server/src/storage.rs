@@ -0,0 +1,291 @@
+//! Persistence for the server. Each room writes every action it accepts to an audit log, so
+//! disputes can be investigated and corrupted games diagnosed after the fact.
+
+use responses::FrontendRequest;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A single action that was accepted by a room.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// The username of the player that took the action.
+    pub actor: String,
+    /// When the action was taken, in milliseconds since the unix epoch.
+    pub timestamp: u128,
+    /// The action itself.
+    pub action: FrontendRequest,
+    /// The [`GameState::state_hash`](game::game::GameState::state_hash) of the game after the
+    /// action was applied.
+    pub state_hash: u64,
+}
+
+impl AuditEntry {
+    /// Creates a new entry for `action` taken by `actor` right now.
+    pub fn now(actor: String, action: FrontendRequest, state_hash: u64) -> Self {
+        // PANIC: the system clock being set before 1970 is not something we support.
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        Self {
+            actor,
+            timestamp,
+            action,
+            state_hash,
+        }
+    }
+}
+
+/// Errors that can happen when reading from or writing to [`Storage`].
+#[derive(Debug, Error)]
+pub enum StorageError {
+    /// Error indicating that the underlying storage could not be accessed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Error indicating that an entry could not be (de)serialized.
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+}
+
+/// Somewhere the server can persist data outside of a running room.
+pub trait Storage: Send + Sync + std::fmt::Debug {
+    /// Appends `entry` to the audit log of the room with `channel`.
+    fn append_action(&self, channel: &str, entry: &AuditEntry) -> Result<(), StorageError>;
+
+    /// Gets the audit log of the room with `channel`, oldest action first.
+    fn actions(&self, channel: &str) -> Result<Vec<AuditEntry>, StorageError>;
+}
+
+/// A handle to the task that writes the audit log of a single room. Entries are written one at a
+/// time on the blocking thread pool, so a slow disk never holds up the room and the log keeps the
+/// order the actions were taken in.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    tx: mpsc::UnboundedSender<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Spawns the task that writes to the audit log of the room with `channel` in `storage`, which
+    /// runs until every handle is dropped. Has to be called from within a tokio runtime.
+    pub fn spawn(channel: String, storage: Arc<dyn Storage>) -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<AuditEntry>();
+
+        tokio::spawn(async move {
+            while let Some(entry) = rx.recv().await {
+                let storage = storage.clone();
+                let log = channel.clone();
+                let written =
+                    tokio::task::spawn_blocking(move || storage.append_action(&log, &entry)).await;
+
+                match written {
+                    Ok(Ok(())) => (),
+                    Ok(Err(error)) => {
+                        tracing::error!(%error, "Could not write to the audit log of '{channel}'")
+                    }
+                    Err(error) => {
+                        tracing::error!(%error, "Writing to the audit log of '{channel}' crashed")
+                    }
+                }
+            }
+        });
+
+        Self { tx }
+    }
+
+    /// Queues `entry` to be written to the log.
+    pub fn append(&self, entry: AuditEntry) {
+        // The task only stops once every handle is dropped, so this cannot fail
+        let _ = self.tx.send(entry);
+    }
+}
+
+/// Reads where to store audit logs from the `AUDIT_LOG_DIR` environment variable. Returns `None`
+/// if it is not set, in which case nothing is stored.
+pub fn storage_from_env() -> Option<Arc<dyn Storage>> {
+    let dir = std::env::var("AUDIT_LOG_DIR").ok()?;
+    Some(Arc::new(FileStorage::new(dir)))
+}
+
+/// Keeps everything in memory, which is mostly useful for tests.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    actions: Mutex<HashMap<String, Vec<AuditEntry>>>,
+}
+
+impl MemoryStorage {
+    /// Creates a new empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits for the audit log of `channel` to hold `len` actions, since an [`AuditLog`] writes
+    /// them in the background, and gets them.
+    #[cfg(test)]
+    pub async fn wait_for_actions(&self, channel: &str, len: usize) -> Vec<AuditEntry> {
+        for _ in 0..100 {
+            // PANIC: reading from memory cannot fail.
+            let actions = self.actions(channel).unwrap();
+            if actions.len() >= len {
+                return actions;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("The audit log of '{channel}' never held {len} actions");
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn append_action(&self, channel: &str, entry: &AuditEntry) -> Result<(), StorageError> {
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        let mut actions = self.actions.lock().unwrap();
+        actions
+            .entry(channel.to_owned())
+            .or_default()
+            .push(entry.clone());
+        Ok(())
+    }
+
+    fn actions(&self, channel: &str) -> Result<Vec<AuditEntry>, StorageError> {
+        // PANIC: see above.
+        let actions = self.actions.lock().unwrap();
+        Ok(actions.get(channel).cloned().unwrap_or_default())
+    }
+}
+
+/// Stores the audit log of each room as a file of json lines in a directory.
+#[derive(Debug)]
+pub struct FileStorage {
+    dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileStorage {
+    /// Creates a new storage that writes to `dir`, which is created if it does not exist yet.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Gets the path of the log of `channel`. Since channel names are chosen by players, anything
+    /// that could escape the directory is replaced.
+    fn path(&self, channel: &str) -> PathBuf {
+        let name = channel
+            .chars()
+            .map(
+                |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    true => c,
+                    false => '_',
+                },
+            )
+            .collect::<String>();
+
+        self.dir.join(format!("{name}.jsonl"))
+    }
+}
+
+impl Storage for FileStorage {
+    fn append_action(&self, channel: &str, entry: &AuditEntry) -> Result<(), StorageError> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        let _lock = self.lock.lock().unwrap();
+        std::fs::create_dir_all(&self.dir)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(channel))?
+            .write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn actions(&self, channel: &str) -> Result<Vec<AuditEntry>, StorageError> {
+        let file = match File::open(self.path(channel)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        BufReader::new(file)
+            .lines()
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    fn entry(actor: &str) -> AuditEntry {
+        AuditEntry::now(
+            actor.to_owned(),
+            FrontendRequest::EndTurn { turn_nonce: 0 },
+            42,
+        )
+    }
+
+    #[test]
+    fn memory_storage_keeps_order() {
+        let storage = MemoryStorage::new();
+        assert_ok!(storage.append_action("channel", &entry("1")));
+        assert_ok!(storage.append_action("channel", &entry("2")));
+        assert_ok!(storage.append_action("other", &entry("3")));
+
+        let actions = assert_ok!(storage.actions("channel"));
+        assert_eq!(
+            actions.iter().map(|a| a.actor.as_str()).collect::<Vec<_>>(),
+            ["1", "2"]
+        );
+        assert_eq!(actions[0].state_hash, 42);
+    }
+
+    #[tokio::test]
+    async fn audit_log_keeps_order() {
+        let storage = Arc::new(MemoryStorage::new());
+        let log = AuditLog::spawn("channel".to_owned(), storage.clone());
+        for actor in ["1", "2", "3"] {
+            log.append(entry(actor));
+        }
+
+        let actions = storage.wait_for_actions("channel", 3).await;
+        assert_eq!(
+            actions.iter().map(|a| a.actor.as_str()).collect::<Vec<_>>(),
+            ["1", "2", "3"]
+        );
+    }
+
+    #[test]
+    fn file_storage_round_trips() {
+        let dir = assert_ok!(tempfile::tempdir());
+        let storage = FileStorage::new(dir.path());
+
+        assert_ok!(storage.append_action("../escape", &entry("1")));
+        assert_ok!(storage.append_action("../escape", &entry("2")));
+        assert!(storage.path("../escape").starts_with(dir.path()));
+
+        let actions = assert_ok!(storage.actions("../escape"));
+        assert_eq!(
+            actions.iter().map(|a| a.actor.as_str()).collect::<Vec<_>>(),
+            ["1", "2"]
+        );
+        assert_eq!(assert_ok!(storage.actions("missing")), []);
+    }
+}
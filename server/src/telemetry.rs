@@ -0,0 +1,132 @@
+//! Anonymized gameplay events for balance analysis. Rooms only report how games are played, like
+//! which actions are taken, how long turns take and which characters end up winning, never who
+//! played them or in which room.
+//!
+//! Nothing is collected unless the server is built with the `telemetry` feature and
+//! `TELEMETRY_URL` is set, and lobbies can opt out with
+//! [`GameConfig::telemetry_opt_out`](game::game::GameConfig::telemetry_opt_out).
+
+use game::{game::CharacterStatsMap, player::Character};
+use serde::Serialize;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+/// A single anonymized event about a game.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", content = "data")]
+pub enum TelemetryEvent {
+    /// A game started.
+    GameStarted {
+        /// A random id which ties together the events of a single game.
+        game_id: u64,
+        /// How many players the game started with.
+        players: usize,
+    },
+    /// A game reached its results.
+    GameEnded {
+        /// A random id which ties together the events of a single game.
+        game_id: u64,
+        /// How long the game took, in seconds.
+        duration_secs: f64,
+        /// How many times each action was taken during the game, keyed by its name.
+        action_counts: BTreeMap<String, u32>,
+        /// Every character that was played, once for each round it was played in.
+        characters: Vec<CharacterPlay>,
+        /// The turns played and the income earned as each character over the whole game.
+        character_stats: CharacterStatsMap,
+    },
+}
+
+/// A character that was played for a single round of a game.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CharacterPlay {
+    /// The character that was played.
+    pub character: Character,
+    /// How long the turn of the character took, in seconds.
+    pub turn_secs: f64,
+    /// Whether the player who played the character won the game.
+    pub won: bool,
+}
+
+/// Somewhere telemetry events are sent to.
+pub trait TelemetrySink: Send + Sync + std::fmt::Debug {
+    /// Sends `event`. This should not block, since rooms call it while handling requests.
+    fn emit(&self, event: TelemetryEvent);
+}
+
+/// Reads where to send telemetry from the `TELEMETRY_URL` environment variable. Returns `None` if
+/// it is not set or the server was built without the `telemetry` feature, in which case nothing
+/// is collected.
+pub fn telemetry_from_env() -> Option<Arc<dyn TelemetrySink>> {
+    #[cfg(feature = "telemetry")]
+    {
+        let url = std::env::var("TELEMETRY_URL").ok()?;
+        Some(Arc::new(HttpExporter::spawn(url)))
+    }
+    #[cfg(not(feature = "telemetry"))]
+    None
+}
+
+/// Keeps every event in memory, which is mostly useful for tests.
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    events: std::sync::Mutex<Vec<TelemetryEvent>>,
+}
+
+impl MemorySink {
+    /// Gets every event that was sent so far, oldest first.
+    pub fn events(&self) -> Vec<TelemetryEvent> {
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        self.events.lock().unwrap().clone()
+    }
+}
+
+impl TelemetrySink for MemorySink {
+    fn emit(&self, event: TelemetryEvent) {
+        // PANIC: a mutex can only poison if any other thread that has access to it crashes. Since
+        // this cannot happen, unwrapping is safe.
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+/// Posts every event as JSON to an HTTP endpoint. Events are sent one at a time from a background
+/// task, so a slow endpoint never holds up a room. Events that cannot be sent are dropped.
+#[cfg(feature = "telemetry")]
+#[derive(Debug)]
+pub struct HttpExporter {
+    tx: tokio::sync::mpsc::UnboundedSender<TelemetryEvent>,
+}
+
+#[cfg(feature = "telemetry")]
+impl HttpExporter {
+    /// Starts the task which posts events to `url`. Must be called from within a tokio runtime.
+    pub fn spawn(url: String) -> Self {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<TelemetryEvent>();
+        let client = reqwest::Client::new();
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let result = client
+                    .post(&url)
+                    .json(&event)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status);
+                if let Err(error) = result {
+                    tracing::warn!(%error, "Could not send telemetry event");
+                }
+            }
+        });
+
+        Self { tx }
+    }
+}
+
+#[cfg(feature = "telemetry")]
+impl TelemetrySink for HttpExporter {
+    fn emit(&self, event: TelemetryEvent) {
+        // The task only stops once the exporter is dropped, so this cannot fail
+        let _ = self.tx.send(event);
+    }
+}
@@ -0,0 +1,264 @@
+//! Votes let the players of a room decide together to kick a player, abort the game or pause it,
+//! in any phase of the game. Only one vote runs at a time, and it ends as soon as enough players
+//! voted for it to pass or for it to be impossible to pass, or once its time is up.
+//!
+//! Players are tracked by username, since their ids change when someone leaves the lobby.
+
+use game::game::GameState;
+use responses::{VoteError, VoteKind};
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// A vote that is running in a room.
+#[derive(Debug, Clone)]
+pub struct Vote {
+    kind: VoteKind,
+    /// The username of the player that would be kicked, for [`VoteKind::Kick`].
+    target: Option<String>,
+    /// The usernames of the players that can vote.
+    voters: Vec<String>,
+    ballots: HashMap<String, bool>,
+    deadline: Instant,
+}
+
+impl Vote {
+    /// Gets what the vote is about.
+    pub fn kind(&self) -> VoteKind {
+        self.kind
+    }
+
+    /// Gets the username of the player that would be kicked, if this is a vote to kick someone.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Gets how many players voted in favor.
+    pub fn votes_for(&self) -> usize {
+        self.ballots.values().filter(|&&approve| approve).count()
+    }
+
+    /// Gets how many players voted against.
+    pub fn votes_against(&self) -> usize {
+        self.ballots.len() - self.votes_for()
+    }
+
+    /// Gets how many players have to vote in favor for the vote to pass. Aborting the game needs
+    /// two thirds of the votes, anything else needs a majority.
+    pub fn votes_needed(&self) -> usize {
+        let voters = self.voters.len();
+        match self.kind {
+            VoteKind::Abort => (voters * 2).div_ceil(3),
+            VoteKind::Kick { .. } | VoteKind::Pause | VoteKind::Resume => voters / 2 + 1,
+        }
+    }
+
+    /// Gets how long players have left to vote at `now`.
+    pub fn time_left(&self, now: Instant) -> Duration {
+        self.deadline.saturating_duration_since(now)
+    }
+
+    /// Gets whether the vote passed, or `None` if it has not been decided yet.
+    fn result(&self) -> Option<bool> {
+        if self.votes_for() >= self.votes_needed() {
+            Some(true)
+        } else if self.voters.len() - self.votes_against() < self.votes_needed() {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// The vote of a single room, if one is running.
+#[derive(Debug)]
+pub struct Votes {
+    current: Option<Vote>,
+    duration: Duration,
+}
+
+impl Votes {
+    /// Creates a room without a vote, where votes run for `duration`.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            current: None,
+            duration,
+        }
+    }
+
+    /// Gets the vote that is running, if any.
+    pub fn current(&self) -> Option<&Vote> {
+        self.current.as_ref()
+    }
+
+    /// Starts a vote of `proposer` about `kind` at `now`, which everyone in `voters` can vote on
+    /// except for `target`, the username of the player that would be kicked. The proposer votes in
+    /// favor. Returns the vote, or the vote and whether it passed if that is already decided.
+    pub fn propose(
+        &mut self,
+        proposer: &str,
+        kind: VoteKind,
+        target: Option<String>,
+        voters: Vec<String>,
+        now: Instant,
+    ) -> Result<(Vote, Option<bool>), VoteError> {
+        if self.current.is_some() {
+            return Err(VoteError::VoteInProgress);
+        }
+        if target.as_deref() == Some(proposer) {
+            return Err(VoteError::CannotKickSelf);
+        }
+
+        self.current = Some(Vote {
+            kind,
+            voters: voters
+                .into_iter()
+                .filter(|voter| Some(voter) != target.as_ref())
+                .collect(),
+            target,
+            ballots: HashMap::new(),
+            deadline: now + self.duration,
+        });
+        self.cast(proposer, true)
+    }
+
+    /// Votes on the running vote as `voter`. Returns the vote, and whether it passed once that is
+    /// decided, after which the vote stops running.
+    pub fn cast(&mut self, voter: &str, approve: bool) -> Result<(Vote, Option<bool>), VoteError> {
+        let vote = self.current.as_mut().ok_or(VoteError::NoVoteInProgress)?;
+        if !vote.voters.iter().any(|v| v == voter) {
+            return Err(VoteError::CannotVote);
+        }
+        if vote.ballots.contains_key(voter) {
+            return Err(VoteError::AlreadyVoted);
+        }
+
+        vote.ballots.insert(voter.to_owned(), approve);
+        match vote.result() {
+            Some(passed) => {
+                // PANIC: we just checked there is a vote running.
+                let vote = self.current.take().unwrap();
+                Ok((vote, Some(passed)))
+            }
+            None => Ok((vote.clone(), None)),
+        }
+    }
+
+    /// Stops the running vote if its time is up at `now`, and returns it.
+    pub fn expire(&mut self, now: Instant) -> Option<Vote> {
+        self.current.take_if(|vote| vote.deadline <= now)
+    }
+}
+
+/// Gets the usernames of the players that can vote in `game`, which are the players that are
+/// connected.
+pub fn voters(game: &GameState) -> Vec<String> {
+    fn names<'a>(players: impl Iterator<Item = (&'a str, bool)>) -> Vec<String> {
+        players
+            .filter(|(_, connected)| *connected)
+            .map(|(name, _)| name.to_owned())
+            .collect()
+    }
+
+    match game {
+        GameState::Lobby(lobby) => names(lobby.players().iter().map(|p| (p.name(), true))),
+        GameState::SelectingCharacters(selecting) => {
+            names(selecting.players().iter().map(|p| (p.name(), p.is_human())))
+        }
+        GameState::Round(round) => names(round.players().iter().map(|p| (p.name(), p.is_human()))),
+        GameState::BankerTarget(round) => {
+            names(round.players().iter().map(|p| (p.name(), p.is_human())))
+        }
+        GameState::Results(results) => {
+            names(results.players().iter().map(|p| (p.name(), p.is_human())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn majority_passes_vote() {
+        let mut votes = Votes::new(Duration::from_secs(60));
+        let (vote, passed) = assert_ok!(votes.propose(
+            "1",
+            VoteKind::Pause,
+            None,
+            names(&["1", "2", "3", "4"]),
+            Instant::now()
+        ));
+        assert_eq!(vote.votes_needed(), 3);
+        assert_none!(passed);
+
+        assert_eq!(
+            votes
+                .propose("2", VoteKind::Abort, None, vec![], Instant::now())
+                .map(drop),
+            Err(VoteError::VoteInProgress)
+        );
+        assert_ok!(votes.cast("2", false));
+        assert_eq!(
+            votes.cast("2", true).map(drop),
+            Err(VoteError::AlreadyVoted)
+        );
+        assert_ok!(votes.cast("3", true));
+
+        let (vote, passed) = assert_ok!(votes.cast("4", true));
+        assert_eq!(passed, Some(true));
+        assert_eq!((vote.votes_for(), vote.votes_against()), (3, 1));
+        assert_none!(votes.current());
+    }
+
+    #[test]
+    fn kick_target_cannot_vote() {
+        let mut votes = Votes::new(Duration::from_secs(60));
+        assert_eq!(
+            votes
+                .propose(
+                    "1",
+                    VoteKind::Kick {
+                        target: game::player::PlayerId(0)
+                    },
+                    Some("1".to_owned()),
+                    names(&["1", "2", "3"]),
+                    Instant::now()
+                )
+                .map(drop),
+            Err(VoteError::CannotKickSelf)
+        );
+
+        assert_ok!(votes.propose(
+            "1",
+            VoteKind::Kick {
+                target: game::player::PlayerId(2)
+            },
+            Some("3".to_owned()),
+            names(&["1", "2", "3"]),
+            Instant::now()
+        ));
+        assert_eq!(votes.cast("3", false).map(drop), Err(VoteError::CannotVote));
+
+        let (_, passed) = assert_ok!(votes.cast("2", false));
+        assert_eq!(passed, Some(false));
+    }
+
+    #[test]
+    fn votes_expire() {
+        let mut votes = Votes::new(Duration::from_secs(60));
+        let now = Instant::now();
+        assert_ok!(votes.propose("1", VoteKind::Abort, None, names(&["1", "2", "3"]), now));
+
+        assert_none!(votes.expire(now + Duration::from_secs(59)));
+        assert_some!(votes.expire(now + Duration::from_secs(60)));
+        assert_none!(votes.current());
+    }
+}
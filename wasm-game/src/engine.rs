@@ -0,0 +1,494 @@
+//! Contains [`Engine`], a fully local instance of the game that is driven with the same
+//! [`FrontendRequest`]s the server accepts.
+
+use std::fmt;
+
+use game::{
+    bot::*,
+    cards::{DataParseError, GameData},
+    errors::GameError,
+    game::*,
+    player::*,
+    utility::serde_asset_liability::EitherAssetLiability,
+};
+use responses::{DirectResponse, FrontendRequest};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// The contents of `boardgame.json`, bundled into the binary so the game can be started without a
+/// server or a filesystem.
+pub static BUNDLED_CARDS: &str = include_str!("../../assets/cards/boardgame.json");
+
+/// Errors that can occur when driving an [`Engine`].
+#[derive(Debug, Error)]
+pub enum OfflineError {
+    /// An error returned by the game itself.
+    #[error(transparent)]
+    Game(#[from] GameError),
+    /// The bundled card data could not be loaded.
+    #[error(transparent)]
+    Data(#[from] DataParseError),
+    /// A request could not be parsed or a result could not be serialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// There is no bot strategy with this name.
+    #[error("Unknown bot strategy: {0}")]
+    UnknownStrategy(String),
+    /// The request only makes sense when playing with others on a server.
+    #[error("This request is not available offline")]
+    OnlineOnly,
+}
+
+/// The phase the game is currently in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// See [`GameState::Lobby`].
+    Lobby,
+    /// See [`GameState::SelectingCharacters`].
+    SelectingCharacters,
+    /// See [`GameState::Round`].
+    Round,
+    /// See [`GameState::BankerTarget`].
+    BankerTarget,
+    /// See [`GameState::Results`].
+    Results,
+}
+
+impl From<&GameState> for Phase {
+    fn from(state: &GameState) -> Self {
+        match state {
+            GameState::Lobby(_) => Self::Lobby,
+            GameState::SelectingCharacters(_) => Self::SelectingCharacters,
+            GameState::Round(_) => Self::Round,
+            GameState::BankerTarget(_) => Self::BankerTarget,
+            GameState::Results(_) => Self::Results,
+        }
+    }
+}
+
+/// Everything a single player is able to see of the game, used by the frontend to render the
+/// offline game after every action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerView {
+    /// The phase the game is currently in.
+    pub phase: Phase,
+    /// The id of the player this view belongs to.
+    pub id: PlayerId,
+    /// The hand of this player.
+    pub hand: Vec<EitherAssetLiability>,
+    /// Public information of every player, including this one.
+    pub players: Vec<PlayerInfo>,
+    /// The current market, if the game has started.
+    pub market: Option<Market>,
+    /// How many cards are left in the asset and liability decks, while the game is played.
+    pub deck_sizes: Option<DeckSizes>,
+    /// Every event that happened so far this game, oldest first.
+    pub events: Vec<Event>,
+    /// The player that is currently playing their turn or selecting a character.
+    pub current_player: Option<PlayerId>,
+    /// The phase of the turn of the current player, if a round is being played.
+    pub turn_phase: Option<TurnPhase>,
+    /// The nonce of the turn of the current player, which is needed to end it, if a round is
+    /// being played.
+    pub turn_nonce: Option<u32>,
+    /// The characters this player can select, if it is their turn to select one.
+    pub selectable_characters: Option<Vec<Character>>,
+    /// The characters this player can ban, if it is their turn to ban one.
+    pub bannable_characters: Option<Vec<Character>>,
+    /// The scores of all players, if the game has ended.
+    pub scores: Option<Vec<PlayerScore>>,
+}
+
+/// An action a bot took while running [`Engine::run_bots`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotMove {
+    /// The id of the bot that took the action.
+    pub player_id: PlayerId,
+    /// The action the bot took.
+    pub action: PlayerAction,
+    /// The json encoded result of the action.
+    pub result: Value,
+}
+
+/// A local game that runs the complete rule set without a server. Requests are applied on behalf
+/// of a player, which makes it possible to control every player in the game from one place.
+#[derive(Default)]
+pub struct Engine {
+    state: GameState,
+    bots: Vec<(PlayerId, Box<dyn Strategy>)>,
+}
+
+impl fmt::Debug for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Engine")
+            .field("state", &self.state)
+            .field(
+                "bots",
+                &self.bots.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Engine {
+    /// Creates a new engine with an empty lobby.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets a reference to the underlying [`GameState`].
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Adds a player to the lobby, returning the id they were given.
+    pub fn join(&mut self, username: String) -> Result<PlayerId, OfflineError> {
+        let lobby = self.state.lobby_mut()?;
+        let player = lobby.join(username).map_err(GameError::from)?;
+
+        Ok(player.id())
+    }
+
+    /// Adds a computer controlled player to the lobby which plays using `strategy`, returning the
+    /// id they were given.
+    pub fn add_bot(
+        &mut self,
+        username: String,
+        strategy: Box<dyn Strategy>,
+    ) -> Result<PlayerId, OfflineError> {
+        let id = self.join(username)?;
+
+        let lobby = self.state.lobby_mut()?;
+        if let Some(player) = lobby.players_mut().iter_mut().find(|p| p.id() == id) {
+            player.set_is_human(false);
+        }
+        self.bots.push((id, strategy));
+
+        Ok(id)
+    }
+
+    /// Adds a bot like [`Engine::add_bot`], where the strategy is picked by name. Supported names
+    /// are `"greedy"` and `"random"`.
+    pub fn add_bot_by_name(
+        &mut self,
+        username: String,
+        strategy: &str,
+    ) -> Result<PlayerId, OfflineError> {
+        let strategy: Box<dyn Strategy> = match strategy {
+            "greedy" => Box::new(GreedyStrategy::new()),
+            "random" => Box::new(RandomStrategy::new()),
+            _ => return Err(OfflineError::UnknownStrategy(strategy.to_owned())),
+        };
+
+        self.add_bot(username, strategy)
+    }
+
+    /// Lets bots play until it is a human player's turn or the game has ended. Every action the
+    /// bots took is returned in order, so the frontend can replay them.
+    pub fn run_bots(&mut self) -> Result<Vec<BotMove>, OfflineError> {
+        // Guards against strategies that never end their turn
+        const MAX_MOVES: usize = 10_000;
+
+        let mut moves = Vec::new();
+        while moves.len() < MAX_MOVES {
+            let Some(id) = acting_player(&self.state) else {
+                break;
+            };
+            let Some((_, strategy)) = self.bots.iter_mut().find(|(bot, _)| *bot == id) else {
+                break;
+            };
+            let Some(action) = strategy.next_action(&self.state, id) else {
+                break;
+            };
+
+            // Bots act on the turn they see
+            let turn_nonce = self.state.round().ok().map(Round::turn_nonce);
+            let (action, result) = match self.apply_action(id, action.clone(), turn_nonce) {
+                Ok(result) => (action, result),
+                // A bot that makes an invalid move forfeits the rest of its turn
+                Err(_) => {
+                    let action = PlayerAction::EndTurn;
+                    let result = self.apply_action(id, action.clone(), turn_nonce)?;
+                    (action, result)
+                }
+            };
+            moves.push(BotMove {
+                player_id: id,
+                action,
+                result,
+            });
+        }
+
+        Ok(moves)
+    }
+
+    /// Starts the game using the bundled card data.
+    pub fn start(&mut self) -> Result<(), OfflineError> {
+        let data = GameData::from_json(BUNDLED_CARDS)?;
+        self.state.start_game_with_data(data)?;
+
+        Ok(())
+    }
+
+    /// Parses `request` as a [`FrontendRequest`] and applies it for player `id`. See
+    /// [`Engine::apply`].
+    pub fn apply_json(&mut self, id: PlayerId, request: &str) -> Result<Value, OfflineError> {
+        let request = serde_json::from_str(request)?;
+
+        self.apply(id, request)
+    }
+
+    /// Applies `request` on behalf of player `id`. On success, the [`DirectResponse`] the server
+    /// would answer with is returned as json. Requests to resync are answered with a
+    /// [`PlayerView`] instead.
+    pub fn apply(&mut self, id: PlayerId, request: FrontendRequest) -> Result<Value, OfflineError> {
+        let response = match request {
+            FrontendRequest::Resync | FrontendRequest::RequestFullState => {
+                return Ok(serde_json::to_value(self.view(id)?)?);
+            }
+            FrontendRequest::RequestHint => DirectResponse::YouGotHint {
+                hint: self.state.round()?.suggest_action(id),
+            },
+            FrontendRequest::KickPlayer { target } => {
+                self.state.lobby_mut()?.kick(id, target)?;
+                DirectResponse::YouKickedPlayer { player_id: target }
+            }
+            request => {
+                let turn_nonce = request.turn_nonce();
+                let action = request.into_action().ok_or(OfflineError::OnlineOnly)?;
+                return self.apply_action(id, action, turn_nonce);
+            }
+        };
+
+        Ok(serde_json::to_value(response)?)
+    }
+
+    /// Applies `action` on behalf of player `id` during the turn with `turn_nonce` with
+    /// [`GameState::apply_in_turn`], and returns what the player is told about it as json.
+    /// Starting the game uses the bundled card data.
+    fn apply_action(
+        &mut self,
+        id: PlayerId,
+        action: PlayerAction,
+        turn_nonce: Option<u32>,
+    ) -> Result<Value, OfflineError> {
+        if action == PlayerAction::StartGame
+            && let Ok(lobby) = self.state.lobby_mut()
+            && lobby.game_data().is_none()
+        {
+            lobby.set_game_data(GameData::from_json(BUNDLED_CARDS)?);
+        }
+        let effects = self.state.apply_in_turn(id, action, turn_nonce)?;
+
+        Ok(serde_json::to_value(DirectResponse::from(
+            effects.to_actor,
+        ))?)
+    }
+
+    /// Gets the [`PlayerView`] for player `id`.
+    pub fn view(&self, id: PlayerId) -> Result<PlayerView, OfflineError> {
+        let phase = Phase::from(&self.state);
+
+        let deck_sizes = self.state.deck_sizes();
+        let view = match &self.state {
+            GameState::Lobby(lobby) => PlayerView {
+                phase,
+                id,
+                hand: Vec::new(),
+                players: lobby.players().iter().map(Into::into).collect(),
+                market: None,
+                deck_sizes,
+                events: Vec::new(),
+                current_player: None,
+                turn_phase: None,
+                turn_nonce: None,
+                selectable_characters: None,
+                bannable_characters: None,
+                scores: None,
+            },
+            GameState::SelectingCharacters(selecting) => PlayerView {
+                phase,
+                id,
+                hand: hand(selecting.player(id)?.hand()),
+                players: selecting.players().iter().map(Into::into).collect(),
+                market: Some(selecting.current_market().clone()),
+                deck_sizes,
+                events: selecting.current_events().to_vec(),
+                current_player: Some(selecting.currently_selecting_id()),
+                turn_phase: None,
+                turn_nonce: None,
+                selectable_characters: selecting.player_get_selectable_characters(id).ok(),
+                bannable_characters: selecting.player_get_bannable_characters(id).ok(),
+                scores: None,
+            },
+            GameState::Round(round) => PlayerView {
+                phase,
+                id,
+                hand: hand(round.player(id)?.hand()),
+                players: round.players().iter().map(Into::into).collect(),
+                market: Some(round.current_market().clone()),
+                deck_sizes,
+                events: round.current_events().to_vec(),
+                current_player: Some(round.current_player().id()),
+                turn_phase: Some(round.turn_phase()),
+                turn_nonce: Some(round.turn_nonce()),
+                selectable_characters: None,
+                bannable_characters: None,
+                scores: None,
+            },
+            GameState::BankerTarget(btround) => PlayerView {
+                phase,
+                id,
+                hand: hand(btround.player(id)?.hand()),
+                players: btround.players().iter().map(Into::into).collect(),
+                market: Some(btround.current_market().clone()),
+                deck_sizes,
+                events: btround.current_events().to_vec(),
+                current_player: Some(btround.current_player().id()),
+                turn_phase: None,
+                turn_nonce: None,
+                selectable_characters: None,
+                bannable_characters: None,
+                scores: None,
+            },
+            GameState::Results(results) => PlayerView {
+                phase,
+                id,
+                hand: hand(results.player(id)?.hand()),
+                players: results.players().iter().map(Into::into).collect(),
+                market: Some(results.player(id)?.market().clone()),
+                deck_sizes,
+                events: results.final_events().to_vec(),
+                current_player: None,
+                turn_phase: None,
+                turn_nonce: None,
+                selectable_characters: None,
+                bannable_characters: None,
+                scores: Some(results.player_scores()),
+            },
+        };
+
+        Ok(view)
+    }
+}
+
+fn hand(hand: &[either::Either<Asset, Liability>]) -> Vec<EitherAssetLiability> {
+    hand.iter().cloned().map(Into::into).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::*;
+
+    fn started_engine(player_count: u8) -> Engine {
+        let mut engine = Engine::new();
+
+        for i in 0..player_count {
+            assert_ok_eq!(engine.join(format!("player {i}")), PlayerId(i));
+        }
+        assert_ok!(engine.start());
+
+        engine
+    }
+
+    #[test]
+    fn bundled_cards_load() {
+        assert_ok!(GameData::from_json(BUNDLED_CARDS));
+    }
+
+    #[test]
+    fn start_needs_enough_players() {
+        let mut engine = Engine::new();
+        assert_ok!(engine.join("player 0".to_owned()));
+
+        assert_matches!(
+            engine.start(),
+            Err(OfflineError::Game(GameError::InvalidPlayerCount(1)))
+        );
+    }
+
+    #[test]
+    fn play_first_turn() {
+        let mut engine = started_engine(4);
+        assert_eq!(Phase::from(engine.state()), Phase::SelectingCharacters);
+
+        while let Ok(selecting) = engine.state().selecting_characters() {
+            let id = selecting.currently_selecting_id();
+            let view = assert_ok!(engine.view(id));
+            let character = assert_some!(view.selectable_characters)[0];
+
+            let request = FrontendRequest::SelectCharacter { character };
+            let json = assert_ok!(serde_json::to_string(&request));
+            assert_ok!(engine.apply_json(id, &json));
+        }
+
+        let id = assert_ok!(engine.state().round()).current_player().id();
+        let turn_nonce = assert_some!(assert_ok!(engine.view(id)).turn_nonce);
+
+        assert_matches!(
+            engine.apply(
+                id,
+                FrontendRequest::DrawCard {
+                    card_type: CardType::Asset,
+                    turn_nonce: turn_nonce + 1,
+                }
+            ),
+            Err(OfflineError::Game(GameError::StaleTurnNonce(_)))
+        );
+        let response = assert_ok!(engine.apply(
+            id,
+            FrontendRequest::DrawCard {
+                card_type: CardType::Asset,
+                turn_nonce,
+            }
+        ));
+        assert_eq!(response["action"], "YouDrewCard");
+        assert_eq!(response["data"]["card"]["card_type"], "asset");
+
+        let view = assert_ok!(engine.view(id));
+        assert_eq!(view.phase, Phase::Round);
+        assert_eq!(view.hand.len(), 5);
+        assert_eq!(view.players.len(), 4);
+
+        assert_matches!(
+            engine.apply(id, FrontendRequest::StartGame),
+            Err(OfflineError::Game(GameError::NotLobbyState))
+        );
+    }
+
+    #[test]
+    fn bots_play_until_human() {
+        let mut engine = Engine::new();
+        assert_ok!(engine.join("human".to_owned()));
+        for i in 0..3 {
+            assert_ok!(engine.add_bot_by_name(format!("bot {i}"), "greedy"));
+        }
+        assert_matches!(
+            engine.add_bot_by_name("bot".to_owned(), "perfect"),
+            Err(OfflineError::UnknownStrategy(_))
+        );
+        assert_ok!(engine.start());
+
+        let moves = assert_ok!(engine.run_bots());
+        assert!(moves.iter().all(|m| m.player_id != PlayerId(0)));
+        assert_some_eq!(acting_player(engine.state()), PlayerId(0));
+    }
+
+    #[test]
+    fn bots_finish_game() {
+        let mut engine = Engine::new();
+        for i in 0..4 {
+            let strategy: Box<dyn Strategy> = match i % 2 {
+                0 => Box::new(GreedyStrategy::new()),
+                _ => Box::new(RandomStrategy::with_seed(i)),
+            };
+            assert_ok!(engine.add_bot(format!("bot {i}"), strategy));
+        }
+        assert_ok!(engine.start());
+
+        assert_ok!(engine.run_bots());
+        assert_eq!(Phase::from(engine.state()), Phase::Results);
+    }
+}
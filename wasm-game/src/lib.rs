@@ -0,0 +1,81 @@
+//! This crate compiles the complete game of _The Bottom Line_ to wasm, so the frontend can offer an
+//! offline practice mode that runs entirely in the browser without a server.
+//!
+//! Requests and results use the same json representation as the websocket protocol, so the
+//! frontend can reuse its request builders.
+
+#![warn(missing_docs)]
+
+pub mod engine;
+
+use game::{cards::GameData, player::PlayerId};
+use responses::FakeGame;
+use wasm_bindgen::prelude::*;
+
+use crate::engine::{BUNDLED_CARDS, Engine, OfflineError};
+
+/// An offline game which can be controlled from javascript.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct OfflineGame {
+    engine: Engine,
+}
+
+#[wasm_bindgen]
+impl OfflineGame {
+    /// Creates a new offline game with an empty lobby.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a player to the lobby, returning the id they were given.
+    pub fn join(&mut self, username: String) -> Result<u8, JsError> {
+        Ok(self.engine.join(username)?.0)
+    }
+
+    /// Adds a computer controlled player to the lobby, returning the id they were given. `strategy`
+    /// is either `"greedy"` or `"random"`.
+    pub fn add_bot(&mut self, username: String, strategy: &str) -> Result<u8, JsError> {
+        Ok(self.engine.add_bot_by_name(username, strategy)?.0)
+    }
+
+    /// Lets bots play until it is a human player's turn or the game has ended, returning the json
+    /// encoded list of moves they made.
+    pub fn run_bots(&mut self) -> Result<String, JsError> {
+        let moves = self.engine.run_bots()?;
+
+        Ok(serde_json::to_string(&moves)?)
+    }
+
+    /// Starts the game using the card data bundled into the wasm binary.
+    pub fn start(&mut self) -> Result<(), JsError> {
+        Ok(self.engine.start()?)
+    }
+
+    /// Applies a json encoded `FrontendRequest` on behalf of `player_id` and returns the json
+    /// encoded result of the action.
+    pub fn apply(&mut self, player_id: u8, request: &str) -> Result<String, JsError> {
+        let value = self.engine.apply_json(PlayerId(player_id), request)?;
+
+        Ok(value.to_string())
+    }
+
+    /// Gets the json encoded view of the game for `player_id`.
+    pub fn view(&self, player_id: u8) -> Result<String, JsError> {
+        let view = self.engine.view(PlayerId(player_id))?;
+
+        Ok(serde_json::to_string(&view)?)
+    }
+}
+
+/// Plays a made-up game between `players` bots with the bundled card data, and returns the json
+/// encoded list of every response its players were sent, each with the id of the player it was
+/// sent to. The same `seed` always gives the same responses.
+#[wasm_bindgen]
+pub fn fake_responses(players: usize, seed: u64) -> Result<String, JsError> {
+    let data = GameData::from_json(BUNDLED_CARDS).map_err(OfflineError::from)?;
+    let game = FakeGame::new(data, players, seed).map_err(OfflineError::from)?;
+
+    Ok(serde_json::to_string(&game.collect::<Vec<_>>())?)
+}